@@ -1,8 +1,10 @@
 mod known_assets;
 mod note_source;
+mod quarantined;
 
 pub mod params;
 pub mod sync;
 
 pub use known_assets::KnownAssets;
 pub use note_source::NoteSource;
+pub use quarantined::{QuarantineGroup, Quarantined};
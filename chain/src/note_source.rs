@@ -8,8 +8,17 @@ pub enum NoteSource {
     Transaction { id: [u8; 32] },
     Genesis,
     FundingStreamReward { epoch_index: u64 },
+    ProposalDepositRefund { proposal_id: u64 },
+    CommunityPoolSpend { proposal_id: u64 },
+    Ics20Transfer { channel: u64, sequence: u64 },
+    SwapOutput { height: u64 },
+    SwapRefund { height: u64 },
 }
 
+// Sources other than transactions are encoded as a code byte at `CODE_INDEX`,
+// with data on either side of it and zeros before `DATA_INDEX`, so that they
+// can't be mistaken for transaction IDs.
+const DATA_INDEX: usize = 15;
 const CODE_INDEX: usize = 23;
 
 impl NoteSource {
@@ -27,6 +36,39 @@ impl NoteSource {
                 bytes[24..].copy_from_slice(&epoch_index.to_le_bytes());
                 bytes
             }
+            Self::ProposalDepositRefund { proposal_id } => {
+                let mut bytes = [0u8; 32];
+                bytes[CODE_INDEX] = 3;
+                bytes[24..].copy_from_slice(&proposal_id.to_le_bytes());
+                bytes
+            }
+            Self::CommunityPoolSpend { proposal_id } => {
+                let mut bytes = [0u8; 32];
+                bytes[CODE_INDEX] = 4;
+                bytes[24..].copy_from_slice(&proposal_id.to_le_bytes());
+                bytes
+            }
+            Self::Ics20Transfer { channel, sequence } => {
+                let mut bytes = [0u8; 32];
+                bytes[DATA_INDEX..CODE_INDEX].copy_from_slice(&channel.to_le_bytes());
+                bytes[CODE_INDEX] = 5;
+                bytes[24..].copy_from_slice(&sequence.to_le_bytes());
+                bytes
+            }
+            // Swap outputs and refunds are identified by the height of the
+            // batch the claimed swap was cleared in.
+            Self::SwapOutput { height } => {
+                let mut bytes = [0u8; 32];
+                bytes[CODE_INDEX] = 6;
+                bytes[24..].copy_from_slice(&height.to_le_bytes());
+                bytes
+            }
+            Self::SwapRefund { height } => {
+                let mut bytes = [0u8; 32];
+                bytes[CODE_INDEX] = 7;
+                bytes[24..].copy_from_slice(&height.to_le_bytes());
+                bytes
+            }
         }
     }
 }
@@ -34,20 +76,54 @@ impl NoteSource {
 impl TryFrom<[u8; 32]> for NoteSource {
     type Error = anyhow::Error;
     fn try_from(bytes: [u8; 32]) -> Result<Self> {
-        if &bytes[..CODE_INDEX] != &[0u8; CODE_INDEX][..] {
+        if &bytes[..DATA_INDEX] != &[0u8; DATA_INDEX][..] {
             Ok(Self::Transaction { id: bytes })
         } else {
-            match (bytes[CODE_INDEX], &bytes[CODE_INDEX + 1..]) {
-                (1, &[0, 0, 0, 0, 0, 0, 0, 0]) => Ok(Self::Genesis),
-                (2, epoch_bytes) => {
+            match (
+                &bytes[DATA_INDEX..CODE_INDEX],
+                bytes[CODE_INDEX],
+                &bytes[CODE_INDEX + 1..],
+            ) {
+                (&[0, 0, 0, 0, 0, 0, 0, 0], 1, &[0, 0, 0, 0, 0, 0, 0, 0]) => Ok(Self::Genesis),
+                (&[0, 0, 0, 0, 0, 0, 0, 0], 2, epoch_bytes) => {
                     let epoch_index =
                         u64::from_le_bytes(epoch_bytes.try_into().expect("slice is of length 8"));
                     Ok(Self::FundingStreamReward { epoch_index })
                 }
-                (code, data) => Err(anyhow!(
+                (&[0, 0, 0, 0, 0, 0, 0, 0], 3, proposal_id_bytes) => {
+                    let proposal_id = u64::from_le_bytes(
+                        proposal_id_bytes.try_into().expect("slice is of length 8"),
+                    );
+                    Ok(Self::ProposalDepositRefund { proposal_id })
+                }
+                (&[0, 0, 0, 0, 0, 0, 0, 0], 4, proposal_id_bytes) => {
+                    let proposal_id = u64::from_le_bytes(
+                        proposal_id_bytes.try_into().expect("slice is of length 8"),
+                    );
+                    Ok(Self::CommunityPoolSpend { proposal_id })
+                }
+                (channel_bytes, 5, sequence_bytes) => {
+                    let channel =
+                        u64::from_le_bytes(channel_bytes.try_into().expect("slice is of length 8"));
+                    let sequence = u64::from_le_bytes(
+                        sequence_bytes.try_into().expect("slice is of length 8"),
+                    );
+                    Ok(Self::Ics20Transfer { channel, sequence })
+                }
+                (&[0, 0, 0, 0, 0, 0, 0, 0], 6, height_bytes) => {
+                    let height =
+                        u64::from_le_bytes(height_bytes.try_into().expect("slice is of length 8"));
+                    Ok(Self::SwapOutput { height })
+                }
+                (&[0, 0, 0, 0, 0, 0, 0, 0], 7, height_bytes) => {
+                    let height =
+                        u64::from_le_bytes(height_bytes.try_into().expect("slice is of length 8"));
+                    Ok(Self::SwapRefund { height })
+                }
+                (_, code, _) => Err(anyhow!(
                     "unknown note source with code {} and data {:?}",
                     code,
-                    data
+                    &bytes[DATA_INDEX..]
                 )),
             }
         }
@@ -84,6 +160,67 @@ impl std::fmt::Debug for NoteSource {
                 "NoteSource::FundingStreamReward({})",
                 epoch_index
             )),
+            NoteSource::ProposalDepositRefund { proposal_id } => f.write_fmt(format_args!(
+                "NoteSource::ProposalDepositRefund({})",
+                proposal_id
+            )),
+            NoteSource::CommunityPoolSpend { proposal_id } => f.write_fmt(format_args!(
+                "NoteSource::CommunityPoolSpend({})",
+                proposal_id
+            )),
+            NoteSource::Ics20Transfer { channel, sequence } => f.write_fmt(format_args!(
+                "NoteSource::Ics20Transfer(channel-{}, {})",
+                channel, sequence
+            )),
+            NoteSource::SwapOutput { height } => {
+                f.write_fmt(format_args!("NoteSource::SwapOutput({})", height))
+            }
+            NoteSource::SwapRefund { height } => {
+                f.write_fmt(format_args!("NoteSource::SwapRefund({})", height))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn note_source_roundtrip() {
+        let sources = [
+            NoteSource::Transaction { id: [7u8; 32] },
+            NoteSource::Genesis,
+            NoteSource::FundingStreamReward { epoch_index: 3 },
+            NoteSource::ProposalDepositRefund { proposal_id: 4 },
+            NoteSource::CommunityPoolSpend { proposal_id: 5 },
+            NoteSource::Ics20Transfer {
+                channel: u64::MAX,
+                sequence: u64::MAX,
+            },
+            NoteSource::SwapOutput { height: 1 << 40 },
+            NoteSource::SwapRefund { height: u64::MAX },
+        ];
+        for source in sources {
+            assert_eq!(NoteSource::try_from(source.to_bytes()).unwrap(), source);
+        }
+
+        // swap outputs and refunds from the same batch are distinct.
+        assert_ne!(
+            NoteSource::SwapOutput { height: 9 }.to_bytes(),
+            NoteSource::SwapRefund { height: 9 }.to_bytes()
+        );
+    }
+
+    #[test]
+    fn note_source_rejects_unknown_codes() {
+        let mut bytes = [0u8; 32];
+        bytes[CODE_INDEX] = 8;
+        assert!(NoteSource::try_from(bytes).is_err());
+
+        // only ICS-20 transfers have data before the code.
+        let mut bytes = NoteSource::SwapOutput { height: 9 }.to_bytes();
+        bytes[DATA_INDEX] = 1;
+        assert!(NoteSource::try_from(bytes).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use penumbra_crypto::asset;
+use penumbra_crypto::{asset, proofs::ProofSystem};
 use penumbra_proto::{chain as pb, crypto as pbc, Protobuf};
 use serde::{Deserialize, Serialize};
 
@@ -55,6 +55,26 @@ pub struct ChainParams {
     pub signed_blocks_window_len: u64,
     /// The maximum number of blocks in the window each validator can miss signing without slashing.
     pub missed_blocks_maximum: u64,
+    /// The minimum fee a transaction must pay, in units of the staking token.
+    pub min_fee: u64,
+    /// The minimum deposit a proposal must escrow, in units of the staking token.
+    pub proposal_min_deposit: u64,
+    /// The minimum number of epochs for which voting on a proposal stays open.
+    pub proposal_min_voting_epochs: u64,
+    /// The fraction of the total voting power that must vote on a proposal for
+    /// its outcome to be valid, expressed in basis points.
+    pub proposal_valid_quorum_bps: u64,
+    /// The fraction of the non-abstaining votes which must be "yes" for a
+    /// proposal to pass, expressed in basis points.
+    pub proposal_pass_threshold_bps: u64,
+    /// The fraction of the votes which must be "no with veto" for a proposal
+    /// to be vetoed, burning its deposit, expressed in basis points.
+    pub proposal_veto_threshold_bps: u64,
+
+    /// The number of blocks in each round of the flow encryption DKG.
+    pub dkg_round_blocks: u64,
+    /// The proof system that spend, output, swap and swap claim proofs must use.
+    pub proof_system: ProofSystem,
 
     /// Whether IBC (forming connections, processing IBC packets) is enabled.
     pub ibc_enabled: bool,
@@ -64,11 +84,118 @@ pub struct ChainParams {
     pub outbound_ics20_transfers_enabled: bool,
 }
 
+impl ChainParams {
+    /// Sets the named parameter to `value`, parsed from its string representation.
+    ///
+    /// The chain ID and epoch duration are fixed at genesis, and can't be changed.
+    pub fn set(&mut self, parameter: &str, value: &str) -> anyhow::Result<()> {
+        match parameter {
+            "unbonding_epochs" => self.unbonding_epochs = value.parse()?,
+            "active_validator_limit" => self.active_validator_limit = value.parse()?,
+            "base_reward_rate" => self.base_reward_rate = value.parse()?,
+            "slashing_penalty_misbehavior_bps" => {
+                self.slashing_penalty_misbehavior_bps = value.parse()?
+            }
+            "slashing_penalty_downtime_bps" => {
+                self.slashing_penalty_downtime_bps = value.parse()?
+            }
+            "signed_blocks_window_len" => self.signed_blocks_window_len = value.parse()?,
+            "missed_blocks_maximum" => self.missed_blocks_maximum = value.parse()?,
+            "min_fee" => self.min_fee = value.parse()?,
+            "proposal_min_deposit" => self.proposal_min_deposit = value.parse()?,
+            "proposal_min_voting_epochs" => self.proposal_min_voting_epochs = value.parse()?,
+            "proposal_valid_quorum_bps" => self.proposal_valid_quorum_bps = value.parse()?,
+            "proposal_pass_threshold_bps" => self.proposal_pass_threshold_bps = value.parse()?,
+            "proposal_veto_threshold_bps" => self.proposal_veto_threshold_bps = value.parse()?,
+            "dkg_round_blocks" => self.dkg_round_blocks = value.parse()?,
+            "proof_system" => self.proof_system = value.parse()?,
+            "ibc_enabled" => self.ibc_enabled = value.parse()?,
+            "inbound_ics20_transfers_enabled" => {
+                self.inbound_ics20_transfers_enabled = value.parse()?
+            }
+            "outbound_ics20_transfers_enabled" => {
+                self.outbound_ics20_transfers_enabled = value.parse()?
+            }
+            "chain_id" | "epoch_duration" => {
+                return Err(anyhow::anyhow!(
+                    "chain parameter {} can't be changed",
+                    parameter
+                ))
+            }
+            _ => return Err(anyhow::anyhow!("unknown chain parameter {}", parameter)),
+        }
+        Ok(())
+    }
+
+    /// Checks that the parameters are consistent with each other, and within
+    /// the ranges the chain can operate with.
+    pub fn check_valid(&self) -> anyhow::Result<()> {
+        if self.epoch_duration == 0 || self.unbonding_epochs == 0 {
+            return Err(anyhow::anyhow!(
+                "epoch duration and unbonding epochs must be nonzero"
+            ));
+        }
+        if self.active_validator_limit == 0 {
+            return Err(anyhow::anyhow!("active validator limit must be nonzero"));
+        }
+        if self.dkg_round_blocks == 0 {
+            return Err(anyhow::anyhow!("DKG round blocks must be nonzero"));
+        }
+        // The DKG for an epoch has to finish within it.
+        if self.dkg_round_blocks.saturating_mul(3) > self.epoch_duration {
+            return Err(anyhow::anyhow!(
+                "the three DKG rounds of {} blocks each must fit in an epoch of {} blocks",
+                self.dkg_round_blocks,
+                self.epoch_duration
+            ));
+        }
+        if self.signed_blocks_window_len == 0
+            || self.missed_blocks_maximum >= self.signed_blocks_window_len
+        {
+            return Err(anyhow::anyhow!(
+                "missed blocks maximum {} must be less than the nonzero signed blocks window length {}",
+                self.missed_blocks_maximum,
+                self.signed_blocks_window_len
+            ));
+        }
+        for (name, bps) in [
+            (
+                "slashing_penalty_misbehavior_bps",
+                self.slashing_penalty_misbehavior_bps,
+            ),
+            (
+                "slashing_penalty_downtime_bps",
+                self.slashing_penalty_downtime_bps,
+            ),
+            ("proposal_valid_quorum_bps", self.proposal_valid_quorum_bps),
+            (
+                "proposal_pass_threshold_bps",
+                self.proposal_pass_threshold_bps,
+            ),
+            (
+                "proposal_veto_threshold_bps",
+                self.proposal_veto_threshold_bps,
+            ),
+        ] {
+            if bps > 10_000 {
+                return Err(anyhow::anyhow!(
+                    "{} is {} basis points, greater than 10000bps = 100%",
+                    name,
+                    bps
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Protobuf<pb::ChainParams> for ChainParams {}
 
-impl From<pb::ChainParams> for ChainParams {
-    fn from(msg: pb::ChainParams) -> Self {
-        ChainParams {
+impl TryFrom<pb::ChainParams> for ChainParams {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::ChainParams) -> anyhow::Result<Self> {
+        Ok(ChainParams {
             chain_id: msg.chain_id,
             epoch_duration: msg.epoch_duration,
             unbonding_epochs: msg.unbonding_epochs,
@@ -78,10 +205,23 @@ impl From<pb::ChainParams> for ChainParams {
             base_reward_rate: msg.base_reward_rate,
             missed_blocks_maximum: msg.missed_blocks_maximum,
             signed_blocks_window_len: msg.signed_blocks_window_len,
+            min_fee: msg.min_fee,
+            proposal_min_deposit: msg.proposal_min_deposit,
+            proposal_min_voting_epochs: msg.proposal_min_voting_epochs,
+            proposal_valid_quorum_bps: msg.proposal_valid_quorum_bps,
+            proposal_pass_threshold_bps: msg.proposal_pass_threshold_bps,
+            proposal_veto_threshold_bps: msg.proposal_veto_threshold_bps,
+            dkg_round_blocks: msg.dkg_round_blocks,
+            // Parameters from before proof systems were selectable use the
+            // default, but an unknown proof system is an error.
+            proof_system: match msg.proof_system {
+                Some(proof_system) => proof_system.try_into()?,
+                None => ProofSystem::default(),
+            },
             ibc_enabled: msg.ibc_enabled,
             inbound_ics20_transfers_enabled: msg.inbound_ics20_transfers_enabled,
             outbound_ics20_transfers_enabled: msg.outbound_ics20_transfers_enabled,
-        }
+        })
     }
 }
 
@@ -97,6 +237,14 @@ impl From<ChainParams> for pb::ChainParams {
             slashing_penalty_downtime_bps: params.slashing_penalty_downtime_bps,
             slashing_penalty_misbehavior_bps: params.slashing_penalty_misbehavior_bps,
             base_reward_rate: params.base_reward_rate,
+            min_fee: params.min_fee,
+            proposal_min_deposit: params.proposal_min_deposit,
+            proposal_min_voting_epochs: params.proposal_min_voting_epochs,
+            proposal_valid_quorum_bps: params.proposal_valid_quorum_bps,
+            proposal_pass_threshold_bps: params.proposal_pass_threshold_bps,
+            proposal_veto_threshold_bps: params.proposal_veto_threshold_bps,
+            dkg_round_blocks: params.dkg_round_blocks,
+            proof_system: Some(params.proof_system.into()),
             ibc_enabled: params.ibc_enabled,
             inbound_ics20_transfers_enabled: params.inbound_ics20_transfers_enabled,
             outbound_ics20_transfers_enabled: params.outbound_ics20_transfers_enabled,
@@ -122,6 +270,18 @@ impl Default for ChainParams {
             slashing_penalty_downtime_bps: 1,
             // 3bps -> 11% return over 365 epochs
             base_reward_rate: 3_0000,
+            min_fee: 0,
+            // 10 penumbra
+            proposal_min_deposit: 10_000_000,
+            proposal_min_voting_epochs: 2,
+            // 4000 basis points = 40%
+            proposal_valid_quorum_bps: 4000,
+            // 5000 basis points = 50%
+            proposal_pass_threshold_bps: 5000,
+            // 3340 basis points = 33.4%
+            proposal_veto_threshold_bps: 3340,
+            dkg_round_blocks: 10,
+            proof_system: ProofSystem::default(),
             ibc_enabled: false,
             inbound_ics20_transfers_enabled: false,
             outbound_ics20_transfers_enabled: false,
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use penumbra_crypto::{FieldExt, Nullifier};
+use penumbra_proto::{chain as pb, Protobuf};
+use penumbra_stake::IdentityKey;
+use penumbra_transaction::action::output;
+use serde::{Deserialize, Serialize};
+
+/// Notes and nullifiers from undelegating transactions, held back from the
+/// shielded pool until the unbonding period has elapsed.
+///
+/// Quarantined notes are only added to the note commitment tree (and
+/// quarantined nullifiers only revealed) at the end of their unbonding epoch.
+/// If the validator is slashed before then, the quarantined notes are
+/// discarded, and the quarantined nullifiers are released, so that the
+/// delegation tokens can be spent again.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(try_from = "pb::Quarantined", into = "pb::Quarantined")]
+pub struct Quarantined {
+    groups: BTreeMap<(u64, IdentityKey), QuarantineGroup>,
+}
+
+/// The quarantined notes and nullifiers of undelegations from a single
+/// validator, scheduled to be released at the end of the same epoch.
+#[derive(Clone, Debug)]
+pub struct QuarantineGroup {
+    pub unbonding_epoch: u64,
+    pub identity_key: IdentityKey,
+    pub outputs: Vec<output::Body>,
+    pub nullifiers: Vec<Nullifier>,
+    /// The total amount of delegation tokens undelegated.
+    pub delegation_amount: u64,
+    /// The total amount of unbonded stake produced by the undelegations.
+    pub unbonded_amount: u64,
+}
+
+impl Quarantined {
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Returns the quarantined groups, ordered by unbonding epoch and validator.
+    pub fn groups(&self) -> impl Iterator<Item = &QuarantineGroup> {
+        self.groups.values()
+    }
+
+    /// Adds the outputs and nullifiers of an undelegation to the quarantine,
+    /// merging them with any previously quarantined undelegations from the
+    /// same validator with the same unbonding epoch.
+    pub fn schedule(
+        &mut self,
+        unbonding_epoch: u64,
+        identity_key: IdentityKey,
+        outputs: impl IntoIterator<Item = output::Body>,
+        nullifiers: impl IntoIterator<Item = Nullifier>,
+        delegation_amount: u64,
+        unbonded_amount: u64,
+    ) {
+        let group = self
+            .groups
+            .entry((unbonding_epoch, identity_key.clone()))
+            .or_insert_with(|| QuarantineGroup {
+                unbonding_epoch,
+                identity_key,
+                outputs: Vec::new(),
+                nullifiers: Vec::new(),
+                delegation_amount: 0,
+                unbonded_amount: 0,
+            });
+        group.outputs.extend(outputs);
+        group.nullifiers.extend(nullifiers);
+        group.delegation_amount += delegation_amount;
+        group.unbonded_amount += unbonded_amount;
+    }
+
+    /// Merges another set of quarantined notes and nullifiers into this one.
+    pub fn extend(&mut self, other: Quarantined) {
+        for group in other.groups.into_values() {
+            self.schedule(
+                group.unbonding_epoch,
+                group.identity_key,
+                group.outputs,
+                group.nullifiers,
+                group.delegation_amount,
+                group.unbonded_amount,
+            );
+        }
+    }
+
+    /// Removes and returns all groups for undelegations from the given validator.
+    pub fn remove_validator(&mut self, identity_key: &IdentityKey) -> Vec<QuarantineGroup> {
+        let (removed, kept) = std::mem::take(&mut self.groups)
+            .into_iter()
+            .partition(|((_, ik), _)| ik == identity_key);
+        self.groups = kept;
+        removed.into_values().collect()
+    }
+}
+
+impl Protobuf<pb::Quarantined> for Quarantined {}
+
+impl From<Quarantined> for pb::Quarantined {
+    fn from(q: Quarantined) -> Self {
+        pb::Quarantined {
+            groups: q.groups.into_values().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::Quarantined> for Quarantined {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::Quarantined) -> Result<Self, Self::Error> {
+        let mut quarantined = Quarantined::default();
+        for group in msg.groups {
+            let group = QuarantineGroup::try_from(group)?;
+            quarantined.schedule(
+                group.unbonding_epoch,
+                group.identity_key,
+                group.outputs,
+                group.nullifiers,
+                group.delegation_amount,
+                group.unbonded_amount,
+            );
+        }
+        Ok(quarantined)
+    }
+}
+
+impl From<QuarantineGroup> for pb::QuarantineGroup {
+    fn from(group: QuarantineGroup) -> Self {
+        pb::QuarantineGroup {
+            unbonding_epoch: group.unbonding_epoch,
+            identity_key: Some(group.identity_key.into()),
+            outputs: group.outputs.into_iter().map(Into::into).collect(),
+            nullifiers: group
+                .nullifiers
+                .into_iter()
+                .map(|v| Bytes::copy_from_slice(&v.0.to_bytes()))
+                .collect(),
+            delegation_amount: group.delegation_amount,
+            unbonded_amount: group.unbonded_amount,
+        }
+    }
+}
+
+impl TryFrom<pb::QuarantineGroup> for QuarantineGroup {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::QuarantineGroup) -> Result<Self, Self::Error> {
+        Ok(QuarantineGroup {
+            unbonding_epoch: msg.unbonding_epoch,
+            identity_key: msg
+                .identity_key
+                .ok_or_else(|| anyhow!("missing identity key in quarantine group"))?
+                .try_into()?,
+            outputs: msg
+                .outputs
+                .into_iter()
+                .map(output::Body::try_from)
+                .collect::<Result<Vec<output::Body>>>()?,
+            nullifiers: msg
+                .nullifiers
+                .into_iter()
+                .map(|v| Nullifier::try_from(&*v))
+                .collect::<Result<Vec<Nullifier>>>()?,
+            delegation_amount: msg.delegation_amount,
+            unbonded_amount: msg.unbonded_amount,
+        })
+    }
+}
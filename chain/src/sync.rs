@@ -4,9 +4,12 @@ use anyhow::Result;
 use bytes::Bytes;
 use penumbra_crypto::{FieldExt, Nullifier};
 use penumbra_proto::{chain as pb, Protobuf};
+use penumbra_stake::IdentityKey;
 use penumbra_transaction::action::output;
 use serde::{Deserialize, Serialize};
 
+use crate::Quarantined;
+
 // Domain type for CompactBlock.
 // Contains the minimum data needed to update client state.
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -17,6 +20,10 @@ pub struct CompactBlock {
     pub outputs: Vec<output::Body>,
     // Nullifiers identifying spent notes.
     pub nullifiers: Vec<Nullifier>,
+    // Notes and nullifiers of undelegations in this block, held until their unbonding epoch.
+    pub quarantined: Quarantined,
+    // Validators slashed in this block, whose quarantined undelegations were rolled back.
+    pub slashed: Vec<IdentityKey>,
 }
 
 impl Protobuf<pb::CompactBlock> for CompactBlock {}
@@ -31,6 +38,8 @@ impl From<CompactBlock> for pb::CompactBlock {
                 .into_iter()
                 .map(|v| Bytes::copy_from_slice(&v.0.to_bytes()))
                 .collect(),
+            quarantined: Some(cb.quarantined.into()),
+            slashed: cb.slashed.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -51,6 +60,16 @@ impl TryFrom<pb::CompactBlock> for CompactBlock {
                 .into_iter()
                 .map(|v| Nullifier::try_from(&*v))
                 .collect::<Result<Vec<Nullifier>>>()?,
+            quarantined: value
+                .quarantined
+                .map(Quarantined::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+            slashed: value
+                .slashed
+                .into_iter()
+                .map(IdentityKey::try_from)
+                .collect::<Result<Vec<IdentityKey>>>()?,
         })
     }
 }
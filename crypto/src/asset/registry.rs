@@ -216,5 +216,15 @@ pub static REGISTRY: Lazy<Registry> = Lazy::new(|| {
                 )
             }) as for<'r> fn(&'r str) -> _,
         )
+        .add_asset(
+            // Note: this regex must be in sync with LpNft::try_from in the
+            // penumbra-dex crate.
+            "^lpnft_(?P<data>(opened|closed|withdrawn)_[0-9a-f]{64})$",
+            &[],
+            (|data: &str| {
+                assert!(!data.is_empty());
+                denom::Inner::new(format!("lpnft_{}", data), vec![])
+            }) as for<'r> fn(&'r str) -> _,
+        )
         .build()
 });
@@ -13,7 +13,8 @@ use crate::{
     Fq, Fr, Nullifier,
 };
 
-static IVK_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| Fq::from_le_bytes_mod_order(b"penumbra.derive.ivk"));
+pub(crate) static IVK_DOMAIN_SEP: Lazy<Fq> =
+    Lazy::new(|| Fq::from_le_bytes_mod_order(b"penumbra.derive.ivk"));
 
 /// The `FullViewingKey` allows one to identify incoming and outgoing notes only.
 #[derive(Clone, Serialize, Deserialize)]
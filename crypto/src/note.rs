@@ -49,7 +49,7 @@ pub struct Note {
 }
 
 /// The domain separator used to generate note commitments.
-static NOTECOMMIT_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| {
+pub(crate) static NOTECOMMIT_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| {
     Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(b"penumbra.notecommit").as_bytes())
 });
 
@@ -233,8 +233,8 @@ impl Note {
     }
 }
 
-/// Use Blake2b-256 to derive the symmetric key material for note and memo encryption.
-pub(crate) fn derive_symmetric_key(
+/// Use Blake2b-256 to derive the symmetric key material for note, memo, and swap encryption.
+pub fn derive_symmetric_key(
     shared_secret: &ka::SharedSecret,
     epk: &ka::Public,
 ) -> blake2b_simd::Hash {
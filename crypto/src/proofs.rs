@@ -1 +1,392 @@
+//! Proofs for spends and outputs, abstracted over the proof system that
+//! produces them.
+//!
+//! The [`SpendProof`] and [`OutputProof`] enums carry a proof from any of the
+//! supported backends, and are encoded with a [`ProofSystem`] tag, so that a
+//! chain can switch from one backend to another by changing a parameter.
+
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::anyhow;
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{ka, keys, merkle, note, value, Fr, Note, Nullifier};
+
+pub mod groth16;
 pub mod transparent;
+
+/// A proof system that can prove the statements of spends and outputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofSystem {
+    /// The transparent proofs, which reveal their private inputs.
+    Transparent,
+    /// Groth16 proofs over BLS12-377.
+    Groth16,
+}
+
+impl Default for ProofSystem {
+    /// The transparent proofs.
+    ///
+    /// This is used for chain parameters from before proof systems were
+    /// selectable, so it is fixed rather than depending on how a node was
+    /// built.
+    fn default() -> Self {
+        ProofSystem::Transparent
+    }
+}
+
+impl std::fmt::Display for ProofSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ProofSystem::Transparent => "transparent",
+            ProofSystem::Groth16 => "groth16",
+        })
+    }
+}
+
+impl std::str::FromStr for ProofSystem {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transparent" => Ok(ProofSystem::Transparent),
+            "groth16" => Ok(ProofSystem::Groth16),
+            _ => Err(anyhow!("unknown proof system {}", s)),
+        }
+    }
+}
+
+/// A proof of a statement about its public inputs.
+pub trait Proof {
+    /// The public inputs of the statement.
+    type PublicInputs;
+
+    /// Verifies the proof against the provided public inputs.
+    fn verify(&self, public_inputs: &Self::PublicInputs) -> anyhow::Result<()>;
+}
+
+/// The public inputs of a spend proof.
+#[derive(Clone, Debug)]
+pub struct SpendPublicInputs {
+    /// The merkle root of the note commitment tree.
+    pub anchor: merkle::Root,
+    /// The value commitment of the note to be spent.
+    pub value_commitment: value::Commitment,
+    /// The nullifier of the note to be spent.
+    pub nullifier: Nullifier,
+    /// The randomized verification spend key.
+    pub rk: VerificationKey<SpendAuth>,
+}
+
+/// The public inputs of an output proof.
+#[derive(Clone, Debug)]
+pub struct OutputPublicInputs {
+    /// The value commitment of the new note.
+    pub value_commitment: value::Commitment,
+    /// The note commitment of the new note.
+    pub note_commitment: note::Commitment,
+    /// The ephemeral public key used to generate the new note.
+    pub epk: ka::Public,
+}
+
+impl Proof for transparent::SpendProof {
+    type PublicInputs = SpendPublicInputs;
+
+    fn verify(&self, public_inputs: &SpendPublicInputs) -> anyhow::Result<()> {
+        Ok(transparent::SpendProof::verify(
+            self,
+            public_inputs.anchor.clone(),
+            public_inputs.value_commitment,
+            public_inputs.nullifier,
+            public_inputs.rk,
+        )?)
+    }
+}
+
+impl Proof for transparent::OutputProof {
+    type PublicInputs = OutputPublicInputs;
+
+    fn verify(&self, public_inputs: &OutputPublicInputs) -> anyhow::Result<()> {
+        Ok(transparent::OutputProof::verify(
+            self,
+            public_inputs.value_commitment,
+            public_inputs.note_commitment,
+            public_inputs.epk.clone(),
+        )?)
+    }
+}
+
+impl Proof for groth16::SpendProof {
+    type PublicInputs = SpendPublicInputs;
+
+    fn verify(&self, public_inputs: &SpendPublicInputs) -> anyhow::Result<()> {
+        groth16::SpendProof::verify(
+            self,
+            &groth16::spend_parameters()?.verifying_key,
+            public_inputs.anchor.clone(),
+            public_inputs.value_commitment,
+            public_inputs.nullifier,
+            public_inputs.rk,
+        )
+    }
+}
+
+impl Proof for groth16::OutputProof {
+    type PublicInputs = OutputPublicInputs;
+
+    fn verify(&self, public_inputs: &OutputPublicInputs) -> anyhow::Result<()> {
+        groth16::OutputProof::verify(
+            self,
+            &groth16::output_parameters()?.verifying_key,
+            public_inputs.value_commitment,
+            public_inputs.note_commitment,
+            public_inputs.epk.clone(),
+        )
+    }
+}
+
+/// A spend proof from any of the supported proof systems.
+#[derive(Clone, Debug)]
+pub enum SpendProof {
+    Transparent(transparent::SpendProof),
+    Groth16(groth16::SpendProof),
+}
+
+impl SpendProof {
+    /// Proves that `note`, witnessed in the note commitment tree with root
+    /// `anchor`, is spent, using the given proof system.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        anchor: merkle::Root,
+        note_commitment_proof: merkle::Proof,
+        note: &Note,
+        v_blinding: Fr,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+    ) -> anyhow::Result<Self> {
+        Ok(match proof_system {
+            ProofSystem::Transparent => SpendProof::Transparent(transparent::SpendProof {
+                note_commitment_proof,
+                g_d: note.diversified_generator(),
+                pk_d: note.transmission_key(),
+                value: note.value(),
+                v_blinding,
+                note_commitment: note.commit(),
+                note_blinding: note.note_blinding(),
+                spend_auth_randomizer,
+                ak,
+                nk,
+            }),
+            ProofSystem::Groth16 => SpendProof::Groth16(groth16::SpendProof::prove(
+                rng,
+                &groth16::spend_parameters()?.proving_key,
+                anchor,
+                &note_commitment_proof,
+                note,
+                v_blinding,
+                spend_auth_randomizer,
+                ak,
+                nk,
+            )?),
+        })
+    }
+
+    /// The proof system that produced this proof.
+    pub fn proof_system(&self) -> ProofSystem {
+        match self {
+            SpendProof::Transparent(_) => ProofSystem::Transparent,
+            SpendProof::Groth16(_) => ProofSystem::Groth16,
+        }
+    }
+}
+
+impl Proof for SpendProof {
+    type PublicInputs = SpendPublicInputs;
+
+    fn verify(&self, public_inputs: &SpendPublicInputs) -> anyhow::Result<()> {
+        match self {
+            SpendProof::Transparent(proof) => Proof::verify(proof, public_inputs),
+            SpendProof::Groth16(proof) => Proof::verify(proof, public_inputs),
+        }
+    }
+}
+
+/// An output proof from any of the supported proof systems.
+#[derive(Clone, Debug)]
+pub enum OutputProof {
+    Transparent(transparent::OutputProof),
+    Groth16(groth16::OutputProof),
+}
+
+impl OutputProof {
+    /// Proves that `note` is well-formed, using the given proof system.
+    pub fn prove<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        note: &Note,
+        v_blinding: Fr,
+        esk: &ka::Secret,
+    ) -> anyhow::Result<Self> {
+        Ok(match proof_system {
+            ProofSystem::Transparent => OutputProof::Transparent(transparent::OutputProof {
+                g_d: note.diversified_generator(),
+                pk_d: note.transmission_key(),
+                value: note.value(),
+                v_blinding,
+                note_blinding: note.note_blinding(),
+                esk: esk.clone(),
+            }),
+            ProofSystem::Groth16 => OutputProof::Groth16(groth16::OutputProof::prove(
+                rng,
+                &groth16::output_parameters()?.proving_key,
+                note,
+                v_blinding,
+                esk,
+            )?),
+        })
+    }
+
+    /// The proof system that produced this proof.
+    pub fn proof_system(&self) -> ProofSystem {
+        match self {
+            OutputProof::Transparent(_) => ProofSystem::Transparent,
+            OutputProof::Groth16(_) => ProofSystem::Groth16,
+        }
+    }
+}
+
+impl Proof for OutputProof {
+    type PublicInputs = OutputPublicInputs;
+
+    fn verify(&self, public_inputs: &OutputPublicInputs) -> anyhow::Result<()> {
+        match self {
+            OutputProof::Transparent(proof) => Proof::verify(proof, public_inputs),
+            OutputProof::Groth16(proof) => Proof::verify(proof, public_inputs),
+        }
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::ProofSystem> for ProofSystem {}
+
+impl From<ProofSystem> for pb::ProofSystem {
+    fn from(proof_system: ProofSystem) -> Self {
+        pb::ProofSystem {
+            system: match proof_system {
+                ProofSystem::Transparent => pb::proof_system::ProofSystemEnum::Transparent,
+                ProofSystem::Groth16 => pb::proof_system::ProofSystemEnum::Groth16,
+            } as i32,
+        }
+    }
+}
+
+impl TryFrom<pb::ProofSystem> for ProofSystem {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::ProofSystem) -> anyhow::Result<Self, Self::Error> {
+        match pb::proof_system::ProofSystemEnum::from_i32(proto.system)
+            .ok_or_else(|| anyhow!("invalid proof system"))?
+        {
+            pb::proof_system::ProofSystemEnum::Transparent => Ok(ProofSystem::Transparent),
+            pb::proof_system::ProofSystemEnum::Groth16 => Ok(ProofSystem::Groth16),
+        }
+    }
+}
+
+impl Protobuf<pb::TaggedProof> for SpendProof {}
+
+impl From<SpendProof> for pb::TaggedProof {
+    fn from(proof: SpendProof) -> Self {
+        let proof_system = proof.proof_system();
+        let inner: Vec<u8> = match proof {
+            SpendProof::Transparent(proof) => proof.into(),
+            SpendProof::Groth16(proof) => proof.into(),
+        };
+        pb::TaggedProof {
+            proof_system: Some(proof_system.into()),
+            inner,
+        }
+    }
+}
+
+impl TryFrom<pb::TaggedProof> for SpendProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::TaggedProof) -> anyhow::Result<Self, Self::Error> {
+        let proof_system = proto
+            .proof_system
+            .ok_or_else(|| anyhow!("missing proof system"))?
+            .try_into()?;
+        Ok(match proof_system {
+            ProofSystem::Transparent => SpendProof::Transparent(proto.inner[..].try_into()?),
+            ProofSystem::Groth16 => SpendProof::Groth16(proto.inner[..].try_into()?),
+        })
+    }
+}
+
+impl From<SpendProof> for Vec<u8> {
+    fn from(spend_proof: SpendProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::TaggedProof = spend_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SpendProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SpendProof, Self::Error> {
+        pb::TaggedProof::decode(bytes)?.try_into()
+    }
+}
+
+impl Protobuf<pb::TaggedProof> for OutputProof {}
+
+impl From<OutputProof> for pb::TaggedProof {
+    fn from(proof: OutputProof) -> Self {
+        let proof_system = proof.proof_system();
+        let inner: Vec<u8> = match proof {
+            OutputProof::Transparent(proof) => proof.into(),
+            OutputProof::Groth16(proof) => proof.into(),
+        };
+        pb::TaggedProof {
+            proof_system: Some(proof_system.into()),
+            inner,
+        }
+    }
+}
+
+impl TryFrom<pb::TaggedProof> for OutputProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::TaggedProof) -> anyhow::Result<Self, Self::Error> {
+        let proof_system = proto
+            .proof_system
+            .ok_or_else(|| anyhow!("missing proof system"))?
+            .try_into()?;
+        Ok(match proof_system {
+            ProofSystem::Transparent => OutputProof::Transparent(proto.inner[..].try_into()?),
+            ProofSystem::Groth16 => OutputProof::Groth16(proto.inner[..].try_into()?),
+        })
+    }
+}
+
+impl From<OutputProof> for Vec<u8> {
+    fn from(output_proof: OutputProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::TaggedProof = output_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for OutputProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<OutputProof, Self::Error> {
+        pb::TaggedProof::decode(bytes)?.try_into()
+    }
+}
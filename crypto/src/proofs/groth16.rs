@@ -0,0 +1,153 @@
+//! Groth16 proofs over BLS12-377 for spending and creating notes.
+//!
+//! These prove the same statements as the [`transparent`](super::transparent)
+//! proofs, but without revealing the note, its position in the note commitment
+//! tree, or the keys that control it.  The circuits are defined over the
+//! scalar field of BLS12-377, which is the base field [`Fq`] of decaf377, so
+//! the Poseidon hashes and decaf377 arithmetic are native to the circuit.
+
+use std::path::Path;
+
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey};
+use ark_serialize::CanonicalDeserialize;
+use ark_snark::SNARK;
+use once_cell::sync::OnceCell;
+
+use crate::{merkle, FieldExt, Fq};
+
+pub mod gadgets;
+mod output;
+mod spend;
+
+pub use output::{OutputCircuit, OutputProof};
+pub use spend::{SpendCircuit, SpendProof};
+
+/// The name of the file holding the [`SpendCircuit`] parameters.
+pub const SPEND_PARAMETERS_FILE: &str = "spend.params";
+/// The name of the file holding the [`OutputCircuit`] parameters.
+pub const OUTPUT_PARAMETERS_FILE: &str = "output.params";
+
+/// The proving and verifying keys for a circuit, produced by a setup ceremony.
+pub struct Parameters {
+    pub proving_key: ProvingKey<Bls12_377>,
+    pub verifying_key: PreparedVerifyingKey<Bls12_377>,
+}
+
+impl Parameters {
+    /// Decodes parameters from the canonical serialization of their proving
+    /// key, which includes the verifying key.
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let proving_key = ProvingKey::<Bls12_377>::deserialize(bytes)
+            .map_err(|_| anyhow::anyhow!("malformed Groth16 proving key"))?;
+        let verifying_key = ark_groth16::prepare_verifying_key(&proving_key.vk);
+        Ok(Self {
+            proving_key,
+            verifying_key,
+        })
+    }
+
+    /// Reads parameters from the file at `path`.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("could not read {}: {}", path.display(), e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+static SPEND_PROOF_PARAMETERS: OnceCell<Parameters> = OnceCell::new();
+static OUTPUT_PROOF_PARAMETERS: OnceCell<Parameters> = OnceCell::new();
+
+/// Loads the parameters for both circuits from the output of a setup
+/// ceremony, stored in `dir` as [`SPEND_PARAMETERS_FILE`] and
+/// [`OUTPUT_PARAMETERS_FILE`].
+///
+/// Until this is called, Groth16 proofs can be neither created nor verified.
+/// There are deliberately no built-in parameters: whoever knows the
+/// randomness used to generate them can forge proofs.
+pub fn load_parameters(dir: &Path) -> anyhow::Result<()> {
+    let spend = Parameters::load(&dir.join(SPEND_PARAMETERS_FILE))?;
+    let output = Parameters::load(&dir.join(OUTPUT_PARAMETERS_FILE))?;
+
+    SPEND_PROOF_PARAMETERS
+        .set(spend)
+        .map_err(|_| anyhow::anyhow!("Groth16 parameters were already loaded"))?;
+    OUTPUT_PROOF_PARAMETERS
+        .set(output)
+        .map_err(|_| anyhow::anyhow!("Groth16 parameters were already loaded"))?;
+
+    Ok(())
+}
+
+/// The parameters for the [`SpendCircuit`], if they have been loaded.
+pub fn spend_parameters() -> anyhow::Result<&'static Parameters> {
+    SPEND_PROOF_PARAMETERS
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Groth16 spend parameters have not been loaded"))
+}
+
+/// The parameters for the [`OutputCircuit`], if they have been loaded.
+pub fn output_parameters() -> anyhow::Result<&'static Parameters> {
+    OUTPUT_PROOF_PARAMETERS
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Groth16 output parameters have not been loaded"))
+}
+
+/// Generation of proving and verifying keys for a circuit, for testing.
+#[cfg(test)]
+pub(crate) trait ParameterSetup {
+    /// Runs a circuit-specific setup with randomness from `rng`.
+    ///
+    /// Whoever knows the randomness can forge proofs, so parameters generated
+    /// this way are only suitable for testing.
+    fn generate_test_parameters<R: rand_core::RngCore + rand_core::CryptoRng>(
+        rng: &mut R,
+    ) -> (ProvingKey<Bls12_377>, ark_groth16::VerifyingKey<Bls12_377>);
+}
+
+/// Runs the circuit-specific setup for `circuit`, whose witness values are
+/// ignored.
+#[cfg(test)]
+fn setup<C, R>(
+    circuit: C,
+    rng: &mut R,
+) -> (ProvingKey<Bls12_377>, ark_groth16::VerifyingKey<Bls12_377>)
+where
+    C: ark_relations::r1cs::ConstraintSynthesizer<Fq>,
+    R: rand_core::RngCore + rand_core::CryptoRng,
+{
+    use ark_snark::CircuitSpecificSetupSNARK;
+    Groth16::<Bls12_377>::circuit_specific_setup(circuit, rng)
+        .expect("can perform circuit specific setup")
+}
+
+/// Verifies a proof against its public inputs.
+pub fn verify(
+    verifying_key: &PreparedVerifyingKey<Bls12_377>,
+    public_inputs: &[Fq],
+    proof: &ark_groth16::Proof<Bls12_377>,
+) -> anyhow::Result<bool> {
+    Ok(Groth16::<Bls12_377>::verify_with_processed_vk(
+        verifying_key,
+        public_inputs,
+        proof,
+    )?)
+}
+
+/// Interprets the encoding of a decaf377 element as its `s` value, which is
+/// how elements are passed to the circuits as public inputs.
+pub fn s_value(encoding: [u8; 32]) -> anyhow::Result<Fq> {
+    Fq::from_bytes(encoding).map_err(|_| anyhow::anyhow!("invalid element encoding"))
+}
+
+/// The authentication path of a note commitment proof, from the root to the
+/// leaf, as the circuits take it.
+pub fn auth_path(note_commitment_proof: &merkle::Proof) -> [[Fq; 3]; gadgets::TREE_HEIGHT] {
+    let mut auth_path = [[Fq::from(0u64); 3]; gadgets::TREE_HEIGHT];
+    for (siblings, hashes) in auth_path.iter_mut().zip(note_commitment_proof.auth_path()) {
+        for (sibling, hash) in siblings.iter_mut().zip(hashes) {
+            *sibling = (*hash).into();
+        }
+    }
+    auth_path
+}
@@ -0,0 +1,176 @@
+//! Gadgets for the statements shared by the circuits, including the circuits
+//! of other crates that spend and create notes.
+//!
+//! Each of these mirrors an out-of-circuit computation elsewhere in the crate,
+//! and must be kept in sync with it.
+
+use std::ops::Deref;
+
+use ark_r1cs_std::{prelude::*, uint8::UInt8};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use decaf377::r1cs::{ElementVar, FqVar};
+use poseidon377::r1cs::{hash_1, hash_2, hash_3, hash_4, hash_5};
+
+use crate::{asset, keys, note, nullifier, value, FieldExt, Fq, Fr};
+
+/// The height of the note commitment tree, and so the length of the
+/// authentication path.
+pub const TREE_HEIGHT: usize = 24;
+
+/// The number of bits of a position in the note commitment tree.
+pub const POSITION_BITS: usize = 2 * TREE_HEIGHT;
+
+/// Allocates a scalar as a witness, returning its little-endian bits.
+pub fn scalar_bits(
+    cs: ConstraintSystemRef<Fq>,
+    scalar: Fr,
+) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+    let mut bits = Vec::with_capacity(256);
+    for byte in UInt8::new_witness_vec(cs, &scalar.to_bytes())? {
+        bits.extend(byte.to_bits_le()?);
+    }
+    Ok(bits)
+}
+
+/// Allocates a `u64` as a witness, returning its little-endian bits and its
+/// value as a field element.
+pub fn u64_witness(
+    cs: ConstraintSystemRef<Fq>,
+    value: u64,
+) -> Result<(Vec<Boolean<Fq>>, FqVar), SynthesisError> {
+    let bits = UInt64::new_witness(cs, || Ok(value))?.to_bits_le();
+    let field = Boolean::le_bits_to_fp_var(&bits)?;
+    Ok((bits, field))
+}
+
+/// Enforces that `element` is not the identity.
+///
+/// The use of decaf means that we do not need to check that elements are not
+/// of small order, but we instead check that they are not the identity.
+pub fn enforce_not_identity(element: &ElementVar) -> Result<(), SynthesisError> {
+    element.is_zero()?.enforce_equal(&Boolean::FALSE)
+}
+
+/// The value generator for `asset_id`, as in [`asset::Id::value_generator`].
+pub fn value_generator(
+    cs: ConstraintSystemRef<Fq>,
+    asset_id: &FqVar,
+) -> Result<ElementVar, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *asset::VALUE_GENERATOR_DOMAIN_SEP)?;
+    ElementVar::encode_to_curve(&hash_1(cs, &domain_sep, asset_id.clone())?)
+}
+
+/// The value commitment to `amount` of `asset_id`, as in [`crate::Value::commit`].
+pub fn value_commitment(
+    cs: ConstraintSystemRef<Fq>,
+    amount_bits: &[Boolean<Fq>],
+    asset_id: &FqVar,
+    blinding_bits: &[Boolean<Fq>],
+) -> Result<ElementVar, SynthesisError> {
+    let value_generator = value_generator(cs.clone(), asset_id)?;
+    let blinding_generator =
+        ElementVar::new_constant(cs, *value::VALUE_BLINDING_GENERATOR.deref())?;
+
+    Ok(value_generator.scalar_mul_le(amount_bits.iter())?
+        + blinding_generator.scalar_mul_le(blinding_bits.iter())?)
+}
+
+/// The note commitment, as in [`note::Commitment::new`].
+pub fn note_commitment(
+    cs: ConstraintSystemRef<Fq>,
+    note_blinding: &FqVar,
+    amount: &FqVar,
+    asset_id: &FqVar,
+    diversified_generator: &ElementVar,
+    transmission_key_s: &FqVar,
+) -> Result<FqVar, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *note::NOTECOMMIT_DOMAIN_SEP)?;
+    hash_5(
+        cs,
+        &domain_sep,
+        (
+            note_blinding.clone(),
+            amount.clone(),
+            asset_id.clone(),
+            diversified_generator.compress_to_field()?,
+            transmission_key_s.clone(),
+        ),
+    )
+}
+
+/// The nullifier of the note with the given commitment and position, as in
+/// [`keys::NullifierKey::derive_nullifier`].
+pub fn nullifier(
+    cs: ConstraintSystemRef<Fq>,
+    nk: &FqVar,
+    note_commitment: &FqVar,
+    position: &FqVar,
+) -> Result<FqVar, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *nullifier::NULLIFIER_DOMAIN_SEP)?;
+    hash_3(
+        cs,
+        &domain_sep,
+        (nk.clone(), note_commitment.clone(), position.clone()),
+    )
+}
+
+/// The little-endian bits of the incoming viewing key for `ak` and `nk`, as in
+/// [`keys::FullViewingKey::from_components`].
+///
+/// The key is derived in `Fq` and reduced into `Fr`; since the group has
+/// order `r`, multiplying by the unreduced bits gives the same result.
+pub fn ivk_bits(
+    cs: ConstraintSystemRef<Fq>,
+    ak: &ElementVar,
+    nk: &FqVar,
+) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *keys::IVK_DOMAIN_SEP)?;
+    let ivk_mod_q = hash_2(cs, &domain_sep, (nk.clone(), ak.compress_to_field()?))?;
+    ivk_mod_q.to_bits_le()
+}
+
+/// The root of the note commitment tree, given the commitment, the bits of
+/// its position, and its authentication path ordered from the root to the
+/// leaf, as in [`penumbra_tct::Proof::verify`].
+pub fn merkle_root(
+    cs: ConstraintSystemRef<Fq>,
+    note_commitment: &FqVar,
+    position_bits: &[Boolean<Fq>],
+    auth_path: &[[FqVar; 3]],
+) -> Result<FqVar, SynthesisError> {
+    let leaf_domain_sep = FqVar::new_constant(cs.clone(), *penumbra_tct::DOMAIN_SEPARATOR)?;
+    let mut root = hash_1(cs.clone(), &leaf_domain_sep, note_commitment.clone())?;
+
+    for (height, siblings) in (1u64..).zip(auth_path.iter().rev()) {
+        // The two bits of the position at this height pick which child of
+        // the node the path passes through.
+        let low = &position_bits[2 * (height as usize - 1)];
+        let high = &position_bits[2 * (height as usize - 1) + 1];
+        let is_leftmost = low.not().and(&high.not())?;
+        let is_left = low.and(&high.not())?;
+        let is_right = low.not().and(high)?;
+        let is_rightmost = low.and(high)?;
+
+        let [a, b, c] = siblings;
+        let leftmost = FqVar::conditionally_select(&is_leftmost, &root, a)?;
+        let left = FqVar::conditionally_select(
+            &is_leftmost,
+            a,
+            &FqVar::conditionally_select(&is_left, &root, b)?,
+        )?;
+        let right = FqVar::conditionally_select(
+            &high.not(),
+            b,
+            &FqVar::conditionally_select(&is_right, &root, c)?,
+        )?;
+        let rightmost = FqVar::conditionally_select(&is_rightmost, &root, c)?;
+
+        let domain_sep = FqVar::new_constant(
+            cs.clone(),
+            *penumbra_tct::DOMAIN_SEPARATOR + Fq::from(height),
+        )?;
+        root = hash_4(cs.clone(), &domain_sep, (leftmost, left, right, rightmost))?;
+    }
+
+    Ok(root)
+}
@@ -0,0 +1,270 @@
+use std::convert::{TryFrom, TryInto};
+
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use decaf377::r1cs::{ElementVar, FqVar};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{gadgets, s_value};
+use crate::{ka, note, value, FieldExt, Fq, Fr, Note, Value};
+
+/// The circuit proving that a new note is well-formed.
+///
+/// The public inputs are:
+/// * value commitment of the new note,
+/// * note commitment of the new note,
+/// * the ephemeral public key used to generate the new note,
+///
+/// each as a field element, in that order.
+#[derive(Clone, Debug)]
+pub struct OutputCircuit {
+    // Private inputs
+    /// The diversified base for the destination address.
+    g_d: decaf377::Element,
+    /// The `s` value of the transmission key for the destination address.
+    pk_d: Fq,
+    /// The value of the newly created note.
+    value: Value,
+    /// The blinding factor used for generating the value commitment.
+    v_blinding: Fr,
+    /// The blinding factor used for generating the note commitment.
+    note_blinding: Fq,
+    /// The ephemeral secret key that corresponds to the public key.
+    esk: Fr,
+
+    // Public inputs
+    value_commitment: Fq,
+    note_commitment: Fq,
+    epk: Fq,
+}
+
+impl ConstraintSynthesizer<Fq> for OutputCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        // Witnesses
+        let g_d = ElementVar::new_witness(cs.clone(), || Ok(self.g_d))?;
+        let pk_d = FqVar::new_witness(cs.clone(), || Ok(self.pk_d))?;
+        let (amount_bits, amount) = gadgets::u64_witness(cs.clone(), self.value.amount)?;
+        let asset_id = FqVar::new_witness(cs.clone(), || Ok(self.value.asset_id.0))?;
+        let v_blinding_bits = gadgets::scalar_bits(cs.clone(), self.v_blinding)?;
+        let note_blinding = FqVar::new_witness(cs.clone(), || Ok(self.note_blinding))?;
+        let esk_bits = gadgets::scalar_bits(cs.clone(), self.esk)?;
+
+        // Public inputs
+        let value_commitment = FqVar::new_input(cs.clone(), || Ok(self.value_commitment))?;
+        let note_commitment = FqVar::new_input(cs.clone(), || Ok(self.note_commitment))?;
+        let epk = FqVar::new_input(cs.clone(), || Ok(self.epk))?;
+
+        // Note commitment integrity.
+        gadgets::note_commitment(cs.clone(), &note_blinding, &amount, &asset_id, &g_d, &pk_d)?
+            .enforce_equal(&note_commitment)?;
+
+        // Value commitment integrity: outputs commit to the negated value.
+        gadgets::value_commitment(cs, &amount_bits, &asset_id, &v_blinding_bits)?
+            .negate()?
+            .compress_to_field()?
+            .enforce_equal(&value_commitment)?;
+
+        // Ephemeral public key integrity.
+        g_d.scalar_mul_le(esk_bits.iter())?
+            .compress_to_field()?
+            .enforce_equal(&epk)?;
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        gadgets::enforce_not_identity(&g_d)?;
+
+        Ok(())
+    }
+}
+
+/// A Groth16 proof for new note creation.
+#[derive(Clone, Debug)]
+pub struct OutputProof(Proof<Bls12_377>);
+
+impl OutputProof {
+    /// Proves that `note` is well-formed, with the given value commitment
+    /// blinding factor and ephemeral secret key.
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        proving_key: &ProvingKey<Bls12_377>,
+        note: &Note,
+        v_blinding: Fr,
+        esk: &ka::Secret,
+    ) -> anyhow::Result<Self> {
+        let epk = esk.diversified_public(&note.diversified_generator());
+        let esk = Fr::from_bytes(esk.to_bytes())
+            .map_err(|_| anyhow::anyhow!("invalid ephemeral secret key"))?;
+
+        let circuit = OutputCircuit {
+            g_d: note.diversified_generator(),
+            pk_d: note.transmission_key_s(),
+            value: note.value(),
+            v_blinding,
+            note_blinding: note.note_blinding(),
+            esk,
+            value_commitment: (-note.value().commit(v_blinding)).0.compress_to_field(),
+            note_commitment: note.commit().0,
+            epk: s_value(epk.0)?,
+        };
+        let proof = Groth16::<Bls12_377>::prove(proving_key, circuit, rng)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(OutputProof(proof))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// The public inputs are:
+    /// * value commitment of the new note,
+    /// * note commitment of the new note,
+    /// * the ephemeral public key used to generate the new note.
+    pub fn verify(
+        &self,
+        verifying_key: &PreparedVerifyingKey<Bls12_377>,
+        value_commitment: value::Commitment,
+        note_commitment: note::Commitment,
+        epk: ka::Public,
+    ) -> anyhow::Result<()> {
+        let public_inputs = [
+            value_commitment.0.compress_to_field(),
+            note_commitment.0,
+            s_value(epk.0)?,
+        ];
+
+        if super::verify(verifying_key, &public_inputs, &self.0)? {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("output proof did not verify"))
+        }
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::OutputProof> for OutputProof {}
+
+impl From<OutputProof> for pb::OutputProof {
+    fn from(proof: OutputProof) -> Self {
+        let mut inner = Vec::new();
+        proof.0.serialize(&mut inner).expect("can serialize proof");
+        pb::OutputProof { inner }
+    }
+}
+
+impl TryFrom<pb::OutputProof> for OutputProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::OutputProof) -> anyhow::Result<Self, Self::Error> {
+        Ok(OutputProof(
+            Proof::deserialize(&proto.inner[..])
+                .map_err(|_| anyhow::anyhow!("output proof malformed"))?,
+        ))
+    }
+}
+
+impl From<OutputProof> for Vec<u8> {
+    fn from(output_proof: OutputProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::OutputProof = output_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for OutputProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<OutputProof, Self::Error> {
+        pb::OutputProof::decode(bytes)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use ark_groth16::VerifyingKey;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        asset,
+        keys::{SeedPhrase, SpendKey, SpendSeed},
+        proofs::groth16::{setup, ParameterSetup},
+    };
+
+    impl ParameterSetup for OutputCircuit {
+        fn generate_test_parameters<R: RngCore + CryptoRng>(
+            rng: &mut R,
+        ) -> (ProvingKey<Bls12_377>, VerifyingKey<Bls12_377>) {
+            // The setup only depends on the shape of the circuit, not the values
+            // of its inputs.
+            let circuit = OutputCircuit {
+                g_d: decaf377::basepoint(),
+                pk_d: Fq::from(0u64),
+                value: Value {
+                    amount: 0,
+                    asset_id: asset::Id(Fq::from(0u64)),
+                },
+                v_blinding: Fr::from(0u64),
+                note_blinding: Fq::from(0u64),
+                esk: Fr::from(0u64),
+                value_commitment: Fq::from(0u64),
+                note_commitment: Fq::from(0u64),
+                epk: Fq::from(0u64),
+            };
+            setup(circuit, rng)
+        }
+    }
+
+    #[test]
+    fn output_proof_verifies_only_against_its_public_inputs() {
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let spend_seed = SpendSeed::from_seed_phrase(seed_phrase, 0);
+        let sk_recipient = SpendKey::new(spend_seed);
+        let fvk_recipient = sk_recipient.full_viewing_key();
+        let ivk_recipient = fvk_recipient.incoming();
+        let (dest, _dtk_d) = ivk_recipient.payment_address(0u64.into());
+
+        let value_to_send = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let note = Note::generate(&mut rng, &dest, value_to_send);
+        let v_blinding = Fr::rand(&mut rng);
+        let esk = ka::Secret::new(&mut rng);
+        let epk = esk.diversified_public(&note.diversified_generator());
+
+        let (pk, vk) = OutputCircuit::generate_test_parameters(&mut rng);
+        let vk = ark_groth16::prepare_verifying_key(&vk);
+
+        let proof = OutputProof::prove(&mut rng, &pk, &note, v_blinding, &esk).unwrap();
+
+        let value_commitment = -note.value().commit(v_blinding);
+        assert!(proof
+            .verify(&vk, value_commitment, note.commit(), epk)
+            .is_ok());
+
+        // The proof doesn't verify against the un-negated value commitment...
+        assert!(proof
+            .verify(&vk, -value_commitment, note.commit(), epk)
+            .is_err());
+
+        // ... or a different ephemeral key ...
+        let other_epk = ka::Secret::new(&mut rng).diversified_public(&note.diversified_generator());
+        assert!(proof
+            .verify(&vk, value_commitment, note.commit(), other_epk)
+            .is_err());
+
+        // ... and survives encoding.
+        let bytes: Vec<u8> = proof.into();
+        let proof = OutputProof::try_from(&bytes[..]).unwrap();
+        assert!(proof
+            .verify(&vk, value_commitment, note.commit(), epk)
+            .is_ok());
+    }
+}
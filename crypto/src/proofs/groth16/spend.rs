@@ -0,0 +1,426 @@
+use std::convert::{TryFrom, TryInto};
+
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use decaf377::r1cs::{ElementVar, FqVar};
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{
+    auth_path,
+    gadgets::{self, POSITION_BITS, TREE_HEIGHT},
+    s_value,
+};
+use crate::{keys, merkle, value, Fq, Fr, Note, Nullifier, Value};
+
+/// The circuit proving that a note in the note commitment tree is spent.
+///
+/// The public inputs are:
+/// * the merkle root of the note commitment tree,
+/// * value commitment of the note to be spent,
+/// * nullifier of the note to be spent,
+/// * the randomized verification spend key,
+///
+/// each as a field element, in that order.
+#[derive(Clone, Debug)]
+pub struct SpendCircuit {
+    // Private inputs
+    /// The position of the note commitment in the note commitment tree.
+    position: u64,
+    /// The authentication path of the note commitment, from the root to the leaf.
+    auth_path: [[Fq; 3]; TREE_HEIGHT],
+    /// The diversified base for the address.
+    g_d: decaf377::Element,
+    /// The `s` value of the transmission key for the address.
+    pk_d: Fq,
+    /// The value of the note.
+    value: Value,
+    /// The blinding factor used for generating the value commitment.
+    v_blinding: Fr,
+    /// The blinding factor used for generating the note commitment.
+    note_blinding: Fq,
+    /// The randomizer used for generating the randomized spend auth key.
+    spend_auth_randomizer: Fr,
+    /// The spend authorization key.
+    ak: decaf377::Element,
+    /// The nullifier deriving key.
+    nk: Fq,
+
+    // Public inputs
+    anchor: Fq,
+    value_commitment: Fq,
+    nullifier: Fq,
+    rk: Fq,
+}
+
+impl ConstraintSynthesizer<Fq> for SpendCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        // Witnesses
+        let (position_bits, position) = gadgets::u64_witness(cs.clone(), self.position)?;
+        let auth_path = self
+            .auth_path
+            .iter()
+            .map(|siblings| {
+                Ok([
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[0]))?,
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[1]))?,
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[2]))?,
+                ])
+            })
+            .collect::<ark_relations::r1cs::Result<Vec<_>>>()?;
+        let g_d = ElementVar::new_witness(cs.clone(), || Ok(self.g_d))?;
+        let pk_d = FqVar::new_witness(cs.clone(), || Ok(self.pk_d))?;
+        let (amount_bits, amount) = gadgets::u64_witness(cs.clone(), self.value.amount)?;
+        let asset_id = FqVar::new_witness(cs.clone(), || Ok(self.value.asset_id.0))?;
+        let v_blinding_bits = gadgets::scalar_bits(cs.clone(), self.v_blinding)?;
+        let note_blinding = FqVar::new_witness(cs.clone(), || Ok(self.note_blinding))?;
+        let spend_auth_randomizer_bits =
+            gadgets::scalar_bits(cs.clone(), self.spend_auth_randomizer)?;
+        let ak = ElementVar::new_witness(cs.clone(), || Ok(self.ak))?;
+        let nk = FqVar::new_witness(cs.clone(), || Ok(self.nk))?;
+
+        // Public inputs
+        let anchor = FqVar::new_input(cs.clone(), || Ok(self.anchor))?;
+        let value_commitment = FqVar::new_input(cs.clone(), || Ok(self.value_commitment))?;
+        let nullifier = FqVar::new_input(cs.clone(), || Ok(self.nullifier))?;
+        let rk = FqVar::new_input(cs.clone(), || Ok(self.rk))?;
+
+        // Note commitment integrity.
+        let note_commitment =
+            gadgets::note_commitment(cs.clone(), &note_blinding, &amount, &asset_id, &g_d, &pk_d)?;
+
+        // Merkle path integrity: positions in the tree have at most 48 bits.
+        for bit in &position_bits[POSITION_BITS..] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        gadgets::merkle_root(cs.clone(), &note_commitment, &position_bits, &auth_path)?
+            .enforce_equal(&anchor)?;
+
+        // Value commitment integrity.
+        gadgets::value_commitment(cs.clone(), &amount_bits, &asset_id, &v_blinding_bits)?
+            .compress_to_field()?
+            .enforce_equal(&value_commitment)?;
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        gadgets::enforce_not_identity(&g_d)?;
+        gadgets::enforce_not_identity(&ak)?;
+
+        // Nullifier integrity.
+        gadgets::nullifier(cs.clone(), &nk, &note_commitment, &position)?
+            .enforce_equal(&nullifier)?;
+
+        // Spend authority.
+        let basepoint = ElementVar::new_constant(cs.clone(), decaf377::basepoint())?;
+        (ak.clone() + basepoint.scalar_mul_le(spend_auth_randomizer_bits.iter())?)
+            .compress_to_field()?
+            .enforce_equal(&rk)?;
+
+        // Diversified address integrity.
+        let ivk_bits = gadgets::ivk_bits(cs, &ak, &nk)?;
+        g_d.scalar_mul_le(ivk_bits.iter())?
+            .compress_to_field()?
+            .enforce_equal(&pk_d)?;
+
+        Ok(())
+    }
+}
+
+/// A Groth16 proof for spending existing notes.
+#[derive(Clone, Debug)]
+pub struct SpendProof(Proof<Bls12_377>);
+
+impl SpendProof {
+    /// Proves that `note`, witnessed in the note commitment tree with root
+    /// `anchor`, is spent with the given blinding factor and randomizer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        proving_key: &ProvingKey<Bls12_377>,
+        anchor: merkle::Root,
+        note_commitment_proof: &merkle::Proof,
+        note: &Note,
+        v_blinding: Fr,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+    ) -> anyhow::Result<Self> {
+        let position = note_commitment_proof.position();
+
+        let ak_element = decaf377::Encoding(ak.into())
+            .decompress()
+            .map_err(|_| anyhow::anyhow!("invalid spend authorization key"))?;
+        let rk: [u8; 32] = ak.randomize(&spend_auth_randomizer).into();
+
+        let circuit = SpendCircuit {
+            position: position.into(),
+            auth_path: auth_path(note_commitment_proof),
+            g_d: note.diversified_generator(),
+            pk_d: note.transmission_key_s(),
+            value: note.value(),
+            v_blinding,
+            note_blinding: note.note_blinding(),
+            spend_auth_randomizer,
+            ak: ak_element,
+            nk: nk.0,
+            anchor: anchor.into(),
+            value_commitment: note.value().commit(v_blinding).0.compress_to_field(),
+            nullifier: nk.derive_nullifier(position, &note.commit()).0,
+            rk: s_value(rk)?,
+        };
+        let proof = Groth16::<Bls12_377>::prove(proving_key, circuit, rng)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(SpendProof(proof))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// The public inputs are:
+    /// * the merkle root of the note commitment tree,
+    /// * value commitment of the note to be spent,
+    /// * nullifier of the note to be spent,
+    /// * the randomized verification spend key,
+    pub fn verify(
+        &self,
+        verifying_key: &PreparedVerifyingKey<Bls12_377>,
+        anchor: merkle::Root,
+        value_commitment: value::Commitment,
+        nullifier: Nullifier,
+        rk: VerificationKey<SpendAuth>,
+    ) -> anyhow::Result<()> {
+        let public_inputs = [
+            anchor.into(),
+            value_commitment.0.compress_to_field(),
+            nullifier.0,
+            s_value(rk.into())?,
+        ];
+
+        if super::verify(verifying_key, &public_inputs, &self.0)? {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("spend proof did not verify"))
+        }
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::SpendProof> for SpendProof {}
+
+impl From<SpendProof> for pb::SpendProof {
+    fn from(proof: SpendProof) -> Self {
+        let mut inner = Vec::new();
+        proof.0.serialize(&mut inner).expect("can serialize proof");
+        pb::SpendProof { inner }
+    }
+}
+
+impl TryFrom<pb::SpendProof> for SpendProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::SpendProof) -> anyhow::Result<Self, Self::Error> {
+        Ok(SpendProof(
+            Proof::deserialize(&proto.inner[..])
+                .map_err(|_| anyhow::anyhow!("spend proof malformed"))?,
+        ))
+    }
+}
+
+impl From<SpendProof> for Vec<u8> {
+    fn from(spend_proof: SpendProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::SpendProof = spend_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SpendProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SpendProof, Self::Error> {
+        pb::SpendProof::decode(bytes)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use ark_groth16::VerifyingKey;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        asset,
+        keys::{SeedPhrase, SpendKey, SpendSeed},
+        merkle::{Keep, NoteCommitmentTree},
+        proofs::groth16::{setup, ParameterSetup},
+    };
+
+    impl ParameterSetup for SpendCircuit {
+        fn generate_test_parameters<R: RngCore + CryptoRng>(
+            rng: &mut R,
+        ) -> (ProvingKey<Bls12_377>, VerifyingKey<Bls12_377>) {
+            // The setup only depends on the shape of the circuit, not the values
+            // of its inputs.
+            let circuit = SpendCircuit {
+                position: 0,
+                auth_path: [[Fq::from(0u64); 3]; TREE_HEIGHT],
+                g_d: decaf377::basepoint(),
+                pk_d: Fq::from(0u64),
+                value: Value {
+                    amount: 0,
+                    asset_id: asset::Id(Fq::from(0u64)),
+                },
+                v_blinding: Fr::from(0u64),
+                note_blinding: Fq::from(0u64),
+                spend_auth_randomizer: Fr::from(0u64),
+                ak: decaf377::basepoint(),
+                nk: Fq::from(0u64),
+                anchor: Fq::from(0u64),
+                value_commitment: Fq::from(0u64),
+                nullifier: Fq::from(0u64),
+                rk: Fq::from(0u64),
+            };
+            setup(circuit, rng)
+        }
+    }
+
+    #[test]
+    fn spend_proof_verifies_only_against_its_public_inputs() {
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let spend_seed = SpendSeed::from_seed_phrase(seed_phrase, 0);
+        let sk_sender = SpendKey::new(spend_seed);
+        let fvk_sender = sk_sender.full_viewing_key();
+        let ivk_sender = fvk_sender.incoming();
+        let (sender, _dtk_d) = ivk_sender.payment_address(0u64.into());
+
+        let value_to_send = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let note = Note::generate(&mut rng, &sender, value_to_send);
+
+        let mut nct = NoteCommitmentTree::new();
+        nct.insert(Keep, note.commit()).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note.commit()).unwrap();
+
+        let v_blinding = Fr::rand(&mut rng);
+        let spend_auth_randomizer = Fr::rand(&mut rng);
+        let ak: VerificationKey<SpendAuth> = (*sk_sender.spend_auth_key()).into();
+        let nk = *sk_sender.nullifier_key();
+        let rk: VerificationKey<SpendAuth> = sk_sender
+            .spend_auth_key()
+            .randomize(&spend_auth_randomizer)
+            .into();
+        let nullifier = nk.derive_nullifier(note_commitment_proof.position(), &note.commit());
+
+        let (pk, vk) = SpendCircuit::generate_test_parameters(&mut rng);
+        let vk = ark_groth16::prepare_verifying_key(&vk);
+
+        let proof = SpendProof::prove(
+            &mut rng,
+            &pk,
+            anchor,
+            &note_commitment_proof,
+            &note,
+            v_blinding,
+            spend_auth_randomizer,
+            ak,
+            nk,
+        )
+        .unwrap();
+
+        let value_commitment = note.value().commit(v_blinding);
+        assert!(proof
+            .verify(&vk, anchor, value_commitment, nullifier, rk)
+            .is_ok());
+
+        // The proof doesn't verify against a different value commitment...
+        let other_commitment = note.value().commit(Fr::rand(&mut rng));
+        assert!(proof
+            .verify(&vk, anchor, other_commitment, nullifier, rk)
+            .is_err());
+
+        // ... or a different nullifier ...
+        let other_nullifier = Nullifier(Fq::rand(&mut rng));
+        assert!(proof
+            .verify(&vk, anchor, value_commitment, other_nullifier, rk)
+            .is_err());
+
+        // ... and survives encoding.
+        let bytes: Vec<u8> = proof.into();
+        let proof = SpendProof::try_from(&bytes[..]).unwrap();
+        assert!(proof
+            .verify(&vk, anchor, value_commitment, nullifier, rk)
+            .is_ok());
+    }
+
+    #[test]
+    fn spend_circuit_rejects_wrong_anchor() {
+        let mut rng = OsRng;
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let spend_seed = SpendSeed::from_seed_phrase(seed_phrase, 0);
+        let sk_sender = SpendKey::new(spend_seed);
+        let (sender, _dtk_d) = sk_sender
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let value_to_send = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let note = Note::generate(&mut rng, &sender, value_to_send);
+
+        let mut nct = NoteCommitmentTree::new();
+        nct.insert(Keep, note.commit()).unwrap();
+        let note_commitment_proof = nct.witness(note.commit()).unwrap();
+        let position = note_commitment_proof.position();
+        let auth_path = auth_path(&note_commitment_proof);
+
+        let v_blinding = Fr::rand(&mut rng);
+        let spend_auth_randomizer = Fr::rand(&mut rng);
+        let ak: VerificationKey<SpendAuth> = (*sk_sender.spend_auth_key()).into();
+        let nk = *sk_sender.nullifier_key();
+        let rk: [u8; 32] = ak.randomize(&spend_auth_randomizer).into();
+
+        let circuit = |anchor: Fq| SpendCircuit {
+            position: position.into(),
+            auth_path,
+            g_d: note.diversified_generator(),
+            pk_d: note.transmission_key_s(),
+            value: note.value(),
+            v_blinding,
+            note_blinding: note.note_blinding(),
+            spend_auth_randomizer,
+            ak: decaf377::Encoding(ak.into()).decompress().unwrap(),
+            nk: nk.0,
+            anchor,
+            value_commitment: note.value().commit(v_blinding).0.compress_to_field(),
+            nullifier: nk.derive_nullifier(position, &note.commit()).0,
+            rk: s_value(rk).unwrap(),
+        };
+
+        let cs = ConstraintSystem::new_ref();
+        circuit(nct.root().into())
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let cs = ConstraintSystem::new_ref();
+        circuit(Fq::rand(&mut rng))
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -7,7 +7,7 @@ use decaf377_rdsa::{SpendAuth, VerificationKey};
 use penumbra_proto::{transparent_proofs, Message, Protobuf};
 use thiserror;
 
-use crate::{asset, ka, keys, merkle, merkle::Hashable, note, value, Fq, Fr, Nullifier, Value};
+use crate::{asset, ka, keys, merkle, note, value, Fq, Fr, Nullifier, Value};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -23,8 +23,6 @@ pub enum Error {
     EphemeralPublicKeyMismatch,
     #[error("Must not be an identity")]
     IdentityUnexpected,
-    #[error("Unexpected depth for merkle path")]
-    MerklePathMismatch,
     #[error("Merkle root mismatch")]
     MerkleRootMismatch,
     #[error("Invalid diversified address")]
@@ -40,10 +38,8 @@ pub enum Error {
 /// This structure keeps track of the auxiliary (private) inputs.
 #[derive(Clone, Debug)]
 pub struct SpendProof {
-    // Path to the note being spent in the note commitment merkle tree.
-    pub merkle_path: merkle::Path,
-    // Position of the note being spent in the note commitment merkle tree.
-    pub position: merkle::Position,
+    // Inclusion proof for the note commitment, including its position in the note commitment tree.
+    pub note_commitment_proof: merkle::Proof,
     // The diversified base for the address.
     pub g_d: decaf377::Element,
     // The transmission key for the address.
@@ -93,32 +89,13 @@ impl SpendProof {
         }
 
         // Merkle path integrity.
-        // 1. Check the Merkle path is a depth of `merkle::DEPTH`.
-        if self.merkle_path.1.len() != merkle::DEPTH {
-            return Err(Error::MerklePathMismatch);
+        // 1. Check the Merkle path is for the note commitment being spent.
+        if note::Commitment::from(self.note_commitment_proof.commitment()) != self.note_commitment {
+            return Err(Error::NoteCommitmentMismatch);
         }
 
         // 2. Check the Merkle path leads to the expected anchor (`merkle::Root`).
-        let mut cur = self.note_commitment;
-
-        // This logic is from `incrementalmerkletree`'s `compute_root_from_auth_path` function which is
-        // `pub(crate)` so is included below.
-        let mut lvl = merkle::Altitude::zero();
-        for (i, v) in self.merkle_path.1.iter().enumerate().map(|(i, v)| {
-            (
-                ((<usize>::try_from(self.position).unwrap() >> i) & 1) == 1,
-                v,
-            )
-        }) {
-            if i {
-                cur = note::Commitment::combine(lvl, v, &cur);
-            } else {
-                cur = note::Commitment::combine(lvl, &cur, v);
-            }
-            lvl = lvl + 1;
-        }
-        let expected_root = merkle::Root(cur.0);
-        if expected_root != anchor {
+        if self.note_commitment_proof.verify(anchor).is_err() {
             return Err(Error::MerkleRootMismatch);
         }
 
@@ -138,7 +115,7 @@ impl SpendProof {
         if nullifier
             != self
                 .nk
-                .derive_nullifier(self.position, &self.note_commitment)
+                .derive_nullifier(self.note_commitment_proof.position(), &self.note_commitment)
         {
             return Err(Error::BadNullifier);
         }
@@ -237,14 +214,7 @@ impl From<SpendProof> for transparent_proofs::SpendProof {
         let ak_bytes: [u8; 32] = msg.ak.into();
         let nk_bytes: [u8; 32] = msg.nk.0.to_bytes();
         transparent_proofs::SpendProof {
-            merkle_path_field_0: u64::from(msg.merkle_path.0) as u32,
-            merkle_path_field_1: msg
-                .merkle_path
-                .1
-                .into_iter()
-                .map(|x| x.0.to_bytes().into())
-                .collect(),
-            position: msg.position.into(),
+            note_commitment_proof: Some(msg.note_commitment_proof.into()),
             g_d: msg.g_d.compress().0.to_vec(),
             pk_d: msg.pk_d.0.to_vec(),
             value_amount: msg.value.amount,
@@ -275,18 +245,12 @@ impl TryFrom<transparent_proofs::SpendProof> for SpendProof {
             .map_err(|_| Error::ProtoMalformed)?;
         let ak = ak_bytes.try_into().map_err(|_| Error::ProtoMalformed)?;
 
-        let mut merkle_path_vec = Vec::<note::Commitment>::new();
-        for merkle_path_segment in proto.merkle_path_field_1 {
-            merkle_path_vec.push(
-                merkle_path_segment[..]
-                    .try_into()
-                    .map_err(|_| Error::ProtoMalformed)?,
-            );
-        }
-
         Ok(SpendProof {
-            merkle_path: ((proto.merkle_path_field_0 as usize).into(), merkle_path_vec),
-            position: (proto.position as usize).into(),
+            note_commitment_proof: proto
+                .note_commitment_proof
+                .ok_or(Error::ProtoMalformed)?
+                .try_into()
+                .map_err(|_| Error::ProtoMalformed)?,
             g_d: g_d_encoding
                 .decompress()
                 .map_err(|_| Error::ProtoMalformed)?,
@@ -437,9 +401,8 @@ mod tests {
     use super::*;
     use crate::{
         keys::{SeedPhrase, SpendKey, SpendSeed},
-        merkle,
-        merkle::{Frontier, Tree, TreeExt},
-        note, Note, Value,
+        merkle::{self, Keep},
+        Note, Value,
     };
 
     #[test]
@@ -652,15 +615,13 @@ mod tests {
         let rsk = sk_sender.spend_auth_key().randomize(&spend_auth_randomizer);
         let nk = *sk_sender.nullifier_key();
         let ak = sk_sender.spend_auth_key().into();
-        let mut nct = merkle::BridgeTree::<note::Commitment, 32>::new(5);
-        nct.append(&note_commitment);
-        let anchor = nct.root2();
-        nct.witness();
-        let merkle_path = nct.authentication_path(&note_commitment).unwrap();
+        let mut nct = merkle::NoteCommitmentTree::new();
+        nct.insert(Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
 
         let proof = SpendProof {
-            merkle_path,
-            position: 0.into(),
+            note_commitment_proof,
             g_d: *sender.diversified_generator(),
             pk_d: *sender.transmission_key(),
             value: value_to_send,
@@ -673,7 +634,7 @@ mod tests {
         };
 
         let rk: VerificationKey<SpendAuth> = rsk.into();
-        let nf = nk.derive_nullifier(0.into(), &note_commitment);
+        let nf = nk.derive_nullifier(0u64.into(), &note_commitment);
         assert!(proof
             .verify(anchor, value_to_send.commit(v_blinding), nf, rk)
             .is_ok());
@@ -701,15 +662,13 @@ mod tests {
         let rsk = sk_sender.spend_auth_key().randomize(&spend_auth_randomizer);
         let nk = *sk_sender.nullifier_key();
         let ak = sk_sender.spend_auth_key().into();
-        let mut nct = merkle::BridgeTree::<note::Commitment, 32>::new(5);
-        let incorrect_anchor = nct.root2();
-        nct.append(&note_commitment);
-        nct.witness();
-        let merkle_path = nct.authentication_path(&note_commitment).unwrap();
+        let mut nct = merkle::NoteCommitmentTree::new();
+        let incorrect_anchor = nct.root();
+        nct.insert(Keep, note_commitment).unwrap();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
 
         let proof = SpendProof {
-            merkle_path,
-            position: 0.into(),
+            note_commitment_proof,
             g_d: *sender.diversified_generator(),
             pk_d: *sender.transmission_key(),
             value: value_to_send,
@@ -722,7 +681,7 @@ mod tests {
         };
 
         let rk: VerificationKey<SpendAuth> = rsk.into();
-        let nf = nk.derive_nullifier(0.into(), &note_commitment);
+        let nf = nk.derive_nullifier(0u64.into(), &note_commitment);
         assert!(proof
             .verify(incorrect_anchor, value_to_send.commit(v_blinding), nf, rk)
             .is_err());
@@ -749,15 +708,13 @@ mod tests {
         let rsk = sk_sender.spend_auth_key().randomize(&spend_auth_randomizer);
         let nk = *sk_sender.nullifier_key();
         let ak = sk_sender.spend_auth_key().into();
-        let mut nct = merkle::BridgeTree::<note::Commitment, 32>::new(5);
-        nct.append(&note_commitment);
-        nct.witness();
-        let anchor = nct.root2();
-        let merkle_path = nct.authentication_path(&note_commitment).unwrap();
+        let mut nct = merkle::NoteCommitmentTree::new();
+        nct.insert(Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
 
         let proof = SpendProof {
-            merkle_path,
-            position: 0.into(),
+            note_commitment_proof,
             g_d: *sender.diversified_generator(),
             pk_d: *sender.transmission_key(),
             value: value_to_send,
@@ -770,7 +727,7 @@ mod tests {
         };
 
         let rk: VerificationKey<SpendAuth> = rsk.into();
-        let nf = nk.derive_nullifier(0.into(), &note_commitment);
+        let nf = nk.derive_nullifier(0u64.into(), &note_commitment);
         assert!(proof
             .verify(anchor, value_to_send.commit(Fr::rand(&mut rng)), nf, rk)
             .is_err());
@@ -797,15 +754,13 @@ mod tests {
         let rsk = sk_sender.spend_auth_key().randomize(&spend_auth_randomizer);
         let nk = *sk_sender.nullifier_key();
         let ak = sk_sender.spend_auth_key().into();
-        let mut nct = merkle::BridgeTree::<note::Commitment, 32>::new(5);
-        nct.append(&note_commitment);
-        nct.witness();
-        let anchor = nct.root2();
-        let merkle_path = nct.authentication_path(&note_commitment).unwrap();
+        let mut nct = merkle::NoteCommitmentTree::new();
+        nct.insert(Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
 
         let proof = SpendProof {
-            merkle_path,
-            position: 0.into(),
+            note_commitment_proof,
             g_d: *sender.diversified_generator(),
             pk_d: *sender.transmission_key(),
             value: value_to_send,
@@ -818,7 +773,7 @@ mod tests {
         };
 
         let rk: VerificationKey<SpendAuth> = rsk.into();
-        let incorrect_nf = nk.derive_nullifier(5.into(), &note_commitment);
+        let incorrect_nf = nk.derive_nullifier(5u64.into(), &note_commitment);
         assert!(proof
             .verify(anchor, value_to_send.commit(v_blinding), incorrect_nf, rk)
             .is_err());
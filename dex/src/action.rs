@@ -0,0 +1,12 @@
+//! DEX-related transaction actions.
+//!
+//! Swaps and swap claims create and spend notes, so they're defined alongside
+//! outputs and spends in the transaction crate.
+
+mod position_close;
+mod position_open;
+mod position_withdraw;
+
+pub use position_close::PositionClose;
+pub use position_open::PositionOpen;
+pub use position_withdraw::PositionWithdraw;
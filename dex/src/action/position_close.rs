@@ -0,0 +1,50 @@
+use anyhow::{anyhow, Result};
+use penumbra_crypto::{value, Fr, Zero};
+use penumbra_proto::{dex as pb, Protobuf};
+
+use crate::{
+    position::{self, State},
+    LpNft,
+};
+
+/// Closes a liquidity position, so that it no longer provides liquidity.
+///
+/// The LP NFT for the opened position is consumed from the transaction's
+/// balance, and one for the closed position is added to it.
+#[derive(Debug, Clone)]
+pub struct PositionClose {
+    pub position_id: position::Id,
+}
+
+impl PositionClose {
+    pub fn value_commitment(&self) -> value::Commitment {
+        LpNft::new(self.position_id, State::Closed)
+            .value()
+            .commit(Fr::zero())
+            - LpNft::new(self.position_id, State::Opened)
+                .value()
+                .commit(Fr::zero())
+    }
+}
+
+impl Protobuf<pb::PositionClose> for PositionClose {}
+
+impl From<PositionClose> for pb::PositionClose {
+    fn from(p: PositionClose) -> Self {
+        pb::PositionClose {
+            position_id: Some(p.position_id.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::PositionClose> for PositionClose {
+    type Error = anyhow::Error;
+    fn try_from(p: pb::PositionClose) -> Result<Self> {
+        Ok(PositionClose {
+            position_id: p
+                .position_id
+                .ok_or_else(|| anyhow!("missing position ID"))?
+                .try_into()?,
+        })
+    }
+}
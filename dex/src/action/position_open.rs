@@ -0,0 +1,71 @@
+use anyhow::{anyhow, Result};
+use penumbra_crypto::{value, Fr, Value, Zero};
+use penumbra_proto::{dex as pb, Protobuf};
+
+use crate::{
+    position::{Reserves, State},
+    LpNft, Position,
+};
+
+/// Opens a liquidity position, funded with its initial reserves.
+///
+/// The reserves are consumed from the transaction's balance, and an LP NFT
+/// for the opened position is added to it.
+#[derive(Debug, Clone)]
+pub struct PositionOpen {
+    pub position: Position,
+    pub initial_reserves: Reserves,
+}
+
+impl PositionOpen {
+    /// The LP NFT created by opening the position.
+    pub fn lp_nft(&self) -> LpNft {
+        LpNft::new(self.position.id(), State::Opened)
+    }
+
+    /// The values of the initial reserves of assets 1 and 2.
+    pub fn reserves(&self) -> [Value; 2] {
+        [
+            Value {
+                amount: self.initial_reserves.r1,
+                asset_id: self.position.trading_pair.asset_1(),
+            },
+            Value {
+                amount: self.initial_reserves.r2,
+                asset_id: self.position.trading_pair.asset_2(),
+            },
+        ]
+    }
+
+    pub fn value_commitment(&self) -> value::Commitment {
+        let [r1, r2] = self.reserves();
+        self.lp_nft().value().commit(Fr::zero()) - r1.commit(Fr::zero()) - r2.commit(Fr::zero())
+    }
+}
+
+impl Protobuf<pb::PositionOpen> for PositionOpen {}
+
+impl From<PositionOpen> for pb::PositionOpen {
+    fn from(p: PositionOpen) -> Self {
+        pb::PositionOpen {
+            position: Some(p.position.into()),
+            initial_reserves: Some(p.initial_reserves.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::PositionOpen> for PositionOpen {
+    type Error = anyhow::Error;
+    fn try_from(p: pb::PositionOpen) -> Result<Self> {
+        Ok(PositionOpen {
+            position: p
+                .position
+                .ok_or_else(|| anyhow!("missing position"))?
+                .try_into()?,
+            initial_reserves: p
+                .initial_reserves
+                .ok_or_else(|| anyhow!("missing initial reserves"))?
+                .try_into()?,
+        })
+    }
+}
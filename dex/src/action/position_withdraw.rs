@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use penumbra_crypto::{value, Fr, Value, Zero};
+use penumbra_proto::{dex as pb, Protobuf};
+
+use crate::{
+    position::{self, Reserves, State},
+    LpNft, TradingPair,
+};
+
+/// Withdraws the reserves of a closed liquidity position.
+///
+/// The LP NFT for the closed position is consumed from the transaction's
+/// balance, and the position's final reserves and an LP NFT for the withdrawn
+/// position are added to it.
+#[derive(Debug, Clone)]
+pub struct PositionWithdraw {
+    pub position_id: position::Id,
+    /// The trading pair of the position, which determines the asset types of
+    /// its reserves.
+    pub trading_pair: TradingPair,
+    /// The final reserves of the position, which must match those recorded by
+    /// the chain.
+    pub reserves: Reserves,
+}
+
+impl PositionWithdraw {
+    /// The values of the withdrawn reserves of assets 1 and 2.
+    pub fn reserves(&self) -> [Value; 2] {
+        [
+            Value {
+                amount: self.reserves.r1,
+                asset_id: self.trading_pair.asset_1(),
+            },
+            Value {
+                amount: self.reserves.r2,
+                asset_id: self.trading_pair.asset_2(),
+            },
+        ]
+    }
+
+    pub fn value_commitment(&self) -> value::Commitment {
+        let [r1, r2] = self.reserves();
+        LpNft::new(self.position_id, State::Withdrawn)
+            .value()
+            .commit(Fr::zero())
+            - LpNft::new(self.position_id, State::Closed)
+                .value()
+                .commit(Fr::zero())
+            + r1.commit(Fr::zero())
+            + r2.commit(Fr::zero())
+    }
+}
+
+impl Protobuf<pb::PositionWithdraw> for PositionWithdraw {}
+
+impl From<PositionWithdraw> for pb::PositionWithdraw {
+    fn from(p: PositionWithdraw) -> Self {
+        pb::PositionWithdraw {
+            position_id: Some(p.position_id.into()),
+            trading_pair: Some(p.trading_pair.into()),
+            reserves: Some(p.reserves.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::PositionWithdraw> for PositionWithdraw {
+    type Error = anyhow::Error;
+    fn try_from(p: pb::PositionWithdraw) -> Result<Self> {
+        Ok(PositionWithdraw {
+            position_id: p
+                .position_id
+                .ok_or_else(|| anyhow!("missing position ID"))?
+                .try_into()?,
+            trading_pair: p
+                .trading_pair
+                .ok_or_else(|| anyhow!("missing trading pair"))?
+                .try_into()?,
+            reserves: p
+                .reserves
+                .ok_or_else(|| anyhow!("missing reserves"))?
+                .try_into()?,
+        })
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::{anyhow, Result};
+use penumbra_proto::{dex as pb, Protobuf};
+
+use crate::{
+    position::{self, PRICE_SCALE},
+    TradingPair,
+};
+
+/// The result of clearing a block's batch of swaps on a trading pair.
+///
+/// Every swap in the batch trades at the same price, so each swap's share of
+/// the batch outputs is proportional to its share of the batch inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSwapOutputData {
+    /// The height of the block whose swaps make up the batch.
+    pub height: u64,
+    /// The trading pair the batch was cleared on.
+    pub trading_pair: TradingPair,
+    /// The total amount of asset 1 swapped for asset 2.
+    pub delta_1: u64,
+    /// The total amount of asset 2 swapped for asset 1.
+    pub delta_2: u64,
+    /// The total amount of asset 1 paid out to swaps of asset 2.
+    pub lambda_1: u64,
+    /// The total amount of asset 2 paid out to swaps of asset 1.
+    pub lambda_2: u64,
+    /// The total amount of asset 1 returned unfilled.
+    pub unfilled_1: u64,
+    /// The total amount of asset 2 returned unfilled.
+    pub unfilled_2: u64,
+}
+
+impl BatchSwapOutputData {
+    /// Clears a batch by matching its two sides against each other, without
+    /// any liquidity positions.
+    ///
+    /// The batch clears at the single price of `delta_2 / delta_1` units of
+    /// asset 2 per unit of asset 1, at which both sides are filled entirely.
+    /// If either side is empty there is no counterparty, so every input is
+    /// returned unfilled.
+    pub fn clear(height: u64, trading_pair: TradingPair, delta_1: u64, delta_2: u64) -> Self {
+        let (lambda_1, lambda_2, unfilled_1, unfilled_2) = if delta_1 > 0 && delta_2 > 0 {
+            (delta_1, delta_2, 0, 0)
+        } else {
+            (0, 0, delta_1, delta_2)
+        };
+        Self {
+            height,
+            trading_pair,
+            delta_1,
+            delta_2,
+            lambda_1,
+            lambda_2,
+            unfilled_1,
+            unfilled_2,
+        }
+    }
+
+    /// Clears a batch against the open liquidity positions on its trading
+    /// pair, updating their reserves.
+    ///
+    /// The batch clears at the lowest price at which the asset 1 offered by
+    /// the swaps and positions is worth at least the asset 2 offered, found by
+    /// bisection.  Every offer is then filled in the same proportion, rounded
+    /// in the chain's favour, so that the batch never pays out more than it
+    /// takes in.  Without any positions, this is the same as [`clear`](Self::clear).
+    pub fn clear_with_positions(
+        height: u64,
+        trading_pair: TradingPair,
+        delta_1: u64,
+        delta_2: u64,
+        positions: &mut [position::Metadata],
+    ) -> Self {
+        if positions.is_empty() {
+            return Self::clear(height, trading_pair, delta_1, delta_2);
+        }
+
+        // None of these sums can overflow a `u128`, or even a `u64`, since
+        // the swap inputs and position reserves of each asset are bounded by
+        // its token supply.
+        let offered = |price: u128| {
+            let mut offered_1 = delta_1 as u128;
+            let mut offered_2 = delta_2 as u128;
+            for metadata in positions.iter() {
+                let (sell, buy) = metadata.position.offered(&metadata.reserves, price);
+                offered_1 += sell;
+                offered_2 += buy;
+            }
+            (offered_1, offered_2)
+        };
+
+        // The value of the offered asset 1 is increasing in the price, and the
+        // offered asset 2 is decreasing, so the clearing price can be found by
+        // bisection.
+        let (mut low, mut high) = (1u128, u64::MAX as u128);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (offered_1, offered_2) = offered(mid);
+            if offered_1.saturating_mul(mid) / PRICE_SCALE as u128 >= offered_2 {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        let price = low;
+        let (offered_1, offered_2) = offered(price);
+
+        let volume_1 = offered_1.min(offered_2 * PRICE_SCALE as u128 / price);
+        let volume_2 = volume_1 * price / PRICE_SCALE as u128;
+
+        for metadata in positions.iter_mut() {
+            let (sell, buy) = metadata.position.offered(&metadata.reserves, price);
+            let r1 = metadata.reserves.r1 as u128 - filled(sell, offered_1, volume_1)
+                + paid(buy, offered_2, volume_1);
+            let r2 = metadata.reserves.r2 as u128 - filled(buy, offered_2, volume_2)
+                + paid(sell, offered_1, volume_2);
+            metadata.reserves = position::Reserves {
+                r1: r1 as u64,
+                r2: r2 as u64,
+            };
+        }
+
+        Self {
+            height,
+            trading_pair,
+            delta_1,
+            delta_2,
+            lambda_1: paid(delta_2 as u128, offered_2, volume_1) as u64,
+            lambda_2: paid(delta_1 as u128, offered_1, volume_2) as u64,
+            unfilled_1: delta_1 - filled(delta_1 as u128, offered_1, volume_1) as u64,
+            unfilled_2: delta_2 - filled(delta_2 as u128, offered_2, volume_2) as u64,
+        }
+    }
+
+    /// The amounts of assets 1 and 2 bought by a swap of `delta_1` and
+    /// `delta_2` in this batch.
+    pub fn outputs(&self, delta_1: u64, delta_2: u64) -> (u64, u64) {
+        (
+            pro_rata(delta_2, self.delta_2, self.lambda_1),
+            pro_rata(delta_1, self.delta_1, self.lambda_2),
+        )
+    }
+
+    /// The amounts of assets 1 and 2 returned unfilled to a swap of `delta_1`
+    /// and `delta_2` in this batch.
+    pub fn refunds(&self, delta_1: u64, delta_2: u64) -> (u64, u64) {
+        (
+            pro_rata(delta_1, self.delta_1, self.unfilled_1),
+            pro_rata(delta_2, self.delta_2, self.unfilled_2),
+        )
+    }
+
+    /// The total amounts of assets 1 and 2 claimed by a swap of `delta_1`
+    /// and `delta_2` in this batch, its outputs plus its refunds.
+    pub fn claim_amounts(&self, delta_1: u64, delta_2: u64) -> (u64, u64) {
+        let (output_1, output_2) = self.outputs(delta_1, delta_2);
+        let (refund_1, refund_2) = self.refunds(delta_1, delta_2);
+        (output_1 + refund_1, output_2 + refund_2)
+    }
+}
+
+/// The trading pairs with swaps in a block, whose batch totals were queued
+/// for decryption in this order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedBatch {
+    /// The height of the block whose swaps make up the batches.
+    pub height: u64,
+    pub trading_pairs: Vec<TradingPair>,
+}
+
+/// The share `part / whole` of `amount`, rounded down.
+///
+/// Rounding down means the batch never pays out more than it cleared; the
+/// remainder stays burned.
+fn pro_rata(part: u64, whole: u64, amount: u64) -> u64 {
+    if whole == 0 {
+        return 0;
+    }
+    ((part as u128 * amount as u128) / whole as u128) as u64
+}
+
+/// The amount taken from an offer of `amount` out of a total of `offered`,
+/// when `volume` of the total is traded, rounded up.
+fn filled(amount: u128, offered: u128, volume: u128) -> u128 {
+    if offered == 0 {
+        return 0;
+    }
+    (amount * volume + offered - 1) / offered
+}
+
+/// The amount paid for an offer of `amount` out of a total of `offered`, when
+/// `volume` is paid for the total, rounded down.
+fn paid(amount: u128, offered: u128, volume: u128) -> u128 {
+    if offered == 0 {
+        return 0;
+    }
+    amount * volume / offered
+}
+
+impl Protobuf<pb::SealedBatch> for SealedBatch {}
+
+impl From<SealedBatch> for pb::SealedBatch {
+    fn from(b: SealedBatch) -> Self {
+        pb::SealedBatch {
+            height: b.height,
+            trading_pairs: b.trading_pairs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::SealedBatch> for SealedBatch {
+    type Error = anyhow::Error;
+    fn try_from(b: pb::SealedBatch) -> Result<Self> {
+        Ok(SealedBatch {
+            height: b.height,
+            trading_pairs: b
+                .trading_pairs
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl Protobuf<pb::BatchSwapOutputData> for BatchSwapOutputData {}
+
+impl From<BatchSwapOutputData> for pb::BatchSwapOutputData {
+    fn from(d: BatchSwapOutputData) -> Self {
+        pb::BatchSwapOutputData {
+            height: d.height,
+            trading_pair: Some(d.trading_pair.into()),
+            delta_1: d.delta_1,
+            delta_2: d.delta_2,
+            lambda_1: d.lambda_1,
+            lambda_2: d.lambda_2,
+            unfilled_1: d.unfilled_1,
+            unfilled_2: d.unfilled_2,
+        }
+    }
+}
+
+impl TryFrom<pb::BatchSwapOutputData> for BatchSwapOutputData {
+    type Error = anyhow::Error;
+    fn try_from(d: pb::BatchSwapOutputData) -> Result<Self> {
+        Ok(BatchSwapOutputData {
+            height: d.height,
+            trading_pair: d
+                .trading_pair
+                .ok_or_else(|| anyhow!("missing batch trading pair"))?
+                .try_into()?,
+            delta_1: d.delta_1,
+            delta_2: d.delta_2,
+            lambda_1: d.lambda_1,
+            lambda_2: d.lambda_2,
+            unfilled_1: d.unfilled_1,
+            unfilled_2: d.unfilled_2,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::{asset, Fq};
+
+    use super::*;
+
+    fn pair() -> TradingPair {
+        TradingPair::new(asset::Id(Fq::from(2u64)), asset::Id(Fq::from(1u64))).unwrap()
+    }
+
+    #[test]
+    fn batch_clears_pro_rata() {
+        // 300 of asset 1 against 600 of asset 2: a price of 2 asset 2 per asset 1.
+        let batch = BatchSwapOutputData::clear(1, pair(), 300, 600);
+
+        assert_eq!(batch.outputs(100, 0), (0, 200));
+        assert_eq!(batch.outputs(200, 0), (0, 400));
+        assert_eq!(batch.outputs(0, 600), (300, 0));
+        assert_eq!(batch.refunds(100, 0), (0, 0));
+    }
+
+    #[test]
+    fn batch_clears_against_position() {
+        // A position buying asset 1 with 1000 of asset 2 between prices of 1.9 and 2.1.
+        let mut positions = [position::Metadata {
+            position: position::Position {
+                trading_pair: pair(),
+                lower_price: 1_900_000_000,
+                upper_price: 2_100_000_000,
+                fee: 0,
+                nonce: [0; 32],
+            },
+            state: position::State::Opened,
+            reserves: position::Reserves { r1: 0, r2: 1000 },
+        }];
+        let batch = BatchSwapOutputData::clear_with_positions(1, pair(), 100, 0, &mut positions);
+
+        // The swap is filled at a price within the position's range...
+        assert_eq!(batch.unfilled_1, 0);
+        assert!((190..=210).contains(&batch.lambda_2));
+        // ... and the position receives the swap's input, paying no more than it gives.
+        assert_eq!(positions[0].reserves.r1, 100);
+        assert!(positions[0].reserves.r2 + batch.lambda_2 <= 1000);
+    }
+
+    #[test]
+    fn one_sided_batch_is_refunded() {
+        let batch = BatchSwapOutputData::clear(1, pair(), 300, 0);
+
+        assert_eq!(batch.outputs(100, 0), (0, 0));
+        assert_eq!(batch.refunds(100, 0), (100, 0));
+        assert_eq!(batch.claim_amounts(100, 0), (100, 0));
+    }
+}
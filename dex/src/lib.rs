@@ -0,0 +1,22 @@
+//! ZSwap, Penumbra's decentralized exchange: swaps are collected into
+//! sealed-bid batches per trading pair, with their amounts encrypted to the
+//! validators' flow encryption key, cleared at a single price per block once
+//! the batch totals are decrypted, and claimed privately in a later
+//! transaction.  Liquidity providers' positions trade against each batch at
+//! its clearing price.
+#![allow(clippy::clone_on_copy)]
+
+mod batch;
+mod lp_nft;
+mod swap;
+mod trading_pair;
+
+pub mod action;
+pub mod position;
+pub mod proofs;
+
+pub use batch::{BatchSwapOutputData, SealedBatch};
+pub use lp_nft::LpNft;
+pub use position::Position;
+pub use swap::SwapPlaintext;
+pub use trading_pair::TradingPair;
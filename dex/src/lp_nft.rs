@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+use penumbra_crypto::{asset, Value};
+
+use crate::position::{self, State};
+
+/// An LP NFT, representing ownership of a position in a given state.
+///
+/// Each state transition of a position consumes the LP NFT for its old state
+/// and creates one for its new state, so whoever holds the NFT controls the
+/// position.
+#[derive(Debug, Clone)]
+pub struct LpNft {
+    position_id: position::Id,
+    state: State,
+    base_denom: asset::Denom,
+}
+
+impl LpNft {
+    pub fn new(position_id: position::Id, state: State) -> Self {
+        // This format string needs to be in sync with the asset registry
+        let base_denom = asset::REGISTRY
+            .parse_denom(&format!("lpnft_{}_{}", state, position_id))
+            .expect("base denom format is valid");
+        LpNft {
+            position_id,
+            state,
+            base_denom,
+        }
+    }
+
+    /// Get the base denomination for this LP NFT.
+    pub fn denom(&self) -> asset::Denom {
+        self.base_denom.clone()
+    }
+
+    /// Get the asset ID for this LP NFT.
+    pub fn asset_id(&self) -> asset::Id {
+        self.base_denom.id()
+    }
+
+    /// The value of a single LP NFT.
+    pub fn value(&self) -> Value {
+        Value {
+            amount: 1,
+            asset_id: self.asset_id(),
+        }
+    }
+
+    pub fn position_id(&self) -> position::Id {
+        self.position_id
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+}
+
+impl TryFrom<asset::Denom> for LpNft {
+    type Error = anyhow::Error;
+    fn try_from(base_denom: asset::Denom) -> Result<Self> {
+        // Note: this format must be in sync with asset::REGISTRY
+        let denom = base_denom.to_string();
+        let (state, position_id) = denom
+            .strip_prefix("lpnft_")
+            .and_then(|rest| rest.split_once('_'))
+            .ok_or_else(|| anyhow!("base denom {} is not an LP NFT", denom))?;
+        let state = match state {
+            "opened" => State::Opened,
+            "closed" => State::Closed,
+            "withdrawn" => State::Withdrawn,
+            _ => return Err(anyhow!("base denom {} is not an LP NFT", denom)),
+        };
+
+        Ok(LpNft {
+            position_id: position_id.parse()?,
+            state,
+            base_denom,
+        })
+    }
+}
@@ -0,0 +1,306 @@
+//! Concentrated liquidity positions.
+
+use anyhow::{anyhow, Result};
+use penumbra_proto::{dex as pb, Protobuf};
+
+use crate::TradingPair;
+
+/// The fixed-point scale of position prices: a price of `PRICE_SCALE`
+/// is one unit of asset 2 per unit of asset 1.
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
+/// The denominator of position fees, which are given in basis points.
+pub const FEE_DENOMINATOR: u32 = 10_000;
+
+/// A liquidity provider's position, providing liquidity for a trading pair
+/// over a range of prices.
+///
+/// The position's reserves of asset 1 are offered for sale evenly across its
+/// price range, and its reserves of asset 2 are offered to buy asset 1 evenly
+/// across the same range.  The fee widens the range in each direction, so that
+/// the position only sells above, and buys below, its range net of fees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub trading_pair: TradingPair,
+    /// The lower bound of the price range, in units of asset 2 per unit of
+    /// asset 1, scaled by [`PRICE_SCALE`].
+    pub lower_price: u64,
+    /// The upper bound of the price range, in units of asset 2 per unit of
+    /// asset 1, scaled by [`PRICE_SCALE`].
+    pub upper_price: u64,
+    /// The fee charged by the position, in basis points.
+    pub fee: u32,
+    /// A random nonce, so that otherwise identical positions have distinct IDs.
+    pub nonce: [u8; 32],
+}
+
+impl Position {
+    /// The ID of the position, which identifies its LP NFTs.
+    pub fn id(&self) -> Id {
+        let hash = blake2b_simd::Params::default()
+            .personal(b"Penumbra_LPID")
+            .hash_length(32)
+            .to_state()
+            .update(&self.trading_pair.asset_1().to_bytes())
+            .update(&self.trading_pair.asset_2().to_bytes())
+            .update(&self.lower_price.to_le_bytes())
+            .update(&self.upper_price.to_le_bytes())
+            .update(&self.fee.to_le_bytes())
+            .update(&self.nonce)
+            .finalize();
+        Id(hash.as_bytes().try_into().expect("hash is 32 bytes"))
+    }
+
+    /// The amounts of assets 1 and 2 the position offers to trade at the
+    /// given price, out of the given reserves.
+    pub fn offered(&self, reserves: &Reserves, price: u128) -> (u128, u128) {
+        let fee = self.fee as u128;
+        let denominator = FEE_DENOMINATOR as u128;
+        let lower = self.lower_price as u128;
+        let upper = self.upper_price as u128;
+
+        // Asset 1 is sold over the range marked up by the fee...
+        let sell = portion(
+            reserves.r1,
+            price,
+            lower * (denominator + fee) / denominator,
+            upper * (denominator + fee) / denominator,
+        );
+        // ... and asset 2 buys asset 1 over the range marked down by the fee.
+        let buy = reserves.r2 as u128
+            - portion(
+                reserves.r2,
+                price,
+                lower * denominator / (denominator + fee),
+                upper * denominator / (denominator + fee),
+            );
+
+        (sell, buy)
+    }
+}
+
+/// The portion of `amount` corresponding to how far `price` is through the
+/// range from `lower` to `upper`, rounded down.
+fn portion(amount: u64, price: u128, lower: u128, upper: u128) -> u128 {
+    if price >= upper {
+        amount as u128
+    } else if price <= lower {
+        0
+    } else {
+        amount as u128 * (price - lower) / (upper - lower)
+    }
+}
+
+/// The ID of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Id(pub [u8; 32]);
+
+impl std::fmt::Display for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl std::str::FromStr for Id {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Id(hex::decode(s)?
+            .try_into()
+            .map_err(|_| anyhow!("position ID must be 32 bytes"))?))
+    }
+}
+
+/// The lifecycle state of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// The position is providing liquidity.
+    Opened,
+    /// The position no longer provides liquidity, but its reserves have not
+    /// been withdrawn.
+    Closed,
+    /// The position's reserves have been withdrawn.
+    Withdrawn,
+}
+
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            State::Opened => write!(f, "opened"),
+            State::Closed => write!(f, "closed"),
+            State::Withdrawn => write!(f, "withdrawn"),
+        }
+    }
+}
+
+/// The reserves of assets 1 and 2 held by a position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reserves {
+    pub r1: u64,
+    pub r2: u64,
+}
+
+/// A position, together with its current state and reserves.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub position: Position,
+    pub state: State,
+    pub reserves: Reserves,
+}
+
+/// A list of position IDs.
+#[derive(Debug, Clone, Default)]
+pub struct Ids {
+    pub ids: Vec<Id>,
+}
+
+impl Protobuf<pb::Position> for Position {}
+
+impl From<Position> for pb::Position {
+    fn from(p: Position) -> Self {
+        pb::Position {
+            trading_pair: Some(p.trading_pair.into()),
+            lower_price: p.lower_price,
+            upper_price: p.upper_price,
+            fee: p.fee,
+            nonce: p.nonce.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::Position> for Position {
+    type Error = anyhow::Error;
+    fn try_from(p: pb::Position) -> Result<Self> {
+        Ok(Position {
+            trading_pair: p
+                .trading_pair
+                .ok_or_else(|| anyhow!("missing position trading pair"))?
+                .try_into()?,
+            lower_price: p.lower_price,
+            upper_price: p.upper_price,
+            fee: p.fee,
+            nonce: p
+                .nonce
+                .try_into()
+                .map_err(|_| anyhow!("position nonce must be 32 bytes"))?,
+        })
+    }
+}
+
+impl Protobuf<pb::PositionId> for Id {}
+
+impl From<Id> for pb::PositionId {
+    fn from(id: Id) -> Self {
+        pb::PositionId {
+            inner: id.0.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::PositionId> for Id {
+    type Error = anyhow::Error;
+    fn try_from(id: pb::PositionId) -> Result<Self> {
+        Ok(Id(id
+            .inner
+            .try_into()
+            .map_err(|_| anyhow!("position ID must be 32 bytes"))?))
+    }
+}
+
+impl Protobuf<pb::PositionState> for State {}
+
+impl From<State> for pb::PositionState {
+    fn from(s: State) -> Self {
+        pb::PositionState {
+            state: match s {
+                State::Opened => pb::position_state::PositionStateEnum::Opened,
+                State::Closed => pb::position_state::PositionStateEnum::Closed,
+                State::Withdrawn => pb::position_state::PositionStateEnum::Withdrawn,
+            } as i32,
+        }
+    }
+}
+
+impl TryFrom<pb::PositionState> for State {
+    type Error = anyhow::Error;
+    fn try_from(s: pb::PositionState) -> Result<Self> {
+        Ok(
+            match pb::position_state::PositionStateEnum::from_i32(s.state)
+                .ok_or_else(|| anyhow!("missing position state"))?
+            {
+                pb::position_state::PositionStateEnum::Opened => State::Opened,
+                pb::position_state::PositionStateEnum::Closed => State::Closed,
+                pb::position_state::PositionStateEnum::Withdrawn => State::Withdrawn,
+            },
+        )
+    }
+}
+
+impl Protobuf<pb::Reserves> for Reserves {}
+
+impl From<Reserves> for pb::Reserves {
+    fn from(r: Reserves) -> Self {
+        pb::Reserves { r1: r.r1, r2: r.r2 }
+    }
+}
+
+impl TryFrom<pb::Reserves> for Reserves {
+    type Error = anyhow::Error;
+    fn try_from(r: pb::Reserves) -> Result<Self> {
+        Ok(Reserves { r1: r.r1, r2: r.r2 })
+    }
+}
+
+impl Protobuf<pb::PositionMetadata> for Metadata {}
+
+impl From<Metadata> for pb::PositionMetadata {
+    fn from(m: Metadata) -> Self {
+        pb::PositionMetadata {
+            position: Some(m.position.into()),
+            state: Some(m.state.into()),
+            reserves: Some(m.reserves.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::PositionMetadata> for Metadata {
+    type Error = anyhow::Error;
+    fn try_from(m: pb::PositionMetadata) -> Result<Self> {
+        Ok(Metadata {
+            position: m
+                .position
+                .ok_or_else(|| anyhow!("missing position"))?
+                .try_into()?,
+            state: m
+                .state
+                .ok_or_else(|| anyhow!("missing position state"))?
+                .try_into()?,
+            reserves: m
+                .reserves
+                .ok_or_else(|| anyhow!("missing position reserves"))?
+                .try_into()?,
+        })
+    }
+}
+
+impl Protobuf<pb::PositionIds> for Ids {}
+
+impl From<Ids> for pb::PositionIds {
+    fn from(ids: Ids) -> Self {
+        pb::PositionIds {
+            ids: ids.ids.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::PositionIds> for Ids {
+    type Error = anyhow::Error;
+    fn try_from(ids: pb::PositionIds) -> Result<Self> {
+        Ok(Ids {
+            ids: ids
+                .ids
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
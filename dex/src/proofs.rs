@@ -0,0 +1,305 @@
+//! Proofs for swaps and swap claims, abstracted over the proof system that
+//! produces them, like the spend and output proofs of
+//! [`penumbra_crypto::proofs`].
+//!
+//! A swap proof shows that the swap's amounts are encrypted to the flow
+//! encryption key, and that its NFT commits to them; a swap claim proof shows
+//! that it spends a swap NFT created in the batch being claimed, and that its
+//! outputs are the swap's share of the batch.  Neither reveals the swap.
+
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::anyhow;
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_crypto::{
+    ka, keys, merkle, note,
+    proofs::{Proof, ProofSystem},
+    value, Fq, Fr, Nullifier,
+};
+use penumbra_flow_encryption::{Ciphertext, EncryptionKey, EncryptionRandomness};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{BatchSwapOutputData, SwapPlaintext, TradingPair};
+
+pub mod groth16;
+pub mod transparent;
+
+/// The public inputs of a swap proof.
+#[derive(Clone, Debug)]
+pub struct SwapPublicInputs {
+    /// The trading pair the swap is on.
+    pub trading_pair: TradingPair,
+    /// The commitment to the swap's inputs.
+    pub value_commitment: value::Commitment,
+    /// The flow encryption key the amounts are encrypted to.
+    pub encryption_key: EncryptionKey,
+    /// The encryption of the amount of asset 1.
+    pub encrypted_delta_1: Ciphertext,
+    /// The encryption of the amount of asset 2.
+    pub encrypted_delta_2: Ciphertext,
+    /// The note commitment of the swap NFT.
+    pub note_commitment: note::Commitment,
+    /// The ephemeral public key used to generate the swap NFT.
+    pub epk: ka::Public,
+}
+
+/// The public inputs of a swap claim proof.
+#[derive(Clone, Debug)]
+pub struct SwapClaimPublicInputs {
+    /// The merkle root of the note commitment tree.
+    pub anchor: merkle::Root,
+    /// The nullifier of the swap NFT.
+    pub nullifier: Nullifier,
+    /// The randomized verification spend key.
+    pub rk: VerificationKey<SpendAuth>,
+    /// The result of the batch being claimed from.
+    pub output_data: BatchSwapOutputData,
+    /// The index of the block of the note commitment tree containing the swap
+    /// NFT, which must be the block of the batch.
+    pub nft_block: u32,
+    /// The note commitment of the claimed amount of asset 1.
+    pub output_1: note::Commitment,
+    /// The note commitment of the claimed amount of asset 2.
+    pub output_2: note::Commitment,
+}
+
+/// A swap proof from any of the supported proof systems.
+#[derive(Clone, Debug)]
+pub enum SwapProof {
+    Transparent(transparent::SwapProof),
+    Groth16(groth16::SwapProof),
+}
+
+impl SwapProof {
+    /// Proves that `swap_plaintext` is encrypted to `encryption_key` with the
+    /// given randomness, and committed to by the swap NFT note with
+    /// `note_blinding` created with `esk`, using the given proof system.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        swap_plaintext: &SwapPlaintext,
+        encryption_key: &EncryptionKey,
+        randomness_1: EncryptionRandomness,
+        randomness_2: EncryptionRandomness,
+        v_blinding: Fr,
+        note_blinding: Fq,
+        esk: &ka::Secret,
+    ) -> anyhow::Result<Self> {
+        Ok(match proof_system {
+            ProofSystem::Transparent => SwapProof::Transparent(transparent::SwapProof {
+                swap_plaintext: swap_plaintext.clone(),
+                v_blinding,
+                note_blinding,
+                esk: esk.clone(),
+                randomness_1,
+                randomness_2,
+            }),
+            ProofSystem::Groth16 => SwapProof::Groth16(groth16::SwapProof::prove(
+                rng,
+                &groth16::swap_parameters()?.proving_key,
+                swap_plaintext,
+                encryption_key,
+                &randomness_1,
+                &randomness_2,
+                v_blinding,
+                note_blinding,
+                esk,
+            )?),
+        })
+    }
+
+    /// The proof system that produced this proof.
+    pub fn proof_system(&self) -> ProofSystem {
+        match self {
+            SwapProof::Transparent(_) => ProofSystem::Transparent,
+            SwapProof::Groth16(_) => ProofSystem::Groth16,
+        }
+    }
+}
+
+impl Proof for SwapProof {
+    type PublicInputs = SwapPublicInputs;
+
+    fn verify(&self, public_inputs: &SwapPublicInputs) -> anyhow::Result<()> {
+        match self {
+            SwapProof::Transparent(proof) => proof.verify(public_inputs),
+            SwapProof::Groth16(proof) => {
+                proof.verify(&groth16::swap_parameters()?.verifying_key, public_inputs)
+            }
+        }
+    }
+}
+
+/// A swap claim proof from any of the supported proof systems.
+#[derive(Clone, Debug)]
+pub enum SwapClaimProof {
+    Transparent(transparent::SwapClaimProof),
+    Groth16(groth16::SwapClaimProof),
+}
+
+impl SwapClaimProof {
+    /// Proves that the swap NFT for `swap_plaintext`, witnessed in the note
+    /// commitment tree with root `anchor`, is spent, and that the notes with
+    /// the given blindings are for its share of the batch `output_data`,
+    /// using the given proof system.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        anchor: merkle::Root,
+        note_commitment_proof: merkle::Proof,
+        swap_plaintext: &SwapPlaintext,
+        nft_note_blinding: Fq,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+        output_data: &BatchSwapOutputData,
+        output_1_blinding: Fq,
+        output_2_blinding: Fq,
+    ) -> anyhow::Result<Self> {
+        Ok(match proof_system {
+            ProofSystem::Transparent => SwapClaimProof::Transparent(transparent::SwapClaimProof {
+                swap_plaintext: swap_plaintext.clone(),
+                note_commitment_proof,
+                nft_note_blinding,
+                spend_auth_randomizer,
+                ak,
+                nk,
+                output_1_blinding,
+                output_2_blinding,
+            }),
+            ProofSystem::Groth16 => SwapClaimProof::Groth16(groth16::SwapClaimProof::prove(
+                rng,
+                &groth16::swap_claim_parameters()?.proving_key,
+                anchor,
+                &note_commitment_proof,
+                swap_plaintext,
+                nft_note_blinding,
+                spend_auth_randomizer,
+                ak,
+                nk,
+                output_data,
+                output_1_blinding,
+                output_2_blinding,
+            )?),
+        })
+    }
+
+    /// The proof system that produced this proof.
+    pub fn proof_system(&self) -> ProofSystem {
+        match self {
+            SwapClaimProof::Transparent(_) => ProofSystem::Transparent,
+            SwapClaimProof::Groth16(_) => ProofSystem::Groth16,
+        }
+    }
+}
+
+impl Proof for SwapClaimProof {
+    type PublicInputs = SwapClaimPublicInputs;
+
+    fn verify(&self, public_inputs: &SwapClaimPublicInputs) -> anyhow::Result<()> {
+        match self {
+            SwapClaimProof::Transparent(proof) => proof.verify(public_inputs),
+            SwapClaimProof::Groth16(proof) => proof.verify(
+                &groth16::swap_claim_parameters()?.verifying_key,
+                public_inputs,
+            ),
+        }
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::TaggedProof> for SwapProof {}
+
+impl From<SwapProof> for pb::TaggedProof {
+    fn from(proof: SwapProof) -> Self {
+        let proof_system = proof.proof_system();
+        let inner: Vec<u8> = match proof {
+            SwapProof::Transparent(proof) => proof.into(),
+            SwapProof::Groth16(proof) => proof.into(),
+        };
+        pb::TaggedProof {
+            proof_system: Some(proof_system.into()),
+            inner,
+        }
+    }
+}
+
+impl TryFrom<pb::TaggedProof> for SwapProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::TaggedProof) -> anyhow::Result<Self, Self::Error> {
+        let proof_system = proto
+            .proof_system
+            .ok_or_else(|| anyhow!("missing proof system"))?
+            .try_into()?;
+        Ok(match proof_system {
+            ProofSystem::Transparent => SwapProof::Transparent(proto.inner[..].try_into()?),
+            ProofSystem::Groth16 => SwapProof::Groth16(proto.inner[..].try_into()?),
+        })
+    }
+}
+
+impl From<SwapProof> for Vec<u8> {
+    fn from(swap_proof: SwapProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::TaggedProof = swap_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SwapProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SwapProof, Self::Error> {
+        pb::TaggedProof::decode(bytes)?.try_into()
+    }
+}
+
+impl Protobuf<pb::TaggedProof> for SwapClaimProof {}
+
+impl From<SwapClaimProof> for pb::TaggedProof {
+    fn from(proof: SwapClaimProof) -> Self {
+        let proof_system = proof.proof_system();
+        let inner: Vec<u8> = match proof {
+            SwapClaimProof::Transparent(proof) => proof.into(),
+            SwapClaimProof::Groth16(proof) => proof.into(),
+        };
+        pb::TaggedProof {
+            proof_system: Some(proof_system.into()),
+            inner,
+        }
+    }
+}
+
+impl TryFrom<pb::TaggedProof> for SwapClaimProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::TaggedProof) -> anyhow::Result<Self, Self::Error> {
+        let proof_system = proto
+            .proof_system
+            .ok_or_else(|| anyhow!("missing proof system"))?
+            .try_into()?;
+        Ok(match proof_system {
+            ProofSystem::Transparent => SwapClaimProof::Transparent(proto.inner[..].try_into()?),
+            ProofSystem::Groth16 => SwapClaimProof::Groth16(proto.inner[..].try_into()?),
+        })
+    }
+}
+
+impl From<SwapClaimProof> for Vec<u8> {
+    fn from(swap_claim_proof: SwapClaimProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::TaggedProof = swap_claim_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SwapClaimProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SwapClaimProof, Self::Error> {
+        pb::TaggedProof::decode(bytes)?.try_into()
+    }
+}
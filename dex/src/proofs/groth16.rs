@@ -0,0 +1,143 @@
+//! Groth16 proofs over BLS12-377 for swaps and swap claims.
+//!
+//! These prove the same statements as the [`transparent`](super::transparent)
+//! proofs, but without revealing the swap, its NFT, or the keys that control
+//! it.  They reuse the gadgets of the spend and output circuits, and take
+//! their parameters from the same setup ceremony.
+
+use std::path::Path;
+
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use decaf377::r1cs::{ElementVar, FqVar};
+use once_cell::sync::OnceCell;
+use penumbra_crypto::{
+    proofs::groth16::{s_value, Parameters},
+    Fq, One,
+};
+use penumbra_flow_encryption::{Ciphertext, LIMBS};
+use poseidon377::r1cs::{hash_2, hash_5};
+
+use crate::swap::{SWAP_ADDRESS_DOMAIN_SEP, SWAP_NFT_DOMAIN_SEP};
+
+mod swap;
+mod swap_claim;
+
+pub use swap::{SwapCircuit, SwapProof};
+pub use swap_claim::{SwapClaimCircuit, SwapClaimProof};
+
+/// The name of the file holding the [`SwapCircuit`] parameters.
+pub const SWAP_PARAMETERS_FILE: &str = "swap.params";
+/// The name of the file holding the [`SwapClaimCircuit`] parameters.
+pub const SWAP_CLAIM_PARAMETERS_FILE: &str = "swap_claim.params";
+
+static SWAP_PROOF_PARAMETERS: OnceCell<Parameters> = OnceCell::new();
+static SWAP_CLAIM_PROOF_PARAMETERS: OnceCell<Parameters> = OnceCell::new();
+
+/// Loads the parameters for both circuits from the output of a setup
+/// ceremony, stored in `dir` as [`SWAP_PARAMETERS_FILE`] and
+/// [`SWAP_CLAIM_PARAMETERS_FILE`], alongside the spend and output parameters
+/// loaded by [`penumbra_crypto::proofs::groth16::load_parameters`].
+///
+/// Until this is called, Groth16 swap proofs can be neither created nor
+/// verified.
+pub fn load_parameters(dir: &Path) -> anyhow::Result<()> {
+    let swap = Parameters::load(&dir.join(SWAP_PARAMETERS_FILE))?;
+    let swap_claim = Parameters::load(&dir.join(SWAP_CLAIM_PARAMETERS_FILE))?;
+
+    SWAP_PROOF_PARAMETERS
+        .set(swap)
+        .map_err(|_| anyhow::anyhow!("Groth16 swap parameters were already loaded"))?;
+    SWAP_CLAIM_PROOF_PARAMETERS
+        .set(swap_claim)
+        .map_err(|_| anyhow::anyhow!("Groth16 swap parameters were already loaded"))?;
+
+    Ok(())
+}
+
+/// The parameters for the [`SwapCircuit`], if they have been loaded.
+pub fn swap_parameters() -> anyhow::Result<&'static Parameters> {
+    SWAP_PROOF_PARAMETERS
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Groth16 swap parameters have not been loaded"))
+}
+
+/// The parameters for the [`SwapClaimCircuit`], if they have been loaded.
+pub fn swap_claim_parameters() -> anyhow::Result<&'static Parameters> {
+    SWAP_CLAIM_PROOF_PARAMETERS
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Groth16 swap claim parameters have not been loaded"))
+}
+
+/// The `s` values of the `c0` and `c1` components of each limb of a
+/// ciphertext, in that order, which is how the circuits take it.
+fn ciphertext_s_values(ciphertext: &Ciphertext) -> anyhow::Result<[Fq; 2 * LIMBS]> {
+    let mut s_values = [Fq::from(0u64); 2 * LIMBS];
+    for (s, encoding) in s_values.iter_mut().zip(ciphertext.to_bytes().chunks(32)) {
+        *s = s_value(encoding.try_into().expect("chunks are 32 bytes"))?;
+    }
+    Ok(s_values)
+}
+
+/// The asset ID of a swap NFT, as in [`SwapPlaintext::asset_id`](crate::SwapPlaintext::asset_id).
+#[allow(clippy::too_many_arguments)]
+fn swap_asset_id(
+    cs: ConstraintSystemRef<Fq>,
+    swap_blinding: &FqVar,
+    asset_1: &FqVar,
+    asset_2: &FqVar,
+    delta_1: &FqVar,
+    delta_2: &FqVar,
+    diversified_generator: &ElementVar,
+    transmission_key_s: &FqVar,
+) -> Result<FqVar, SynthesisError> {
+    let address_domain_sep = FqVar::new_constant(cs.clone(), *SWAP_ADDRESS_DOMAIN_SEP)?;
+    let address_hash = hash_2(
+        cs.clone(),
+        &address_domain_sep,
+        (
+            diversified_generator.compress_to_field()?,
+            transmission_key_s.clone(),
+        ),
+    )?;
+
+    let shift = FqVar::new_constant(cs.clone(), Fq::from(u64::MAX) + Fq::one())?;
+    let packed_deltas = delta_1 + delta_2 * shift;
+
+    let domain_sep = FqVar::new_constant(cs.clone(), *SWAP_NFT_DOMAIN_SEP)?;
+    hash_5(
+        cs,
+        &domain_sep,
+        (
+            swap_blinding.clone(),
+            asset_1.clone(),
+            asset_2.clone(),
+            packed_deltas,
+            address_hash,
+        ),
+    )
+}
+
+/// Runs the circuit-specific setup for `circuit`, whose witness values are
+/// ignored.
+///
+/// Whoever knows the randomness can forge proofs, so parameters generated
+/// this way are only suitable for testing.
+#[cfg(test)]
+fn setup<C, R>(
+    circuit: C,
+    rng: &mut R,
+) -> (
+    ark_groth16::ProvingKey<ark_bls12_377::Bls12_377>,
+    ark_groth16::PreparedVerifyingKey<ark_bls12_377::Bls12_377>,
+)
+where
+    C: ark_relations::r1cs::ConstraintSynthesizer<Fq>,
+    R: rand_core::RngCore + rand_core::CryptoRng,
+{
+    use ark_snark::CircuitSpecificSetupSNARK;
+    let (pk, vk) =
+        ark_groth16::Groth16::<ark_bls12_377::Bls12_377>::circuit_specific_setup(circuit, rng)
+            .expect("can perform circuit specific setup");
+    (pk, ark_groth16::prepare_verifying_key(&vk))
+}
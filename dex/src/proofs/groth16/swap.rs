@@ -0,0 +1,425 @@
+use std::convert::{TryFrom, TryInto};
+
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use decaf377::r1cs::{ElementVar, FqVar};
+use penumbra_crypto::{
+    ka,
+    proofs::groth16::{gadgets, s_value, verify},
+    FieldExt, Fq, Fr, Note,
+};
+use penumbra_flow_encryption::{EncryptionKey, EncryptionRandomness, LIMBS, LIMB_BITS};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{ciphertext_s_values, swap_asset_id};
+use crate::{proofs::SwapPublicInputs, SwapPlaintext};
+
+/// The circuit proving that a swap is well-formed.
+///
+/// The public inputs are:
+/// * the asset IDs of the trading pair,
+/// * the commitment to the swap's inputs,
+/// * the flow encryption key,
+/// * the `c0` and `c1` components of each limb of the encrypted amount of
+///   asset 1, then of asset 2,
+/// * the note commitment of the swap NFT,
+/// * the ephemeral public key used to generate the swap NFT,
+///
+/// each as a field element, in that order.
+#[derive(Clone, Debug)]
+pub struct SwapCircuit {
+    // Private inputs
+    /// The amount of asset 1 to swap for asset 2.
+    delta_1: u64,
+    /// The amount of asset 2 to swap for asset 1.
+    delta_2: u64,
+    /// The diversified base for the claim address.
+    g_d: decaf377::Element,
+    /// The `s` value of the transmission key for the claim address.
+    pk_d: Fq,
+    /// The blinding factor of the swap NFT's asset ID.
+    swap_blinding: Fq,
+    /// The blinding factor used for generating the value commitment.
+    v_blinding: Fr,
+    /// The blinding factor used for generating the swap NFT's note commitment.
+    note_blinding: Fq,
+    /// The ephemeral secret key that corresponds to the public key.
+    esk: Fr,
+    /// The randomness used to encrypt each limb of the amount of asset 1.
+    randomness_1: [Fr; LIMBS],
+    /// The randomness used to encrypt each limb of the amount of asset 2.
+    randomness_2: [Fr; LIMBS],
+    /// The flow encryption key.
+    encryption_key: decaf377::Element,
+
+    // Public inputs
+    asset_1: Fq,
+    asset_2: Fq,
+    value_commitment: Fq,
+    encryption_key_s: Fq,
+    encrypted_delta_1: [Fq; 2 * LIMBS],
+    encrypted_delta_2: [Fq; 2 * LIMBS],
+    note_commitment: Fq,
+    epk: Fq,
+}
+
+impl ConstraintSynthesizer<Fq> for SwapCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        // Witnesses
+        let (delta_1_bits, delta_1) = gadgets::u64_witness(cs.clone(), self.delta_1)?;
+        let (delta_2_bits, delta_2) = gadgets::u64_witness(cs.clone(), self.delta_2)?;
+        let g_d = ElementVar::new_witness(cs.clone(), || Ok(self.g_d))?;
+        let pk_d = FqVar::new_witness(cs.clone(), || Ok(self.pk_d))?;
+        let swap_blinding = FqVar::new_witness(cs.clone(), || Ok(self.swap_blinding))?;
+        let v_blinding_bits = gadgets::scalar_bits(cs.clone(), self.v_blinding)?;
+        let note_blinding = FqVar::new_witness(cs.clone(), || Ok(self.note_blinding))?;
+        let esk_bits = gadgets::scalar_bits(cs.clone(), self.esk)?;
+        let randomness_bits = [self.randomness_1, self.randomness_2]
+            .iter()
+            .map(|randomness| {
+                randomness
+                    .iter()
+                    .map(|e| gadgets::scalar_bits(cs.clone(), *e))
+                    .collect::<ark_relations::r1cs::Result<Vec<_>>>()
+            })
+            .collect::<ark_relations::r1cs::Result<Vec<_>>>()?;
+        let encryption_key = ElementVar::new_witness(cs.clone(), || Ok(self.encryption_key))?;
+
+        // Public inputs
+        let asset_1 = FqVar::new_input(cs.clone(), || Ok(self.asset_1))?;
+        let asset_2 = FqVar::new_input(cs.clone(), || Ok(self.asset_2))?;
+        let value_commitment = FqVar::new_input(cs.clone(), || Ok(self.value_commitment))?;
+        let encryption_key_s = FqVar::new_input(cs.clone(), || Ok(self.encryption_key_s))?;
+        let encrypted_deltas = [self.encrypted_delta_1, self.encrypted_delta_2]
+            .iter()
+            .map(|ciphertext| {
+                ciphertext
+                    .iter()
+                    .map(|s| FqVar::new_input(cs.clone(), || Ok(*s)))
+                    .collect::<ark_relations::r1cs::Result<Vec<_>>>()
+            })
+            .collect::<ark_relations::r1cs::Result<Vec<_>>>()?;
+        let note_commitment = FqVar::new_input(cs.clone(), || Ok(self.note_commitment))?;
+        let epk = FqVar::new_input(cs.clone(), || Ok(self.epk))?;
+
+        // Value commitment integrity: the inputs are burned, so the
+        // commitment is negated.
+        (gadgets::value_commitment(cs.clone(), &delta_1_bits, &asset_1, &v_blinding_bits)?
+            + gadgets::value_generator(cs.clone(), &asset_2)?
+                .scalar_mul_le(delta_2_bits.iter())?)
+        .negate()?
+        .compress_to_field()?
+        .enforce_equal(&value_commitment)?;
+
+        // Swap NFT integrity.
+        let asset_id = swap_asset_id(
+            cs.clone(),
+            &swap_blinding,
+            &asset_1,
+            &asset_2,
+            &delta_1,
+            &delta_2,
+            &g_d,
+            &pk_d,
+        )?;
+        gadgets::note_commitment(
+            cs.clone(),
+            &note_blinding,
+            &FqVar::one(),
+            &asset_id,
+            &g_d,
+            &pk_d,
+        )?
+        .enforce_equal(&note_commitment)?;
+
+        // Ephemeral public key integrity.
+        g_d.scalar_mul_le(esk_bits.iter())?
+            .compress_to_field()?
+            .enforce_equal(&epk)?;
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        gadgets::enforce_not_identity(&g_d)?;
+
+        // Flow encryption integrity: each limb `v` of each amount is
+        // encrypted as `(e * G, v * G + e * D)`.
+        encryption_key
+            .compress_to_field()?
+            .enforce_equal(&encryption_key_s)?;
+        let basepoint = ElementVar::new_constant(cs, decaf377::basepoint())?;
+        for ((amount_bits, randomness_bits), ciphertext) in [delta_1_bits, delta_2_bits]
+            .iter()
+            .zip(randomness_bits.iter())
+            .zip(encrypted_deltas.iter())
+        {
+            let limbs = amount_bits.chunks(LIMB_BITS as usize);
+            for (i, (limb_bits, e_bits)) in limbs.zip(randomness_bits.iter()).enumerate() {
+                basepoint
+                    .scalar_mul_le(e_bits.iter())?
+                    .compress_to_field()?
+                    .enforce_equal(&ciphertext[2 * i])?;
+                (basepoint.scalar_mul_le(limb_bits.iter())?
+                    + encryption_key.scalar_mul_le(e_bits.iter())?)
+                .compress_to_field()?
+                .enforce_equal(&ciphertext[2 * i + 1])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A Groth16 proof that a swap is well-formed.
+#[derive(Clone, Debug)]
+pub struct SwapProof(Proof<Bls12_377>);
+
+impl SwapProof {
+    /// Proves that `swap_plaintext` is encrypted to `encryption_key` with the
+    /// given randomness, and committed to by the swap NFT note with
+    /// `note_blinding` created with `esk`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        proving_key: &ProvingKey<Bls12_377>,
+        swap_plaintext: &SwapPlaintext,
+        encryption_key: &EncryptionKey,
+        randomness_1: &EncryptionRandomness,
+        randomness_2: &EncryptionRandomness,
+        v_blinding: Fr,
+        note_blinding: Fq,
+        esk: &ka::Secret,
+    ) -> anyhow::Result<Self> {
+        let claim_address = &swap_plaintext.claim_address;
+        let nft = Note::from_parts(
+            *claim_address.diversifier(),
+            *claim_address.transmission_key(),
+            swap_plaintext.nft_value(),
+            note_blinding,
+        )?;
+        let epk = esk.diversified_public(&nft.diversified_generator());
+        let esk = Fr::from_bytes(esk.to_bytes())
+            .map_err(|_| anyhow::anyhow!("invalid ephemeral secret key"))?;
+
+        let encryption_key_bytes = encryption_key.to_bytes();
+        let encrypted_delta_1 =
+            encryption_key.encrypt_with_randomness(swap_plaintext.delta_1, randomness_1);
+        let encrypted_delta_2 =
+            encryption_key.encrypt_with_randomness(swap_plaintext.delta_2, randomness_2);
+
+        let circuit = SwapCircuit {
+            delta_1: swap_plaintext.delta_1,
+            delta_2: swap_plaintext.delta_2,
+            g_d: nft.diversified_generator(),
+            pk_d: nft.transmission_key_s(),
+            swap_blinding: swap_plaintext.swap_blinding,
+            v_blinding,
+            note_blinding,
+            esk,
+            randomness_1: randomness_1.limbs(),
+            randomness_2: randomness_2.limbs(),
+            encryption_key: decaf377::Encoding(encryption_key_bytes)
+                .decompress()
+                .map_err(|_| anyhow::anyhow!("invalid flow encryption key"))?,
+            asset_1: swap_plaintext.trading_pair.asset_1().0,
+            asset_2: swap_plaintext.trading_pair.asset_2().0,
+            value_commitment: swap_plaintext
+                .input_commitment(v_blinding)
+                .0
+                .compress_to_field(),
+            encryption_key_s: s_value(encryption_key_bytes)?,
+            encrypted_delta_1: ciphertext_s_values(&encrypted_delta_1)?,
+            encrypted_delta_2: ciphertext_s_values(&encrypted_delta_2)?,
+            note_commitment: nft.commit().0,
+            epk: s_value(epk.0)?,
+        };
+        let proof = Groth16::<Bls12_377>::prove(proving_key, circuit, rng)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(SwapProof(proof))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(
+        &self,
+        verifying_key: &PreparedVerifyingKey<Bls12_377>,
+        public_inputs: &SwapPublicInputs,
+    ) -> anyhow::Result<()> {
+        let mut inputs = vec![
+            public_inputs.trading_pair.asset_1().0,
+            public_inputs.trading_pair.asset_2().0,
+            public_inputs.value_commitment.0.compress_to_field(),
+            s_value(public_inputs.encryption_key.to_bytes())?,
+        ];
+        inputs.extend(ciphertext_s_values(&public_inputs.encrypted_delta_1)?);
+        inputs.extend(ciphertext_s_values(&public_inputs.encrypted_delta_2)?);
+        inputs.push(public_inputs.note_commitment.0);
+        inputs.push(s_value(public_inputs.epk.0)?);
+
+        if verify(verifying_key, &inputs, &self.0)? {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("swap proof did not verify"))
+        }
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::SwapProof> for SwapProof {}
+
+impl From<SwapProof> for pb::SwapProof {
+    fn from(proof: SwapProof) -> Self {
+        let mut inner = Vec::new();
+        proof.0.serialize(&mut inner).expect("can serialize proof");
+        pb::SwapProof { inner }
+    }
+}
+
+impl TryFrom<pb::SwapProof> for SwapProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::SwapProof) -> anyhow::Result<Self, Self::Error> {
+        Ok(SwapProof(
+            Proof::deserialize(&proto.inner[..])
+                .map_err(|_| anyhow::anyhow!("swap proof malformed"))?,
+        ))
+    }
+}
+
+impl From<SwapProof> for Vec<u8> {
+    fn from(swap_proof: SwapProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::SwapProof = swap_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SwapProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SwapProof, Self::Error> {
+        pb::SwapProof::decode(bytes)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use penumbra_crypto::{
+        asset,
+        keys::{SeedPhrase, SpendKey, SpendSeed},
+        Zero,
+    };
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{proofs::groth16::setup, TradingPair};
+
+    #[test]
+    fn swap_proof_verifies_only_against_its_public_inputs() {
+        let mut rng = OsRng;
+
+        let sk = SpendKey::new(SpendSeed::from_seed_phrase(
+            SeedPhrase::generate(&mut rng),
+            0,
+        ));
+        let (claim_address, _dtk_d) = sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let trading_pair =
+            TradingPair::new(asset::Id(Fq::from(1u64)), asset::Id(Fq::from(2u64))).unwrap();
+        let swap = SwapPlaintext::new(&mut rng, trading_pair, 100_000, 0, claim_address);
+
+        let encryption_key =
+            EncryptionKey::try_from(&(Fr::rand(&mut rng) * decaf377::basepoint()).compress().0[..])
+                .unwrap();
+        let randomness_1 = EncryptionRandomness::new(&mut rng);
+        let randomness_2 = EncryptionRandomness::new(&mut rng);
+        let v_blinding = Fr::rand(&mut rng);
+        let note_blinding = Fq::rand(&mut rng);
+        let esk = ka::Secret::new(&mut rng);
+
+        // The setup only depends on the shape of the circuit, not the values
+        // of its inputs.
+        let (pk, vk) = setup(
+            SwapCircuit {
+                delta_1: 0,
+                delta_2: 0,
+                g_d: decaf377::basepoint(),
+                pk_d: Fq::zero(),
+                swap_blinding: Fq::zero(),
+                v_blinding: Fr::zero(),
+                note_blinding: Fq::zero(),
+                esk: Fr::zero(),
+                randomness_1: [Fr::zero(); LIMBS],
+                randomness_2: [Fr::zero(); LIMBS],
+                encryption_key: decaf377::basepoint(),
+                asset_1: Fq::zero(),
+                asset_2: Fq::zero(),
+                value_commitment: Fq::zero(),
+                encryption_key_s: Fq::zero(),
+                encrypted_delta_1: [Fq::zero(); 2 * LIMBS],
+                encrypted_delta_2: [Fq::zero(); 2 * LIMBS],
+                note_commitment: Fq::zero(),
+                epk: Fq::zero(),
+            },
+            &mut rng,
+        );
+
+        let proof = SwapProof::prove(
+            &mut rng,
+            &pk,
+            &swap,
+            &encryption_key,
+            &randomness_1,
+            &randomness_2,
+            v_blinding,
+            note_blinding,
+            &esk,
+        )
+        .unwrap();
+
+        let nft = Note::from_parts(
+            *claim_address.diversifier(),
+            *claim_address.transmission_key(),
+            swap.nft_value(),
+            note_blinding,
+        )
+        .unwrap();
+        let public_inputs = SwapPublicInputs {
+            trading_pair,
+            value_commitment: swap.input_commitment(v_blinding),
+            encryption_key,
+            encrypted_delta_1: encryption_key.encrypt_with_randomness(100_000, &randomness_1),
+            encrypted_delta_2: encryption_key.encrypt_with_randomness(0, &randomness_2),
+            note_commitment: nft.commit(),
+            epk: esk.diversified_public(claim_address.diversified_generator()),
+        };
+        assert!(proof.verify(&vk, &public_inputs).is_ok());
+
+        // The proof doesn't verify against an encryption of a different amount...
+        let other_amount = SwapPublicInputs {
+            encrypted_delta_1: encryption_key.encrypt_with_randomness(100_001, &randomness_1),
+            ..public_inputs.clone()
+        };
+        assert!(proof.verify(&vk, &other_amount).is_err());
+
+        // ... or a different commitment to the inputs ...
+        let other_commitment = SwapPublicInputs {
+            value_commitment: swap.input_commitment(Fr::rand(&mut rng)),
+            ..public_inputs.clone()
+        };
+        assert!(proof.verify(&vk, &other_commitment).is_err());
+
+        // ... and survives encoding.
+        let bytes: Vec<u8> = proof.into();
+        let proof = SwapProof::try_from(&bytes[..]).unwrap();
+        assert!(proof.verify(&vk, &public_inputs).is_ok());
+    }
+}
@@ -0,0 +1,539 @@
+use std::convert::{TryFrom, TryInto};
+
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use decaf377::r1cs::{ElementVar, FqVar};
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_crypto::{
+    keys, merkle,
+    proofs::groth16::{
+        auth_path,
+        gadgets::{self, POSITION_BITS, TREE_HEIGHT},
+        s_value, verify,
+    },
+    Fq, Fr, Note, Value,
+};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use super::swap_asset_id;
+use crate::{proofs::SwapClaimPublicInputs, BatchSwapOutputData, SwapPlaintext};
+
+/// The circuit proving that a swap claim spends a swap NFT from the batch
+/// being claimed, and creates notes for the swap's share of its outputs.
+///
+/// The public inputs are:
+/// * the merkle root of the note commitment tree,
+/// * the nullifier of the swap NFT,
+/// * the randomized verification spend key,
+/// * the asset IDs of the batch's trading pair,
+/// * the batch's `delta_1`, `delta_2`, `lambda_1`, `lambda_2`, `unfilled_1`
+///   and `unfilled_2`,
+/// * the index of the block of the note commitment tree containing the swap NFT,
+/// * the note commitments of the claimed amounts of assets 1 and 2,
+///
+/// each as a field element, in that order.
+#[derive(Clone, Debug)]
+pub struct SwapClaimCircuit {
+    // Private inputs
+    /// The position of the swap NFT in the note commitment tree.
+    position: u64,
+    /// The authentication path of the swap NFT, from the root to the leaf.
+    auth_path: [[Fq; 3]; TREE_HEIGHT],
+    /// The diversified base for the claim address.
+    g_d: decaf377::Element,
+    /// The `s` value of the transmission key for the claim address.
+    pk_d: Fq,
+    /// The blinding factor of the swap NFT's asset ID.
+    swap_blinding: Fq,
+    /// The amount of asset 1 swapped for asset 2.
+    delta_1: u64,
+    /// The amount of asset 2 swapped for asset 1.
+    delta_2: u64,
+    /// The blinding factor of the swap NFT's note commitment.
+    nft_note_blinding: Fq,
+    /// The randomizer used for generating the randomized spend auth key.
+    spend_auth_randomizer: Fr,
+    /// The spend authorization key.
+    ak: decaf377::Element,
+    /// The nullifier deriving key.
+    nk: Fq,
+    /// The blinding factor of the note for the claimed amount of asset 1.
+    output_1_blinding: Fq,
+    /// The blinding factor of the note for the claimed amount of asset 2.
+    output_2_blinding: Fq,
+
+    // Public inputs
+    anchor: Fq,
+    nullifier: Fq,
+    rk: Fq,
+    output_data: BatchSwapOutputData,
+    nft_block: u32,
+    output_1: Fq,
+    output_2: Fq,
+}
+
+impl ConstraintSynthesizer<Fq> for SwapClaimCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        // Witnesses
+        let (position_bits, position) = gadgets::u64_witness(cs.clone(), self.position)?;
+        let auth_path = self
+            .auth_path
+            .iter()
+            .map(|siblings| {
+                Ok([
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[0]))?,
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[1]))?,
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[2]))?,
+                ])
+            })
+            .collect::<ark_relations::r1cs::Result<Vec<_>>>()?;
+        let g_d = ElementVar::new_witness(cs.clone(), || Ok(self.g_d))?;
+        let pk_d = FqVar::new_witness(cs.clone(), || Ok(self.pk_d))?;
+        let swap_blinding = FqVar::new_witness(cs.clone(), || Ok(self.swap_blinding))?;
+        let (_, delta_1) = gadgets::u64_witness(cs.clone(), self.delta_1)?;
+        let (_, delta_2) = gadgets::u64_witness(cs.clone(), self.delta_2)?;
+        let nft_note_blinding = FqVar::new_witness(cs.clone(), || Ok(self.nft_note_blinding))?;
+        let spend_auth_randomizer_bits =
+            gadgets::scalar_bits(cs.clone(), self.spend_auth_randomizer)?;
+        let ak = ElementVar::new_witness(cs.clone(), || Ok(self.ak))?;
+        let nk = FqVar::new_witness(cs.clone(), || Ok(self.nk))?;
+        let output_1_blinding = FqVar::new_witness(cs.clone(), || Ok(self.output_1_blinding))?;
+        let output_2_blinding = FqVar::new_witness(cs.clone(), || Ok(self.output_2_blinding))?;
+
+        // Public inputs
+        let anchor = FqVar::new_input(cs.clone(), || Ok(self.anchor))?;
+        let nullifier = FqVar::new_input(cs.clone(), || Ok(self.nullifier))?;
+        let rk = FqVar::new_input(cs.clone(), || Ok(self.rk))?;
+        let data = self.output_data;
+        let asset_1 = FqVar::new_input(cs.clone(), || Ok(data.trading_pair.asset_1().0))?;
+        let asset_2 = FqVar::new_input(cs.clone(), || Ok(data.trading_pair.asset_2().0))?;
+        let amount_input = |amount: u64| FqVar::new_input(cs.clone(), || Ok(Fq::from(amount)));
+        let batch_delta_1 = amount_input(data.delta_1)?;
+        let batch_delta_2 = amount_input(data.delta_2)?;
+        let lambda_1 = amount_input(data.lambda_1)?;
+        let lambda_2 = amount_input(data.lambda_2)?;
+        let unfilled_1 = amount_input(data.unfilled_1)?;
+        let unfilled_2 = amount_input(data.unfilled_2)?;
+        let nft_block = FqVar::new_input(cs.clone(), || Ok(Fq::from(self.nft_block as u64)))?;
+        let output_1 = FqVar::new_input(cs.clone(), || Ok(self.output_1))?;
+        let output_2 = FqVar::new_input(cs.clone(), || Ok(self.output_2))?;
+
+        // Swap NFT integrity.
+        let asset_id = swap_asset_id(
+            cs.clone(),
+            &swap_blinding,
+            &asset_1,
+            &asset_2,
+            &delta_1,
+            &delta_2,
+            &g_d,
+            &pk_d,
+        )?;
+        let nft_commitment = gadgets::note_commitment(
+            cs.clone(),
+            &nft_note_blinding,
+            &FqVar::one(),
+            &asset_id,
+            &g_d,
+            &pk_d,
+        )?;
+
+        // Merkle path integrity: positions in the tree have at most 48 bits.
+        for bit in &position_bits[POSITION_BITS..] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        gadgets::merkle_root(cs.clone(), &nft_commitment, &position_bits, &auth_path)?
+            .enforce_equal(&anchor)?;
+
+        // The swap NFT was created in the batch's block: the epoch and block
+        // indices are the top 32 bits of its position.
+        Boolean::le_bits_to_fp_var(&position_bits[16..POSITION_BITS])?.enforce_equal(&nft_block)?;
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        gadgets::enforce_not_identity(&g_d)?;
+        gadgets::enforce_not_identity(&ak)?;
+
+        // Nullifier integrity.
+        gadgets::nullifier(cs.clone(), &nk, &nft_commitment, &position)?
+            .enforce_equal(&nullifier)?;
+
+        // Spend authority.
+        let basepoint = ElementVar::new_constant(cs.clone(), decaf377::basepoint())?;
+        (ak.clone() + basepoint.scalar_mul_le(spend_auth_randomizer_bits.iter())?)
+            .compress_to_field()?
+            .enforce_equal(&rk)?;
+
+        // Diversified address integrity.
+        let ivk_bits = gadgets::ivk_bits(cs.clone(), &ak, &nk)?;
+        g_d.scalar_mul_le(ivk_bits.iter())?
+            .compress_to_field()?
+            .enforce_equal(&pk_d)?;
+
+        // Output note integrity: each output is the swap's share of the
+        // batch's payout of that asset, plus its share of the unfilled inputs.
+        let amount_1 = pro_rata(
+            cs.clone(),
+            (&delta_2, self.delta_2),
+            (&batch_delta_2, data.delta_2),
+            (&lambda_1, data.lambda_1),
+        )? + pro_rata(
+            cs.clone(),
+            (&delta_1, self.delta_1),
+            (&batch_delta_1, data.delta_1),
+            (&unfilled_1, data.unfilled_1),
+        )?;
+        let amount_2 = pro_rata(
+            cs.clone(),
+            (&delta_1, self.delta_1),
+            (&batch_delta_1, data.delta_1),
+            (&lambda_2, data.lambda_2),
+        )? + pro_rata(
+            cs.clone(),
+            (&delta_2, self.delta_2),
+            (&batch_delta_2, data.delta_2),
+            (&unfilled_2, data.unfilled_2),
+        )?;
+        gadgets::note_commitment(
+            cs.clone(),
+            &output_1_blinding,
+            &amount_1,
+            &asset_1,
+            &g_d,
+            &pk_d,
+        )?
+        .enforce_equal(&output_1)?;
+        gadgets::note_commitment(cs, &output_2_blinding, &amount_2, &asset_2, &g_d, &pk_d)?
+            .enforce_equal(&output_2)?;
+
+        Ok(())
+    }
+}
+
+/// The share `part / whole` of `amount`, rounded down, as in the batch's
+/// pro rata split, given each variable together with its value.
+///
+/// The quotient `q` and remainder `r` are witnessed as 64-bit values with
+/// `part * amount = q * whole + r` and `r < whole`.  Every term is below
+/// 2^129, so the equation cannot wrap around the field.  If `whole` is zero,
+/// there is nothing to share, and the quotient is zero.
+fn pro_rata(
+    cs: ConstraintSystemRef<Fq>,
+    (part, part_value): (&FqVar, u64),
+    (whole, whole_value): (&FqVar, u64),
+    (amount, amount_value): (&FqVar, u64),
+) -> Result<FqVar, SynthesisError> {
+    let (quotient_value, remainder_value) = if whole_value == 0 {
+        (0, 0)
+    } else {
+        let product = part_value as u128 * amount_value as u128;
+        (
+            (product / whole_value as u128) as u64,
+            (product % whole_value as u128) as u64,
+        )
+    };
+    let (_, quotient) = gadgets::u64_witness(cs.clone(), quotient_value)?;
+    let (_, remainder) = gadgets::u64_witness(cs.clone(), remainder_value)?;
+
+    (part * amount).enforce_equal(&(&quotient * whole + &remainder))?;
+
+    // The remainder is less than the whole, unless the whole is zero...
+    let whole_is_zero = whole.is_zero()?;
+    let slack_value = whole_value
+        .saturating_sub(remainder_value)
+        .saturating_sub(1);
+    let (_, slack) = gadgets::u64_witness(cs, slack_value)?;
+    slack.conditional_enforce_equal(&(whole - &remainder - FqVar::one()), &whole_is_zero.not())?;
+
+    // ... in which case the share is zero.
+    quotient.conditional_enforce_equal(&FqVar::zero(), &whole_is_zero)?;
+
+    Ok(quotient)
+}
+
+/// A Groth16 proof that a swap claim is well-formed.
+#[derive(Clone, Debug)]
+pub struct SwapClaimProof(Proof<Bls12_377>);
+
+impl SwapClaimProof {
+    /// Proves that the swap NFT for `swap_plaintext`, witnessed in the note
+    /// commitment tree with root `anchor`, is spent, and that the notes with
+    /// the given blindings are for its share of the batch `output_data`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        proving_key: &ProvingKey<Bls12_377>,
+        anchor: merkle::Root,
+        note_commitment_proof: &merkle::Proof,
+        swap_plaintext: &SwapPlaintext,
+        nft_note_blinding: Fq,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+        output_data: &BatchSwapOutputData,
+        output_1_blinding: Fq,
+        output_2_blinding: Fq,
+    ) -> anyhow::Result<Self> {
+        let circuit = SwapClaimCircuit::new(
+            anchor,
+            note_commitment_proof,
+            swap_plaintext,
+            nft_note_blinding,
+            spend_auth_randomizer,
+            ak,
+            nk,
+            output_data,
+            output_1_blinding,
+            output_2_blinding,
+        )?;
+        let proof = Groth16::<Bls12_377>::prove(proving_key, circuit, rng)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(SwapClaimProof(proof))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(
+        &self,
+        verifying_key: &PreparedVerifyingKey<Bls12_377>,
+        public_inputs: &SwapClaimPublicInputs,
+    ) -> anyhow::Result<()> {
+        let data = &public_inputs.output_data;
+        let inputs = [
+            public_inputs.anchor.clone().into(),
+            public_inputs.nullifier.0,
+            s_value(public_inputs.rk.into())?,
+            data.trading_pair.asset_1().0,
+            data.trading_pair.asset_2().0,
+            Fq::from(data.delta_1),
+            Fq::from(data.delta_2),
+            Fq::from(data.lambda_1),
+            Fq::from(data.lambda_2),
+            Fq::from(data.unfilled_1),
+            Fq::from(data.unfilled_2),
+            Fq::from(public_inputs.nft_block as u64),
+            public_inputs.output_1.0,
+            public_inputs.output_2.0,
+        ];
+
+        if verify(verifying_key, &inputs, &self.0)? {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("swap claim proof did not verify"))
+        }
+    }
+}
+
+impl SwapClaimCircuit {
+    /// The circuit for claiming `swap_plaintext` from the batch `output_data`.
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        anchor: merkle::Root,
+        note_commitment_proof: &merkle::Proof,
+        swap_plaintext: &SwapPlaintext,
+        nft_note_blinding: Fq,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+        output_data: &BatchSwapOutputData,
+        output_1_blinding: Fq,
+        output_2_blinding: Fq,
+    ) -> anyhow::Result<Self> {
+        let position = note_commitment_proof.position();
+        let claim_address = &swap_plaintext.claim_address;
+
+        let nft = Note::from_parts(
+            *claim_address.diversifier(),
+            *claim_address.transmission_key(),
+            swap_plaintext.nft_value(),
+            nft_note_blinding,
+        )?;
+        let ak_element = decaf377::Encoding(ak.into())
+            .decompress()
+            .map_err(|_| anyhow::anyhow!("invalid spend authorization key"))?;
+        let rk: [u8; 32] = ak.randomize(&spend_auth_randomizer).into();
+
+        let (amount_1, amount_2) =
+            output_data.claim_amounts(swap_plaintext.delta_1, swap_plaintext.delta_2);
+        let output = |amount, asset_id, note_blinding| {
+            Note::from_parts(
+                *claim_address.diversifier(),
+                *claim_address.transmission_key(),
+                Value { amount, asset_id },
+                note_blinding,
+            )
+            .map(|note| note.commit().0)
+        };
+
+        Ok(SwapClaimCircuit {
+            position: position.into(),
+            auth_path: auth_path(note_commitment_proof),
+            g_d: nft.diversified_generator(),
+            pk_d: nft.transmission_key_s(),
+            swap_blinding: swap_plaintext.swap_blinding,
+            delta_1: swap_plaintext.delta_1,
+            delta_2: swap_plaintext.delta_2,
+            nft_note_blinding,
+            spend_auth_randomizer,
+            ak: ak_element,
+            nk: nk.0,
+            output_1_blinding,
+            output_2_blinding,
+            anchor: anchor.into(),
+            nullifier: nk.derive_nullifier(position, &nft.commit()).0,
+            rk: s_value(rk)?,
+            output_data: *output_data,
+            nft_block: (u64::from(position) >> 16) as u32,
+            output_1: output(
+                amount_1,
+                output_data.trading_pair.asset_1(),
+                output_1_blinding,
+            )?,
+            output_2: output(
+                amount_2,
+                output_data.trading_pair.asset_2(),
+                output_2_blinding,
+            )?,
+        })
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::SwapClaimProof> for SwapClaimProof {}
+
+impl From<SwapClaimProof> for pb::SwapClaimProof {
+    fn from(proof: SwapClaimProof) -> Self {
+        let mut inner = Vec::new();
+        proof.0.serialize(&mut inner).expect("can serialize proof");
+        pb::SwapClaimProof { inner }
+    }
+}
+
+impl TryFrom<pb::SwapClaimProof> for SwapClaimProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::SwapClaimProof) -> anyhow::Result<Self, Self::Error> {
+        Ok(SwapClaimProof(
+            Proof::deserialize(&proto.inner[..])
+                .map_err(|_| anyhow::anyhow!("swap claim proof malformed"))?,
+        ))
+    }
+}
+
+impl From<SwapClaimProof> for Vec<u8> {
+    fn from(swap_claim_proof: SwapClaimProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::SwapClaimProof = swap_claim_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SwapClaimProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SwapClaimProof, Self::Error> {
+        pb::SwapClaimProof::decode(bytes)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use penumbra_crypto::{
+        asset,
+        keys::{SeedPhrase, SpendKey, SpendSeed},
+        merkle::{Keep, NoteCommitmentTree},
+    };
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::TradingPair;
+
+    #[test]
+    fn swap_claim_circuit_pays_out_the_swaps_share() {
+        let mut rng = OsRng;
+
+        let sk = SpendKey::new(SpendSeed::from_seed_phrase(
+            SeedPhrase::generate(&mut rng),
+            0,
+        ));
+        let (claim_address, _dtk_d) = sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let trading_pair =
+            TradingPair::new(asset::Id(Fq::from(1u64)), asset::Id(Fq::from(2u64))).unwrap();
+        let swap = SwapPlaintext::new(&mut rng, trading_pair, 100, 0, claim_address);
+        let nft_note_blinding = Fq::rand(&mut rng);
+        let nft = Note::from_parts(
+            *claim_address.diversifier(),
+            *claim_address.transmission_key(),
+            swap.nft_value(),
+            nft_note_blinding,
+        )
+        .unwrap();
+
+        let mut nct = NoteCommitmentTree::new();
+        nct.insert(Keep, nft.commit()).unwrap();
+        let note_commitment_proof = nct.witness(nft.commit()).unwrap();
+
+        // 300 of asset 1 against 600 of asset 2, so the swap buys 200 of asset 2.
+        let output_data = BatchSwapOutputData::clear(1, trading_pair, 300, 600);
+        let ak: VerificationKey<SpendAuth> = (*sk.spend_auth_key()).into();
+        let spend_auth_randomizer = Fr::rand(&mut rng);
+        let output_1_blinding = Fq::rand(&mut rng);
+        let output_2_blinding = Fq::rand(&mut rng);
+        let circuit = |output_data: &BatchSwapOutputData| {
+            SwapClaimCircuit::new(
+                nct.root(),
+                &note_commitment_proof,
+                &swap,
+                nft_note_blinding,
+                spend_auth_randomizer,
+                ak,
+                *sk.nullifier_key(),
+                output_data,
+                output_1_blinding,
+                output_2_blinding,
+            )
+            .unwrap()
+        };
+
+        let claim = circuit(&output_data);
+        assert_eq!(claim.nft_block, 0);
+        let cs = ConstraintSystem::new_ref();
+        claim.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // The claim doesn't hold for a swap NFT from a different block...
+        let cs = ConstraintSystem::new_ref();
+        SwapClaimCircuit {
+            nft_block: 1,
+            ..claim.clone()
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // ... or for more than the swap's share of the batch.
+        let cs = ConstraintSystem::new_ref();
+        SwapClaimCircuit {
+            output_2: circuit(&BatchSwapOutputData {
+                lambda_2: 900,
+                ..output_data
+            })
+            .output_2,
+            ..claim
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,326 @@
+//! Transparent proofs for swaps and swap claims, which reveal the swap.
+
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::{anyhow, Result};
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_crypto::{ka, keys, merkle, note, FieldExt, Fq, Fr, Note, Value};
+use penumbra_flow_encryption::EncryptionRandomness;
+use penumbra_proto::{dex as pb, Message, Protobuf};
+
+use super::{SwapClaimPublicInputs, SwapPublicInputs};
+use crate::SwapPlaintext;
+
+/// Transparent proof that a swap is well-formed.
+///
+/// This structure keeps track of the auxiliary (private) inputs.
+#[derive(Clone, Debug)]
+pub struct SwapProof {
+    // The contents of the swap.
+    pub swap_plaintext: SwapPlaintext,
+    // The blinding factor used for generating the value commitment.
+    pub v_blinding: Fr,
+    // The blinding factor used for generating the swap NFT's note commitment.
+    pub note_blinding: Fq,
+    // The ephemeral secret key used to generate the swap NFT.
+    pub esk: ka::Secret,
+    // The randomness used to encrypt the amount of asset 1.
+    pub randomness_1: EncryptionRandomness,
+    // The randomness used to encrypt the amount of asset 2.
+    pub randomness_2: EncryptionRandomness,
+}
+
+impl SwapProof {
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(&self, public_inputs: &SwapPublicInputs) -> Result<()> {
+        let swap = &self.swap_plaintext;
+
+        if swap.trading_pair != public_inputs.trading_pair {
+            return Err(anyhow!("trading pair mismatch"));
+        }
+
+        // Value commitment integrity.
+        if swap.input_commitment(self.v_blinding) != public_inputs.value_commitment {
+            return Err(anyhow!("value commitment mismatch"));
+        }
+
+        // Swap NFT integrity.
+        let nft = swap_nft(swap, self.note_blinding)?;
+        if nft.commit() != public_inputs.note_commitment {
+            return Err(anyhow!("note commitment mismatch"));
+        }
+
+        // Ephemeral public key integrity.
+        if self.esk.diversified_public(&nft.diversified_generator()) != public_inputs.epk {
+            return Err(anyhow!("ephemeral public key mismatch"));
+        }
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        if nft.diversified_generator().is_identity() {
+            return Err(anyhow!("must not be an identity"));
+        }
+
+        // Flow encryption integrity.
+        let encryption_key = &public_inputs.encryption_key;
+        if encryption_key.encrypt_with_randomness(swap.delta_1, &self.randomness_1)
+            != public_inputs.encrypted_delta_1
+            || encryption_key.encrypt_with_randomness(swap.delta_2, &self.randomness_2)
+                != public_inputs.encrypted_delta_2
+        {
+            return Err(anyhow!("encrypted amount mismatch"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Transparent proof that a swap claim is well-formed.
+///
+/// This structure keeps track of the auxiliary (private) inputs.
+#[derive(Clone, Debug)]
+pub struct SwapClaimProof {
+    // The contents of the swap being claimed.
+    pub swap_plaintext: SwapPlaintext,
+    // Inclusion proof for the swap NFT's note commitment.
+    pub note_commitment_proof: merkle::Proof,
+    // The blinding factor of the swap NFT's note commitment.
+    pub nft_note_blinding: Fq,
+    // The randomizer used for generating the randomized spend auth key.
+    pub spend_auth_randomizer: Fr,
+    // The spend authorization key.
+    pub ak: VerificationKey<SpendAuth>,
+    // The nullifier deriving key.
+    pub nk: keys::NullifierKey,
+    // The blinding factor of the note for the claimed amount of asset 1.
+    pub output_1_blinding: Fq,
+    // The blinding factor of the note for the claimed amount of asset 2.
+    pub output_2_blinding: Fq,
+}
+
+impl SwapClaimProof {
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(&self, public_inputs: &SwapClaimPublicInputs) -> Result<()> {
+        let swap = &self.swap_plaintext;
+        let output_data = &public_inputs.output_data;
+
+        if swap.trading_pair != output_data.trading_pair {
+            return Err(anyhow!("trading pair mismatch"));
+        }
+
+        // Merkle path integrity.
+        let nft = swap_nft(swap, self.nft_note_blinding)?;
+        let nft_commitment = nft.commit();
+        if note::Commitment::from(self.note_commitment_proof.commitment()) != nft_commitment {
+            return Err(anyhow!("note commitment mismatch"));
+        }
+        if self
+            .note_commitment_proof
+            .verify(public_inputs.anchor.clone())
+            .is_err()
+        {
+            return Err(anyhow!("merkle root mismatch"));
+        }
+
+        // The swap NFT was created in the batch's block.
+        let position = self.note_commitment_proof.position();
+        if u64::from(position) >> 16 != public_inputs.nft_block as u64 {
+            return Err(anyhow!("swap NFT is not from the batch's block"));
+        }
+
+        // Nullifier integrity.
+        if public_inputs.nullifier != self.nk.derive_nullifier(position, &nft_commitment) {
+            return Err(anyhow!("bad nullifier"));
+        }
+
+        // Spend authority.
+        let rk_bytes: [u8; 32] = public_inputs.rk.into();
+        let rk_test_bytes: [u8; 32] = self.ak.randomize(&self.spend_auth_randomizer).into();
+        if rk_bytes != rk_test_bytes {
+            return Err(anyhow!("invalid spend auth randomizer"));
+        }
+
+        if nft.diversified_generator().is_identity() || self.ak.is_identity() {
+            return Err(anyhow!("must not be an identity"));
+        }
+
+        // Diversified address integrity.
+        let fvk = keys::FullViewingKey::from_components(self.ak, self.nk);
+        if nft.transmission_key()
+            != fvk
+                .incoming()
+                .diversified_public(&nft.diversified_generator())
+        {
+            return Err(anyhow!("invalid diversified address"));
+        }
+
+        // Output note integrity: the outputs are the swap's share of the batch.
+        let (amount_1, amount_2) = output_data.claim_amounts(swap.delta_1, swap.delta_2);
+        let outputs = [
+            (
+                amount_1,
+                output_data.trading_pair.asset_1(),
+                self.output_1_blinding,
+                public_inputs.output_1,
+            ),
+            (
+                amount_2,
+                output_data.trading_pair.asset_2(),
+                self.output_2_blinding,
+                public_inputs.output_2,
+            ),
+        ];
+        for (amount, asset_id, note_blinding, commitment) in outputs {
+            let note = Note::from_parts(
+                *swap.claim_address.diversifier(),
+                *swap.claim_address.transmission_key(),
+                Value { amount, asset_id },
+                note_blinding,
+            )?;
+            if note.commit() != commitment {
+                return Err(anyhow!("output note commitment mismatch"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The swap NFT note for `swap`.
+fn swap_nft(swap: &SwapPlaintext, note_blinding: Fq) -> Result<Note> {
+    Ok(Note::from_parts(
+        *swap.claim_address.diversifier(),
+        *swap.claim_address.transmission_key(),
+        swap.nft_value(),
+        note_blinding,
+    )?)
+}
+
+// Conversions
+
+fn decode_fq(bytes: &[u8]) -> Result<Fq> {
+    Fq::from_bytes(
+        bytes
+            .try_into()
+            .map_err(|_| anyhow!("transparent proof proto malformed"))?,
+    )
+    .map_err(|_| anyhow!("transparent proof proto malformed"))
+}
+
+fn decode_fr(bytes: &[u8]) -> Result<Fr> {
+    Fr::from_bytes(
+        bytes
+            .try_into()
+            .map_err(|_| anyhow!("transparent proof proto malformed"))?,
+    )
+    .map_err(|_| anyhow!("transparent proof proto malformed"))
+}
+
+impl Protobuf<pb::TransparentSwapProof> for SwapProof {}
+
+impl From<SwapProof> for pb::TransparentSwapProof {
+    fn from(msg: SwapProof) -> Self {
+        pb::TransparentSwapProof {
+            swap_plaintext: Some(msg.swap_plaintext.into()),
+            v_blinding: msg.v_blinding.to_bytes().to_vec(),
+            note_blinding: msg.note_blinding.to_bytes().to_vec(),
+            esk: msg.esk.to_bytes().to_vec(),
+            delta_1_randomness: msg.randomness_1.to_bytes(),
+            delta_2_randomness: msg.randomness_2.to_bytes(),
+        }
+    }
+}
+
+impl TryFrom<pb::TransparentSwapProof> for SwapProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::TransparentSwapProof) -> Result<Self, Self::Error> {
+        Ok(SwapProof {
+            swap_plaintext: proto
+                .swap_plaintext
+                .ok_or_else(|| anyhow!("missing swap plaintext"))?
+                .try_into()?,
+            v_blinding: decode_fr(&proto.v_blinding)?,
+            note_blinding: decode_fq(&proto.note_blinding)?,
+            esk: ka::Secret::new_from_field(decode_fr(&proto.esk)?),
+            randomness_1: proto.delta_1_randomness[..].try_into()?,
+            randomness_2: proto.delta_2_randomness[..].try_into()?,
+        })
+    }
+}
+
+impl From<SwapProof> for Vec<u8> {
+    fn from(swap_proof: SwapProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::TransparentSwapProof = swap_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SwapProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SwapProof, Self::Error> {
+        pb::TransparentSwapProof::decode(bytes)?.try_into()
+    }
+}
+
+impl Protobuf<pb::TransparentSwapClaimProof> for SwapClaimProof {}
+
+impl From<SwapClaimProof> for pb::TransparentSwapClaimProof {
+    fn from(msg: SwapClaimProof) -> Self {
+        let ak_bytes: [u8; 32] = msg.ak.into();
+        pb::TransparentSwapClaimProof {
+            swap_plaintext: Some(msg.swap_plaintext.into()),
+            note_commitment_proof: Some(msg.note_commitment_proof.into()),
+            nft_note_blinding: msg.nft_note_blinding.to_bytes().to_vec(),
+            spend_auth_randomizer: msg.spend_auth_randomizer.to_bytes().to_vec(),
+            ak: ak_bytes.to_vec(),
+            nk: msg.nk.0.to_bytes().to_vec(),
+            output_1_blinding: msg.output_1_blinding.to_bytes().to_vec(),
+            output_2_blinding: msg.output_2_blinding.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::TransparentSwapClaimProof> for SwapClaimProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::TransparentSwapClaimProof) -> Result<Self, Self::Error> {
+        let ak_bytes: [u8; 32] = proto.ak[..]
+            .try_into()
+            .map_err(|_| anyhow!("transparent proof proto malformed"))?;
+
+        Ok(SwapClaimProof {
+            swap_plaintext: proto
+                .swap_plaintext
+                .ok_or_else(|| anyhow!("missing swap plaintext"))?
+                .try_into()?,
+            note_commitment_proof: proto
+                .note_commitment_proof
+                .ok_or_else(|| anyhow!("missing note commitment proof"))?
+                .try_into()?,
+            nft_note_blinding: decode_fq(&proto.nft_note_blinding)?,
+            spend_auth_randomizer: decode_fr(&proto.spend_auth_randomizer)?,
+            ak: ak_bytes.try_into()?,
+            nk: keys::NullifierKey(decode_fq(&proto.nk)?),
+            output_1_blinding: decode_fq(&proto.output_1_blinding)?,
+            output_2_blinding: decode_fq(&proto.output_2_blinding)?,
+        })
+    }
+}
+
+impl From<SwapClaimProof> for Vec<u8> {
+    fn from(swap_claim_proof: SwapClaimProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::TransparentSwapClaimProof = swap_claim_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for SwapClaimProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SwapClaimProof, Self::Error> {
+        pb::TransparentSwapClaimProof::decode(bytes)?.try_into()
+    }
+}
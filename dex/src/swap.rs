@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Result};
+use ark_ff::{PrimeField, UniformRand};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use once_cell::sync::Lazy;
+use penumbra_crypto::{
+    asset, ka, keys::IncomingViewingKey, note, value, Address, FieldExt, Fq, Fr, One, Value, Zero,
+};
+use penumbra_proto::{dex as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::TradingPair;
+
+/// The domain separator used to derive the asset IDs of swap NFTs.
+pub(crate) static SWAP_NFT_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| {
+    Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(b"penumbra.swapnft").as_bytes())
+});
+
+/// The domain separator used to hash a claim address into a swap NFT's asset ID.
+pub(crate) static SWAP_ADDRESS_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| {
+    Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(b"penumbra.swapnft.address").as_bytes())
+});
+
+/// The nonce used for swap encryption.
+///
+/// The swap is encrypted with the same key as its NFT note, which uses the
+/// note encryption nonce, so this must differ from it.
+pub static SWAP_ENCRYPTION_NONCE: Lazy<[u8; 12]> = Lazy::new(|| [1u8; 12]);
+
+/// The contents of a swap.
+///
+/// A swap burns its inputs and creates a swap NFT, a note of a single unit of
+/// an asset whose ID is a hiding commitment to the swap's contents.  Spending
+/// the NFT in a swap claim proves knowledge of the plaintext, which determines
+/// the claimed outputs, without revealing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapPlaintext {
+    /// The trading pair to swap on.
+    pub trading_pair: TradingPair,
+    /// The amount of asset 1 to swap for asset 2.
+    pub delta_1: u64,
+    /// The amount of asset 2 to swap for asset 1.
+    pub delta_2: u64,
+    /// The address the swap's outputs are claimed to.
+    pub claim_address: Address,
+    /// The blinding factor that makes the swap NFT's asset ID hiding.
+    pub swap_blinding: Fq,
+}
+
+impl SwapPlaintext {
+    /// Creates a swap with a random blinding factor.
+    pub fn new<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        trading_pair: TradingPair,
+        delta_1: u64,
+        delta_2: u64,
+        claim_address: Address,
+    ) -> Self {
+        Self {
+            trading_pair,
+            delta_1,
+            delta_2,
+            claim_address,
+            swap_blinding: Fq::rand(rng),
+        }
+    }
+
+    /// The asset ID of the swap NFT for this swap.
+    ///
+    /// This is a Poseidon hash, so that the swap circuits can compute it, of
+    /// the blinding factor, the trading pair, both amounts packed into one
+    /// field element, and a hash of the claim address.
+    pub fn asset_id(&self) -> asset::Id {
+        let transmission_key_s = Fq::from_bytes(self.claim_address.transmission_key().0)
+            .expect("transmission key in address is always valid");
+        let address_hash = poseidon377::hash_2(
+            &SWAP_ADDRESS_DOMAIN_SEP,
+            (
+                self.claim_address
+                    .diversified_generator()
+                    .compress_to_field(),
+                transmission_key_s,
+            ),
+        );
+
+        asset::Id(poseidon377::hash_5(
+            &SWAP_NFT_DOMAIN_SEP,
+            (
+                self.swap_blinding,
+                self.trading_pair.asset_1().0,
+                self.trading_pair.asset_2().0,
+                packed_deltas(self.delta_1, self.delta_2),
+                address_hash,
+            ),
+        ))
+    }
+
+    /// The value of the swap NFT for this swap.
+    pub fn nft_value(&self) -> Value {
+        Value {
+            amount: 1,
+            asset_id: self.asset_id(),
+        }
+    }
+
+    /// The values of the swap's inputs, which it burns.
+    pub fn inputs(&self) -> [Value; 2] {
+        [
+            Value {
+                amount: self.delta_1,
+                asset_id: self.trading_pair.asset_1(),
+            },
+            Value {
+                amount: self.delta_2,
+                asset_id: self.trading_pair.asset_2(),
+            },
+        ]
+    }
+
+    /// The commitment to the swap's inputs, negated since they are burned.
+    ///
+    /// A single blinding factor covers both inputs.
+    pub fn input_commitment(&self, v_blinding: Fr) -> value::Commitment {
+        let [input_1, input_2] = self.inputs();
+        -(input_1.commit(v_blinding) + input_2.commit(Fr::zero()))
+    }
+
+    /// Encrypts the swap to its claim address, with the key of the swap NFT
+    /// note created with `esk`.
+    pub fn encrypt(&self, esk: &ka::Secret) -> Vec<u8> {
+        let epk = esk.diversified_public(self.claim_address.diversified_generator());
+        let shared_secret = esk
+            .key_agreement_with(self.claim_address.transmission_key())
+            .expect("key agreement succeeded");
+
+        let key = note::derive_symmetric_key(&shared_secret, &epk);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_bytes()));
+        let nonce = Nonce::from_slice(&*SWAP_ENCRYPTION_NONCE);
+
+        let plaintext = pb::SwapPlaintext::from(self.clone()).encode_to_vec();
+        cipher
+            .encrypt(nonce, plaintext.as_ref())
+            .expect("swap encryption succeeded")
+    }
+
+    /// Decrypts a swap ciphertext with the ephemeral key of its swap NFT note.
+    pub fn decrypt(ciphertext: &[u8], ivk: &IncomingViewingKey, epk: &ka::Public) -> Result<Self> {
+        let shared_secret = ivk
+            .key_agreement_with(epk)
+            .map_err(|_| anyhow!("could not decrypt swap"))?;
+
+        let key = note::derive_symmetric_key(&shared_secret, epk);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_bytes()));
+        let nonce = Nonce::from_slice(&*SWAP_ENCRYPTION_NONCE);
+        let plaintext = cipher
+            .decrypt(nonce, ciphertext)
+            .map_err(|_| anyhow!("could not decrypt swap"))?;
+
+        SwapPlaintext::decode(plaintext.as_ref())
+    }
+}
+
+/// Both amounts of a swap as a single field element, `delta_1 + delta_2 * 2^64`.
+pub(crate) fn packed_deltas(delta_1: u64, delta_2: u64) -> Fq {
+    Fq::from(delta_1) + Fq::from(delta_2) * (Fq::from(u64::MAX) + Fq::one())
+}
+
+impl Protobuf<pb::SwapPlaintext> for SwapPlaintext {}
+
+impl From<SwapPlaintext> for pb::SwapPlaintext {
+    fn from(s: SwapPlaintext) -> Self {
+        pb::SwapPlaintext {
+            trading_pair: Some(s.trading_pair.into()),
+            delta_1: s.delta_1,
+            delta_2: s.delta_2,
+            claim_address: Some(s.claim_address.into()),
+            swap_blinding: s.swap_blinding.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::SwapPlaintext> for SwapPlaintext {
+    type Error = anyhow::Error;
+    fn try_from(s: pb::SwapPlaintext) -> Result<Self> {
+        Ok(SwapPlaintext {
+            trading_pair: s
+                .trading_pair
+                .ok_or_else(|| anyhow!("missing swap trading pair"))?
+                .try_into()?,
+            delta_1: s.delta_1,
+            delta_2: s.delta_2,
+            claim_address: s
+                .claim_address
+                .ok_or_else(|| anyhow!("missing swap claim address"))?
+                .try_into()?,
+            swap_blinding: Fq::from_bytes(
+                s.swap_blinding[..]
+                    .try_into()
+                    .map_err(|_| anyhow!("swap blinding factor malformed"))?,
+            )
+            .map_err(|_| anyhow!("swap blinding factor malformed"))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::keys::{SeedPhrase, SpendKey, SpendSeed};
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn swap_round_trips_through_encryption() {
+        let mut rng = OsRng;
+
+        let sk = SpendKey::new(SpendSeed::from_seed_phrase(
+            SeedPhrase::generate(&mut rng),
+            0,
+        ));
+        let ivk = sk.full_viewing_key().incoming();
+        let (claim_address, _dtk) = ivk.payment_address(0u64.into());
+
+        let trading_pair =
+            TradingPair::new(asset::Id(Fq::from(1u64)), asset::Id(Fq::from(2u64))).unwrap();
+        let swap = SwapPlaintext::new(&mut rng, trading_pair, 100, 0, claim_address);
+
+        let esk = ka::Secret::new(&mut rng);
+        let epk = esk.diversified_public(claim_address.diversified_generator());
+        let ciphertext = swap.encrypt(&esk);
+
+        assert_eq!(
+            SwapPlaintext::decrypt(&ciphertext, &ivk, &epk).unwrap(),
+            swap
+        );
+
+        // The NFT's asset ID hides the swap: the same swap with a different
+        // blinding factor has an unrelated ID.
+        let other = SwapPlaintext::new(&mut rng, trading_pair, 100, 0, claim_address);
+        assert_ne!(swap.asset_id(), other.asset_id());
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::{anyhow, Result};
+use penumbra_crypto::asset;
+use penumbra_proto::{dex as pb, Protobuf};
+
+/// A pair of distinct assets which can be swapped for one another.
+///
+/// The assets are always held in canonical order, so that swaps in either
+/// direction between the same two assets are batched together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TradingPair {
+    asset_1: asset::Id,
+    asset_2: asset::Id,
+}
+
+impl TradingPair {
+    /// Constructs the trading pair between two distinct assets, in either order.
+    pub fn new(a: asset::Id, b: asset::Id) -> Result<Self> {
+        if a == b {
+            return Err(anyhow!("cannot trade asset {} against itself", a));
+        }
+        let (asset_1, asset_2) = if a < b { (a, b) } else { (b, a) };
+        Ok(Self { asset_1, asset_2 })
+    }
+
+    /// The asset with the smaller asset ID.
+    pub fn asset_1(&self) -> asset::Id {
+        self.asset_1
+    }
+
+    /// The asset with the larger asset ID.
+    pub fn asset_2(&self) -> asset::Id {
+        self.asset_2
+    }
+}
+
+impl std::fmt::Display for TradingPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.asset_1, self.asset_2)
+    }
+}
+
+impl Protobuf<pb::TradingPair> for TradingPair {}
+
+impl From<TradingPair> for pb::TradingPair {
+    fn from(pair: TradingPair) -> Self {
+        pb::TradingPair {
+            asset_1: Some(pair.asset_1.into()),
+            asset_2: Some(pair.asset_2.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::TradingPair> for TradingPair {
+    type Error = anyhow::Error;
+    fn try_from(pair: pb::TradingPair) -> Result<Self> {
+        let asset_1: asset::Id = pair
+            .asset_1
+            .ok_or_else(|| anyhow!("missing trading pair asset 1"))?
+            .try_into()?;
+        let asset_2: asset::Id = pair
+            .asset_2
+            .ok_or_else(|| anyhow!("missing trading pair asset 2"))?
+            .try_into()?;
+        // Reject non-canonical encodings, rather than silently reordering them,
+        // since the deltas of a swap refer to the assets by position.
+        if asset_1 >= asset_2 {
+            return Err(anyhow!("trading pair assets are not in canonical order"));
+        }
+        Ok(Self { asset_1, asset_2 })
+    }
+}
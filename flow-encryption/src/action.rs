@@ -0,0 +1,209 @@
+use penumbra_crypto::rdsa::{Signature, SpendAuth};
+use penumbra_proto::{flow_encryption as pb, Protobuf};
+use penumbra_stake::IdentityKey;
+
+use crate::{
+    dkg::{Commitment, DealingSecret, EncryptedShare},
+    DecryptionShare,
+};
+
+/// A validator's message in the DKG for an epoch, authorized by its identity
+/// key.
+///
+/// Like a validator vote, the signature is over the encoded body rather than
+/// the transaction, so the message can be submitted in any transaction.
+#[derive(Debug, Clone)]
+pub struct DkgMessage {
+    pub body: Body,
+    /// The signature of the validator's identity key over the encoded body.
+    pub auth_sig: Signature<SpendAuth>,
+}
+
+/// The body of a DKG message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    /// The epoch whose flow encryption key is being generated.
+    pub epoch_index: u64,
+    /// The validator sending the message.
+    pub identity_key: IdentityKey,
+    pub message: Message,
+}
+
+/// The content of a DKG message, one for each round of the DKG.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// The validator's commitment to its polynomial.
+    Commitment(Commitment),
+    /// The validator's encrypted shares for every participant.
+    Deal(Vec<EncryptedShare>),
+    /// A complaint about the shares the validator received.
+    Complaint(DealingSecret),
+}
+
+/// A validator's shares of the decryption of the flows queued at a height,
+/// authorized by its identity key like a [`DkgMessage`].
+#[derive(Debug, Clone)]
+pub struct FlowDecryption {
+    pub body: FlowDecryptionBody,
+    /// The signature of the validator's identity key over the encoded body.
+    pub auth_sig: Signature<SpendAuth>,
+}
+
+/// The body of a flow decryption.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowDecryptionBody {
+    /// The height at which the flows were queued.
+    pub height: u64,
+    /// The validator sending the shares.
+    pub identity_key: IdentityKey,
+    /// The validator's share of the decryption of each of the flows, in order.
+    pub shares: Vec<DecryptionShare>,
+}
+
+impl Body {
+    /// The context for the proof of knowledge in a commitment, binding it to
+    /// the epoch and the validator.
+    pub fn context(&self) -> Vec<u8> {
+        commitment_context(self.epoch_index, &self.identity_key)
+    }
+}
+
+/// The context for the proof of knowledge in the commitment of the given
+/// validator, in the DKG for the given epoch.
+pub fn commitment_context(epoch_index: u64, identity_key: &IdentityKey) -> Vec<u8> {
+    let mut context = epoch_index.to_le_bytes().to_vec();
+    context.extend_from_slice(&identity_key.0.to_bytes());
+    context
+}
+
+impl Protobuf<pb::DkgMessage> for DkgMessage {}
+
+impl From<DkgMessage> for pb::DkgMessage {
+    fn from(m: DkgMessage) -> Self {
+        pb::DkgMessage {
+            body: Some(m.body.into()),
+            auth_sig: m.auth_sig.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::DkgMessage> for DkgMessage {
+    type Error = anyhow::Error;
+    fn try_from(m: pb::DkgMessage) -> Result<Self, Self::Error> {
+        Ok(DkgMessage {
+            body: m
+                .body
+                .ok_or_else(|| anyhow::anyhow!("missing DKG message body"))?
+                .try_into()?,
+            auth_sig: m.auth_sig.as_slice().try_into()?,
+        })
+    }
+}
+
+impl Protobuf<pb::DkgMessageBody> for Body {}
+
+impl From<Body> for pb::DkgMessageBody {
+    fn from(b: Body) -> Self {
+        pb::DkgMessageBody {
+            epoch_index: b.epoch_index,
+            identity_key: Some(b.identity_key.into()),
+            message: Some(match b.message {
+                Message::Commitment(commitment) => {
+                    pb::dkg_message_body::Message::Commitment(commitment.into())
+                }
+                Message::Deal(shares) => pb::dkg_message_body::Message::Deal(pb::DkgDeal {
+                    shares: shares.into_iter().map(Into::into).collect(),
+                }),
+                Message::Complaint(dealing_secret) => {
+                    pb::dkg_message_body::Message::Complaint(pb::DkgComplaint {
+                        dealing_secret: dealing_secret.to_bytes().to_vec(),
+                    })
+                }
+            }),
+        }
+    }
+}
+
+impl TryFrom<pb::DkgMessageBody> for Body {
+    type Error = anyhow::Error;
+    fn try_from(b: pb::DkgMessageBody) -> Result<Self, Self::Error> {
+        Ok(Body {
+            epoch_index: b.epoch_index,
+            identity_key: b
+                .identity_key
+                .ok_or_else(|| anyhow::anyhow!("missing validator identity key"))?
+                .try_into()?,
+            message: match b
+                .message
+                .ok_or_else(|| anyhow::anyhow!("missing DKG message"))?
+            {
+                pb::dkg_message_body::Message::Commitment(commitment) => {
+                    Message::Commitment(commitment.try_into()?)
+                }
+                pb::dkg_message_body::Message::Deal(deal) => Message::Deal(
+                    deal.shares
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                ),
+                pb::dkg_message_body::Message::Complaint(complaint) => {
+                    Message::Complaint(complaint.dealing_secret.as_slice().try_into()?)
+                }
+            },
+        })
+    }
+}
+
+impl Protobuf<pb::FlowDecryption> for FlowDecryption {}
+
+impl From<FlowDecryption> for pb::FlowDecryption {
+    fn from(d: FlowDecryption) -> Self {
+        pb::FlowDecryption {
+            body: Some(d.body.into()),
+            auth_sig: d.auth_sig.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowDecryption> for FlowDecryption {
+    type Error = anyhow::Error;
+    fn try_from(d: pb::FlowDecryption) -> Result<Self, Self::Error> {
+        Ok(FlowDecryption {
+            body: d
+                .body
+                .ok_or_else(|| anyhow::anyhow!("missing flow decryption body"))?
+                .try_into()?,
+            auth_sig: d.auth_sig.as_slice().try_into()?,
+        })
+    }
+}
+
+impl Protobuf<pb::FlowDecryptionBody> for FlowDecryptionBody {}
+
+impl From<FlowDecryptionBody> for pb::FlowDecryptionBody {
+    fn from(b: FlowDecryptionBody) -> Self {
+        pb::FlowDecryptionBody {
+            height: b.height,
+            identity_key: Some(b.identity_key.into()),
+            shares: b.shares.iter().map(DecryptionShare::to_bytes).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::FlowDecryptionBody> for FlowDecryptionBody {
+    type Error = anyhow::Error;
+    fn try_from(b: pb::FlowDecryptionBody) -> Result<Self, Self::Error> {
+        Ok(FlowDecryptionBody {
+            height: b.height,
+            identity_key: b
+                .identity_key
+                .ok_or_else(|| anyhow::anyhow!("missing validator identity key"))?
+                .try_into()?,
+            shares: b
+                .shares
+                .iter()
+                .map(|share| share.as_slice().try_into())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ark_ff::{Field, UniformRand, Zero};
+use decaf377::{FieldExt, Fr};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{
+    dkg::{KeyShare, PublicShare},
+    elgamal::{decode_element, decode_scalar},
+    hash::Challenge,
+    Ciphertext, Error, LIMBS, LIMB_BITS,
+};
+
+/// A participant's share of the decryption of a [`Ciphertext`], with a proof
+/// that it was computed with the participant's [`KeyShare`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecryptionShare {
+    participant: u32,
+    limbs: [decaf377::Element; LIMBS],
+    proofs: [ShareProof; LIMBS],
+}
+
+/// A Chaum-Pedersen proof that a limb's decryption share and the
+/// participant's public share have the same discrete log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ShareProof {
+    r: Fr,
+    t: Fr,
+}
+
+impl KeyShare {
+    /// Computes this participant's share of the decryption of `ciphertext`.
+    pub fn decryption_share<R: RngCore + CryptoRng>(
+        &self,
+        ciphertext: &Ciphertext,
+        mut rng: R,
+    ) -> DecryptionShare {
+        let g = decaf377::basepoint();
+        let mut limbs = [decaf377::Element::default(); LIMBS];
+        let mut proofs = [ShareProof {
+            r: Fr::zero(),
+            t: Fr::zero(),
+        }; LIMBS];
+
+        for (i, c) in ciphertext.limbs.iter().enumerate() {
+            let s = self.secret * c.c0;
+            let k = Fr::rand(&mut rng);
+            let t = share_challenge(&s, &c.c0, i, self.index, &(k * g), &(k * c.c0));
+            limbs[i] = s;
+            proofs[i] = ShareProof {
+                r: k - self.secret * t,
+                t,
+            };
+        }
+
+        DecryptionShare {
+            participant: self.index,
+            limbs,
+            proofs,
+        }
+    }
+}
+
+impl DecryptionShare {
+    /// The index of the participant who computed this share.
+    pub fn participant(&self) -> u32 {
+        self.participant
+    }
+
+    /// Verifies this share of the decryption of `ciphertext` against the
+    /// participant's public share.
+    pub fn verify(&self, ciphertext: &Ciphertext, public_share: &PublicShare) -> Result<(), Error> {
+        let g = decaf377::basepoint();
+        for (i, c) in ciphertext.limbs.iter().enumerate() {
+            let s = self.limbs[i];
+            let p = self.proofs[i];
+            let alpha = p.r * g + p.t * public_share.0;
+            let gamma = p.r * c.c0 + p.t * s;
+            if share_challenge(&s, &c.c0, i, self.participant, &alpha, &gamma) != p.t {
+                return Err(Error::InvalidDecryptionShare(self.participant));
+            }
+        }
+        Ok(())
+    }
+
+    /// The encoding of the share: the participant index, then the share and
+    /// proof of each limb in turn.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.participant.to_le_bytes().to_vec();
+        for (s, p) in self.limbs.iter().zip(self.proofs.iter()) {
+            bytes.extend_from_slice(&s.compress().0);
+            bytes.extend_from_slice(&p.r.to_bytes());
+            bytes.extend_from_slice(&p.t.to_bytes());
+        }
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for DecryptionShare {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 4 + LIMBS * 96 {
+            return Err(Error::SliceLenError);
+        }
+        let participant = u32::from_le_bytes(bytes[..4].try_into().expect("length was checked"));
+        let mut limbs = [decaf377::Element::default(); LIMBS];
+        let mut proofs = [ShareProof {
+            r: Fr::zero(),
+            t: Fr::zero(),
+        }; LIMBS];
+        for (i, chunk) in bytes[4..].chunks(96).enumerate() {
+            limbs[i] = decode_element(&chunk[..32])?;
+            proofs[i] = ShareProof {
+                r: decode_scalar(&chunk[32..64])?,
+                t: decode_scalar(&chunk[64..])?,
+            };
+        }
+        Ok(DecryptionShare {
+            participant,
+            limbs,
+            proofs,
+        })
+    }
+}
+
+/// Decrypts `ciphertext` from the decryption shares of at least `threshold`
+/// distinct participants, which must already have been verified.
+///
+/// The limbs of the plaintext are recovered using `table`, and recombined
+/// into an amount which, since each limb of an aggregated ciphertext may
+/// exceed [`LIMB_BITS`] bits, can be larger than a `u64`.
+pub fn decrypt(
+    ciphertext: &Ciphertext,
+    shares: &[DecryptionShare],
+    threshold: usize,
+    table: &DecryptionTable,
+) -> Result<u128, Error> {
+    let shares = shares
+        .iter()
+        .map(|share| (share.participant, share))
+        .collect::<BTreeMap<_, _>>();
+    if shares.len() < threshold {
+        return Err(Error::NotEnoughShares(shares.len(), threshold));
+    }
+    let shares = shares.into_iter().take(threshold).collect::<Vec<_>>();
+
+    // The Lagrange coefficients for interpolating the shares at zero.
+    let coefficients = shares
+        .iter()
+        .map(|(i, _)| {
+            let x_i = Fr::from(*i as u64);
+            shares
+                .iter()
+                .filter(|(j, _)| j != i)
+                .fold(Fr::from(1u64), |acc, (j, _)| {
+                    let x_j = Fr::from(*j as u64);
+                    acc * x_j * (x_j - x_i).inverse().expect("indices are distinct")
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let mut amount = 0u128;
+    for (limb, c) in ciphertext.limbs.iter().enumerate() {
+        let d = shares
+            .iter()
+            .zip(coefficients.iter())
+            .fold(decaf377::Element::default(), |acc, ((_, share), lambda)| {
+                acc + *lambda * share.limbs[limb]
+            });
+        let v = table.lookup(&(c.c1 - d))?;
+        amount += (v as u128) << (LIMB_BITS as usize * limb);
+    }
+
+    Ok(amount)
+}
+
+/// A table for recovering decrypted limbs from group elements, by
+/// baby-step giant-step search over limbs of up to `bits` bits.
+///
+/// The table holds `2^(bits/2)` elements, so it should be computed once and
+/// reused.
+pub struct DecryptionTable {
+    bits: u32,
+    steps: u64,
+    baby_steps: HashMap<[u8; 32], u64>,
+}
+
+impl DecryptionTable {
+    pub fn new(bits: u32) -> Self {
+        let steps = 1u64 << ((bits + 1) / 2);
+        let g = decaf377::basepoint();
+
+        let mut baby_steps = HashMap::with_capacity(steps as usize);
+        let mut point = decaf377::Element::default();
+        for j in 0..steps {
+            baby_steps.insert(point.compress().0, j);
+            point = point + g;
+        }
+
+        Self {
+            bits,
+            steps,
+            baby_steps,
+        }
+    }
+
+    /// Finds `v` such that `element = v * G`, if `v` has at most the table's
+    /// number of bits.
+    fn lookup(&self, element: &decaf377::Element) -> Result<u64, Error> {
+        let giant_step = Fr::from(self.steps) * decaf377::basepoint();
+        let mut point = *element;
+        for i in 0..(1u64 << self.bits) / self.steps + 1 {
+            if let Some(j) = self.baby_steps.get(&point.compress().0) {
+                let v = i * self.steps + j;
+                return if v < 1 << self.bits {
+                    Ok(v)
+                } else {
+                    Err(Error::OutOfRange)
+                };
+            }
+            point = point - giant_step;
+        }
+        Err(Error::OutOfRange)
+    }
+}
+
+fn share_challenge(
+    share: &decaf377::Element,
+    c0: &decaf377::Element,
+    limb: usize,
+    participant: u32,
+    alpha: &decaf377::Element,
+    gamma: &decaf377::Element,
+) -> Fr {
+    Challenge::new(b"penumbra.fe.dec")
+        .element(share)
+        .element(c0)
+        .bytes(&(limb as u32).to_le_bytes())
+        .bytes(&participant.to_le_bytes())
+        .element(alpha)
+        .element(gamma)
+        .finalize()
+}
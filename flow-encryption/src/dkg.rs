@@ -0,0 +1,411 @@
+//! Distributed generation of the flow encryption key.
+//!
+//! This is the DKG from [FROST], a variant of Pedersen's DKG in which each
+//! dealer proves knowledge of its secret to prevent rogue-key attacks, made
+//! robust by a complaint round as in [Gennaro et al]:
+//!
+//! 1. Each participant, acting as a dealer, publishes a [`Commitment`] to a
+//!    random polynomial of degree `threshold - 1`, along with a fresh
+//!    [`DealingKey`] for receiving shares.
+//! 2. Each dealer publishes its polynomial evaluated at every other
+//!    participant's index, each [`EncryptedShare`] encrypted to the
+//!    recipient's dealing key.
+//! 3. A participant who receives an invalid share complains by revealing its
+//!    [`DealingSecret`], so that anyone can decrypt the shares it was sent
+//!    and disqualify the dealers whose shares don't match their commitments.
+//!
+//! The flow encryption key is the sum of the qualified dealers' constant
+//! terms, and each participant's [`KeyShare`] is the sum of the shares it
+//! received from them.  Since dealing keys are only used for one run of the
+//! DKG, a complaint only reveals the complainer's own key share.
+//!
+//! [FROST]: https://eprint.iacr.org/2020/852.pdf
+//! [Gennaro et al]: http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.134.6445&rep=rep1&type=pdf
+
+use ark_ff::{UniformRand, Zero};
+use decaf377::{FieldExt, Fr};
+use penumbra_proto::{flow_encryption as pb, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+use crate::{
+    elgamal::{decode_element, decode_scalar},
+    hash::Challenge,
+    EncryptionKey, Error,
+};
+
+/// A participant's secret state as a dealer in one run of the DKG.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct Dealer {
+    index: u32,
+    coefficients: Vec<Fr>,
+    dealing_secret: DealingSecret,
+}
+
+/// A dealer's public commitment to its polynomial.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commitment {
+    /// Commitments to each coefficient of the polynomial, starting with the
+    /// constant term.
+    coefficients: Vec<decaf377::Element>,
+    /// A Schnorr proof of knowledge of the constant term.
+    proof_commitment: decaf377::Element,
+    proof_response: Fr,
+    /// The key the dealer's own shares are encrypted to.
+    dealing_key: DealingKey,
+}
+
+/// A public key for receiving shares in one run of the DKG.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DealingKey(decaf377::Element);
+
+/// The secret key for a [`DealingKey`].
+#[derive(Clone, Debug, PartialEq, Eq, Zeroize)]
+#[zeroize(drop)]
+pub struct DealingSecret(Fr);
+
+/// A dealer's share for a recipient, encrypted to the recipient's dealing key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncryptedShare {
+    pub recipient: u32,
+    pub ciphertext: [u8; 32],
+}
+
+/// A share of a dealer's polynomial, evaluated at the recipient's index.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct SecretShare(Fr);
+
+/// A participant's share of the flow decryption key.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct KeyShare {
+    pub(crate) index: u32,
+    pub(crate) secret: Fr,
+}
+
+/// The public counterpart of a participant's [`KeyShare`], used to verify its
+/// decryption shares.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PublicShare(pub(crate) decaf377::Element);
+
+impl Dealer {
+    /// Creates a dealer for the participant with the given (nonzero) index,
+    /// for a DKG requiring `threshold` participants to decrypt.
+    pub fn new<R: RngCore + CryptoRng>(
+        index: u32,
+        threshold: u32,
+        mut rng: R,
+    ) -> Result<Self, Error> {
+        if index == 0 {
+            return Err(Error::InvalidIndex);
+        }
+        if threshold == 0 {
+            return Err(Error::InvalidThreshold);
+        }
+        Ok(Self {
+            index,
+            coefficients: (0..threshold).map(|_| Fr::rand(&mut rng)).collect(),
+            dealing_secret: DealingSecret(Fr::rand(&mut rng)),
+        })
+    }
+
+    /// The dealer's commitment to its polynomial.
+    ///
+    /// The `context` should identify both the run of the DKG and the dealer,
+    /// so that the proof of knowledge can't be replayed.
+    pub fn commitment<R: RngCore + CryptoRng>(&self, context: &[u8], mut rng: R) -> Commitment {
+        let g = decaf377::basepoint();
+        let coefficients: Vec<_> = self.coefficients.iter().map(|a| *a * g).collect();
+
+        let k = Fr::rand(&mut rng);
+        let proof_commitment = k * g;
+        let c = pok_challenge(context, &coefficients[0], &proof_commitment);
+
+        Commitment {
+            coefficients,
+            proof_commitment,
+            proof_response: k + self.coefficients[0] * c,
+            dealing_key: self.dealing_secret.dealing_key(),
+        }
+    }
+
+    /// Deals shares to each of the given recipients, encrypted to their
+    /// dealing keys.
+    pub fn deal(&self, recipients: &[(u32, DealingKey)]) -> Vec<EncryptedShare> {
+        recipients
+            .iter()
+            .map(|(recipient, dealing_key)| {
+                let share = self.share_for(*recipient);
+                let pad = share_pad(
+                    &(self.dealing_secret.0 * dealing_key.0),
+                    self.index,
+                    *recipient,
+                );
+                let mut ciphertext = share.0.to_bytes();
+                for (c, p) in ciphertext.iter_mut().zip(pad.iter()) {
+                    *c ^= p;
+                }
+                EncryptedShare {
+                    recipient: *recipient,
+                    ciphertext,
+                }
+            })
+            .collect()
+    }
+
+    /// The secret for this dealer's dealing key, to be revealed in a
+    /// complaint.
+    pub fn dealing_secret(&self) -> DealingSecret {
+        self.dealing_secret.clone()
+    }
+
+    fn share_for(&self, recipient: u32) -> SecretShare {
+        // Evaluate the polynomial at the recipient's index by Horner's rule.
+        let x = Fr::from(recipient as u64);
+        SecretShare(
+            self.coefficients
+                .iter()
+                .rev()
+                .fold(Fr::zero(), |acc, a| acc * x + a),
+        )
+    }
+}
+
+impl Commitment {
+    /// The number of shares required to reconstruct this dealer's secret.
+    pub fn threshold(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    pub fn dealing_key(&self) -> DealingKey {
+        self.dealing_key
+    }
+
+    /// Verifies the dealer's proof of knowledge of its secret, for the given
+    /// context.
+    pub fn verify(&self, context: &[u8]) -> Result<(), Error> {
+        let constant = self.coefficients.first().ok_or(Error::InvalidThreshold)?;
+        let c = pok_challenge(context, constant, &self.proof_commitment);
+        if self.proof_response * decaf377::basepoint() == self.proof_commitment + c * *constant {
+            Ok(())
+        } else {
+            Err(Error::InvalidProofOfKnowledge)
+        }
+    }
+
+    /// The commitment to this dealer's share for the participant with the given index.
+    fn share_commitment(&self, index: u32) -> decaf377::Element {
+        let x = Fr::from(index as u64);
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(decaf377::Element::default(), |acc, a| x * acc + *a)
+    }
+
+    pub fn coefficients(&self) -> &[decaf377::Element] {
+        &self.coefficients
+    }
+}
+
+impl DealingSecret {
+    pub fn dealing_key(&self) -> DealingKey {
+        DealingKey(self.0 * decaf377::basepoint())
+    }
+
+    /// Decrypts the share sent to the participant with this dealing secret,
+    /// at `recipient`, by the dealer at `dealer`, and checks it against the
+    /// dealer's commitment.
+    pub fn decrypt_share(
+        &self,
+        recipient: u32,
+        dealer: u32,
+        commitment: &Commitment,
+        share: &EncryptedShare,
+    ) -> Result<SecretShare, Error> {
+        let invalid = Error::InvalidShare { dealer, recipient };
+        if share.recipient != recipient {
+            return Err(invalid);
+        }
+
+        let pad = share_pad(&(self.0 * commitment.dealing_key.0), dealer, recipient);
+        let mut plaintext = share.ciphertext;
+        for (c, p) in plaintext.iter_mut().zip(pad.iter()) {
+            *c ^= p;
+        }
+        let secret = Fr::from_bytes(plaintext).map_err(|_| invalid.clone())?;
+
+        if secret * decaf377::basepoint() == commitment.share_commitment(recipient) {
+            Ok(SecretShare(secret))
+        } else {
+            Err(invalid)
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for DealingSecret {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(DealingSecret(decode_scalar(bytes)?))
+    }
+}
+
+impl DealingKey {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.compress().0
+    }
+}
+
+impl TryFrom<&[u8]> for DealingKey {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(DealingKey(decode_element(bytes)?))
+    }
+}
+
+impl KeyShare {
+    /// Combines the shares a participant received from each qualified dealer
+    /// into its key share.
+    pub fn new(index: u32, shares: impl IntoIterator<Item = SecretShare>) -> Result<Self, Error> {
+        if index == 0 {
+            return Err(Error::InvalidIndex);
+        }
+        Ok(Self {
+            index,
+            secret: shares.into_iter().fold(Fr::zero(), |acc, s| acc + s.0),
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn public_share(&self) -> PublicShare {
+        PublicShare(self.secret * decaf377::basepoint())
+    }
+}
+
+impl PublicShare {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.compress().0
+    }
+}
+
+impl TryFrom<&[u8]> for PublicShare {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(PublicShare(decode_element(bytes)?))
+    }
+}
+
+/// The flow encryption key produced by the given qualified dealers.
+pub fn encryption_key<'a>(qualified: impl IntoIterator<Item = &'a Commitment>) -> EncryptionKey {
+    EncryptionKey(
+        qualified
+            .into_iter()
+            .fold(decaf377::Element::default(), |acc, c| {
+                acc + c.coefficients[0]
+            }),
+    )
+}
+
+/// The public share of the participant with the given index, for the given
+/// qualified dealers.
+pub fn public_share<'a>(
+    qualified: impl IntoIterator<Item = &'a Commitment>,
+    index: u32,
+) -> PublicShare {
+    PublicShare(
+        qualified
+            .into_iter()
+            .fold(decaf377::Element::default(), |acc, c| {
+                acc + c.share_commitment(index)
+            }),
+    )
+}
+
+fn pok_challenge(
+    context: &[u8],
+    constant: &decaf377::Element,
+    proof_commitment: &decaf377::Element,
+) -> Fr {
+    Challenge::new(b"penumbra.fe.pok")
+        .bytes(context)
+        .element(constant)
+        .element(proof_commitment)
+        .finalize()
+}
+
+fn share_pad(shared_secret: &decaf377::Element, dealer: u32, recipient: u32) -> [u8; 32] {
+    let hash = blake2b_simd::Params::default()
+        .personal(b"penumbra.fe.shr")
+        .hash_length(32)
+        .to_state()
+        .update(&shared_secret.compress().0)
+        .update(&dealer.to_le_bytes())
+        .update(&recipient.to_le_bytes())
+        .finalize();
+    hash.as_bytes().try_into().expect("hash is 32 bytes")
+}
+
+impl Protobuf<pb::DkgCommitment> for Commitment {}
+
+impl From<Commitment> for pb::DkgCommitment {
+    fn from(c: Commitment) -> Self {
+        pb::DkgCommitment {
+            coefficients: c
+                .coefficients
+                .iter()
+                .map(|a| a.compress().0.to_vec())
+                .collect(),
+            proof_commitment: c.proof_commitment.compress().0.to_vec(),
+            proof_response: c.proof_response.to_bytes().to_vec(),
+            dealing_key: c.dealing_key.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::DkgCommitment> for Commitment {
+    type Error = anyhow::Error;
+    fn try_from(c: pb::DkgCommitment) -> anyhow::Result<Self> {
+        Ok(Commitment {
+            coefficients: c
+                .coefficients
+                .iter()
+                .map(|a| decode_element(a))
+                .collect::<Result<_, _>>()?,
+            proof_commitment: decode_element(&c.proof_commitment)?,
+            proof_response: decode_scalar(&c.proof_response)?,
+            dealing_key: c.dealing_key.as_slice().try_into()?,
+        })
+    }
+}
+
+impl Protobuf<pb::EncryptedShare> for EncryptedShare {}
+
+impl From<EncryptedShare> for pb::EncryptedShare {
+    fn from(s: EncryptedShare) -> Self {
+        pb::EncryptedShare {
+            recipient: s.recipient,
+            ciphertext: s.ciphertext.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::EncryptedShare> for EncryptedShare {
+    type Error = anyhow::Error;
+    fn try_from(s: pb::EncryptedShare) -> anyhow::Result<Self> {
+        Ok(EncryptedShare {
+            recipient: s.recipient,
+            ciphertext: s
+                .ciphertext
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("encrypted share must be 32 bytes"))?,
+        })
+    }
+}
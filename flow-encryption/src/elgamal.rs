@@ -0,0 +1,322 @@
+use std::ops::Add;
+
+use ark_ff::{UniformRand, Zero};
+use decaf377::{FieldExt, Fr};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{hash::Challenge, Error};
+
+/// The number of limbs an amount is split into for encryption.
+pub const LIMBS: usize = 4;
+
+/// The number of bits in each limb of an encrypted amount.
+pub const LIMB_BITS: u32 = 16;
+
+/// The flow encryption key, shared by the validators and produced by the
+/// [DKG](crate::dkg).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EncryptionKey(pub(crate) decaf377::Element);
+
+/// An ElGamal encryption of a single limb of an amount.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LimbCiphertext {
+    pub c0: decaf377::Element,
+    pub c1: decaf377::Element,
+}
+
+/// An encryption of an amount to the [`EncryptionKey`], limb by limb.
+///
+/// Ciphertexts are additively homomorphic: the sum of two ciphertexts is an
+/// encryption of the sum of their amounts.  Since each limb is added
+/// separately, the limbs of a sum can exceed [`LIMB_BITS`] bits, and are only
+/// recombined after decryption.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ciphertext {
+    pub(crate) limbs: [LimbCiphertext; LIMBS],
+}
+
+/// The random scalars used to encrypt each limb of an amount.
+///
+/// Whoever knows the randomness of a ciphertext can recompute it from the
+/// amount, so it serves as a witness that the ciphertext encrypts that amount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncryptionRandomness([Fr; LIMBS]);
+
+/// A proof of a single limb's encryption.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LimbProof {
+    r: Fr,
+    s: Fr,
+    t: Fr,
+}
+
+/// A proof that a [`Ciphertext`] is a well-formed encryption to the
+/// [`EncryptionKey`], with a known amount and randomness for each limb.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncryptionProof {
+    limbs: [LimbProof; LIMBS],
+}
+
+impl EncryptionKey {
+    /// Encrypts `amount`, returning the ciphertext and a proof of its
+    /// correctness.
+    #[allow(non_snake_case)]
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        &self,
+        amount: u64,
+        mut rng: R,
+    ) -> (Ciphertext, EncryptionProof) {
+        let G = decaf377::basepoint();
+        let D = self.0;
+
+        let randomness = EncryptionRandomness::new(&mut rng);
+        let ciphertext = self.encrypt_with_randomness(amount, &randomness);
+
+        let mut proofs = [LimbProof::default(); LIMBS];
+        for (i, (c, e)) in ciphertext.limbs.iter().zip(randomness.0).enumerate() {
+            let v = Fr::from(limb(amount, i));
+
+            let k1 = Fr::rand(&mut rng);
+            let k2 = Fr::rand(&mut rng);
+            let alpha = k1 * G + k2 * D;
+            let gamma = k2 * G;
+            let t = c.challenge(&D, &alpha, &gamma);
+
+            proofs[i] = LimbProof {
+                r: k1 - v * t,
+                s: k2 - e * t,
+                t,
+            };
+        }
+
+        (ciphertext, EncryptionProof { limbs: proofs })
+    }
+
+    /// Encrypts `amount` with the given randomness.
+    ///
+    /// This is deterministic, so that a proof of a larger statement can show
+    /// that a ciphertext encrypts an amount by exhibiting its randomness.
+    #[allow(non_snake_case)]
+    pub fn encrypt_with_randomness(
+        &self,
+        amount: u64,
+        randomness: &EncryptionRandomness,
+    ) -> Ciphertext {
+        let G = decaf377::basepoint();
+        let D = self.0;
+
+        let mut limbs = [LimbCiphertext::default(); LIMBS];
+        for (i, e) in randomness.0.iter().enumerate() {
+            let v = Fr::from(limb(amount, i));
+            limbs[i] = LimbCiphertext {
+                c0: *e * G,
+                c1: v * G + *e * D,
+            };
+        }
+
+        Ciphertext { limbs }
+    }
+
+    /// Verifies that `ciphertext` is a well-formed encryption to this key.
+    #[allow(non_snake_case)]
+    pub fn verify(&self, ciphertext: &Ciphertext, proof: &EncryptionProof) -> Result<(), Error> {
+        let G = decaf377::basepoint();
+        let D = self.0;
+
+        for (c, p) in ciphertext.limbs.iter().zip(proof.limbs.iter()) {
+            let alpha = p.s * D + p.r * G + p.t * c.c1;
+            let gamma = p.s * G + p.t * c.c0;
+            if c.challenge(&D, &alpha, &gamma) != p.t {
+                return Err(Error::InvalidEncryptionProof);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.compress().0
+    }
+}
+
+/// The `i`th limb of `amount`, starting from the least significant.
+fn limb(amount: u64, i: usize) -> u64 {
+    (amount >> (LIMB_BITS * i as u32)) & ((1 << LIMB_BITS) - 1)
+}
+
+impl EncryptionRandomness {
+    pub fn new<R: RngCore + CryptoRng>(mut rng: R) -> Self {
+        let mut limbs = [Fr::zero(); LIMBS];
+        for e in limbs.iter_mut() {
+            *e = Fr::rand(&mut rng);
+        }
+        Self(limbs)
+    }
+
+    /// The randomness for each limb, starting from the least significant.
+    pub fn limbs(&self) -> [Fr; LIMBS] {
+        self.0
+    }
+
+    /// The encoding of the randomness, as the scalar of each limb in turn.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|e| e.to_bytes()).collect()
+    }
+}
+
+impl TryFrom<&[u8]> for EncryptionRandomness {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != LIMBS * 32 {
+            return Err(Error::SliceLenError);
+        }
+        let mut limbs = [Fr::zero(); LIMBS];
+        for (e, chunk) in limbs.iter_mut().zip(bytes.chunks(32)) {
+            *e = decode_scalar(chunk)?;
+        }
+        Ok(Self(limbs))
+    }
+}
+
+impl TryFrom<&[u8]> for EncryptionKey {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(EncryptionKey(decode_element(bytes)?))
+    }
+}
+
+impl LimbCiphertext {
+    #[allow(non_snake_case)]
+    fn challenge(
+        &self,
+        D: &decaf377::Element,
+        alpha: &decaf377::Element,
+        gamma: &decaf377::Element,
+    ) -> Fr {
+        Challenge::new(b"penumbra.fe.enc")
+            .element(&self.c0)
+            .element(&self.c1)
+            .element(D)
+            .element(alpha)
+            .element(gamma)
+            .finalize()
+    }
+}
+
+impl Default for LimbCiphertext {
+    fn default() -> Self {
+        Self {
+            c0: decaf377::Element::default(),
+            c1: decaf377::Element::default(),
+        }
+    }
+}
+
+impl Default for LimbProof {
+    fn default() -> Self {
+        Self {
+            r: Fr::zero(),
+            s: Fr::zero(),
+            t: Fr::zero(),
+        }
+    }
+}
+
+impl Ciphertext {
+    /// The encoding of the ciphertext, as the `c0` and `c1` components of
+    /// each limb in turn.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.limbs
+            .iter()
+            .flat_map(|c| [c.c0.compress().0, c.c1.compress().0])
+            .flatten()
+            .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for Ciphertext {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != LIMBS * 64 {
+            return Err(Error::SliceLenError);
+        }
+        let mut limbs = [LimbCiphertext::default(); LIMBS];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(64)) {
+            *limb = LimbCiphertext {
+                c0: decode_element(&chunk[..32])?,
+                c1: decode_element(&chunk[32..])?,
+            };
+        }
+        Ok(Ciphertext { limbs })
+    }
+}
+
+/// The ciphertext with every limb encrypting zero with zero randomness, which
+/// is the identity for addition.
+impl Default for Ciphertext {
+    fn default() -> Self {
+        Self {
+            limbs: [LimbCiphertext::default(); LIMBS],
+        }
+    }
+}
+
+impl Add for Ciphertext {
+    type Output = Ciphertext;
+    fn add(self, other: Ciphertext) -> Ciphertext {
+        let mut limbs = self.limbs;
+        for (limb, other) in limbs.iter_mut().zip(other.limbs.iter()) {
+            limb.c0 = limb.c0 + other.c0;
+            limb.c1 = limb.c1 + other.c1;
+        }
+        Ciphertext { limbs }
+    }
+}
+
+impl std::iter::Sum for Ciphertext {
+    fn sum<I: Iterator<Item = Ciphertext>>(iter: I) -> Self {
+        iter.fold(Ciphertext::default(), |acc, c| acc + c)
+    }
+}
+
+impl EncryptionProof {
+    /// The encoding of the proof, as the `r`, `s`, and `t` scalars of each
+    /// limb in turn.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.limbs
+            .iter()
+            .flat_map(|p| [p.r.to_bytes(), p.s.to_bytes(), p.t.to_bytes()])
+            .flatten()
+            .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for EncryptionProof {
+    type Error = Error;
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != LIMBS * 96 {
+            return Err(Error::SliceLenError);
+        }
+        let mut limbs = [LimbProof::default(); LIMBS];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(96)) {
+            *limb = LimbProof {
+                r: decode_scalar(&chunk[..32])?,
+                s: decode_scalar(&chunk[32..64])?,
+                t: decode_scalar(&chunk[64..])?,
+            };
+        }
+        Ok(EncryptionProof { limbs })
+    }
+}
+
+pub(crate) fn decode_element(bytes: &[u8]) -> Result<decaf377::Element, Error> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| Error::SliceLenError)?;
+    decaf377::Encoding(bytes)
+        .decompress()
+        .map_err(|_| Error::InvalidElement)
+}
+
+pub(crate) fn decode_scalar(bytes: &[u8]) -> Result<Fr, Error> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| Error::SliceLenError)?;
+    Fr::from_bytes(bytes).map_err(|_| Error::InvalidScalar)
+}
@@ -0,0 +1,39 @@
+use thiserror::Error;
+
+/// An error in flow encryption, key generation, or decryption.
+#[derive(Clone, Error, Debug)]
+pub enum Error {
+    /// A participant index was zero, which is reserved for the shared secret.
+    #[error("Participant indices must be nonzero.")]
+    InvalidIndex,
+    /// The threshold was zero.
+    #[error("The threshold must be nonzero.")]
+    InvalidThreshold,
+    /// A group element encoding was invalid.
+    #[error("Invalid group element encoding.")]
+    InvalidElement,
+    /// A scalar encoding was invalid.
+    #[error("Invalid scalar encoding.")]
+    InvalidScalar,
+    /// Supplied bytes were the wrong length.
+    #[error("Supplied bytes are incorrect length.")]
+    SliceLenError,
+    /// A proof that a ciphertext encrypts a limb did not verify.
+    #[error("Invalid encryption proof.")]
+    InvalidEncryptionProof,
+    /// A dealer's proof of knowledge of its secret did not verify.
+    #[error("Invalid proof of knowledge of the dealer's secret.")]
+    InvalidProofOfKnowledge,
+    /// A dealer's share did not match its commitments.
+    #[error("Invalid share from dealer {dealer} to participant {recipient}.")]
+    InvalidShare { dealer: u32, recipient: u32 },
+    /// A decryption share's proof did not verify.
+    #[error("Invalid decryption share from participant {0}.")]
+    InvalidDecryptionShare(u32),
+    /// Too few distinct decryption shares were supplied.
+    #[error("{0} decryption shares were provided, but {1} are required.")]
+    NotEnoughShares(usize, usize),
+    /// A decrypted limb was larger than the decryption table allows.
+    #[error("Decrypted value is out of range.")]
+    OutOfRange,
+}
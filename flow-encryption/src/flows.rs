@@ -0,0 +1,40 @@
+use penumbra_proto::{flow_encryption as pb, Protobuf};
+
+use crate::Ciphertext;
+
+/// The flows queued for threshold decryption at the end of a block.
+///
+/// The validators who took part in the DKG for `epoch_index` submit their
+/// shares of the decryption of every ciphertext, in order, and the flows are
+/// decrypted once enough of them have.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flows {
+    /// The epoch whose flow encryption key the flows are encrypted to.
+    pub epoch_index: u64,
+    pub ciphertexts: Vec<Ciphertext>,
+}
+
+impl Protobuf<pb::Flows> for Flows {}
+
+impl From<Flows> for pb::Flows {
+    fn from(f: Flows) -> Self {
+        pb::Flows {
+            epoch_index: f.epoch_index,
+            ciphertexts: f.ciphertexts.iter().map(Ciphertext::to_bytes).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::Flows> for Flows {
+    type Error = anyhow::Error;
+    fn try_from(f: pb::Flows) -> Result<Self, Self::Error> {
+        Ok(Flows {
+            epoch_index: f.epoch_index,
+            ciphertexts: f
+                .ciphertexts
+                .iter()
+                .map(|c| c.as_slice().try_into())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
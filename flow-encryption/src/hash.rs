@@ -0,0 +1,29 @@
+use ark_ff::PrimeField;
+use decaf377::Fr;
+
+/// A Fiat-Shamir challenge, hashing a transcript to a scalar.
+pub struct Challenge(blake2b_simd::State);
+
+impl Challenge {
+    pub fn new(personal: &[u8]) -> Self {
+        Self(
+            blake2b_simd::Params::default()
+                .personal(personal)
+                .to_state(),
+        )
+    }
+
+    pub fn element(mut self, element: &decaf377::Element) -> Self {
+        self.0.update(&element.compress().0);
+        self
+    }
+
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.update(bytes);
+        self
+    }
+
+    pub fn finalize(&self) -> Fr {
+        Fr::from_le_bytes_mod_order(self.0.finalize().as_bytes())
+    }
+}
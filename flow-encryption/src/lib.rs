@@ -0,0 +1,29 @@
+//! Threshold [flow encryption][flow]: additively homomorphic ElGamal
+//! encryption of amounts to a key shared by the validators, the distributed
+//! key generation protocol they use to create it, and the threshold decryption
+//! of aggregated flows.
+//!
+//! [flow]: https://protocol.penumbra.zone/main/crypto/threshold.html
+#![allow(clippy::clone_on_copy)]
+
+mod decryption;
+mod elgamal;
+mod error;
+mod flows;
+mod hash;
+mod participants;
+
+pub mod action;
+pub mod dkg;
+
+pub use decryption::{decrypt, DecryptionShare, DecryptionTable};
+pub use elgamal::{
+    Ciphertext, EncryptionKey, EncryptionProof, EncryptionRandomness, LIMBS, LIMB_BITS,
+};
+pub use error::Error;
+pub use flows::Flows;
+pub use participants::{DkgOutput, DkgParticipants, Round};
+
+/// The largest number of bits a limb of an aggregated ciphertext may have
+/// and still be decrypted, which bounds the number of flows in a batch.
+pub const MAX_LIMB_BITS: u32 = 23;
@@ -0,0 +1,147 @@
+use penumbra_proto::{flow_encryption as pb, Protobuf};
+use penumbra_stake::IdentityKey;
+
+use crate::{dkg::PublicShare, EncryptionKey};
+
+/// A round of the DKG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Round {
+    /// Dealers publish their commitments and dealing keys.
+    Commitment,
+    /// Dealers publish their encrypted shares.
+    Deal,
+    /// Participants complain about invalid shares.
+    Complaint,
+}
+
+/// The participants in the DKG for an epoch: the validators active at its
+/// start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DkgParticipants {
+    /// The participating validators, in order of their indices, starting from 1.
+    pub identity_keys: Vec<IdentityKey>,
+    /// The number of participants required to decrypt.
+    pub threshold: u32,
+    /// The height of the first block of the DKG.
+    pub start_height: u64,
+    /// The number of blocks in each round of the DKG, fixed when it starts.
+    pub round_blocks: u64,
+}
+
+impl DkgParticipants {
+    /// The participants for a DKG among the given validators, requiring more
+    /// than two thirds of them to decrypt.
+    pub fn new(mut identity_keys: Vec<IdentityKey>, start_height: u64, round_blocks: u64) -> Self {
+        identity_keys.sort();
+        let threshold = (2 * identity_keys.len() / 3 + 1) as u32;
+        Self {
+            identity_keys,
+            threshold,
+            start_height,
+            round_blocks,
+        }
+    }
+
+    /// The index of the given validator in the DKG, if it's participating.
+    pub fn index_of(&self, identity_key: &IdentityKey) -> Option<u32> {
+        self.identity_keys
+            .iter()
+            .position(|ik| ik == identity_key)
+            .map(|i| i as u32 + 1)
+    }
+
+    /// The indices of all the participants.
+    pub fn indices(&self) -> impl Iterator<Item = u32> {
+        1..=self.identity_keys.len() as u32
+    }
+
+    /// The round of the DKG at the given height, if it's running.
+    pub fn round_at(&self, height: u64) -> Option<Round> {
+        match height
+            .checked_sub(self.start_height)?
+            .checked_div(self.round_blocks)?
+        {
+            0 => Some(Round::Commitment),
+            1 => Some(Round::Deal),
+            2 => Some(Round::Complaint),
+            _ => None,
+        }
+    }
+
+    /// The height of the last block of the DKG.
+    pub fn end_height(&self) -> u64 {
+        self.start_height + 3 * self.round_blocks - 1
+    }
+}
+
+/// The result of a successful DKG.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DkgOutput {
+    /// The flow encryption key.
+    pub encryption_key: EncryptionKey,
+    /// The indices of the dealers whose shares make up each participant's key
+    /// share.
+    pub qualified_dealers: Vec<u32>,
+    /// The public share of each participant, in order of their indices.
+    pub public_shares: Vec<PublicShare>,
+}
+
+impl Protobuf<pb::DkgParticipants> for DkgParticipants {}
+
+impl From<DkgParticipants> for pb::DkgParticipants {
+    fn from(p: DkgParticipants) -> Self {
+        pb::DkgParticipants {
+            identity_keys: p.identity_keys.into_iter().map(Into::into).collect(),
+            threshold: p.threshold,
+            start_height: p.start_height,
+            round_blocks: p.round_blocks,
+        }
+    }
+}
+
+impl TryFrom<pb::DkgParticipants> for DkgParticipants {
+    type Error = anyhow::Error;
+    fn try_from(p: pb::DkgParticipants) -> Result<Self, Self::Error> {
+        Ok(DkgParticipants {
+            identity_keys: p
+                .identity_keys
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            threshold: p.threshold,
+            start_height: p.start_height,
+            round_blocks: p.round_blocks,
+        })
+    }
+}
+
+impl Protobuf<pb::DkgOutput> for DkgOutput {}
+
+impl From<DkgOutput> for pb::DkgOutput {
+    fn from(o: DkgOutput) -> Self {
+        pb::DkgOutput {
+            encryption_key: o.encryption_key.to_bytes().to_vec(),
+            qualified_dealers: o.qualified_dealers,
+            public_shares: o
+                .public_shares
+                .iter()
+                .map(|share| share.to_bytes().to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::DkgOutput> for DkgOutput {
+    type Error = anyhow::Error;
+    fn try_from(o: pb::DkgOutput) -> Result<Self, Self::Error> {
+        Ok(DkgOutput {
+            encryption_key: o.encryption_key.as_slice().try_into()?,
+            qualified_dealers: o.qualified_dealers,
+            public_shares: o
+                .public_shares
+                .iter()
+                .map(|share| share.as_slice().try_into())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
@@ -0,0 +1,144 @@
+use penumbra_flow_encryption::{
+    decrypt,
+    dkg::{self, Dealer, KeyShare},
+    DecryptionTable, Error,
+};
+use rand_core::OsRng;
+
+const CONTEXT: &[u8] = b"test dkg";
+
+/// Runs the DKG among `n` participants, returning each participant's key share
+/// and the dealers' commitments.
+fn run_dkg(n: u32, threshold: u32) -> (Vec<KeyShare>, Vec<dkg::Commitment>) {
+    let dealers = (1..=n)
+        .map(|i| Dealer::new(i, threshold, OsRng).unwrap())
+        .collect::<Vec<_>>();
+    let commitments = dealers
+        .iter()
+        .map(|dealer| dealer.commitment(CONTEXT, OsRng))
+        .collect::<Vec<_>>();
+    for commitment in &commitments {
+        commitment.verify(CONTEXT).unwrap();
+    }
+
+    let recipients = commitments
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i as u32 + 1, c.dealing_key()))
+        .collect::<Vec<_>>();
+    let deals = dealers
+        .iter()
+        .map(|dealer| dealer.deal(&recipients))
+        .collect::<Vec<_>>();
+
+    let key_shares =
+        dealers
+            .iter()
+            .enumerate()
+            .map(|(r, recipient)| {
+                let index = r as u32 + 1;
+                let shares = deals.iter().zip(commitments.iter()).enumerate().map(
+                    |(d, (deal, commitment))| {
+                        recipient
+                            .dealing_secret()
+                            .decrypt_share(index, d as u32 + 1, commitment, &deal[r])
+                            .unwrap()
+                    },
+                );
+                KeyShare::new(index, shares).unwrap()
+            })
+            .collect();
+
+    (key_shares, commitments)
+}
+
+#[test]
+fn threshold_decryption_of_aggregated_flows() {
+    let (key_shares, commitments) = run_dkg(4, 3);
+    let key = dkg::encryption_key(&commitments);
+
+    // Each participant's public share matches its key share.
+    for share in &key_shares {
+        assert_eq!(
+            dkg::public_share(&commitments, share.index()),
+            share.public_share()
+        );
+    }
+
+    let amounts = [1_000_000u64, 2_345, u64::MAX];
+    let ciphertexts = amounts
+        .iter()
+        .map(|amount| {
+            let (ciphertext, proof) = key.encrypt(*amount, OsRng);
+            key.verify(&ciphertext, &proof).unwrap();
+            ciphertext
+        })
+        .collect::<Vec<_>>();
+    let total = ciphertexts.into_iter().sum();
+
+    // Any three participants can decrypt the total.
+    let decryption_shares = key_shares[1..]
+        .iter()
+        .map(|share| {
+            let decryption_share = share.decryption_share(&total, OsRng);
+            decryption_share
+                .verify(&total, &share.public_share())
+                .unwrap();
+            decryption_share
+        })
+        .collect::<Vec<_>>();
+
+    let table = DecryptionTable::new(18);
+    assert_eq!(
+        decrypt(&total, &decryption_shares, 3, &table).unwrap(),
+        amounts.iter().map(|a| *a as u128).sum::<u128>()
+    );
+
+    // But two participants can't.
+    assert!(matches!(
+        decrypt(&total, &decryption_shares[..2], 3, &table),
+        Err(Error::NotEnoughShares(2, 3))
+    ));
+}
+
+#[test]
+fn decryption_share_from_wrong_key_share_fails() {
+    let (key_shares, commitments) = run_dkg(3, 2);
+    let key = dkg::encryption_key(&commitments);
+    let (ciphertext, _) = key.encrypt(42, OsRng);
+
+    let decryption_share = key_shares[0].decryption_share(&ciphertext, OsRng);
+    assert!(decryption_share
+        .verify(&ciphertext, &key_shares[1].public_share())
+        .is_err());
+}
+
+#[test]
+fn invalid_share_is_detected_from_complaint() {
+    let dealer = Dealer::new(1, 2, OsRng).unwrap();
+    let recipient = Dealer::new(2, 2, OsRng).unwrap();
+    let commitment = dealer.commitment(CONTEXT, OsRng);
+
+    let mut deal = dealer.deal(&[(2, recipient.dealing_secret().dealing_key())]);
+    deal[0].ciphertext[0] ^= 1;
+
+    // Anyone with the recipient's revealed dealing secret can check the share.
+    assert!(matches!(
+        recipient
+            .dealing_secret()
+            .decrypt_share(2, 1, &commitment, &deal[0]),
+        Err(Error::InvalidShare {
+            dealer: 1,
+            recipient: 2
+        })
+    ));
+}
+
+#[test]
+fn proof_of_knowledge_is_bound_to_context() {
+    let dealer = Dealer::new(1, 2, OsRng).unwrap();
+    let commitment = dealer.commitment(CONTEXT, OsRng);
+
+    assert!(commitment.verify(CONTEXT).is_ok());
+    assert!(commitment.verify(b"another dkg").is_err());
+}
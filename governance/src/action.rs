@@ -0,0 +1,17 @@
+//! Governance-related transaction actions.
+
+mod community_pool_deposit;
+mod delegator_vote;
+mod validator_vote;
+
+pub use crate::Proposal;
+pub use community_pool_deposit::CommunityPoolDeposit;
+pub use delegator_vote::DelegatorVote;
+pub use validator_vote::ValidatorVote;
+
+/// The bodies of the governance actions, stored separately from the signatures
+/// that authorize them.
+pub mod body {
+    pub use super::delegator_vote::Body as DelegatorVoteBody;
+    pub use super::validator_vote::Body as ValidatorVoteBody;
+}
@@ -0,0 +1,39 @@
+use penumbra_crypto::{value, Fr, Value, Zero};
+use penumbra_proto::{governance as pb, Protobuf};
+
+/// A deposit of funds into the community pool.
+#[derive(Debug, Clone)]
+pub struct CommunityPoolDeposit {
+    /// The value deposited.
+    pub value: Value,
+}
+
+impl CommunityPoolDeposit {
+    /// Compute a commitment to the value contributed to a transaction by this deposit.
+    pub fn value_commitment(&self) -> value::Commitment {
+        // The deposited value is consumed from the transaction's balance.
+        -self.value.commit(Fr::zero())
+    }
+}
+
+impl Protobuf<pb::CommunityPoolDeposit> for CommunityPoolDeposit {}
+
+impl From<CommunityPoolDeposit> for pb::CommunityPoolDeposit {
+    fn from(d: CommunityPoolDeposit) -> Self {
+        pb::CommunityPoolDeposit {
+            value: Some(d.value.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::CommunityPoolDeposit> for CommunityPoolDeposit {
+    type Error = anyhow::Error;
+    fn try_from(d: pb::CommunityPoolDeposit) -> Result<Self, Self::Error> {
+        Ok(CommunityPoolDeposit {
+            value: d
+                .value
+                .ok_or_else(|| anyhow::anyhow!("missing community pool deposit value"))?
+                .try_into()?,
+        })
+    }
+}
@@ -0,0 +1,187 @@
+use penumbra_crypto::{
+    keys, merkle,
+    proofs::ProofSystem,
+    rdsa::{Signature, SigningKey, SpendAuth, VerificationKey},
+    Fr, Note, Nullifier, Value,
+};
+use penumbra_proto::{governance as pb, Protobuf};
+use penumbra_stake::IdentityKey;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::{
+    proofs::{DelegatorVoteProof, DelegatorVotePublicInputs},
+    Vote,
+};
+
+/// A delegator's vote on a proposal.
+///
+/// The vote proves ownership of a note of delegation tokens which existed when
+/// voting began, overriding the vote of the note's validator for the voting
+/// power of those tokens.  The note is not spent: its nullifier is only
+/// recorded as having voted on this proposal.
+#[derive(Debug, Clone)]
+pub struct DelegatorVote {
+    pub body: Body,
+    /// The spend authorization signature over the transaction's sighash.
+    pub auth_sig: Signature<SpendAuth>,
+}
+
+/// The body of a delegator vote, stored separately from its signature.
+#[derive(Debug, Clone)]
+pub struct Body {
+    /// The proposal being voted on.
+    pub proposal_id: u64,
+    /// The vote.
+    pub vote: Vote,
+    /// The validator whose delegation tokens are used to vote.
+    pub identity_key: IdentityKey,
+    /// The amount of delegation tokens in the note used to vote.
+    pub delegation_amount: u64,
+    /// The nullifier of the note used to vote.
+    pub nullifier: Nullifier,
+    /// The randomized verification key for the spend authorization signature.
+    pub rk: VerificationKey<SpendAuth>,
+    /// The proof of ownership of the note, relative to the note commitment
+    /// tree at the start of voting.
+    pub proof: DelegatorVoteProof,
+}
+
+impl Body {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        proposal_id: u64,
+        vote: Vote,
+        identity_key: IdentityKey,
+        ask: SigningKey<SpendAuth>,
+        spend_auth_randomizer: Fr,
+        anchor: merkle::Root,
+        note_commitment_proof: merkle::Proof,
+        note: Note,
+        nk: keys::NullifierKey,
+    ) -> Body {
+        let rsk = ask.randomize(&spend_auth_randomizer);
+        let rk = rsk.into();
+        let note_commitment = note.commit();
+        let position = note_commitment_proof.position();
+        let proof = DelegatorVoteProof::prove(
+            proof_system,
+            rng,
+            anchor,
+            note_commitment_proof,
+            &note,
+            spend_auth_randomizer,
+            ask.into(),
+            nk,
+        )
+        .expect("can generate delegator vote proof");
+        Body {
+            proposal_id,
+            vote,
+            identity_key,
+            delegation_amount: note.value().amount,
+            nullifier: nk.derive_nullifier(position, &note_commitment),
+            rk,
+            proof,
+        }
+    }
+
+    /// The value of the note used to vote.
+    pub fn value(&self) -> Value {
+        Value {
+            amount: self.delegation_amount,
+            asset_id: self.identity_key.delegation_token().id(),
+        }
+    }
+
+    /// The public inputs of the proof, given the root of the note commitment
+    /// tree at the start of voting.
+    pub fn public_inputs(&self, anchor: merkle::Root) -> DelegatorVotePublicInputs {
+        DelegatorVotePublicInputs {
+            anchor,
+            value: self.value(),
+            nullifier: self.nullifier.clone(),
+            rk: self.rk,
+        }
+    }
+}
+
+impl Protobuf<pb::DelegatorVote> for DelegatorVote {}
+
+impl From<DelegatorVote> for pb::DelegatorVote {
+    fn from(v: DelegatorVote) -> Self {
+        let sig_bytes: [u8; 64] = v.auth_sig.into();
+        pb::DelegatorVote {
+            body: Some(v.body.into()),
+            auth_sig: sig_bytes.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::DelegatorVote> for DelegatorVote {
+    type Error = anyhow::Error;
+    fn try_from(v: pb::DelegatorVote) -> Result<Self, Self::Error> {
+        let sig_bytes: [u8; 64] = v.auth_sig[..]
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("delegator vote malformed"))?;
+
+        Ok(DelegatorVote {
+            body: v
+                .body
+                .ok_or_else(|| anyhow::anyhow!("missing delegator vote body"))?
+                .try_into()?,
+            auth_sig: sig_bytes.into(),
+        })
+    }
+}
+
+impl Protobuf<pb::DelegatorVoteBody> for Body {}
+
+impl From<Body> for pb::DelegatorVoteBody {
+    fn from(b: Body) -> Self {
+        let nullifier_bytes: [u8; 32] = b.nullifier.into();
+        let rk_bytes: [u8; 32] = b.rk.into();
+        let proof: Vec<u8> = b.proof.into();
+        pb::DelegatorVoteBody {
+            proposal_id: b.proposal_id,
+            vote: Some(b.vote.into()),
+            identity_key: Some(b.identity_key.into()),
+            delegation_amount: b.delegation_amount,
+            nullifier: nullifier_bytes.to_vec(),
+            rk: rk_bytes.to_vec(),
+            zkproof: proof,
+        }
+    }
+}
+
+impl TryFrom<pb::DelegatorVoteBody> for Body {
+    type Error = anyhow::Error;
+    fn try_from(b: pb::DelegatorVoteBody) -> Result<Self, Self::Error> {
+        let rk_bytes: [u8; 32] = b.rk[..]
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("delegator vote body malformed"))?;
+
+        Ok(Body {
+            proposal_id: b.proposal_id,
+            vote: b
+                .vote
+                .ok_or_else(|| anyhow::anyhow!("missing vote"))?
+                .try_into()?,
+            identity_key: b
+                .identity_key
+                .ok_or_else(|| anyhow::anyhow!("missing validator identity key"))?
+                .try_into()?,
+            delegation_amount: b.delegation_amount,
+            nullifier: b.nullifier[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("delegator vote body malformed"))?,
+            rk: rk_bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("delegator vote body malformed"))?,
+            proof: b.zkproof[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("delegator vote body malformed"))?,
+        })
+    }
+}
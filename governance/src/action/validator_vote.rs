@@ -0,0 +1,81 @@
+use penumbra_crypto::rdsa::{Signature, SpendAuth};
+use penumbra_proto::{governance as pb, Protobuf};
+use penumbra_stake::IdentityKey;
+
+use crate::Vote;
+
+/// A validator's vote on a proposal, authorized by its identity key.
+///
+/// The validator's vote acts as the default vote for its entire delegation
+/// pool, except for the delegation tokens whose owners cast their own
+/// [`DelegatorVote`](super::DelegatorVote).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorVote {
+    pub body: Body,
+    /// The signature of the validator's identity key over the encoded body.
+    pub auth_sig: Signature<SpendAuth>,
+}
+
+/// The body of a validator vote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Body {
+    /// The proposal being voted on.
+    pub proposal_id: u64,
+    /// The vote.
+    pub vote: Vote,
+    /// The validator casting the vote.
+    pub identity_key: IdentityKey,
+}
+
+impl Protobuf<pb::ValidatorVote> for ValidatorVote {}
+
+impl From<ValidatorVote> for pb::ValidatorVote {
+    fn from(v: ValidatorVote) -> Self {
+        pb::ValidatorVote {
+            body: Some(v.body.into()),
+            auth_sig: v.auth_sig.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::ValidatorVote> for ValidatorVote {
+    type Error = anyhow::Error;
+    fn try_from(v: pb::ValidatorVote) -> Result<Self, Self::Error> {
+        Ok(ValidatorVote {
+            body: v
+                .body
+                .ok_or_else(|| anyhow::anyhow!("missing validator vote body"))?
+                .try_into()?,
+            auth_sig: v.auth_sig.as_slice().try_into()?,
+        })
+    }
+}
+
+impl Protobuf<pb::ValidatorVoteBody> for Body {}
+
+impl From<Body> for pb::ValidatorVoteBody {
+    fn from(b: Body) -> Self {
+        pb::ValidatorVoteBody {
+            proposal_id: b.proposal_id,
+            vote: Some(b.vote.into()),
+            identity_key: Some(b.identity_key.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::ValidatorVoteBody> for Body {
+    type Error = anyhow::Error;
+    fn try_from(b: pb::ValidatorVoteBody) -> Result<Self, Self::Error> {
+        Ok(Body {
+            proposal_id: b.proposal_id,
+            vote: b
+                .vote
+                .ok_or_else(|| anyhow::anyhow!("missing vote"))?
+                .try_into()?,
+            identity_key: b
+                .identity_key
+                .ok_or_else(|| anyhow::anyhow!("missing validator identity key"))?
+                .try_into()?,
+        })
+    }
+}
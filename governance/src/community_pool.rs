@@ -0,0 +1,103 @@
+use anyhow::Result;
+use penumbra_crypto::{Address, Value};
+use penumbra_proto::{governance as pb, Protobuf};
+
+/// A payment from the community pool to an address, made by a passed proposal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommunityPoolOutput {
+    pub value: Value,
+    pub address: Address,
+}
+
+impl Protobuf<pb::CommunityPoolOutput> for CommunityPoolOutput {}
+
+impl From<CommunityPoolOutput> for pb::CommunityPoolOutput {
+    fn from(output: CommunityPoolOutput) -> Self {
+        pb::CommunityPoolOutput {
+            value: Some(output.value.into()),
+            address: Some(output.address.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::CommunityPoolOutput> for CommunityPoolOutput {
+    type Error = anyhow::Error;
+    fn try_from(output: pb::CommunityPoolOutput) -> Result<Self> {
+        Ok(CommunityPoolOutput {
+            value: output
+                .value
+                .ok_or_else(|| anyhow::anyhow!("missing community pool output value"))?
+                .try_into()?,
+            address: output
+                .address
+                .ok_or_else(|| anyhow::anyhow!("missing community pool output address"))?
+                .try_into()?,
+        })
+    }
+}
+
+/// A payment from the community pool to be minted by the shielded pool.
+#[derive(Debug, Clone)]
+pub struct CommunityPoolPayout {
+    pub proposal_id: u64,
+    pub value: Value,
+    pub address: Address,
+}
+
+impl Protobuf<pb::CommunityPoolPayout> for CommunityPoolPayout {}
+
+impl From<CommunityPoolPayout> for pb::CommunityPoolPayout {
+    fn from(payout: CommunityPoolPayout) -> Self {
+        pb::CommunityPoolPayout {
+            proposal_id: payout.proposal_id,
+            value: Some(payout.value.into()),
+            address: Some(payout.address.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::CommunityPoolPayout> for CommunityPoolPayout {
+    type Error = anyhow::Error;
+    fn try_from(payout: pb::CommunityPoolPayout) -> Result<Self> {
+        Ok(CommunityPoolPayout {
+            proposal_id: payout.proposal_id,
+            value: payout
+                .value
+                .ok_or_else(|| anyhow::anyhow!("missing community pool payout value"))?
+                .try_into()?,
+            address: payout
+                .address
+                .ok_or_else(|| anyhow::anyhow!("missing community pool payout address"))?
+                .try_into()?,
+        })
+    }
+}
+
+/// A list of payments from the community pool to be minted by the shielded pool.
+#[derive(Debug, Clone, Default)]
+pub struct CommunityPoolPayouts {
+    pub payouts: Vec<CommunityPoolPayout>,
+}
+
+impl Protobuf<pb::CommunityPoolPayouts> for CommunityPoolPayouts {}
+
+impl From<CommunityPoolPayouts> for pb::CommunityPoolPayouts {
+    fn from(payouts: CommunityPoolPayouts) -> Self {
+        pb::CommunityPoolPayouts {
+            payouts: payouts.payouts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::CommunityPoolPayouts> for CommunityPoolPayouts {
+    type Error = anyhow::Error;
+    fn try_from(payouts: pb::CommunityPoolPayouts) -> Result<Self> {
+        Ok(CommunityPoolPayouts {
+            payouts: payouts
+                .payouts
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
@@ -0,0 +1,21 @@
+//! On-chain governance: proposals, the votes cast on them by validators and
+//! delegators, and the community pool they can spend from.
+#![allow(clippy::clone_on_copy)]
+
+mod community_pool;
+mod proposal;
+mod refund;
+mod tally;
+mod vote;
+
+pub mod action;
+pub mod proofs;
+
+pub use community_pool::{CommunityPoolOutput, CommunityPoolPayout, CommunityPoolPayouts};
+pub use proposal::{
+    Outcome, ParameterChange, Proposal, ProposalList, ProposalOutcome, ProposalPayload,
+    ProposalState,
+};
+pub use refund::{DepositRefund, DepositRefunds};
+pub use tally::Tally;
+pub use vote::Vote;
@@ -0,0 +1,163 @@
+//! Proofs for delegator votes, abstracted over the proof system that produces
+//! them, like the spend and output proofs of [`penumbra_crypto::proofs`].
+//!
+//! A delegator vote proof shows ownership of a note of delegation tokens in
+//! the note commitment tree at the start of voting, without spending it.  The
+//! note's value is public, since it determines the vote's power.
+
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::anyhow;
+use penumbra_crypto::{
+    keys, merkle,
+    proofs::{transparent, Proof, ProofSystem},
+    rdsa::{SpendAuth, VerificationKey},
+    Fr, Note, Nullifier, Value, Zero,
+};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+pub mod groth16;
+
+/// The public inputs of a delegator vote proof.
+#[derive(Clone, Debug)]
+pub struct DelegatorVotePublicInputs {
+    /// The merkle root of the note commitment tree at the start of voting.
+    pub anchor: merkle::Root,
+    /// The value of the note used to vote.
+    pub value: Value,
+    /// The nullifier of the note used to vote.
+    pub nullifier: Nullifier,
+    /// The randomized verification spend key.
+    pub rk: VerificationKey<SpendAuth>,
+}
+
+/// A delegator vote proof from any of the supported proof systems.
+///
+/// The transparent proof is a transparent spend proof whose value commitment
+/// has a zero blinding factor, so that it commits to the public value.
+#[derive(Clone, Debug)]
+pub enum DelegatorVoteProof {
+    Transparent(transparent::SpendProof),
+    Groth16(groth16::DelegatorVoteProof),
+}
+
+impl DelegatorVoteProof {
+    /// Proves that `note`, witnessed in the note commitment tree with root
+    /// `anchor`, is controlled by the voter, using the given proof system.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        anchor: merkle::Root,
+        note_commitment_proof: merkle::Proof,
+        note: &Note,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+    ) -> anyhow::Result<Self> {
+        Ok(match proof_system {
+            ProofSystem::Transparent => DelegatorVoteProof::Transparent(transparent::SpendProof {
+                note_commitment_proof,
+                g_d: note.diversified_generator(),
+                pk_d: note.transmission_key(),
+                value: note.value(),
+                v_blinding: Fr::zero(),
+                note_commitment: note.commit(),
+                note_blinding: note.note_blinding(),
+                spend_auth_randomizer,
+                ak,
+                nk,
+            }),
+            ProofSystem::Groth16 => {
+                DelegatorVoteProof::Groth16(groth16::DelegatorVoteProof::prove(
+                    rng,
+                    &groth16::delegator_vote_parameters()?.proving_key,
+                    anchor,
+                    &note_commitment_proof,
+                    note,
+                    spend_auth_randomizer,
+                    ak,
+                    nk,
+                )?)
+            }
+        })
+    }
+
+    /// The proof system that produced this proof.
+    pub fn proof_system(&self) -> ProofSystem {
+        match self {
+            DelegatorVoteProof::Transparent(_) => ProofSystem::Transparent,
+            DelegatorVoteProof::Groth16(_) => ProofSystem::Groth16,
+        }
+    }
+}
+
+impl Proof for DelegatorVoteProof {
+    type PublicInputs = DelegatorVotePublicInputs;
+
+    fn verify(&self, public_inputs: &DelegatorVotePublicInputs) -> anyhow::Result<()> {
+        match self {
+            DelegatorVoteProof::Transparent(proof) => Ok(proof.verify(
+                public_inputs.anchor.clone(),
+                public_inputs.value.commit(Fr::zero()),
+                public_inputs.nullifier,
+                public_inputs.rk,
+            )?),
+            DelegatorVoteProof::Groth16(proof) => proof.verify(
+                &groth16::delegator_vote_parameters()?.verifying_key,
+                public_inputs,
+            ),
+        }
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::TaggedProof> for DelegatorVoteProof {}
+
+impl From<DelegatorVoteProof> for pb::TaggedProof {
+    fn from(proof: DelegatorVoteProof) -> Self {
+        let proof_system = proof.proof_system();
+        let inner: Vec<u8> = match proof {
+            DelegatorVoteProof::Transparent(proof) => proof.into(),
+            DelegatorVoteProof::Groth16(proof) => proof.into(),
+        };
+        pb::TaggedProof {
+            proof_system: Some(proof_system.into()),
+            inner,
+        }
+    }
+}
+
+impl TryFrom<pb::TaggedProof> for DelegatorVoteProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::TaggedProof) -> anyhow::Result<Self, Self::Error> {
+        let proof_system = proto
+            .proof_system
+            .ok_or_else(|| anyhow!("missing proof system"))?
+            .try_into()?;
+        Ok(match proof_system {
+            ProofSystem::Transparent => {
+                DelegatorVoteProof::Transparent(proto.inner[..].try_into()?)
+            }
+            ProofSystem::Groth16 => DelegatorVoteProof::Groth16(proto.inner[..].try_into()?),
+        })
+    }
+}
+
+impl From<DelegatorVoteProof> for Vec<u8> {
+    fn from(delegator_vote_proof: DelegatorVoteProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::TaggedProof = delegator_vote_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for DelegatorVoteProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<DelegatorVoteProof, Self::Error> {
+        pb::TaggedProof::decode(bytes)?.try_into()
+    }
+}
@@ -0,0 +1,372 @@
+//! Groth16 proofs over BLS12-377 for delegator votes.
+//!
+//! These prove the same statement as the transparent proofs, but without
+//! revealing the note, its position in the note commitment tree, or the keys
+//! that control it.  They reuse the gadgets of the spend circuit, and take
+//! their parameters from the same setup ceremony.
+
+use std::{
+    convert::{TryFrom, TryInto},
+    path::Path,
+};
+
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use decaf377::r1cs::{ElementVar, FqVar};
+use once_cell::sync::OnceCell;
+use penumbra_crypto::{
+    keys, merkle,
+    proofs::groth16::{
+        auth_path,
+        gadgets::{self, POSITION_BITS, TREE_HEIGHT},
+        s_value, verify, Parameters,
+    },
+    rdsa::{SpendAuth, VerificationKey},
+    Fq, Fr, Note, Value,
+};
+use penumbra_proto::{zk_proofs as pb, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use super::DelegatorVotePublicInputs;
+
+/// The name of the file holding the [`DelegatorVoteCircuit`] parameters.
+pub const DELEGATOR_VOTE_PARAMETERS_FILE: &str = "delegator_vote.params";
+
+static DELEGATOR_VOTE_PROOF_PARAMETERS: OnceCell<Parameters> = OnceCell::new();
+
+/// Loads the parameters for the [`DelegatorVoteCircuit`] from the output of a
+/// setup ceremony, stored in `dir` as [`DELEGATOR_VOTE_PARAMETERS_FILE`],
+/// alongside the spend and output parameters loaded by
+/// [`penumbra_crypto::proofs::groth16::load_parameters`].
+///
+/// Until this is called, Groth16 delegator vote proofs can be neither created
+/// nor verified.
+pub fn load_parameters(dir: &Path) -> anyhow::Result<()> {
+    let delegator_vote = Parameters::load(&dir.join(DELEGATOR_VOTE_PARAMETERS_FILE))?;
+
+    DELEGATOR_VOTE_PROOF_PARAMETERS
+        .set(delegator_vote)
+        .map_err(|_| anyhow::anyhow!("Groth16 delegator vote parameters were already loaded"))?;
+
+    Ok(())
+}
+
+/// The parameters for the [`DelegatorVoteCircuit`], if they have been loaded.
+pub fn delegator_vote_parameters() -> anyhow::Result<&'static Parameters> {
+    DELEGATOR_VOTE_PROOF_PARAMETERS
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Groth16 delegator vote parameters have not been loaded"))
+}
+
+/// The circuit proving that a delegator vote uses a note in the note
+/// commitment tree, controlled by the voter.
+///
+/// Unlike the spend circuit, the value of the note is public rather than
+/// committed to, and the note is not spent.
+///
+/// The public inputs are:
+/// * the merkle root of the note commitment tree,
+/// * the amount of the note,
+/// * the asset ID of the note,
+/// * the nullifier of the note,
+/// * the randomized verification spend key,
+///
+/// each as a field element, in that order.
+#[derive(Clone, Debug)]
+pub struct DelegatorVoteCircuit {
+    // Private inputs
+    /// The position of the note commitment in the note commitment tree.
+    position: u64,
+    /// The authentication path of the note commitment, from the root to the leaf.
+    auth_path: [[Fq; 3]; TREE_HEIGHT],
+    /// The diversified base for the address.
+    g_d: decaf377::Element,
+    /// The `s` value of the transmission key for the address.
+    pk_d: Fq,
+    /// The blinding factor used for generating the note commitment.
+    note_blinding: Fq,
+    /// The randomizer used for generating the randomized spend auth key.
+    spend_auth_randomizer: Fr,
+    /// The spend authorization key.
+    ak: decaf377::Element,
+    /// The nullifier deriving key.
+    nk: Fq,
+
+    // Public inputs
+    anchor: Fq,
+    value: Value,
+    nullifier: Fq,
+    rk: Fq,
+}
+
+impl ConstraintSynthesizer<Fq> for DelegatorVoteCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        // Witnesses
+        let (position_bits, position) = gadgets::u64_witness(cs.clone(), self.position)?;
+        let auth_path = self
+            .auth_path
+            .iter()
+            .map(|siblings| {
+                Ok([
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[0]))?,
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[1]))?,
+                    FqVar::new_witness(cs.clone(), || Ok(siblings[2]))?,
+                ])
+            })
+            .collect::<ark_relations::r1cs::Result<Vec<_>>>()?;
+        let g_d = ElementVar::new_witness(cs.clone(), || Ok(self.g_d))?;
+        let pk_d = FqVar::new_witness(cs.clone(), || Ok(self.pk_d))?;
+        let note_blinding = FqVar::new_witness(cs.clone(), || Ok(self.note_blinding))?;
+        let spend_auth_randomizer_bits =
+            gadgets::scalar_bits(cs.clone(), self.spend_auth_randomizer)?;
+        let ak = ElementVar::new_witness(cs.clone(), || Ok(self.ak))?;
+        let nk = FqVar::new_witness(cs.clone(), || Ok(self.nk))?;
+
+        // Public inputs
+        let anchor = FqVar::new_input(cs.clone(), || Ok(self.anchor))?;
+        let amount = FqVar::new_input(cs.clone(), || Ok(Fq::from(self.value.amount)))?;
+        let asset_id = FqVar::new_input(cs.clone(), || Ok(self.value.asset_id.0))?;
+        let nullifier = FqVar::new_input(cs.clone(), || Ok(self.nullifier))?;
+        let rk = FqVar::new_input(cs.clone(), || Ok(self.rk))?;
+
+        // Note commitment integrity.
+        let note_commitment =
+            gadgets::note_commitment(cs.clone(), &note_blinding, &amount, &asset_id, &g_d, &pk_d)?;
+
+        // Merkle path integrity: positions in the tree have at most 48 bits.
+        for bit in &position_bits[POSITION_BITS..] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        gadgets::merkle_root(cs.clone(), &note_commitment, &position_bits, &auth_path)?
+            .enforce_equal(&anchor)?;
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        gadgets::enforce_not_identity(&g_d)?;
+        gadgets::enforce_not_identity(&ak)?;
+
+        // Nullifier integrity.
+        gadgets::nullifier(cs.clone(), &nk, &note_commitment, &position)?
+            .enforce_equal(&nullifier)?;
+
+        // Spend authority.
+        let basepoint = ElementVar::new_constant(cs.clone(), decaf377::basepoint())?;
+        (ak.clone() + basepoint.scalar_mul_le(spend_auth_randomizer_bits.iter())?)
+            .compress_to_field()?
+            .enforce_equal(&rk)?;
+
+        // Diversified address integrity.
+        let ivk_bits = gadgets::ivk_bits(cs, &ak, &nk)?;
+        g_d.scalar_mul_le(ivk_bits.iter())?
+            .compress_to_field()?
+            .enforce_equal(&pk_d)?;
+
+        Ok(())
+    }
+}
+
+impl DelegatorVoteCircuit {
+    /// The circuit for voting with `note`, witnessed in the note commitment
+    /// tree with root `anchor`.
+    fn new(
+        anchor: merkle::Root,
+        note_commitment_proof: &merkle::Proof,
+        note: &Note,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+    ) -> anyhow::Result<Self> {
+        let position = note_commitment_proof.position();
+
+        let ak_element = decaf377::Encoding(ak.into())
+            .decompress()
+            .map_err(|_| anyhow::anyhow!("invalid spend authorization key"))?;
+        let rk: [u8; 32] = ak.randomize(&spend_auth_randomizer).into();
+
+        Ok(DelegatorVoteCircuit {
+            position: position.into(),
+            auth_path: auth_path(note_commitment_proof),
+            g_d: note.diversified_generator(),
+            pk_d: note.transmission_key_s(),
+            note_blinding: note.note_blinding(),
+            spend_auth_randomizer,
+            ak: ak_element,
+            nk: nk.0,
+            anchor: anchor.into(),
+            value: note.value(),
+            nullifier: nk.derive_nullifier(position, &note.commit()).0,
+            rk: s_value(rk)?,
+        })
+    }
+}
+
+/// A Groth16 proof that a delegator vote uses a note controlled by the voter.
+#[derive(Clone, Debug)]
+pub struct DelegatorVoteProof(Proof<Bls12_377>);
+
+impl DelegatorVoteProof {
+    /// Proves that `note`, witnessed in the note commitment tree with root
+    /// `anchor`, is controlled by the voter.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        proving_key: &ProvingKey<Bls12_377>,
+        anchor: merkle::Root,
+        note_commitment_proof: &merkle::Proof,
+        note: &Note,
+        spend_auth_randomizer: Fr,
+        ak: VerificationKey<SpendAuth>,
+        nk: keys::NullifierKey,
+    ) -> anyhow::Result<Self> {
+        let circuit = DelegatorVoteCircuit::new(
+            anchor,
+            note_commitment_proof,
+            note,
+            spend_auth_randomizer,
+            ak,
+            nk,
+        )?;
+        let proof = Groth16::<Bls12_377>::prove(proving_key, circuit, rng)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(DelegatorVoteProof(proof))
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(
+        &self,
+        verifying_key: &PreparedVerifyingKey<Bls12_377>,
+        public_inputs: &DelegatorVotePublicInputs,
+    ) -> anyhow::Result<()> {
+        let inputs = [
+            public_inputs.anchor.clone().into(),
+            Fq::from(public_inputs.value.amount),
+            public_inputs.value.asset_id.0,
+            public_inputs.nullifier.0,
+            s_value(public_inputs.rk.into())?,
+        ];
+
+        if verify(verifying_key, &inputs, &self.0)? {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("delegator vote proof did not verify"))
+        }
+    }
+}
+
+// Conversions
+
+impl Protobuf<pb::DelegatorVoteProof> for DelegatorVoteProof {}
+
+impl From<DelegatorVoteProof> for pb::DelegatorVoteProof {
+    fn from(proof: DelegatorVoteProof) -> Self {
+        let mut inner = Vec::new();
+        proof.0.serialize(&mut inner).expect("can serialize proof");
+        pb::DelegatorVoteProof { inner }
+    }
+}
+
+impl TryFrom<pb::DelegatorVoteProof> for DelegatorVoteProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: pb::DelegatorVoteProof) -> anyhow::Result<Self, Self::Error> {
+        Ok(DelegatorVoteProof(
+            Proof::deserialize(&proto.inner[..])
+                .map_err(|_| anyhow::anyhow!("delegator vote proof malformed"))?,
+        ))
+    }
+}
+
+impl From<DelegatorVoteProof> for Vec<u8> {
+    fn from(delegator_vote_proof: DelegatorVoteProof) -> Vec<u8> {
+        let protobuf_serialized_proof: pb::DelegatorVoteProof = delegator_vote_proof.into();
+        protobuf_serialized_proof.encode_to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for DelegatorVoteProof {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<DelegatorVoteProof, Self::Error> {
+        pb::DelegatorVoteProof::decode(bytes)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use penumbra_crypto::{
+        keys::{SeedPhrase, SpendKey, SpendSeed},
+        merkle::{Keep, NoteCommitmentTree},
+    };
+    use penumbra_stake::IdentityKey;
+    use rand_core::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn delegator_vote_circuit_proves_the_notes_value() {
+        let mut rng = OsRng;
+
+        let sk = SpendKey::new(SpendSeed::from_seed_phrase(
+            SeedPhrase::generate(&mut rng),
+            0,
+        ));
+        let (address, _dtk_d) = sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let identity_key = IdentityKey((*sk.spend_auth_key()).into());
+        let value = Value {
+            amount: 100,
+            asset_id: identity_key.delegation_token().id(),
+        };
+        let note = Note::generate(&mut rng, &address, value);
+
+        let mut nct = NoteCommitmentTree::new();
+        nct.insert(Keep, note.commit()).unwrap();
+        let note_commitment_proof = nct.witness(note.commit()).unwrap();
+
+        let vote = DelegatorVoteCircuit::new(
+            nct.root(),
+            &note_commitment_proof,
+            &note,
+            Fr::rand(&mut rng),
+            (*sk.spend_auth_key()).into(),
+            *sk.nullifier_key(),
+        )
+        .unwrap();
+        let cs = ConstraintSystem::new_ref();
+        vote.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // The vote doesn't hold for a different amount...
+        let cs = ConstraintSystem::new_ref();
+        DelegatorVoteCircuit {
+            value: Value {
+                amount: 101,
+                ..value
+            },
+            ..vote.clone()
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        // ... or against a different anchor.
+        let cs = ConstraintSystem::new_ref();
+        DelegatorVoteCircuit {
+            anchor: Fq::rand(&mut rng),
+            ..vote
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,309 @@
+use penumbra_crypto::{value, Address, Fr, Value, Zero};
+use penumbra_proto::{governance as pb, Protobuf};
+use penumbra_stake::STAKING_TOKEN_ASSET_ID;
+
+use crate::{CommunityPoolOutput, Tally};
+
+/// A proposal to be voted upon by validators and delegators.
+///
+/// Submitting a proposal escrows its deposit, which is returned to the
+/// proposer once voting has finished, unless the proposal was vetoed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    /// A short title summarizing the proposal.
+    pub title: String,
+    /// A natural-language description of the proposal.
+    pub description: String,
+    /// What the proposal does, if it passes.
+    pub payload: ProposalPayload,
+    /// The amount of the staking token escrowed as a deposit.
+    pub deposit_amount: u64,
+    /// The address to which the deposit is returned, unless the proposal is vetoed.
+    pub deposit_refund_address: Address,
+    /// The number of epochs for which voting stays open, after the epoch in
+    /// which the proposal is submitted.
+    pub voting_epochs: u64,
+}
+
+impl Proposal {
+    /// Compute a commitment to the value contributed to a transaction by this proposal.
+    pub fn value_commitment(&self) -> value::Commitment {
+        // The deposit is consumed from the transaction's balance.
+        -Value {
+            amount: self.deposit_amount,
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        }
+        .commit(Fr::zero())
+    }
+}
+
+/// The kind of a proposal, and the data it carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProposalPayload {
+    /// A signaling proposal records the sense of the chain's stakeholders, but
+    /// has no automatic effect when it passes.
+    Signaling {
+        /// An optional commit hash for the code the proposal refers to.
+        commit: Option<String>,
+    },
+    /// A parameter change proposal updates the chain parameters at the end of
+    /// the epoch in which it passes.
+    ParameterChange {
+        /// The changes to apply, in order.
+        changes: Vec<ParameterChange>,
+    },
+    /// A community pool spend proposal pays out funds from the community pool
+    /// once it passes.
+    CommunityPoolSpend {
+        /// The payments to make from the community pool.
+        outputs: Vec<CommunityPoolOutput>,
+    },
+}
+
+/// A change to a single chain parameter.
+///
+/// Parameters are identified by name, and their new values are given in their
+/// string representation, since the chain parameters themselves are defined
+/// downstream of this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterChange {
+    /// The name of the parameter.
+    pub parameter: String,
+    /// The new value of the parameter.
+    pub value: String,
+}
+
+impl Protobuf<pb::Proposal> for Proposal {}
+
+impl From<Proposal> for pb::Proposal {
+    fn from(p: Proposal) -> Self {
+        pb::Proposal {
+            title: p.title,
+            description: p.description,
+            payload: Some(p.payload.into()),
+            deposit_amount: p.deposit_amount,
+            deposit_refund_address: Some(p.deposit_refund_address.into()),
+            voting_epochs: p.voting_epochs,
+        }
+    }
+}
+
+impl TryFrom<pb::Proposal> for Proposal {
+    type Error = anyhow::Error;
+    fn try_from(p: pb::Proposal) -> Result<Self, Self::Error> {
+        Ok(Proposal {
+            title: p.title,
+            description: p.description,
+            payload: p
+                .payload
+                .ok_or_else(|| anyhow::anyhow!("missing proposal payload"))?
+                .try_into()?,
+            deposit_amount: p.deposit_amount,
+            deposit_refund_address: p
+                .deposit_refund_address
+                .ok_or_else(|| anyhow::anyhow!("missing deposit refund address"))?
+                .try_into()?,
+            voting_epochs: p.voting_epochs,
+        })
+    }
+}
+
+impl Protobuf<pb::ProposalPayload> for ProposalPayload {}
+
+impl From<ProposalPayload> for pb::ProposalPayload {
+    fn from(p: ProposalPayload) -> Self {
+        pb::ProposalPayload {
+            payload: Some(match p {
+                ProposalPayload::Signaling { commit } => {
+                    pb::proposal_payload::Payload::Signaling(pb::proposal_payload::Signaling {
+                        commit,
+                    })
+                }
+                ProposalPayload::ParameterChange { changes } => {
+                    pb::proposal_payload::Payload::ParameterChange(
+                        pb::proposal_payload::ParameterChange {
+                            changes: changes.into_iter().map(Into::into).collect(),
+                        },
+                    )
+                }
+                ProposalPayload::CommunityPoolSpend { outputs } => {
+                    pb::proposal_payload::Payload::CommunityPoolSpend(
+                        pb::proposal_payload::CommunityPoolSpend {
+                            outputs: outputs.into_iter().map(Into::into).collect(),
+                        },
+                    )
+                }
+            }),
+        }
+    }
+}
+
+impl TryFrom<pb::ProposalPayload> for ProposalPayload {
+    type Error = anyhow::Error;
+    fn try_from(p: pb::ProposalPayload) -> Result<Self, Self::Error> {
+        match p
+            .payload
+            .ok_or_else(|| anyhow::anyhow!("missing proposal payload"))?
+        {
+            pb::proposal_payload::Payload::Signaling(signaling) => Ok(ProposalPayload::Signaling {
+                commit: signaling.commit,
+            }),
+            pb::proposal_payload::Payload::ParameterChange(change) => {
+                Ok(ProposalPayload::ParameterChange {
+                    changes: change.changes.into_iter().map(Into::into).collect(),
+                })
+            }
+            pb::proposal_payload::Payload::CommunityPoolSpend(spend) => {
+                Ok(ProposalPayload::CommunityPoolSpend {
+                    outputs: spend
+                        .outputs
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+        }
+    }
+}
+
+impl Protobuf<pb::ChainParameterChange> for ParameterChange {}
+
+impl From<ParameterChange> for pb::ChainParameterChange {
+    fn from(c: ParameterChange) -> Self {
+        pb::ChainParameterChange {
+            parameter: c.parameter,
+            value: c.value,
+        }
+    }
+}
+
+impl From<pb::ChainParameterChange> for ParameterChange {
+    fn from(c: pb::ChainParameterChange) -> Self {
+        ParameterChange {
+            parameter: c.parameter,
+            value: c.value,
+        }
+    }
+}
+
+/// The state of a proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalState {
+    /// Voting on the proposal is open.
+    Voting,
+    /// Voting on the proposal has finished, with the given outcome.
+    Finished { outcome: ProposalOutcome },
+}
+
+/// The outcome of a proposal, along with the final tally of votes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProposalOutcome {
+    pub outcome: Outcome,
+    pub tally: Tally,
+}
+
+/// The outcome of a proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The proposal passed, and its deposit was returned.
+    Passed,
+    /// The proposal failed, and its deposit was returned.
+    Failed,
+    /// The proposal was vetoed, and its deposit was burned.
+    Vetoed,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outcome::Passed => write!(f, "passed"),
+            Outcome::Failed => write!(f, "failed"),
+            Outcome::Vetoed => write!(f, "vetoed"),
+        }
+    }
+}
+
+impl Protobuf<pb::ProposalState> for ProposalState {}
+
+impl From<ProposalState> for pb::ProposalState {
+    fn from(s: ProposalState) -> Self {
+        match s {
+            ProposalState::Voting => pb::ProposalState {
+                state: pb::proposal_state::ProposalStateEnum::Voting as i32,
+                outcome: None,
+            },
+            ProposalState::Finished { outcome } => pb::ProposalState {
+                state: pb::proposal_state::ProposalStateEnum::Finished as i32,
+                outcome: Some(outcome.into()),
+            },
+        }
+    }
+}
+
+impl TryFrom<pb::ProposalState> for ProposalState {
+    type Error = anyhow::Error;
+    fn try_from(s: pb::ProposalState) -> Result<Self, Self::Error> {
+        Ok(
+            match pb::proposal_state::ProposalStateEnum::from_i32(s.state)
+                .ok_or_else(|| anyhow::anyhow!("invalid proposal state"))?
+            {
+                pb::proposal_state::ProposalStateEnum::Voting => ProposalState::Voting,
+                pb::proposal_state::ProposalStateEnum::Finished => ProposalState::Finished {
+                    outcome: s
+                        .outcome
+                        .ok_or_else(|| anyhow::anyhow!("missing proposal outcome"))?
+                        .try_into()?,
+                },
+            },
+        )
+    }
+}
+
+impl Protobuf<pb::ProposalOutcome> for ProposalOutcome {}
+
+impl From<ProposalOutcome> for pb::ProposalOutcome {
+    fn from(o: ProposalOutcome) -> Self {
+        pb::ProposalOutcome {
+            outcome: match o.outcome {
+                Outcome::Passed => pb::proposal_outcome::ProposalOutcomeEnum::Passed,
+                Outcome::Failed => pb::proposal_outcome::ProposalOutcomeEnum::Failed,
+                Outcome::Vetoed => pb::proposal_outcome::ProposalOutcomeEnum::Vetoed,
+            } as i32,
+            tally: Some(o.tally.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::ProposalOutcome> for ProposalOutcome {
+    type Error = anyhow::Error;
+    fn try_from(o: pb::ProposalOutcome) -> Result<Self, Self::Error> {
+        Ok(ProposalOutcome {
+            outcome: match pb::proposal_outcome::ProposalOutcomeEnum::from_i32(o.outcome)
+                .ok_or_else(|| anyhow::anyhow!("invalid proposal outcome"))?
+            {
+                pb::proposal_outcome::ProposalOutcomeEnum::Passed => Outcome::Passed,
+                pb::proposal_outcome::ProposalOutcomeEnum::Failed => Outcome::Failed,
+                pb::proposal_outcome::ProposalOutcomeEnum::Vetoed => Outcome::Vetoed,
+            },
+            tally: o.tally.unwrap_or_default().into(),
+        })
+    }
+}
+
+/// A list of proposals, identified by their IDs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProposalList(pub Vec<u64>);
+
+impl Protobuf<pb::ProposalList> for ProposalList {}
+
+impl From<ProposalList> for pb::ProposalList {
+    fn from(l: ProposalList) -> Self {
+        pb::ProposalList { proposal_ids: l.0 }
+    }
+}
+
+impl From<pb::ProposalList> for ProposalList {
+    fn from(l: pb::ProposalList) -> Self {
+        ProposalList(l.proposal_ids)
+    }
+}
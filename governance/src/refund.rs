@@ -0,0 +1,66 @@
+use anyhow::Result;
+use penumbra_crypto::Address;
+use penumbra_proto::{governance as pb, Protobuf};
+
+/// A proposal deposit to be returned to the proposer once voting has finished.
+#[derive(Debug, Clone)]
+pub struct DepositRefund {
+    pub proposal_id: u64,
+    pub amount: u64,
+    pub address: Address,
+}
+
+impl Protobuf<pb::DepositRefund> for DepositRefund {}
+
+impl From<DepositRefund> for pb::DepositRefund {
+    fn from(refund: DepositRefund) -> Self {
+        pb::DepositRefund {
+            proposal_id: refund.proposal_id,
+            amount: refund.amount,
+            address: Some(refund.address.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::DepositRefund> for DepositRefund {
+    type Error = anyhow::Error;
+    fn try_from(refund: pb::DepositRefund) -> Result<Self> {
+        Ok(DepositRefund {
+            proposal_id: refund.proposal_id,
+            amount: refund.amount,
+            address: refund
+                .address
+                .ok_or_else(|| anyhow::anyhow!("missing deposit refund address"))?
+                .try_into()?,
+        })
+    }
+}
+
+/// A list of deposit refunds to be minted by the shielded pool.
+#[derive(Debug, Clone, Default)]
+pub struct DepositRefunds {
+    pub refunds: Vec<DepositRefund>,
+}
+
+impl Protobuf<pb::DepositRefunds> for DepositRefunds {}
+
+impl From<DepositRefunds> for pb::DepositRefunds {
+    fn from(refunds: DepositRefunds) -> Self {
+        pb::DepositRefunds {
+            refunds: refunds.refunds.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::DepositRefunds> for DepositRefunds {
+    type Error = anyhow::Error;
+    fn try_from(refunds: pb::DepositRefunds) -> Result<Self> {
+        Ok(DepositRefunds {
+            refunds: refunds
+                .refunds
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
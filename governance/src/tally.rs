@@ -0,0 +1,125 @@
+use penumbra_proto::{governance as pb, Protobuf};
+
+use crate::{Outcome, Vote};
+
+/// The voting power cast for each option on a proposal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub yes: u64,
+    pub no: u64,
+    pub abstain: u64,
+    pub no_with_veto: u64,
+}
+
+impl Tally {
+    /// Adds `power` to the tally of the given `vote`.
+    pub fn add(&mut self, vote: Vote, power: u64) {
+        let count = match vote {
+            Vote::Yes => &mut self.yes,
+            Vote::No => &mut self.no,
+            Vote::Abstain => &mut self.abstain,
+            Vote::NoWithVeto => &mut self.no_with_veto,
+        };
+        *count = count.saturating_add(power);
+    }
+
+    /// The total voting power cast on the proposal, including abstentions.
+    pub fn total(&self) -> u64 {
+        self.yes
+            .saturating_add(self.no)
+            .saturating_add(self.abstain)
+            .saturating_add(self.no_with_veto)
+    }
+
+    /// Determines the outcome of a proposal with this final tally.
+    ///
+    /// The thresholds are expressed in basis points:
+    ///
+    /// - at least `valid_quorum_bps` of the `total_voting_power` must have
+    ///   voted, or the proposal fails;
+    /// - if more than `veto_threshold_bps` of the votes cast were
+    ///   [`Vote::NoWithVeto`], the proposal is vetoed;
+    /// - otherwise, the proposal passes if more than `pass_threshold_bps` of
+    ///   the non-abstaining votes were [`Vote::Yes`].
+    pub fn outcome(
+        &self,
+        total_voting_power: u64,
+        valid_quorum_bps: u64,
+        pass_threshold_bps: u64,
+        veto_threshold_bps: u64,
+    ) -> Outcome {
+        let total = self.total() as u128;
+
+        if total == 0 || total * 10_000 < total_voting_power as u128 * valid_quorum_bps as u128 {
+            return Outcome::Failed;
+        }
+
+        if self.no_with_veto as u128 * 10_000 > total * veto_threshold_bps as u128 {
+            return Outcome::Vetoed;
+        }
+
+        let non_abstaining = total - self.abstain as u128;
+        if non_abstaining > 0
+            && self.yes as u128 * 10_000 > non_abstaining * pass_threshold_bps as u128
+        {
+            Outcome::Passed
+        } else {
+            Outcome::Failed
+        }
+    }
+}
+
+impl Protobuf<pb::Tally> for Tally {}
+
+impl From<Tally> for pb::Tally {
+    fn from(t: Tally) -> Self {
+        pb::Tally {
+            yes: t.yes,
+            no: t.no,
+            abstain: t.abstain,
+            no_with_veto: t.no_with_veto,
+        }
+    }
+}
+
+impl From<pb::Tally> for Tally {
+    fn from(t: pb::Tally) -> Self {
+        Tally {
+            yes: t.yes,
+            no: t.no,
+            abstain: t.abstain,
+            no_with_veto: t.no_with_veto,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tally(yes: u64, no: u64, abstain: u64, no_with_veto: u64) -> Tally {
+        Tally {
+            yes,
+            no,
+            abstain,
+            no_with_veto,
+        }
+    }
+
+    #[test]
+    fn outcome_thresholds() {
+        // 40% quorum, 50% to pass, 33.34% to veto.
+        let outcome = |t: Tally| t.outcome(100, 4000, 5000, 3334);
+
+        // Not enough voting power was cast.
+        assert_eq!(outcome(tally(39, 0, 0, 0)), Outcome::Failed);
+        // Abstentions count towards the quorum, but not towards passing.
+        assert_eq!(outcome(tally(21, 20, 30, 0)), Outcome::Passed);
+        assert_eq!(outcome(tally(20, 20, 30, 0)), Outcome::Failed);
+        // Vetoes count against passing, and burn the deposit past their own threshold.
+        assert_eq!(outcome(tally(30, 0, 0, 15)), Outcome::Passed);
+        assert_eq!(outcome(tally(30, 0, 0, 16)), Outcome::Vetoed);
+        // Nobody voting at all fails the proposal.
+        assert_eq!(tally(0, 0, 0, 0).outcome(0, 0, 5000, 3334), Outcome::Failed);
+    }
+}
@@ -0,0 +1,71 @@
+use penumbra_proto::{governance as pb, Protobuf};
+
+/// A vote on a proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Vote {
+    /// The voter abstains, counting towards the quorum without taking a side.
+    Abstain,
+    /// The voter is in favor of the proposal.
+    Yes,
+    /// The voter is against the proposal.
+    No,
+    /// The voter is against the proposal, and considers it spam: if enough
+    /// voting power agrees, the proposer's deposit is burned.
+    NoWithVeto,
+}
+
+impl std::fmt::Display for Vote {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Vote::Abstain => write!(f, "abstain"),
+            Vote::Yes => write!(f, "yes"),
+            Vote::No => write!(f, "no"),
+            Vote::NoWithVeto => write!(f, "no_with_veto"),
+        }
+    }
+}
+
+impl std::str::FromStr for Vote {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.replace('-', "_").to_lowercase().as_str() {
+            "abstain" => Ok(Vote::Abstain),
+            "yes" => Ok(Vote::Yes),
+            "no" => Ok(Vote::No),
+            "no_with_veto" | "veto" => Ok(Vote::NoWithVeto),
+            _ => Err(anyhow::anyhow!("invalid vote {}", s)),
+        }
+    }
+}
+
+impl Protobuf<pb::Vote> for Vote {}
+
+impl From<Vote> for pb::Vote {
+    fn from(v: Vote) -> Self {
+        pb::Vote {
+            vote: match v {
+                Vote::Abstain => pb::vote::VoteEnum::Abstain,
+                Vote::Yes => pb::vote::VoteEnum::Yes,
+                Vote::No => pb::vote::VoteEnum::No,
+                Vote::NoWithVeto => pb::vote::VoteEnum::NoWithVeto,
+            } as i32,
+        }
+    }
+}
+
+impl TryFrom<pb::Vote> for Vote {
+    type Error = anyhow::Error;
+    fn try_from(v: pb::Vote) -> Result<Self, Self::Error> {
+        Ok(
+            match pb::vote::VoteEnum::from_i32(v.vote)
+                .ok_or_else(|| anyhow::anyhow!("invalid vote"))?
+            {
+                pb::vote::VoteEnum::Abstain => Vote::Abstain,
+                pb::vote::VoteEnum::Yes => Vote::Yes,
+                pb::vote::VoteEnum::No => Vote::No,
+                pb::vote::VoteEnum::NoWithVeto => Vote::NoWithVeto,
+            },
+        )
+    }
+}
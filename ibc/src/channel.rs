@@ -0,0 +1,44 @@
+use ibc::core::ics04_channel::channel::ChannelEnd;
+use ibc_proto::ibc::core::channel::v1::Channel as RawChannel;
+use penumbra_proto::{ibc as pb, Protobuf};
+
+#[derive(Clone, Debug)]
+pub struct ChannelCounter(pub u64);
+
+impl Protobuf<pb::ChannelCounter> for ChannelCounter {}
+
+impl TryFrom<pb::ChannelCounter> for ChannelCounter {
+    type Error = anyhow::Error;
+
+    fn try_from(p: pb::ChannelCounter) -> Result<Self, Self::Error> {
+        Ok(ChannelCounter(p.counter))
+    }
+}
+
+impl From<ChannelCounter> for pb::ChannelCounter {
+    fn from(c: ChannelCounter) -> Self {
+        pb::ChannelCounter { counter: c.0 }
+    }
+}
+
+/// An ICS-04 channel end, stored in the Penumbra state.
+#[derive(Clone, Debug)]
+pub struct Channel(pub ChannelEnd);
+
+impl Protobuf<RawChannel> for Channel {}
+
+impl TryFrom<RawChannel> for Channel {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawChannel) -> Result<Self, Self::Error> {
+        Ok(Channel(ChannelEnd::try_from(raw).map_err(|e| {
+            anyhow::anyhow!("could not decode channel end: {}", e)
+        })?))
+    }
+}
+
+impl From<Channel> for RawChannel {
+    fn from(c: Channel) -> Self {
+        c.0.into()
+    }
+}
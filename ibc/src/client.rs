@@ -88,9 +88,24 @@ impl TryFrom<pb::ConsensusState> for ConsensusState {
     type Error = anyhow::Error;
 
     fn try_from(raw: pb::ConsensusState) -> Result<Self, Self::Error> {
-        let state = raw
-            .consensus_state
-            .ok_or_else(|| anyhow::anyhow!("missing consensus state"))?;
+        raw.consensus_state
+            .ok_or_else(|| anyhow::anyhow!("missing consensus state"))?
+            .try_into()
+    }
+}
+
+impl From<ConsensusState> for pb::ConsensusState {
+    fn from(value: ConsensusState) -> Self {
+        pb::ConsensusState {
+            consensus_state: Some(value.into()),
+        }
+    }
+}
+
+impl TryFrom<prost_types::Any> for ConsensusState {
+    type Error = anyhow::Error;
+
+    fn try_from(state: prost_types::Any) -> Result<Self, Self::Error> {
         match state.type_url.as_str() {
             TENDERMINT_CONSENSUS_STATE_TYPE_URL => {
                 Ok(ConsensusState(AnyConsensusState::Tendermint(
@@ -108,16 +123,14 @@ impl TryFrom<pb::ConsensusState> for ConsensusState {
     }
 }
 
-impl From<ConsensusState> for pb::ConsensusState {
+impl From<ConsensusState> for prost_types::Any {
     fn from(value: ConsensusState) -> Self {
         match value {
-            ConsensusState(AnyConsensusState::Tendermint(value)) => pb::ConsensusState {
-                consensus_state: Some(prost_types::Any {
-                    type_url: TENDERMINT_CONSENSUS_STATE_TYPE_URL.to_string(),
-                    value: value
-                        .encode_vec()
-                        .expect("encoding to `Any` from `ConsensusState::Tendermint`"),
-                }),
+            ConsensusState(AnyConsensusState::Tendermint(value)) => prost_types::Any {
+                type_url: TENDERMINT_CONSENSUS_STATE_TYPE_URL.to_string(),
+                value: value
+                    .encode_vec()
+                    .expect("encoding to `Any` from `ConsensusState::Tendermint`"),
             },
         }
     }
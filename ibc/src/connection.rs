@@ -0,0 +1,80 @@
+use std::str::FromStr;
+
+use ibc::core::{
+    ics03_connection::connection::ConnectionEnd, ics24_host::identifier::ConnectionId,
+};
+use ibc_proto::ibc::core::connection::v1::{
+    ClientPaths as RawClientPaths, ConnectionEnd as RawConnectionEnd,
+};
+use penumbra_proto::{ibc as pb, Protobuf};
+
+#[derive(Clone, Debug)]
+pub struct ConnectionCounter(pub u64);
+
+impl Protobuf<pb::ConnectionCounter> for ConnectionCounter {}
+
+impl TryFrom<pb::ConnectionCounter> for ConnectionCounter {
+    type Error = anyhow::Error;
+
+    fn try_from(p: pb::ConnectionCounter) -> Result<Self, Self::Error> {
+        Ok(ConnectionCounter(p.counter))
+    }
+}
+
+impl From<ConnectionCounter> for pb::ConnectionCounter {
+    fn from(c: ConnectionCounter) -> Self {
+        pb::ConnectionCounter { counter: c.0 }
+    }
+}
+
+/// An ICS-03 connection end, stored in the Penumbra state.
+#[derive(Clone, Debug)]
+pub struct Connection(pub ConnectionEnd);
+
+impl Protobuf<RawConnectionEnd> for Connection {}
+
+impl TryFrom<RawConnectionEnd> for Connection {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawConnectionEnd) -> Result<Self, Self::Error> {
+        Ok(Connection(ConnectionEnd::try_from(raw).map_err(|e| {
+            anyhow::anyhow!("could not decode connection end: {}", e)
+        })?))
+    }
+}
+
+impl From<Connection> for RawConnectionEnd {
+    fn from(c: Connection) -> Self {
+        c.0.into()
+    }
+}
+
+/// The identifiers of all the connections built on top of a given client.
+#[derive(Clone, Debug, Default)]
+pub struct ClientConnections {
+    pub connection_ids: Vec<ConnectionId>,
+}
+
+impl Protobuf<RawClientPaths> for ClientConnections {}
+
+impl TryFrom<RawClientPaths> for ClientConnections {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawClientPaths) -> Result<Self, Self::Error> {
+        Ok(ClientConnections {
+            connection_ids: raw
+                .paths
+                .iter()
+                .map(|id| ConnectionId::from_str(id))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<ClientConnections> for RawClientPaths {
+    fn from(c: ClientConnections) -> Self {
+        RawClientPaths {
+            paths: c.connection_ids.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
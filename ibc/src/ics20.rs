@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use ibc::core::ics24_host::identifier::ChannelId;
+use penumbra_crypto::{asset::Denom, value, Address, Fr, Value, Zero};
+use penumbra_proto::{ibc as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+
+/// The port the ICS-20 transfer application is bound to.
+pub const TRANSFER_PORT: &str = "transfer";
+
+/// The version of the ICS-20 transfer application.
+pub const TRANSFER_VERSION: &str = "ics20-1";
+
+/// An outbound ICS-20 fungible token transfer from Penumbra to a counterparty chain.
+///
+/// The transferred value is consumed from the transaction's balance, and is
+/// either escrowed, if the asset is native to Penumbra, or burned, if it is
+/// being returned to the chain it came from.
+#[derive(Debug, Clone)]
+pub struct Ics20Withdrawal {
+    /// The amount of the asset to transfer.
+    pub amount: u64,
+    /// The denomination of the asset to transfer.
+    pub denom: Denom,
+    /// The address on the destination chain to send the transfer to.
+    pub destination_chain_address: String,
+    /// The Penumbra address to refund the transfer to if it times out or is rejected.
+    pub return_address: Address,
+    /// The height on the destination chain after which the transfer times out, or 0 for none.
+    pub timeout_height: u64,
+    /// The time on the destination chain, in nanoseconds since the Unix
+    /// epoch, after which the transfer times out, or 0 for none.
+    pub timeout_time: u64,
+    /// The channel on which to send the transfer.
+    pub source_channel: ChannelId,
+}
+
+impl Ics20Withdrawal {
+    /// The value transferred.
+    pub fn value(&self) -> Value {
+        Value {
+            amount: self.amount,
+            asset_id: self.denom.id(),
+        }
+    }
+
+    /// Compute a commitment to the value contributed to a transaction by this withdrawal.
+    pub fn value_commitment(&self) -> value::Commitment {
+        // The transferred value is consumed from the transaction's balance.
+        -self.value().commit(Fr::zero())
+    }
+
+    /// The data of the packet sent to the destination chain.
+    pub fn packet_data(&self) -> FungibleTokenPacketData {
+        FungibleTokenPacketData {
+            denom: self.denom.to_string(),
+            amount: self.amount.to_string(),
+            sender: self.return_address.to_string(),
+            receiver: self.destination_chain_address.clone(),
+        }
+    }
+}
+
+impl Protobuf<pb::Ics20Withdrawal> for Ics20Withdrawal {}
+
+impl From<Ics20Withdrawal> for pb::Ics20Withdrawal {
+    fn from(w: Ics20Withdrawal) -> Self {
+        pb::Ics20Withdrawal {
+            amount: w.amount,
+            denom: Some(w.denom.into()),
+            destination_chain_address: w.destination_chain_address,
+            return_address: Some(w.return_address.into()),
+            timeout_height: w.timeout_height,
+            timeout_time: w.timeout_time,
+            source_channel: w.source_channel.to_string(),
+        }
+    }
+}
+
+impl TryFrom<pb::Ics20Withdrawal> for Ics20Withdrawal {
+    type Error = anyhow::Error;
+    fn try_from(w: pb::Ics20Withdrawal) -> Result<Self, Self::Error> {
+        Ok(Ics20Withdrawal {
+            amount: w.amount,
+            denom: w
+                .denom
+                .ok_or_else(|| anyhow::anyhow!("missing ics20 withdrawal denom"))?
+                .try_into()?,
+            destination_chain_address: w.destination_chain_address,
+            return_address: w
+                .return_address
+                .ok_or_else(|| anyhow::anyhow!("missing ics20 withdrawal return address"))?
+                .try_into()?,
+            timeout_height: w.timeout_height,
+            timeout_time: w.timeout_time,
+            source_channel: ChannelId::from_str(&w.source_channel)?,
+        })
+    }
+}
+
+/// The data carried by an ICS-20 packet, JSON-encoded as specified by ICS-20.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FungibleTokenPacketData {
+    /// The denomination of the transferred asset, prefixed with the port and
+    /// channel of every hop it has taken away from its native chain.
+    pub denom: String,
+    /// The amount transferred, as a decimal string.
+    pub amount: String,
+    /// The sender of the transfer, on the sending chain.
+    pub sender: String,
+    /// The receiver of the transfer, on the receiving chain.
+    pub receiver: String,
+}
+
+/// A note to be minted by the shielded pool as the result of an ICS-20
+/// transfer, either received from a counterparty or refunded to its sender.
+#[derive(Debug, Clone)]
+pub struct Ics20Mint {
+    pub value: Value,
+    pub address: Address,
+    /// The channel the transfer was made over.
+    pub channel_id: ChannelId,
+    /// The sequence number of the transfer's packet.
+    pub sequence: u64,
+}
+
+impl Protobuf<pb::Ics20Mint> for Ics20Mint {}
+
+impl From<Ics20Mint> for pb::Ics20Mint {
+    fn from(m: Ics20Mint) -> Self {
+        pb::Ics20Mint {
+            value: Some(m.value.into()),
+            address: Some(m.address.into()),
+            channel_id: m.channel_id.to_string(),
+            sequence: m.sequence,
+        }
+    }
+}
+
+impl TryFrom<pb::Ics20Mint> for Ics20Mint {
+    type Error = anyhow::Error;
+    fn try_from(m: pb::Ics20Mint) -> Result<Self, Self::Error> {
+        Ok(Ics20Mint {
+            value: m
+                .value
+                .ok_or_else(|| anyhow::anyhow!("missing ics20 mint value"))?
+                .try_into()?,
+            address: m
+                .address
+                .ok_or_else(|| anyhow::anyhow!("missing ics20 mint address"))?
+                .try_into()?,
+            channel_id: ChannelId::from_str(&m.channel_id)?,
+            sequence: m.sequence,
+        })
+    }
+}
+
+/// A list of notes to be minted by the shielded pool as the result of ICS-20 transfers.
+#[derive(Debug, Clone, Default)]
+pub struct Ics20Mints {
+    pub mints: Vec<Ics20Mint>,
+}
+
+impl Protobuf<pb::Ics20Mints> for Ics20Mints {}
+
+impl From<Ics20Mints> for pb::Ics20Mints {
+    fn from(m: Ics20Mints) -> Self {
+        pb::Ics20Mints {
+            mints: m.mints.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::Ics20Mints> for Ics20Mints {
+    type Error = anyhow::Error;
+    fn try_from(m: pb::Ics20Mints) -> Result<Self, Self::Error> {
+        Ok(Ics20Mints {
+            mints: m
+                .mints
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
@@ -3,8 +3,17 @@
 // marked as unreachable only when not building in test configuration.
 #![allow(unreachable_patterns)]
 
+mod channel;
 mod client;
+mod connection;
 mod ibcaction;
+mod ics20;
 
+pub use channel::{Channel, ChannelCounter};
 pub use client::{ClientCounter, ClientData, ConsensusState, VerifiedHeights};
+pub use connection::{ClientConnections, Connection, ConnectionCounter};
 pub use ibcaction::IBCAction;
+pub use ics20::{
+    FungibleTokenPacketData, Ics20Mint, Ics20Mints, Ics20Withdrawal, TRANSFER_PORT,
+    TRANSFER_VERSION,
+};
@@ -159,6 +159,7 @@ impl StakeCmd {
                     .validator_info(ValidatorInfoRequest {
                         show_inactive: true,
                         chain_id: state.chain_id().unwrap_or_default(),
+                        height: 0,
                     })
                     .await?
                     .into_inner()
@@ -258,6 +259,7 @@ impl StakeCmd {
                     .validator_info(ValidatorInfoRequest {
                         show_inactive: *show_inactive,
                         chain_id: state.chain_id().unwrap_or_default(),
+                        height: 0,
                     })
                     .await?
                     .into_inner()
@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use penumbra_crypto::{memo, merkle::TreeExt, Value};
+use penumbra_crypto::{memo, Value};
 use penumbra_transaction::Transaction;
 use rand_core::OsRng;
 use structopt::StructOpt;
@@ -118,12 +118,16 @@ async fn sweep(opt: &Opt, state: &mut ClientStateFile) -> Result<()> {
             for group in notes.chunks_exact(SWEEP_COUNT) {
                 tracing::info!(?denom, "building sweep transaction");
                 let mut tx_builder =
-                    Transaction::build_with_root(state.note_commitment_tree().root2());
-                tx_builder.set_fee(0).set_chain_id(
-                    state
-                        .chain_id()
-                        .ok_or_else(|| anyhow!("missing chain_id"))?,
-                );
+                    Transaction::build_with_root(state.note_commitment_tree().root());
+                tx_builder
+                    .set_fee(0)
+                    .set_chain_id(
+                        state
+                            .chain_id()
+                            .ok_or_else(|| anyhow!("missing chain_id"))?,
+                    )
+                    .set_proof_system(state.proof_system())
+                    .set_expiry_height(state.default_expiry_height()?);
 
                 for note in group {
                     tx_builder.add_spend(
@@ -5,7 +5,7 @@ use futures::TryStreamExt;
 use penumbra_proto::{stake::Validator as ProtoValidator, Message};
 use penumbra_stake::{
     action::ValidatorDefinition, validator, validator::Validator, FundingStream, FundingStreams,
-    IdentityKey,
+    IdentityKey, Recipient,
 };
 use rand_core::OsRng;
 use structopt::StructOpt;
@@ -127,7 +127,7 @@ impl ValidatorCmd {
                     website: String::new(),
                     description: String::new(),
                     funding_streams: FundingStreams::try_from(vec![FundingStream {
-                        address,
+                        recipient: Recipient::Address(address),
                         rate_bps: 100,
                     }])?,
                     sequence_number: 0,
@@ -168,6 +168,7 @@ impl ValidatorCmd {
                     .validator_info(ValidatorInfoRequest {
                         show_inactive: true,
                         chain_id: state.chain_id().unwrap_or_default(),
+                        height: 0,
                     })
                     .await?
                     .into_inner()
@@ -31,10 +31,11 @@ pub async fn chain_params(opt: &Opt, state: &mut ClientStateFile) -> Result<()>
     let params = client
         .chain_params(tonic::Request::new(ChainParamsRequest {
             chain_id: state.chain_id().unwrap_or_default(),
+            height: 0,
         }))
         .await?
         .into_inner()
-        .into();
+        .try_into()?;
 
     tracing::info!(?params, "saving chain params");
 
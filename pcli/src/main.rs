@@ -40,6 +40,9 @@ pub struct Opt {
     /// The location of the wallet file [default: platform appdata directory]
     #[structopt(short, long)]
     pub wallet_location: Option<String>,
+    /// The directory holding the Groth16 spend and output parameters produced by a setup ceremony, needed to create Groth16 proofs.
+    #[structopt(long)]
+    pub groth16_parameters: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -52,6 +55,10 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     let opt = Opt::from_args();
 
+    if let Some(dir) = &opt.groth16_parameters {
+        penumbra_crypto::proofs::groth16::load_parameters(dir)?;
+    }
+
     let project_dir =
         ProjectDirs::from("zone", "penumbra", "pcli").expect("can access penumbra project dir");
     // Currently we use just the data directory. Create it if it is missing.
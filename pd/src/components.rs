@@ -1,12 +1,20 @@
 mod component;
 
 pub mod app;
+pub mod community_pool;
+pub mod dex;
+pub mod flow_encryption;
+pub mod governance;
 pub mod ibc;
 pub mod shielded_pool;
 pub mod staking;
 
 pub use self::ibc::IBCComponent;
 pub use app::App;
+pub use community_pool::CommunityPool;
 pub use component::Component;
+pub use dex::Dex;
+pub use flow_encryption::FlowEncryption;
+pub use governance::Governance;
 pub use shielded_pool::ShieldedPool;
 pub use staking::Staking;
@@ -12,7 +12,9 @@ use tracing::instrument;
 
 use crate::{genesis, Overlay, OverlayExt, Storage};
 
-use super::{Component, IBCComponent, ShieldedPool, Staking};
+use super::{
+    CommunityPool, Component, Dex, FlowEncryption, Governance, IBCComponent, ShieldedPool, Staking,
+};
 
 /// The Penumbra application, written as a bundle of [`Component`]s.
 ///
@@ -24,6 +26,10 @@ pub struct App {
     shielded_pool: ShieldedPool,
     ibc: IBCComponent,
     staking: Staking,
+    governance: Governance,
+    community_pool: CommunityPool,
+    dex: Dex,
+    flow_encryption: FlowEncryption,
 }
 
 impl App {
@@ -40,6 +46,10 @@ impl App {
         // Now re-instantiate all of the components:
         self.staking = Staking::new(self.overlay.clone()).await;
         self.ibc = IBCComponent::new(self.overlay.clone()).await;
+        self.governance = Governance::new(self.overlay.clone()).await;
+        self.community_pool = CommunityPool::new(self.overlay.clone()).await;
+        self.dex = Dex::new(self.overlay.clone()).await;
+        self.flow_encryption = FlowEncryption::new(self.overlay.clone()).await;
         self.shielded_pool = ShieldedPool::new(self.overlay.clone()).await;
 
         Ok((root_hash, version))
@@ -57,6 +67,10 @@ impl Component for App {
     async fn new(overlay: Overlay) -> Self {
         let staking = Staking::new(overlay.clone()).await;
         let ibc = IBCComponent::new(overlay.clone()).await;
+        let governance = Governance::new(overlay.clone()).await;
+        let community_pool = CommunityPool::new(overlay.clone()).await;
+        let dex = Dex::new(overlay.clone()).await;
+        let flow_encryption = FlowEncryption::new(overlay.clone()).await;
         let shielded_pool = ShieldedPool::new(overlay.clone()).await;
 
         Self {
@@ -64,6 +78,10 @@ impl Component for App {
             shielded_pool,
             staking,
             ibc,
+            governance,
+            community_pool,
+            dex,
+            flow_encryption,
         }
     }
 
@@ -81,6 +99,10 @@ impl Component for App {
 
         self.staking.init_chain(app_state).await;
         self.ibc.init_chain(app_state).await;
+        self.governance.init_chain(app_state).await;
+        self.community_pool.init_chain(app_state).await;
+        self.dex.init_chain(app_state).await;
+        self.flow_encryption.init_chain(app_state).await;
 
         // Shielded pool always executes last.
         self.shielded_pool.init_chain(app_state).await;
@@ -99,6 +121,10 @@ impl Component for App {
 
         self.staking.begin_block(begin_block).await;
         self.ibc.begin_block(begin_block).await;
+        self.governance.begin_block(begin_block).await;
+        self.community_pool.begin_block(begin_block).await;
+        self.dex.begin_block(begin_block).await;
+        self.flow_encryption.begin_block(begin_block).await;
         // Shielded pool always executes last.
         self.shielded_pool.begin_block(begin_block).await;
     }
@@ -107,14 +133,55 @@ impl Component for App {
     fn check_tx_stateless(tx: &Transaction) -> Result<()> {
         Staking::check_tx_stateless(tx)?;
         IBCComponent::check_tx_stateless(tx)?;
+        Governance::check_tx_stateless(tx)?;
+        CommunityPool::check_tx_stateless(tx)?;
+        Dex::check_tx_stateless(tx)?;
+        FlowEncryption::check_tx_stateless(tx)?;
         ShieldedPool::check_tx_stateless(tx)?;
         Ok(())
     }
 
     #[instrument(skip(self, tx))]
     async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        // Check that the transaction was built for this chain, so that it
+        // can't be replayed across chains...
+        let chain_id = self.overlay.get_chain_id().await?;
+        if tx.transaction_body.chain_id != chain_id {
+            return Err(anyhow!(
+                "transaction was built for chain {}, but this is chain {}",
+                tx.transaction_body.chain_id,
+                chain_id
+            ));
+        }
+
+        // ... and that it hasn't expired. An expiry height of 0 means the
+        // transaction never expires.
+        let expiry_height = tx.transaction_body.expiry_height as u64;
+        let block_height = self.overlay.get_block_height().await?;
+        if expiry_height != 0 && expiry_height < block_height {
+            return Err(anyhow!(
+                "transaction expired at height {}, but the current height is {}",
+                expiry_height,
+                block_height
+            ));
+        }
+
+        // ... and that it pays at least the minimum fee.
+        let min_fee = self.overlay.get_chain_params().await?.min_fee;
+        if tx.transaction_body.fee.0 < min_fee {
+            return Err(anyhow!(
+                "transaction fee {} is less than the minimum fee {}",
+                tx.transaction_body.fee.0,
+                min_fee
+            ));
+        }
+
         self.staking.check_tx_stateful(tx).await?;
         self.ibc.check_tx_stateful(tx).await?;
+        self.governance.check_tx_stateful(tx).await?;
+        self.community_pool.check_tx_stateful(tx).await?;
+        self.dex.check_tx_stateful(tx).await?;
+        self.flow_encryption.check_tx_stateful(tx).await?;
 
         // Shielded pool always executes last.
         self.shielded_pool.check_tx_stateful(tx).await?;
@@ -125,6 +192,10 @@ impl Component for App {
     async fn execute_tx(&mut self, tx: &Transaction) {
         self.staking.execute_tx(tx).await;
         self.ibc.execute_tx(tx).await;
+        self.governance.execute_tx(tx).await;
+        self.community_pool.execute_tx(tx).await;
+        self.dex.execute_tx(tx).await;
+        self.flow_encryption.execute_tx(tx).await;
         // Shielded pool always executes last.
         self.shielded_pool.execute_tx(tx).await;
     }
@@ -133,10 +204,25 @@ impl Component for App {
     async fn end_block(&mut self, end_block: &abci::request::EndBlock) {
         self.staking.end_block(end_block).await;
         self.ibc.end_block(end_block).await;
+        self.governance.end_block(end_block).await;
+        self.community_pool.end_block(end_block).await;
+        self.dex.end_block(end_block).await;
+        self.flow_encryption.end_block(end_block).await;
 
         // Shielded pool always executes last.
         self.shielded_pool.end_block(end_block).await;
     }
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        let mut events = self.staking.take_events();
+        events.extend(self.ibc.take_events());
+        events.extend(self.governance.take_events());
+        events.extend(self.community_pool.take_events());
+        events.extend(self.dex.take_events());
+        events.extend(self.flow_encryption.take_events());
+        events.extend(self.shielded_pool.take_events());
+        events
+    }
 }
 
 /// This trait provides read and write access to common parts of the Penumbra
@@ -237,3 +323,128 @@ pub trait View: OverlayExt {
 }
 
 impl<T: OverlayExt> View for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use penumbra_crypto::merkle;
+    use penumbra_transaction::{Fee, TransactionBody};
+    use tempfile::{tempdir, TempDir};
+
+    const CHAIN_ID: &str = "penumbra-test";
+
+    // an app at height 10, whose empty note commitment tree root is a valid anchor.
+    async fn app(chain_params: ChainParams) -> (TempDir, App) {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("app-testing.db"))
+            .await
+            .unwrap();
+        let overlay = storage.overlay().await.unwrap();
+
+        overlay.put_chain_params(chain_params).await;
+        overlay.put_block_height(10).await;
+        overlay
+            .put_proto(
+                format!(
+                    "shielded_pool/valid_anchors/{}",
+                    merkle::NoteCommitmentTree::new().root()
+                )
+                .into(),
+                0u64,
+            )
+            .await;
+
+        (dir, App::new(overlay).await)
+    }
+
+    // a transaction with no actions.
+    fn empty_tx(chain_id: &str, expiry_height: u32, fee: u64) -> Transaction {
+        Transaction {
+            transaction_body: TransactionBody {
+                actions: vec![],
+                merkle_root: merkle::NoteCommitmentTree::new().root(),
+                expiry_height,
+                chain_id: chain_id.to_string(),
+                fee: Fee(fee),
+            },
+            binding_sig: [0u8; 64].into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_tx_chain_id() {
+        let (_dir, app) = app(ChainParams {
+            chain_id: CHAIN_ID.to_string(),
+            ..Default::default()
+        })
+        .await;
+
+        app.check_tx_stateful(&empty_tx(CHAIN_ID, 0, 0))
+            .await
+            .unwrap();
+
+        // transactions built for another chain, or without a chain ID, are rejected.
+        for chain_id in ["penumbra-other", ""] {
+            let error = app
+                .check_tx_stateful(&empty_tx(chain_id, 0, 0))
+                .await
+                .unwrap_err();
+            assert!(
+                error.to_string().contains("was built for chain"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_tx_expiry_height() {
+        let (_dir, app) = app(ChainParams {
+            chain_id: CHAIN_ID.to_string(),
+            ..Default::default()
+        })
+        .await;
+
+        // a transaction can be included up to and including its expiry height, and an expiry
+        // height of 0 never expires.
+        for expiry_height in [0, 10, 11] {
+            app.check_tx_stateful(&empty_tx(CHAIN_ID, expiry_height, 0))
+                .await
+                .unwrap();
+        }
+
+        let error = app
+            .check_tx_stateful(&empty_tx(CHAIN_ID, 9, 0))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "transaction expired at height 9, but the current height is 10"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_tx_min_fee() {
+        let (_dir, app) = app(ChainParams {
+            chain_id: CHAIN_ID.to_string(),
+            min_fee: 100,
+            ..Default::default()
+        })
+        .await;
+
+        for fee in [100, 101] {
+            app.check_tx_stateful(&empty_tx(CHAIN_ID, 0, fee))
+                .await
+                .unwrap();
+        }
+
+        let error = app
+            .check_tx_stateful(&empty_tx(CHAIN_ID, 0, 99))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "transaction fee 99 is less than the minimum fee 100"
+        );
+    }
+}
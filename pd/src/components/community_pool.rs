@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use penumbra_crypto::{asset, Value};
+use penumbra_governance::CommunityPoolPayouts;
+use penumbra_transaction::Transaction;
+use tendermint::abci;
+use tracing::instrument;
+
+use super::{shielded_pool::View as _, Component};
+use crate::{genesis, Overlay, OverlayExt};
+
+mod event;
+
+/// The community pool, holding funds which can only be spent by governance.
+///
+/// Funds enter the pool through deposits and validators' funding streams, and
+/// leave it through passed community pool spend proposals.  Funds held by the
+/// pool are not counted in the token supply: deposits burn them, and spends
+/// mint them again.
+pub struct CommunityPool {
+    overlay: Overlay,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
+}
+
+#[async_trait]
+impl Component for CommunityPool {
+    #[instrument(name = "community_pool", skip(overlay))]
+    async fn new(overlay: Overlay) -> Self {
+        Self {
+            overlay,
+            events: Vec::new(),
+        }
+    }
+
+    #[instrument(name = "community_pool", skip(self, _app_state))]
+    async fn init_chain(&mut self, _app_state: &genesis::AppState) {}
+
+    #[instrument(name = "community_pool", skip(self, _begin_block))]
+    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+
+    #[instrument(name = "community_pool", skip(tx))]
+    fn check_tx_stateless(tx: &Transaction) -> Result<()> {
+        for deposit in tx.community_pool_deposits() {
+            if deposit.value.amount == 0 {
+                return Err(anyhow!("community pool deposit is empty"));
+            }
+            // Deposits are burned from the token supply, which is updated by
+            // a signed amount.
+            if i64::try_from(deposit.value.amount).is_err() {
+                return Err(anyhow!(
+                    "community pool deposit of {} is too large",
+                    deposit.value.amount
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(name = "community_pool", skip(self, _tx))]
+    async fn check_tx_stateful(&self, _tx: &Transaction) -> Result<()> {
+        Ok(())
+    }
+
+    #[instrument(name = "community_pool", skip(self, tx))]
+    async fn execute_tx(&mut self, tx: &Transaction) {
+        for deposit in tx.community_pool_deposits() {
+            let burned = i64::try_from(deposit.value.amount)
+                .expect("deposits were checked to fit in an i64 in check_tx_stateless");
+            self.overlay
+                .update_token_supply(&deposit.value.asset_id, -burned)
+                .await
+                .unwrap();
+            self.overlay
+                .community_pool_deposit(deposit.value)
+                .await
+                .unwrap();
+            self.events.push(event::deposit(&deposit.value));
+        }
+    }
+
+    #[instrument(name = "community_pool", skip(self, _end_block))]
+    async fn end_block(&mut self, _end_block: &abci::request::EndBlock) {}
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Extension trait providing read/write access to community pool data.
+#[async_trait]
+pub trait View: OverlayExt {
+    /// The amount of the given asset held by the community pool.
+    async fn community_pool_balance(&self, asset_id: &asset::Id) -> Result<u64> {
+        Ok(self
+            .get_proto(format!("community_pool/balance/{}", asset_id).into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_community_pool_balance(&self, asset_id: &asset::Id, amount: u64) {
+        self.put_proto(
+            format!("community_pool/balance/{}", asset_id).into(),
+            amount,
+        )
+        .await
+    }
+
+    #[instrument(skip(self))]
+    async fn community_pool_deposit(&self, value: Value) -> Result<()> {
+        let balance = self.community_pool_balance(&value.asset_id).await?;
+        let new_balance = balance.checked_add(value.amount).ok_or_else(|| {
+            anyhow!(
+                "overflow depositing {} into community pool balance {}",
+                value.amount,
+                balance
+            )
+        })?;
+        self.set_community_pool_balance(&value.asset_id, new_balance)
+            .await;
+        Ok(())
+    }
+
+    /// Withdraws funds from the community pool, erroring if its balance is insufficient.
+    #[instrument(skip(self))]
+    async fn community_pool_withdraw(&self, value: Value) -> Result<()> {
+        let balance = self.community_pool_balance(&value.asset_id).await?;
+        let new_balance = balance.checked_sub(value.amount).ok_or_else(|| {
+            anyhow!(
+                "community pool balance {} of asset {} is less than {}",
+                balance,
+                value.asset_id,
+                value.amount
+            )
+        })?;
+        self.set_community_pool_balance(&value.asset_id, new_balance)
+            .await;
+        Ok(())
+    }
+
+    async fn community_pool_payouts(&self, height: u64) -> Result<Option<CommunityPoolPayouts>> {
+        self.get_domain(format!("community_pool/payouts/{}", height).into())
+            .await
+    }
+
+    async fn set_community_pool_payouts(&self, height: u64, payouts: CommunityPoolPayouts) {
+        self.put_domain(format!("community_pool/payouts/{}", height).into(), payouts)
+            .await
+    }
+
+    async fn delete_community_pool_payouts(&self, height: u64) {
+        self.delete(format!("community_pool/payouts/{}", height).into())
+            .await
+    }
+}
+
+impl<T: OverlayExt + Send + Sync> View for T {}
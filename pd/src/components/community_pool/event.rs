@@ -0,0 +1,13 @@
+use penumbra_crypto::Value;
+use tendermint::abci::{Event, EventAttributeIndexExt};
+
+/// Funds were deposited into the community pool.
+pub fn deposit(value: &Value) -> Event {
+    Event::new(
+        "community_pool_deposit",
+        vec![
+            ("asset_id", value.asset_id.to_string()).index(),
+            ("amount", value.amount.to_string()).no_index(),
+        ],
+    )
+}
@@ -124,4 +124,13 @@ pub trait Component: Sized {
     /// This method should only be called after [`Component::begin_block`].
     /// No methods should be called following this method.
     async fn end_block(&mut self, end_block: &abci::request::EndBlock);
+
+    /// Takes the ABCI [`Event`](abci::Event)s recorded by this component since
+    /// the last call, so that they can be returned to Tendermint in the
+    /// response to the current ABCI request.
+    ///
+    /// Events let indexers and other external observers learn about state
+    /// changes (e.g., spent nullifiers or validator state transitions)
+    /// without having to inspect the chain state directly.
+    fn take_events(&mut self) -> Vec<abci::Event>;
 }
@@ -0,0 +1,539 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use penumbra_dex::{
+    position::{self, State, FEE_DENOMINATOR},
+    BatchSwapOutputData, LpNft, SealedBatch, TradingPair,
+};
+use penumbra_flow_encryption::{Ciphertext, Flows, LIMB_BITS, MAX_LIMB_BITS};
+use penumbra_proto::dex as pb;
+use penumbra_transaction::Transaction;
+use tendermint::abci;
+use tracing::instrument;
+
+use super::{app::View as _, flow_encryption::View as _, shielded_pool::View as _, Component};
+use crate::{genesis, Overlay, OverlayExt};
+
+mod event;
+
+/// The largest number of swaps on a trading pair in a block, so that the
+/// limbs of the batch totals can still be decrypted.
+const MAX_BATCH_SWAPS: usize = 1 << (MAX_LIMB_BITS - LIMB_BITS);
+
+/// ZSwap, the decentralized exchange.
+///
+/// Swaps burn their inputs and are collected into a batch per trading pair.
+/// The amounts of each swap are encrypted to the flow encryption key, so only
+/// the batch totals are revealed, once the validators have decrypted them,
+/// after which the batch is cleared at a single price.  Each swap creates a
+/// swap NFT, which is spent in a later transaction to privately claim the
+/// swap's share of the batch outputs.
+///
+/// Liquidity providers' positions are recorded publicly, and each open
+/// position on a trading pair trades against the pair's batch at its clearing
+/// price.  Ownership of a position is represented by an LP NFT for its
+/// current state.
+pub struct Dex {
+    overlay: Overlay,
+    /// The number of swaps on each trading pair in this block, and their
+    /// encrypted total inputs of assets 1 and 2.
+    batches: BTreeMap<TradingPair, (usize, Ciphertext, Ciphertext)>,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
+}
+
+#[async_trait]
+impl Component for Dex {
+    #[instrument(name = "dex", skip(overlay))]
+    async fn new(overlay: Overlay) -> Self {
+        Self {
+            overlay,
+            batches: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    #[instrument(name = "dex", skip(self, _app_state))]
+    async fn init_chain(&mut self, _app_state: &genesis::AppState) {}
+
+    #[instrument(name = "dex", skip(self, _begin_block))]
+    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+
+    #[instrument(name = "dex", skip(tx))]
+    fn check_tx_stateless(tx: &Transaction) -> Result<()> {
+        // A swap NFT must be added to the NCT in the block of its batch, so
+        // it can't be held in quarantine with an undelegation.
+        if tx.swaps().next().is_some() && tx.undelegations().next().is_some() {
+            return Err(anyhow!("swaps cannot be made alongside undelegations"));
+        }
+
+        for open in tx.position_opens() {
+            let position = &open.position;
+            if position.lower_price == 0 || position.lower_price >= position.upper_price {
+                return Err(anyhow!(
+                    "position price range [{}, {}] is invalid",
+                    position.lower_price,
+                    position.upper_price
+                ));
+            }
+            if position.fee >= FEE_DENOMINATOR {
+                return Err(anyhow!("position fee {} is too large", position.fee));
+            }
+            if open.initial_reserves.r1 == 0 && open.initial_reserves.r2 == 0 {
+                return Err(anyhow!("position has no initial reserves"));
+            }
+        }
+
+        // Each position can only change state once per transaction, since
+        // the state transitions are checked against the existing state.
+        let mut position_ids = BTreeSet::new();
+        for id in tx
+            .position_opens()
+            .map(|open| open.position.id())
+            .chain(tx.position_closes().map(|close| close.position_id))
+            .chain(
+                tx.position_withdrawals()
+                    .map(|withdraw| withdraw.position_id),
+            )
+        {
+            if !position_ids.insert(id) {
+                return Err(anyhow!("position {} is used more than once", id));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "dex", skip(self, tx))]
+    async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        if tx.swaps().next().is_some() {
+            let (_, output) = self
+                .overlay
+                .latest_dkg_output()
+                .await?
+                .ok_or_else(|| anyhow!("no flow encryption key is available for swaps"))?;
+
+            let mut counts = BTreeMap::new();
+            for swap in tx.swaps() {
+                if swap.body.encryption_key != output.encryption_key {
+                    return Err(anyhow!(
+                        "swap is not encrypted to the current flow encryption key"
+                    ));
+                }
+                *counts.entry(swap.body.trading_pair).or_insert(0) += 1;
+            }
+            for (trading_pair, count) in counts {
+                let batched = self
+                    .batches
+                    .get(&trading_pair)
+                    .map(|(batched, _, _)| *batched)
+                    .unwrap_or_default();
+                if batched + count > MAX_BATCH_SWAPS {
+                    return Err(anyhow!(
+                        "batch on {} is limited to {} swaps per block",
+                        trading_pair,
+                        MAX_BATCH_SWAPS
+                    ));
+                }
+            }
+        }
+
+        for claim in tx.swap_claims() {
+            let output_data = &claim.body.output_data;
+
+            // The claimed batch must be the one recorded by the chain...
+            let recorded = self
+                .overlay
+                .batch_swap_output_data(output_data.height, &output_data.trading_pair)
+                .await?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "no batch on {} was cleared at height {}",
+                        output_data.trading_pair,
+                        output_data.height
+                    )
+                })?;
+            if recorded != *output_data {
+                return Err(anyhow!(
+                    "claimed batch output data {:?} does not match recorded output data {:?}",
+                    output_data,
+                    recorded
+                ));
+            }
+
+            // ... and the swap NFT must have been created in that batch's
+            // block, which the proof checks against the NFT's position.
+            let nct_block = self
+                .overlay
+                .nct_block(output_data.height)
+                .await?
+                .ok_or_else(|| anyhow!("no block at height {}", output_data.height))?;
+            if claim.body.nft_block != nct_block {
+                return Err(anyhow!(
+                    "swap claim against batch at height {} does not spend a swap NFT from its block",
+                    output_data.height
+                ));
+            }
+        }
+
+        for open in tx.position_opens() {
+            let id = open.position.id();
+            if self.overlay.position_by_id(&id).await?.is_some() {
+                return Err(anyhow!("position {} already exists", id));
+            }
+        }
+
+        for close in tx.position_closes() {
+            let metadata = self
+                .overlay
+                .position_by_id(&close.position_id)
+                .await?
+                .ok_or_else(|| anyhow!("position {} does not exist", close.position_id))?;
+            if metadata.state != State::Opened {
+                return Err(anyhow!(
+                    "position {} is {}, not opened",
+                    close.position_id,
+                    metadata.state
+                ));
+            }
+        }
+
+        for withdraw in tx.position_withdrawals() {
+            let metadata = self
+                .overlay
+                .position_by_id(&withdraw.position_id)
+                .await?
+                .ok_or_else(|| anyhow!("position {} does not exist", withdraw.position_id))?;
+            if metadata.state != State::Closed {
+                return Err(anyhow!(
+                    "position {} is {}, not closed",
+                    withdraw.position_id,
+                    metadata.state
+                ));
+            }
+            // The withdrawal's value commitment is computed from the claimed
+            // reserves, so they must be exactly those recorded by the chain.
+            if withdraw.trading_pair != metadata.position.trading_pair
+                || withdraw.reserves != metadata.reserves
+            {
+                return Err(anyhow!(
+                    "withdrawal of {:?} on {} does not match position reserves {:?} on {}",
+                    withdraw.reserves,
+                    withdraw.trading_pair,
+                    metadata.reserves,
+                    metadata.position.trading_pair
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "dex", skip(self, tx))]
+    async fn execute_tx(&mut self, tx: &Transaction) {
+        for swap in tx.swaps() {
+            // The swap's inputs leave the token supply when its batch is
+            // cleared, once the batch totals are known.
+            let batch = self.batches.entry(swap.body.trading_pair).or_insert((
+                0,
+                Ciphertext::default(),
+                Ciphertext::default(),
+            ));
+            batch.0 += 1;
+            batch.1 = batch.1 + swap.body.encrypted_delta_1;
+            batch.2 = batch.2 + swap.body.encrypted_delta_2;
+
+            self.events.push(event::swap(&swap.body.trading_pair));
+        }
+
+        for claim in tx.swap_claims() {
+            self.events.push(event::swap_claim(&claim.body.output_data));
+        }
+
+        for open in tx.position_opens() {
+            let id = open.position.id();
+
+            // The position's reserves leave the token supply until they're withdrawn.
+            for reserve in open.reserves() {
+                if reserve.amount > 0 {
+                    self.overlay
+                        .update_token_supply(&reserve.asset_id, -(reserve.amount as i64))
+                        .await
+                        .unwrap();
+                }
+            }
+            self.mint_lp_nft(LpNft::new(id, State::Opened)).await;
+
+            let trading_pair = open.position.trading_pair;
+            let mut open_positions = self.overlay.open_positions(&trading_pair).await.unwrap();
+            open_positions.ids.push(id);
+            self.overlay
+                .set_open_positions(&trading_pair, open_positions)
+                .await;
+            self.overlay
+                .set_position(position::Metadata {
+                    position: open.position.clone(),
+                    state: State::Opened,
+                    reserves: open.initial_reserves,
+                })
+                .await;
+
+            self.events.push(event::position_open(open));
+        }
+
+        for close in tx.position_closes() {
+            let mut metadata = self
+                .overlay
+                .position_by_id(&close.position_id)
+                .await
+                .unwrap()
+                .expect("position was checked to exist");
+
+            self.burn_lp_nft(LpNft::new(close.position_id, State::Opened))
+                .await;
+            self.mint_lp_nft(LpNft::new(close.position_id, State::Closed))
+                .await;
+
+            let trading_pair = metadata.position.trading_pair;
+            let mut open_positions = self.overlay.open_positions(&trading_pair).await.unwrap();
+            open_positions.ids.retain(|id| *id != close.position_id);
+            self.overlay
+                .set_open_positions(&trading_pair, open_positions)
+                .await;
+            metadata.state = State::Closed;
+            self.overlay.set_position(metadata).await;
+
+            self.events.push(event::position_close(close));
+        }
+
+        for withdraw in tx.position_withdrawals() {
+            let mut metadata = self
+                .overlay
+                .position_by_id(&withdraw.position_id)
+                .await
+                .unwrap()
+                .expect("position was checked to exist");
+
+            self.burn_lp_nft(LpNft::new(withdraw.position_id, State::Closed))
+                .await;
+            self.mint_lp_nft(LpNft::new(withdraw.position_id, State::Withdrawn))
+                .await;
+
+            // The reserves return to the token supply as they're withdrawn.
+            for reserve in withdraw.reserves() {
+                if reserve.amount > 0 {
+                    self.overlay
+                        .update_token_supply(&reserve.asset_id, reserve.amount as i64)
+                        .await
+                        .unwrap();
+                }
+            }
+
+            metadata.state = State::Withdrawn;
+            metadata.reserves = Default::default();
+            self.overlay.set_position(metadata).await;
+
+            self.events.push(event::position_withdraw(withdraw));
+        }
+    }
+
+    #[instrument(name = "dex", skip(self, end_block))]
+    async fn end_block(&mut self, end_block: &abci::request::EndBlock) {
+        let height = end_block.height as u64;
+
+        // Queue this block's batch totals for decryption...
+        let batches = std::mem::take(&mut self.batches);
+        if !batches.is_empty() {
+            let (epoch_index, _) = self
+                .overlay
+                .latest_dkg_output()
+                .await
+                .unwrap()
+                .expect("swaps were checked to have a flow encryption key");
+            let mut trading_pairs = Vec::new();
+            let mut ciphertexts = Vec::new();
+            for (trading_pair, (_, delta_1, delta_2)) in batches {
+                trading_pairs.push(trading_pair);
+                ciphertexts.extend([delta_1, delta_2]);
+            }
+            self.overlay
+                .queue_flows(
+                    height,
+                    Flows {
+                        epoch_index,
+                        ciphertexts,
+                    },
+                )
+                .await
+                .unwrap();
+
+            let mut sealed = self.overlay.sealed_batches().await.unwrap();
+            sealed.push(SealedBatch {
+                height,
+                trading_pairs,
+            });
+            self.overlay.set_sealed_batches(sealed).await;
+        }
+
+        // ... and clear the batches whose totals have been decrypted.
+        let mut still_sealed = Vec::new();
+        for batch in self.overlay.sealed_batches().await.unwrap() {
+            match self.overlay.flow_plaintexts(batch.height).await.unwrap() {
+                Some(amounts) => self.clear_batch(&batch, &amounts).await.unwrap(),
+                None => still_sealed.push(batch),
+            }
+        }
+        self.overlay.set_sealed_batches(still_sealed).await;
+    }
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Dex {
+    /// Clears the batches of a block, whose total inputs of assets 1 and 2
+    /// on each trading pair are `amounts`, in order.
+    async fn clear_batch(&mut self, batch: &SealedBatch, amounts: &[u64]) -> Result<()> {
+        for (trading_pair, totals) in batch.trading_pairs.iter().zip(amounts.chunks(2)) {
+            let (delta_1, delta_2) = (totals[0], totals[1]);
+
+            let mut positions = Vec::new();
+            for id in self.overlay.open_positions(trading_pair).await?.ids {
+                positions.push(
+                    self.overlay
+                        .position_by_id(&id)
+                        .await?
+                        .ok_or_else(|| anyhow!("open position {} does not exist", id))?,
+                );
+            }
+
+            let output_data = BatchSwapOutputData::clear_with_positions(
+                batch.height,
+                *trading_pair,
+                delta_1,
+                delta_2,
+                &mut positions,
+            );
+            tracing::debug!(?output_data, "cleared batch swap");
+
+            // The swap inputs leave the token supply, and the batch outputs
+            // and unfilled inputs return to it, to be claimed.  Claims round
+            // down, so the recorded supply can slightly exceed the claimed
+            // amounts.
+            let change_1 = (output_data.lambda_1 + output_data.unfilled_1) as i64 - delta_1 as i64;
+            let change_2 = (output_data.lambda_2 + output_data.unfilled_2) as i64 - delta_2 as i64;
+            self.overlay
+                .update_token_supply(&trading_pair.asset_1(), change_1)
+                .await?;
+            self.overlay
+                .update_token_supply(&trading_pair.asset_2(), change_2)
+                .await?;
+
+            for metadata in positions {
+                self.overlay.set_position(metadata).await;
+            }
+            self.overlay.set_batch_swap_output_data(output_data).await;
+            self.events.push(event::batch_swap(&output_data));
+        }
+
+        Ok(())
+    }
+
+    /// Adds an LP NFT to the token supply, registering its denom if needed.
+    async fn mint_lp_nft(&self, lp_nft: LpNft) {
+        self.overlay.register_denom(&lp_nft.denom()).await.unwrap();
+        self.overlay
+            .update_token_supply(&lp_nft.asset_id(), 1)
+            .await
+            .unwrap();
+    }
+
+    /// Removes an LP NFT from the token supply.
+    async fn burn_lp_nft(&self, lp_nft: LpNft) {
+        self.overlay
+            .update_token_supply(&lp_nft.asset_id(), -1)
+            .await
+            .unwrap();
+    }
+}
+
+/// Extension trait providing read/write access to DEX data.
+#[async_trait]
+pub trait View: OverlayExt {
+    /// The result of clearing the batch of swaps on the given pair at the given height.
+    async fn batch_swap_output_data(
+        &self,
+        height: u64,
+        trading_pair: &TradingPair,
+    ) -> Result<Option<BatchSwapOutputData>> {
+        self.get_domain(format!("dex/output/{}/{}", height, trading_pair).into())
+            .await
+    }
+
+    async fn set_batch_swap_output_data(&self, output_data: BatchSwapOutputData) {
+        self.put_domain(
+            format!(
+                "dex/output/{}/{}",
+                output_data.height, output_data.trading_pair
+            )
+            .into(),
+            output_data,
+        )
+        .await
+    }
+
+    /// The batches whose totals are still awaiting decryption.
+    async fn sealed_batches(&self) -> Result<Vec<SealedBatch>> {
+        self.get_proto::<pb::SealedBatches>("dex/sealed_batches".into())
+            .await?
+            .map(|sealed| {
+                sealed
+                    .batches
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_>>()
+            })
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    async fn set_sealed_batches(&self, batches: Vec<SealedBatch>) {
+        self.put_proto(
+            "dex/sealed_batches".into(),
+            pb::SealedBatches {
+                batches: batches.into_iter().map(Into::into).collect(),
+            },
+        )
+        .await
+    }
+
+    /// The position with the given ID, with its current state and reserves.
+    async fn position_by_id(&self, id: &position::Id) -> Result<Option<position::Metadata>> {
+        self.get_domain(format!("dex/position/{}", id).into()).await
+    }
+
+    async fn set_position(&self, metadata: position::Metadata) {
+        self.put_domain(
+            format!("dex/position/{}", metadata.position.id()).into(),
+            metadata,
+        )
+        .await
+    }
+
+    /// The IDs of the open positions on the given trading pair.
+    async fn open_positions(&self, trading_pair: &TradingPair) -> Result<position::Ids> {
+        Ok(self
+            .get_domain(format!("dex/open_positions/{}", trading_pair).into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_open_positions(&self, trading_pair: &TradingPair, ids: position::Ids) {
+        self.put_domain(format!("dex/open_positions/{}", trading_pair).into(), ids)
+            .await
+    }
+}
+
+impl<T: OverlayExt + Send + Sync> View for T {}
@@ -0,0 +1,77 @@
+use penumbra_dex::{
+    action::{PositionClose, PositionOpen, PositionWithdraw},
+    BatchSwapOutputData, TradingPair,
+};
+use tendermint::abci::{Event, EventAttributeIndexExt};
+
+/// A swap was added to the current block's batch for its trading pair.
+pub fn swap(trading_pair: &TradingPair) -> Event {
+    Event::new(
+        "swap",
+        vec![("trading_pair", trading_pair.to_string()).index()],
+    )
+}
+
+/// The outputs of a swap were claimed.
+pub fn swap_claim(output_data: &BatchSwapOutputData) -> Event {
+    Event::new(
+        "swap_claim",
+        vec![
+            ("trading_pair", output_data.trading_pair.to_string()).index(),
+            ("height", output_data.height.to_string()).index(),
+        ],
+    )
+}
+
+/// A batch of swaps on a trading pair was cleared.
+pub fn batch_swap(output_data: &BatchSwapOutputData) -> Event {
+    Event::new(
+        "batch_swap",
+        vec![
+            ("trading_pair", output_data.trading_pair.to_string()).index(),
+            ("height", output_data.height.to_string()).index(),
+            ("delta_1", output_data.delta_1.to_string()).no_index(),
+            ("delta_2", output_data.delta_2.to_string()).no_index(),
+            ("lambda_1", output_data.lambda_1.to_string()).no_index(),
+            ("lambda_2", output_data.lambda_2.to_string()).no_index(),
+            ("unfilled_1", output_data.unfilled_1.to_string()).no_index(),
+            ("unfilled_2", output_data.unfilled_2.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A liquidity position was opened.
+pub fn position_open(open: &PositionOpen) -> Event {
+    Event::new(
+        "position_open",
+        vec![
+            ("position_id", open.position.id().to_string()).index(),
+            ("trading_pair", open.position.trading_pair.to_string()).index(),
+            ("lower_price", open.position.lower_price.to_string()).no_index(),
+            ("upper_price", open.position.upper_price.to_string()).no_index(),
+            ("fee", open.position.fee.to_string()).no_index(),
+            ("r1", open.initial_reserves.r1.to_string()).no_index(),
+            ("r2", open.initial_reserves.r2.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A liquidity position was closed.
+pub fn position_close(close: &PositionClose) -> Event {
+    Event::new(
+        "position_close",
+        vec![("position_id", close.position_id.to_string()).index()],
+    )
+}
+
+/// The reserves of a closed liquidity position were withdrawn.
+pub fn position_withdraw(withdraw: &PositionWithdraw) -> Event {
+    Event::new(
+        "position_withdraw",
+        vec![
+            ("position_id", withdraw.position_id.to_string()).index(),
+            ("r1", withdraw.reserves.r1.to_string()).no_index(),
+            ("r2", withdraw.reserves.r2.to_string()).no_index(),
+        ],
+    )
+}
@@ -0,0 +1,771 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use penumbra_flow_encryption::{
+    action::Message,
+    decrypt,
+    dkg::{self, Commitment, DealingSecret, EncryptedShare},
+    DecryptionShare, DecryptionTable, DkgOutput, DkgParticipants, Flows, Round, MAX_LIMB_BITS,
+};
+use penumbra_proto::{flow_encryption as pb, Protobuf};
+use penumbra_stake::validator;
+use penumbra_transaction::Transaction;
+use tendermint::abci;
+use tracing::instrument;
+
+use super::{app::View as _, staking::View as _, Component};
+use crate::{genesis, Overlay, OverlayExt};
+
+mod event;
+
+/// The table for recovering the limbs of decrypted flows, which is large
+/// enough for any flow with at most [`MAX_LIMB_BITS`] bits per limb.
+static DECRYPTION_TABLE: Lazy<DecryptionTable> = Lazy::new(|| DecryptionTable::new(MAX_LIMB_BITS));
+
+/// Flow encryption, whose key is generated by the validators for each epoch.
+///
+/// The DKG for an epoch runs among the validators active at its start, in
+/// three rounds of `dkg_round_blocks` blocks each (as of its start), with each round's messages
+/// submitted in transactions.  At the end of the last round, dealers shown by
+/// a complaint to have dealt an invalid share are disqualified, and the flow
+/// encryption key and the participants' public shares are recorded.
+///
+/// Other components queue flows encrypted to the latest key for decryption at
+/// the end of a block.  The validators who took part in that key's DKG each
+/// submit their shares of the decryption, and once enough of them have, the
+/// flows are decrypted and their plaintexts recorded.
+pub struct FlowEncryption {
+    overlay: Overlay,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
+}
+
+#[async_trait]
+impl Component for FlowEncryption {
+    #[instrument(name = "flow_encryption", skip(overlay))]
+    async fn new(overlay: Overlay) -> Self {
+        Self {
+            overlay,
+            events: Vec::new(),
+        }
+    }
+
+    #[instrument(name = "flow_encryption", skip(self, _app_state))]
+    async fn init_chain(&mut self, _app_state: &genesis::AppState) {
+        // The first epoch's DKG starts with the first block.
+        self.start_dkg(0, 1).await.unwrap();
+    }
+
+    #[instrument(name = "flow_encryption", skip(self, _begin_block))]
+    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+
+    #[instrument(name = "flow_encryption", skip(tx))]
+    fn check_tx_stateless(tx: &Transaction) -> Result<()> {
+        // Check that DKG messages are signed by the validator, and that each
+        // validator sends at most one per transaction.
+        let mut senders = BTreeSet::new();
+        for message in tx.dkg_messages() {
+            let body_bytes = message.body.encode_to_vec();
+            message
+                .body
+                .identity_key
+                .0
+                .verify(&body_bytes, &message.auth_sig)
+                .context("DKG message signature failed to verify")?;
+
+            if !senders.insert(message.body.identity_key.clone()) {
+                return Err(anyhow!(
+                    "validator {} sends more than one DKG message",
+                    message.body.identity_key
+                ));
+            }
+
+            if let Message::Commitment(commitment) = &message.body.message {
+                commitment
+                    .verify(&message.body.context())
+                    .context("DKG commitment proof of knowledge failed to verify")?;
+            }
+        }
+
+        // Likewise for flow decryptions.
+        let mut senders = BTreeSet::new();
+        for decryption in tx.flow_decryptions() {
+            let body_bytes = decryption.body.encode_to_vec();
+            decryption
+                .body
+                .identity_key
+                .0
+                .verify(&body_bytes, &decryption.auth_sig)
+                .context("flow decryption signature failed to verify")?;
+
+            if !senders.insert(decryption.body.identity_key.clone()) {
+                return Err(anyhow!(
+                    "validator {} sends more than one flow decryption",
+                    decryption.body.identity_key
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "flow_encryption", skip(self, tx))]
+    async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        for decryption in tx.flow_decryptions() {
+            let body = &decryption.body;
+            let flows = self
+                .overlay
+                .flows(body.height)
+                .await?
+                .ok_or_else(|| anyhow!("no flows were queued at height {}", body.height))?;
+            if self.overlay.flow_plaintexts(body.height).await?.is_some() {
+                return Err(anyhow!(
+                    "flows queued at height {} are already decrypted",
+                    body.height
+                ));
+            }
+
+            let index = self
+                .overlay
+                .dkg_participants(flows.epoch_index)
+                .await?
+                .and_then(|participants| participants.index_of(&body.identity_key))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "validator {} did not take part in the DKG for epoch {}",
+                        body.identity_key,
+                        flows.epoch_index
+                    )
+                })?;
+            if self
+                .overlay
+                .flow_decryption_shares(body.height, index)
+                .await?
+                .is_some()
+            {
+                return Err(anyhow!(
+                    "validator {} has already decrypted the flows queued at height {}",
+                    body.identity_key,
+                    body.height
+                ));
+            }
+
+            // Each share must be for the corresponding flow, and computed
+            // with the validator's key share.
+            let output = self
+                .overlay
+                .dkg_output(flows.epoch_index)
+                .await?
+                .ok_or_else(|| anyhow!("the DKG for epoch {} failed", flows.epoch_index))?;
+            let public_share = &output.public_shares[index as usize - 1];
+            if body.shares.len() != flows.ciphertexts.len() {
+                return Err(anyhow!(
+                    "flow decryption has {} shares, but {} flows were queued",
+                    body.shares.len(),
+                    flows.ciphertexts.len()
+                ));
+            }
+            for (share, ciphertext) in body.shares.iter().zip(flows.ciphertexts.iter()) {
+                if share.participant() != index {
+                    return Err(anyhow!(
+                        "decryption share is for participant {}, not {}",
+                        share.participant(),
+                        index
+                    ));
+                }
+                share.verify(ciphertext, public_share)?;
+            }
+        }
+
+        if tx.dkg_messages().next().is_none() {
+            return Ok(());
+        }
+
+        let height = self.overlay.get_block_height().await?;
+        let epoch = self.overlay.get_current_epoch().await?;
+
+        for message in tx.dkg_messages() {
+            let body = &message.body;
+            if body.epoch_index != epoch.index {
+                return Err(anyhow!(
+                    "DKG message for epoch {} sent in epoch {}",
+                    body.epoch_index,
+                    epoch.index
+                ));
+            }
+            let participants = self
+                .overlay
+                .dkg_participants(epoch.index)
+                .await?
+                .ok_or_else(|| anyhow!("no DKG is running in epoch {}", epoch.index))?;
+            let index = participants.index_of(&body.identity_key).ok_or_else(|| {
+                anyhow!(
+                    "validator {} is not participating in the DKG for epoch {}",
+                    body.identity_key,
+                    epoch.index
+                )
+            })?;
+
+            let round = participants.round_at(height);
+            let expected = match body.message {
+                Message::Commitment(_) => Round::Commitment,
+                Message::Deal(_) => Round::Deal,
+                Message::Complaint(_) => Round::Complaint,
+            };
+            if round != Some(expected) {
+                return Err(anyhow!(
+                    "DKG message for the {:?} round sent during the {:?} round",
+                    expected,
+                    round
+                ));
+            }
+
+            match &body.message {
+                Message::Commitment(commitment) => {
+                    if self
+                        .overlay
+                        .dkg_commitment(epoch.index, index)
+                        .await?
+                        .is_some()
+                    {
+                        return Err(anyhow!(
+                            "validator {} has already committed",
+                            body.identity_key
+                        ));
+                    }
+                    if commitment.threshold() != participants.threshold as usize {
+                        return Err(anyhow!(
+                            "commitment has threshold {}, but the DKG requires {}",
+                            commitment.threshold(),
+                            participants.threshold
+                        ));
+                    }
+                }
+                Message::Deal(shares) => {
+                    if self
+                        .overlay
+                        .dkg_commitment(epoch.index, index)
+                        .await?
+                        .is_none()
+                    {
+                        return Err(anyhow!(
+                            "validator {} deals without having committed",
+                            body.identity_key
+                        ));
+                    }
+                    if self.overlay.dkg_deal(epoch.index, index).await?.is_some() {
+                        return Err(anyhow!("validator {} has already dealt", body.identity_key));
+                    }
+
+                    // Every participant who committed must be dealt exactly one share.
+                    let mut committed = Vec::new();
+                    for i in participants.indices() {
+                        if self.overlay.dkg_commitment(epoch.index, i).await?.is_some() {
+                            committed.push(i);
+                        }
+                    }
+                    let recipients = shares.iter().map(|s| s.recipient).collect::<Vec<_>>();
+                    if recipients != committed {
+                        return Err(anyhow!(
+                            "deal has shares for participants {:?}, but {:?} committed",
+                            recipients,
+                            committed
+                        ));
+                    }
+                }
+                Message::Complaint(dealing_secret) => {
+                    let commitment = self
+                        .overlay
+                        .dkg_commitment(epoch.index, index)
+                        .await?
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "validator {} complains without having committed",
+                                body.identity_key
+                            )
+                        })?;
+                    if self
+                        .overlay
+                        .dkg_complaint(epoch.index, index)
+                        .await?
+                        .is_some()
+                    {
+                        return Err(anyhow!(
+                            "validator {} has already complained",
+                            body.identity_key
+                        ));
+                    }
+                    if dealing_secret.dealing_key() != commitment.dealing_key() {
+                        return Err(anyhow!(
+                            "complaint does not reveal the validator's dealing secret"
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "flow_encryption", skip(self, tx))]
+    async fn execute_tx(&mut self, tx: &Transaction) {
+        for message in tx.dkg_messages() {
+            let body = &message.body;
+            let index = self
+                .overlay
+                .dkg_participants(body.epoch_index)
+                .await
+                .unwrap()
+                .and_then(|participants| participants.index_of(&body.identity_key))
+                .expect("validator was checked to be participating");
+
+            match &body.message {
+                Message::Commitment(commitment) => {
+                    self.overlay
+                        .set_dkg_commitment(body.epoch_index, index, commitment.clone())
+                        .await
+                }
+                Message::Deal(shares) => {
+                    self.overlay
+                        .set_dkg_deal(body.epoch_index, index, shares.clone())
+                        .await
+                }
+                Message::Complaint(dealing_secret) => {
+                    self.overlay
+                        .set_dkg_complaint(body.epoch_index, index, dealing_secret)
+                        .await
+                }
+            }
+
+            self.events.push(event::dkg_message(body));
+        }
+
+        for decryption in tx.flow_decryptions() {
+            let body = &decryption.body;
+            let flows = self
+                .overlay
+                .flows(body.height)
+                .await
+                .unwrap()
+                .expect("flows were checked to exist");
+            let index = self
+                .overlay
+                .dkg_participants(flows.epoch_index)
+                .await
+                .unwrap()
+                .and_then(|participants| participants.index_of(&body.identity_key))
+                .expect("validator was checked to be participating");
+
+            self.overlay
+                .set_flow_decryption_shares(body.height, index, body.shares.clone())
+                .await;
+            self.events.push(event::flow_decryption(body));
+        }
+    }
+
+    #[instrument(name = "flow_encryption", skip(self, end_block))]
+    async fn end_block(&mut self, end_block: &abci::request::EndBlock) {
+        let height = end_block.height as u64;
+        let epoch = self.overlay.get_current_epoch().await.unwrap();
+
+        // If the last round of this epoch's DKG just ended, finish it.  A DKG
+        // that doesn't finish within its epoch is abandoned.
+        if let Some(participants) = self.overlay.dkg_participants(epoch.index).await.unwrap() {
+            if height == participants.end_height() {
+                self.finish_dkg(epoch.index, &participants).await.unwrap();
+            }
+        }
+
+        // The Staking component has already updated the validator set for
+        // the next epoch, so its DKG can start with the next block.
+        if epoch.is_epoch_end(height) {
+            self.start_dkg(epoch.index + 1, height + 1).await.unwrap();
+        }
+
+        self.decrypt_pending_flows().await.unwrap();
+    }
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl FlowEncryption {
+    /// Starts the DKG for the given epoch among the active validators.
+    async fn start_dkg(&mut self, epoch_index: u64, start_height: u64) -> Result<()> {
+        let mut active = Vec::new();
+        for identity_key in self.overlay.validator_list().await? {
+            if matches!(
+                self.overlay.validator_state(&identity_key).await?,
+                Some(validator::State::Active)
+            ) {
+                active.push(identity_key);
+            }
+        }
+        if active.is_empty() {
+            tracing::warn!(epoch_index, "no active validators to run the DKG");
+            return Ok(());
+        }
+
+        // The round length is fixed for the whole DKG, even if the chain
+        // parameters change while it's running.
+        let round_blocks = self.overlay.get_chain_params().await?.dkg_round_blocks;
+        let participants = DkgParticipants::new(active, start_height, round_blocks);
+        tracing::debug!(epoch_index, ?participants, "starting DKG");
+        self.events
+            .push(event::dkg_start(epoch_index, &participants));
+        self.overlay
+            .set_dkg_participants(epoch_index, participants)
+            .await;
+        Ok(())
+    }
+
+    /// Finishes the DKG for the given epoch, recording the flow encryption
+    /// key if enough dealers qualified.
+    async fn finish_dkg(&mut self, epoch_index: u64, participants: &DkgParticipants) -> Result<()> {
+        let mut commitments = BTreeMap::new();
+        for index in participants.indices() {
+            if let Some(commitment) = self.overlay.dkg_commitment(epoch_index, index).await? {
+                commitments.insert(index, commitment);
+            }
+        }
+        let mut deals = BTreeMap::new();
+        for index in commitments.keys() {
+            if let Some(shares) = self.overlay.dkg_deal(epoch_index, *index).await? {
+                deals.insert(*index, shares);
+            }
+        }
+
+        // Each complaint reveals the complainer's dealing secret, so the
+        // shares it was dealt can be checked publicly.
+        let mut disqualified = BTreeSet::new();
+        for recipient in commitments.keys() {
+            if let Some(dealing_secret) =
+                self.overlay.dkg_complaint(epoch_index, *recipient).await?
+            {
+                for (dealer, shares) in &deals {
+                    let share = shares
+                        .iter()
+                        .find(|share| share.recipient == *recipient)
+                        .expect("deals cover every committed participant");
+                    if dealing_secret
+                        .decrypt_share(*recipient, *dealer, &commitments[dealer], share)
+                        .is_err()
+                    {
+                        tracing::debug!(dealer, recipient, "disqualifying dealer");
+                        disqualified.insert(*dealer);
+                    }
+                }
+            }
+        }
+
+        let qualified_dealers = deals
+            .keys()
+            .filter(|dealer| !disqualified.contains(*dealer))
+            .copied()
+            .collect::<Vec<_>>();
+        if qualified_dealers.len() < participants.threshold as usize {
+            tracing::warn!(
+                epoch_index,
+                qualified = qualified_dealers.len(),
+                "DKG failed with too few qualified dealers"
+            );
+            self.events
+                .push(event::dkg_failed(epoch_index, qualified_dealers.len()));
+            return Ok(());
+        }
+
+        let qualified = qualified_dealers
+            .iter()
+            .map(|dealer| &commitments[dealer])
+            .collect::<Vec<_>>();
+        let output = DkgOutput {
+            encryption_key: dkg::encryption_key(qualified.iter().copied()),
+            public_shares: participants
+                .indices()
+                .map(|index| dkg::public_share(qualified.iter().copied(), index))
+                .collect(),
+            qualified_dealers,
+        };
+        tracing::debug!(epoch_index, ?output, "finished DKG");
+        self.events.push(event::dkg_complete(epoch_index, &output));
+        self.overlay.set_dkg_output(epoch_index, output).await;
+        self.overlay.set_latest_dkg_epoch(epoch_index).await;
+        Ok(())
+    }
+
+    /// Decrypts the pending flows of every height for which enough
+    /// participants have submitted their decryption shares.
+    async fn decrypt_pending_flows(&mut self) -> Result<()> {
+        let mut pending = self.overlay.pending_flows().await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut still_pending = Vec::new();
+        for height in pending.drain(..) {
+            let flows = self
+                .overlay
+                .flows(height)
+                .await?
+                .ok_or_else(|| anyhow!("missing flows queued at height {}", height))?;
+            let participants = self
+                .overlay
+                .dkg_participants(flows.epoch_index)
+                .await?
+                .ok_or_else(|| anyhow!("missing DKG participants for flows"))?;
+
+            // The shares of each participant are for every flow, in order.
+            let mut shares = vec![Vec::new(); flows.ciphertexts.len()];
+            for index in participants.indices() {
+                if let Some(participant_shares) =
+                    self.overlay.flow_decryption_shares(height, index).await?
+                {
+                    for (flow_shares, share) in shares.iter_mut().zip(participant_shares) {
+                        flow_shares.push(share);
+                    }
+                }
+            }
+            let submitted = shares.first().map(Vec::len).unwrap_or_default();
+            if submitted < participants.threshold as usize {
+                still_pending.push(height);
+                continue;
+            }
+
+            let mut amounts = Vec::with_capacity(flows.ciphertexts.len());
+            for (ciphertext, flow_shares) in flows.ciphertexts.iter().zip(shares.iter()) {
+                let amount = decrypt(
+                    ciphertext,
+                    flow_shares,
+                    participants.threshold as usize,
+                    &DECRYPTION_TABLE,
+                )?;
+                // Every flow is a sum of amounts that are bounded by the
+                // token supply of their asset, so it fits in a `u64`.
+                amounts.push(u64::try_from(amount).context("decrypted flow is too large")?);
+            }
+
+            tracing::debug!(height, ?amounts, "decrypted flows");
+            self.events.push(event::flows_decrypted(height));
+            self.overlay.set_flow_plaintexts(height, amounts).await;
+        }
+
+        self.overlay.set_pending_flows(still_pending).await;
+        Ok(())
+    }
+}
+
+/// Extension trait providing read/write access to flow encryption data.
+#[async_trait]
+pub trait View: OverlayExt {
+    /// The participants in the DKG for the given epoch.
+    async fn dkg_participants(&self, epoch_index: u64) -> Result<Option<DkgParticipants>> {
+        self.get_domain(format!("flow_encryption/dkg/{}/participants", epoch_index).into())
+            .await
+    }
+
+    async fn set_dkg_participants(&self, epoch_index: u64, participants: DkgParticipants) {
+        self.put_domain(
+            format!("flow_encryption/dkg/{}/participants", epoch_index).into(),
+            participants,
+        )
+        .await
+    }
+
+    /// The commitment of the participant with the given index.
+    async fn dkg_commitment(&self, epoch_index: u64, index: u32) -> Result<Option<Commitment>> {
+        self.get_domain(format!("flow_encryption/dkg/{}/commitments/{}", epoch_index, index).into())
+            .await
+    }
+
+    async fn set_dkg_commitment(&self, epoch_index: u64, index: u32, commitment: Commitment) {
+        self.put_domain(
+            format!("flow_encryption/dkg/{}/commitments/{}", epoch_index, index).into(),
+            commitment,
+        )
+        .await
+    }
+
+    /// The shares dealt by the participant with the given index.
+    async fn dkg_deal(&self, epoch_index: u64, index: u32) -> Result<Option<Vec<EncryptedShare>>> {
+        self.get_proto::<pb::DkgDeal>(
+            format!("flow_encryption/dkg/{}/deals/{}", epoch_index, index).into(),
+        )
+        .await?
+        .map(|deal| {
+            deal.shares
+                .into_iter()
+                .map(EncryptedShare::try_from)
+                .collect::<Result<_>>()
+        })
+        .transpose()
+    }
+
+    async fn set_dkg_deal(&self, epoch_index: u64, index: u32, shares: Vec<EncryptedShare>) {
+        self.put_proto(
+            format!("flow_encryption/dkg/{}/deals/{}", epoch_index, index).into(),
+            pb::DkgDeal {
+                shares: shares.into_iter().map(Into::into).collect(),
+            },
+        )
+        .await
+    }
+
+    /// The dealing secret revealed by a complaint from the participant with the given index.
+    async fn dkg_complaint(&self, epoch_index: u64, index: u32) -> Result<Option<DealingSecret>> {
+        self.get_proto::<pb::DkgComplaint>(
+            format!("flow_encryption/dkg/{}/complaints/{}", epoch_index, index).into(),
+        )
+        .await?
+        .map(|complaint| {
+            Ok(DealingSecret::try_from(
+                complaint.dealing_secret.as_slice(),
+            )?)
+        })
+        .transpose()
+    }
+
+    async fn set_dkg_complaint(
+        &self,
+        epoch_index: u64,
+        index: u32,
+        dealing_secret: &DealingSecret,
+    ) {
+        self.put_proto(
+            format!("flow_encryption/dkg/{}/complaints/{}", epoch_index, index).into(),
+            pb::DkgComplaint {
+                dealing_secret: dealing_secret.to_bytes().to_vec(),
+            },
+        )
+        .await
+    }
+
+    /// The result of the DKG for the given epoch, including its flow encryption key.
+    async fn dkg_output(&self, epoch_index: u64) -> Result<Option<DkgOutput>> {
+        self.get_domain(format!("flow_encryption/dkg/{}/output", epoch_index).into())
+            .await
+    }
+
+    async fn set_dkg_output(&self, epoch_index: u64, output: DkgOutput) {
+        self.put_domain(
+            format!("flow_encryption/dkg/{}/output", epoch_index).into(),
+            output,
+        )
+        .await
+    }
+
+    /// The epoch of the most recent successful DKG.
+    async fn latest_dkg_epoch(&self) -> Result<Option<u64>> {
+        self.get_proto("flow_encryption/dkg/latest".into()).await
+    }
+
+    async fn set_latest_dkg_epoch(&self, epoch_index: u64) {
+        self.put_proto("flow_encryption/dkg/latest".into(), epoch_index)
+            .await
+    }
+
+    /// The result of the most recent successful DKG, whose flow encryption
+    /// key flows are currently encrypted to, with its epoch.
+    async fn latest_dkg_output(&self) -> Result<Option<(u64, DkgOutput)>> {
+        match self.latest_dkg_epoch().await? {
+            Some(epoch_index) => Ok(self
+                .dkg_output(epoch_index)
+                .await?
+                .map(|output| (epoch_index, output))),
+            None => Ok(None),
+        }
+    }
+
+    /// The flows queued for decryption at the given height.
+    async fn flows(&self, height: u64) -> Result<Option<Flows>> {
+        self.get_domain(format!("flow_encryption/flows/{}", height).into())
+            .await
+    }
+
+    /// Queues flows for decryption, once enough of the participants in the
+    /// DKG for their key have submitted their decryption shares.
+    async fn queue_flows(&self, height: u64, flows: Flows) -> Result<()> {
+        self.put_domain(format!("flow_encryption/flows/{}", height).into(), flows)
+            .await;
+        let mut pending = self.pending_flows().await?;
+        pending.push(height);
+        self.set_pending_flows(pending).await;
+        Ok(())
+    }
+
+    /// The heights whose flows are still awaiting decryption.
+    async fn pending_flows(&self) -> Result<Vec<u64>> {
+        Ok(self
+            .get_proto::<pb::PendingFlows>("flow_encryption/pending_flows".into())
+            .await?
+            .map(|pending| pending.heights)
+            .unwrap_or_default())
+    }
+
+    async fn set_pending_flows(&self, heights: Vec<u64>) {
+        self.put_proto(
+            "flow_encryption/pending_flows".into(),
+            pb::PendingFlows { heights },
+        )
+        .await
+    }
+
+    /// The shares of the decryption of the flows queued at the given height,
+    /// from the participant with the given index.
+    async fn flow_decryption_shares(
+        &self,
+        height: u64,
+        index: u32,
+    ) -> Result<Option<Vec<DecryptionShare>>> {
+        self.get_proto::<pb::FlowDecryptionShares>(
+            format!("flow_encryption/flows/{}/shares/{}", height, index).into(),
+        )
+        .await?
+        .map(|shares| {
+            shares
+                .shares
+                .iter()
+                .map(|share| Ok(DecryptionShare::try_from(share.as_slice())?))
+                .collect::<Result<_>>()
+        })
+        .transpose()
+    }
+
+    async fn set_flow_decryption_shares(
+        &self,
+        height: u64,
+        index: u32,
+        shares: Vec<DecryptionShare>,
+    ) {
+        self.put_proto(
+            format!("flow_encryption/flows/{}/shares/{}", height, index).into(),
+            pb::FlowDecryptionShares {
+                shares: shares.iter().map(DecryptionShare::to_bytes).collect(),
+            },
+        )
+        .await
+    }
+
+    /// The decrypted amounts of the flows queued at the given height, in
+    /// order, once they've been decrypted.
+    async fn flow_plaintexts(&self, height: u64) -> Result<Option<Vec<u64>>> {
+        Ok(self
+            .get_proto::<pb::FlowPlaintexts>(
+                format!("flow_encryption/flows/{}/plaintexts", height).into(),
+            )
+            .await?
+            .map(|plaintexts| plaintexts.amounts))
+    }
+
+    async fn set_flow_plaintexts(&self, height: u64, amounts: Vec<u64>) {
+        self.put_proto(
+            format!("flow_encryption/flows/{}/plaintexts", height).into(),
+            pb::FlowPlaintexts { amounts },
+        )
+        .await
+    }
+}
+
+impl<T: OverlayExt + Send + Sync> View for T {}
@@ -0,0 +1,82 @@
+use penumbra_flow_encryption::{
+    action::{Body, FlowDecryptionBody, Message},
+    DkgOutput, DkgParticipants,
+};
+use tendermint::abci::{Event, EventAttributeIndexExt};
+
+/// The DKG for an epoch was started among its active validators.
+pub fn dkg_start(epoch_index: u64, participants: &DkgParticipants) -> Event {
+    Event::new(
+        "dkg_start",
+        vec![
+            ("epoch", epoch_index.to_string()).index(),
+            ("participants", participants.identity_keys.len().to_string()).no_index(),
+            ("threshold", participants.threshold.to_string()).no_index(),
+            ("start_height", participants.start_height.to_string()).no_index(),
+            ("round_blocks", participants.round_blocks.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A validator sent a message in the DKG.
+pub fn dkg_message(body: &Body) -> Event {
+    let round = match body.message {
+        Message::Commitment(_) => "commitment",
+        Message::Deal(_) => "deal",
+        Message::Complaint(_) => "complaint",
+    };
+    Event::new(
+        "action_dkg_message",
+        vec![
+            ("epoch", body.epoch_index.to_string()).index(),
+            ("validator", body.identity_key.to_string()).index(),
+            ("round", round.to_string()).no_index(),
+        ],
+    )
+}
+
+/// The DKG for an epoch produced a flow encryption key.
+pub fn dkg_complete(epoch_index: u64, output: &DkgOutput) -> Event {
+    Event::new(
+        "dkg_complete",
+        vec![
+            ("epoch", epoch_index.to_string()).index(),
+            (
+                "qualified_dealers",
+                output.qualified_dealers.len().to_string(),
+            )
+                .no_index(),
+        ],
+    )
+}
+
+/// The DKG for an epoch failed, with too few qualified dealers.
+pub fn dkg_failed(epoch_index: u64, qualified_dealers: usize) -> Event {
+    Event::new(
+        "dkg_failed",
+        vec![
+            ("epoch", epoch_index.to_string()).index(),
+            ("qualified_dealers", qualified_dealers.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A validator submitted its shares of the decryption of the flows queued at
+/// a height.
+pub fn flow_decryption(body: &FlowDecryptionBody) -> Event {
+    Event::new(
+        "action_flow_decryption",
+        vec![
+            ("height", body.height.to_string()).index(),
+            ("validator", body.identity_key.to_string()).index(),
+        ],
+    )
+}
+
+/// The flows queued at a height were decrypted.
+pub fn flows_decrypted(height: u64) -> Event {
+    Event::new(
+        "flows_decrypted",
+        vec![("height", height.to_string()).index()],
+    )
+}
@@ -0,0 +1,849 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use penumbra_crypto::{asset, proofs::Proof, Nullifier, Value};
+use penumbra_governance::{
+    action::{DelegatorVote, Proposal, ValidatorVote},
+    CommunityPoolOutput, CommunityPoolPayout, DepositRefund, DepositRefunds, Outcome, ProposalList,
+    ProposalOutcome, ProposalPayload, ProposalState, Tally, Vote,
+};
+use penumbra_proto::Protobuf;
+use penumbra_stake::{validator, IdentityKey, STAKING_TOKEN_ASSET_ID};
+use penumbra_transaction::Transaction;
+use tendermint::abci;
+use tracing::instrument;
+
+use super::{
+    app::View as _, community_pool::View as _, shielded_pool::View as _, staking::View as _,
+    Component,
+};
+use crate::{genesis, Overlay, OverlayExt};
+
+mod event;
+
+// Governance component
+pub struct Governance {
+    overlay: Overlay,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
+}
+
+#[async_trait]
+impl Component for Governance {
+    #[instrument(name = "governance", skip(overlay))]
+    async fn new(overlay: Overlay) -> Self {
+        Self {
+            overlay,
+            events: Vec::new(),
+        }
+    }
+
+    #[instrument(name = "governance", skip(self, _app_state))]
+    async fn init_chain(&mut self, _app_state: &genesis::AppState) {
+        self.overlay.set_next_proposal_id(0).await;
+    }
+
+    #[instrument(name = "governance", skip(self, _begin_block))]
+    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+
+    #[instrument(name = "governance", skip(tx))]
+    fn check_tx_stateless(tx: &Transaction) -> Result<()> {
+        for proposal in tx.proposals() {
+            if proposal.title.is_empty() {
+                return Err(anyhow!("proposal has an empty title"));
+            }
+            if proposal.voting_epochs == 0 {
+                return Err(anyhow!("proposal must be open for at least one epoch"));
+            }
+            match &proposal.payload {
+                ProposalPayload::Signaling { .. } => {}
+                ProposalPayload::ParameterChange { changes } => {
+                    if changes.is_empty() {
+                        return Err(anyhow!("parameter change proposal changes no parameters"));
+                    }
+                }
+                ProposalPayload::CommunityPoolSpend { outputs } => {
+                    if outputs.is_empty() {
+                        return Err(anyhow!("community pool spend proposal has no outputs"));
+                    }
+                    if outputs.iter().any(|output| output.value.amount == 0) {
+                        return Err(anyhow!("community pool spend proposal has an empty output"));
+                    }
+                    total_spend(outputs)?;
+                }
+            }
+        }
+
+        // Check that validator votes are signed by the validator, and that
+        // each validator votes at most once per proposal.
+        let mut validator_votes = BTreeSet::<(u64, IdentityKey)>::new();
+        for vote in tx.validator_votes() {
+            let body_bytes = vote.body.encode_to_vec();
+            vote.body
+                .identity_key
+                .0
+                .verify(&body_bytes, &vote.auth_sig)
+                .context("validator vote signature failed to verify")?;
+
+            if !validator_votes.insert((vote.body.proposal_id, vote.body.identity_key.clone())) {
+                return Err(anyhow!(
+                    "validator {} votes on proposal {} more than once",
+                    vote.body.identity_key,
+                    vote.body.proposal_id
+                ));
+            }
+        }
+
+        // Check that delegator votes are authorized by the owner of the note,
+        // and that each note votes at most once per proposal.  The proofs are
+        // checked statefully, since they're relative to the note commitment
+        // tree at the start of voting.
+        let sighash = tx.transaction_body().sighash();
+        let mut delegator_votes = BTreeSet::<(u64, Nullifier)>::new();
+        for vote in tx.delegator_votes() {
+            vote.body
+                .rk
+                .verify(&sighash, &vote.auth_sig)
+                .context("delegator vote auth signature failed to verify")?;
+
+            if !delegator_votes.insert((vote.body.proposal_id, vote.body.nullifier.clone())) {
+                return Err(anyhow!(
+                    "nullifier {} votes on proposal {} more than once",
+                    vote.body.nullifier,
+                    vote.body.proposal_id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "governance", skip(self, tx))]
+    async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        let chain_params = self.overlay.get_chain_params().await?;
+        for proposal in tx.proposals() {
+            if proposal.deposit_amount < chain_params.proposal_min_deposit {
+                return Err(anyhow!(
+                    "proposal deposit {} is less than the minimum deposit {}",
+                    proposal.deposit_amount,
+                    chain_params.proposal_min_deposit
+                ));
+            }
+            if proposal.voting_epochs < chain_params.proposal_min_voting_epochs {
+                return Err(anyhow!(
+                    "proposal voting period of {} epochs is shorter than the minimum of {} epochs",
+                    proposal.voting_epochs,
+                    chain_params.proposal_min_voting_epochs
+                ));
+            }
+            match &proposal.payload {
+                ProposalPayload::Signaling { .. } => {}
+                // Parameter changes must apply cleanly to the current parameters.
+                ProposalPayload::ParameterChange { changes } => {
+                    let mut new_params = chain_params.clone();
+                    for change in changes {
+                        new_params.set(&change.parameter, &change.value)?;
+                    }
+                    new_params
+                        .check_valid()
+                        .context("proposed parameter change is invalid")?;
+                }
+                // Spends must be covered by the current community pool balance.
+                ProposalPayload::CommunityPoolSpend { outputs } => {
+                    for (asset_id, amount) in total_spend(outputs)? {
+                        let balance = self.overlay.community_pool_balance(&asset_id).await?;
+                        if balance < amount {
+                            return Err(anyhow!(
+                                "community pool balance {} of asset {} is less than the proposed spend of {}",
+                                balance,
+                                asset_id,
+                                amount
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        for vote in tx.validator_votes() {
+            let proposal_id = vote.body.proposal_id;
+            let identity_key = &vote.body.identity_key;
+            self.check_voting(proposal_id).await?;
+
+            if self
+                .overlay
+                .proposal_validator_power(proposal_id, identity_key)
+                .await?
+                .is_none()
+            {
+                return Err(anyhow!(
+                    "validator {} was not active when voting on proposal {} began",
+                    identity_key,
+                    proposal_id
+                ));
+            }
+            if self
+                .overlay
+                .validator_vote(proposal_id, identity_key)
+                .await?
+                .is_some()
+            {
+                return Err(anyhow!(
+                    "validator {} has already voted on proposal {}",
+                    identity_key,
+                    proposal_id
+                ));
+            }
+        }
+
+        for vote in tx.delegator_votes() {
+            let proposal_id = vote.body.proposal_id;
+            let identity_key = &vote.body.identity_key;
+            let start_height = self.check_voting(proposal_id).await?;
+
+            if self
+                .overlay
+                .proposal_delegation_supply(proposal_id, identity_key)
+                .await?
+                .is_none()
+            {
+                return Err(anyhow!(
+                    "validator {} was not active when voting on proposal {} began",
+                    identity_key,
+                    proposal_id
+                ));
+            }
+
+            // The note must have existed when voting began, ...
+            let anchor = self
+                .overlay
+                .nct_anchor_by_height(start_height)
+                .await?
+                .ok_or_else(|| anyhow!("missing NCT anchor for height {}", start_height))?;
+            if vote
+                .body
+                .proof
+                .verify(&vote.body.public_inputs(anchor))
+                .is_err()
+            {
+                return Err(anyhow!("a delegator vote proof did not verify"));
+            }
+
+            // ... and must not have voted on this proposal already, ...
+            if self
+                .overlay
+                .proposal_voted_nullifier(proposal_id, vote.body.nullifier.clone())
+                .await?
+            {
+                return Err(anyhow!(
+                    "nullifier {} has already voted on proposal {}",
+                    vote.body.nullifier,
+                    proposal_id
+                ));
+            }
+
+            // ... or been spent before voting began, or be locked in quarantine
+            // by a pending undelegation.
+            if let Some(spend_height) = self
+                .overlay
+                .nullifier_spend_height(vote.body.nullifier.clone())
+                .await?
+            {
+                if spend_height <= start_height {
+                    return Err(anyhow!(
+                        "nullifier {} was spent before voting on proposal {} began",
+                        vote.body.nullifier,
+                        proposal_id
+                    ));
+                }
+            }
+            self.overlay
+                .check_nullifier_unquarantined(vote.body.nullifier.clone())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "governance", skip(self, tx))]
+    async fn execute_tx(&mut self, tx: &Transaction) {
+        for proposal in tx.proposals() {
+            self.submit_proposal(proposal).await.unwrap();
+        }
+        for vote in tx.validator_votes() {
+            self.overlay
+                .set_validator_vote(
+                    vote.body.proposal_id,
+                    &vote.body.identity_key,
+                    vote.body.vote,
+                )
+                .await;
+            self.events.push(event::validator_vote(vote));
+        }
+        for vote in tx.delegator_votes() {
+            self.cast_delegator_vote(vote).await.unwrap();
+        }
+    }
+
+    #[instrument(name = "governance", skip(self, end_block))]
+    async fn end_block(&mut self, end_block: &abci::request::EndBlock) {
+        let height = end_block.height as u64;
+        let current_epoch = self.overlay.get_current_epoch().await.unwrap();
+        if current_epoch.is_epoch_end(height) {
+            self.finish_voting(current_epoch.index, height)
+                .await
+                .unwrap();
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Governance {
+    /// Checks that voting on the given proposal is open, returning the height
+    /// at which it began.
+    async fn check_voting(&self, proposal_id: u64) -> Result<u64> {
+        match self.overlay.proposal_state(proposal_id).await? {
+            Some(ProposalState::Voting) => {}
+            Some(ProposalState::Finished { .. }) => {
+                return Err(anyhow!("voting on proposal {} has finished", proposal_id))
+            }
+            None => return Err(anyhow!("proposal {} does not exist", proposal_id)),
+        }
+
+        // Votes cast in the same block as the proposal can't be checked
+        // against the note commitment tree at the start of voting, which
+        // isn't final until the end of that block.
+        let start_height = self
+            .overlay
+            .proposal_start_height(proposal_id)
+            .await?
+            .ok_or_else(|| anyhow!("missing start height for proposal {}", proposal_id))?;
+        if self.overlay.get_block_height().await? <= start_height {
+            return Err(anyhow!(
+                "voting on proposal {} opens after height {}",
+                proposal_id,
+                start_height
+            ));
+        }
+
+        Ok(start_height)
+    }
+
+    /// Records a new proposal, snapshotting the voting power of the active
+    /// validators and the supply of their delegation tokens.
+    #[instrument(skip(self, proposal))]
+    async fn submit_proposal(&mut self, proposal: &Proposal) -> Result<()> {
+        let proposal_id = self.overlay.next_proposal_id().await?;
+        self.overlay.set_next_proposal_id(proposal_id + 1).await;
+        tracing::debug!(?proposal_id, "submitting proposal");
+
+        let height = self.overlay.get_block_height().await?;
+        let end_epoch = self.overlay.get_current_epoch().await?.index + proposal.voting_epochs;
+
+        self.overlay
+            .put_domain(
+                format!("governance/proposals/{}/proposal", proposal_id).into(),
+                proposal.clone(),
+            )
+            .await;
+        self.overlay
+            .set_proposal_state(proposal_id, ProposalState::Voting)
+            .await;
+        self.overlay
+            .put_proto(
+                format!("governance/proposals/{}/start_height", proposal_id).into(),
+                height,
+            )
+            .await;
+
+        let mut total_power = 0u64;
+        for identity_key in self.overlay.validator_list().await? {
+            if !matches!(
+                self.overlay.validator_state(&identity_key).await?,
+                Some(validator::State::Active)
+            ) {
+                continue;
+            }
+            let power = self
+                .overlay
+                .validator_power(&identity_key)
+                .await?
+                .unwrap_or_default();
+            let delegation_supply = self
+                .overlay
+                .token_supply(&identity_key.delegation_token().id())
+                .await?
+                .unwrap_or_default();
+
+            self.overlay
+                .put_proto(
+                    format!(
+                        "governance/proposals/{}/validator_power/{}",
+                        proposal_id, identity_key
+                    )
+                    .into(),
+                    power,
+                )
+                .await;
+            self.overlay
+                .put_proto(
+                    format!(
+                        "governance/proposals/{}/delegation_supply/{}",
+                        proposal_id, identity_key
+                    )
+                    .into(),
+                    delegation_supply,
+                )
+                .await;
+            total_power += power;
+        }
+        self.overlay
+            .put_proto(
+                format!("governance/proposals/{}/total_power", proposal_id).into(),
+                total_power,
+            )
+            .await;
+
+        let mut ending = self.overlay.proposals_ending_in(end_epoch).await?;
+        ending.0.push(proposal_id);
+        self.overlay
+            .set_proposals_ending_in(end_epoch, ending)
+            .await;
+
+        // The deposit is escrowed by burning it, and minted again when it's refunded.
+        self.overlay
+            .update_token_supply(&STAKING_TOKEN_ASSET_ID, -(proposal.deposit_amount as i64))
+            .await?;
+
+        self.events.push(event::proposal(proposal_id, end_epoch));
+
+        Ok(())
+    }
+
+    /// Records a delegator vote, with voting power proportional to the share
+    /// of the validator's delegation tokens it represents.
+    #[instrument(skip(self, vote))]
+    async fn cast_delegator_vote(&mut self, vote: &DelegatorVote) -> Result<()> {
+        let proposal_id = vote.body.proposal_id;
+        let identity_key = &vote.body.identity_key;
+
+        let validator_power = self
+            .overlay
+            .proposal_validator_power(proposal_id, identity_key)
+            .await?
+            .unwrap_or_default();
+        let delegation_supply = self
+            .overlay
+            .proposal_delegation_supply(proposal_id, identity_key)
+            .await?
+            .unwrap_or_default();
+        let power = if delegation_supply == 0 {
+            0
+        } else {
+            (validator_power as u128 * vote.body.delegation_amount as u128
+                / delegation_supply as u128) as u64
+        };
+
+        self.overlay
+            .put_proto(
+                format!(
+                    "governance/proposals/{}/voted_nullifiers/{}",
+                    proposal_id, vote.body.nullifier
+                )
+                .into(),
+                true,
+            )
+            .await;
+
+        let mut tally = self.overlay.delegator_tally(proposal_id).await?;
+        tally.add(vote.body.vote, power);
+        self.overlay
+            .put_domain(
+                format!("governance/proposals/{}/delegator_tally", proposal_id).into(),
+                tally,
+            )
+            .await;
+
+        let delegator_power = self
+            .overlay
+            .delegator_power(proposal_id, identity_key)
+            .await?;
+        self.overlay
+            .put_proto(
+                format!(
+                    "governance/proposals/{}/delegator_power/{}",
+                    proposal_id, identity_key
+                )
+                .into(),
+                delegator_power + power,
+            )
+            .await;
+
+        self.events.push(event::delegator_vote(vote, power));
+
+        Ok(())
+    }
+
+    /// Tallies the votes on the proposals whose voting period ends with the
+    /// given epoch, recording their outcomes and scheduling their deposit refunds.
+    #[instrument(skip(self))]
+    async fn finish_voting(&mut self, epoch_index: u64, height: u64) -> Result<()> {
+        let ending = self.overlay.proposals_ending_in(epoch_index).await?;
+        if ending.0.is_empty() {
+            return Ok(());
+        }
+
+        let chain_params = self.overlay.get_chain_params().await?;
+        let mut refunds = DepositRefunds::default();
+
+        for proposal_id in ending.0 {
+            let proposal = self
+                .overlay
+                .proposal(proposal_id)
+                .await?
+                .ok_or_else(|| anyhow!("missing proposal {}", proposal_id))?;
+
+            // Delegators' votes override their validator's vote for the power
+            // of their delegation tokens, so validators only vote with the
+            // remainder of their power.
+            let mut tally = self.overlay.delegator_tally(proposal_id).await?;
+            for identity_key in self.overlay.validator_list().await? {
+                let vote = match self
+                    .overlay
+                    .validator_vote(proposal_id, &identity_key)
+                    .await?
+                {
+                    Some(vote) => vote,
+                    None => continue,
+                };
+                let power = self
+                    .overlay
+                    .proposal_validator_power(proposal_id, &identity_key)
+                    .await?
+                    .unwrap_or_default();
+                let delegator_power = self
+                    .overlay
+                    .delegator_power(proposal_id, &identity_key)
+                    .await?;
+                tally.add(vote, power.saturating_sub(delegator_power));
+            }
+
+            let total_power = self
+                .overlay
+                .get_proto(format!("governance/proposals/{}/total_power", proposal_id).into())
+                .await?
+                .unwrap_or_default();
+            let outcome = ProposalOutcome {
+                outcome: tally.outcome(
+                    total_power,
+                    chain_params.proposal_valid_quorum_bps,
+                    chain_params.proposal_pass_threshold_bps,
+                    chain_params.proposal_veto_threshold_bps,
+                ),
+                tally,
+            };
+            tracing::debug!(?proposal_id, ?outcome, "finished voting on proposal");
+
+            self.overlay
+                .set_proposal_state(proposal_id, ProposalState::Finished { outcome })
+                .await;
+
+            // Vetoed proposals forfeit their deposit, which stays burned.
+            if outcome.outcome != Outcome::Vetoed && proposal.deposit_amount > 0 {
+                refunds.refunds.push(DepositRefund {
+                    proposal_id,
+                    amount: proposal.deposit_amount,
+                    address: proposal.deposit_refund_address,
+                });
+            }
+
+            if outcome.outcome == Outcome::Passed {
+                self.enact(proposal_id, &proposal.payload).await?;
+            }
+
+            self.events
+                .push(event::proposal_finished(proposal_id, &outcome));
+        }
+
+        // The ShieldedPool mints the refunds at the end of this block.
+        if !refunds.refunds.is_empty() {
+            self.overlay.set_deposit_refunds(height, refunds).await;
+        }
+        self.overlay
+            .set_proposals_ending_in(epoch_index, ProposalList::default())
+            .await;
+
+        Ok(())
+    }
+
+    /// Enacts the payload of a proposal that passed.
+    #[instrument(skip(self, payload))]
+    async fn enact(&mut self, proposal_id: u64, payload: &ProposalPayload) -> Result<()> {
+        match payload {
+            // Signaling proposals have no effect on the chain.
+            ProposalPayload::Signaling { .. } => {}
+            ProposalPayload::ParameterChange { changes } => {
+                let old_params = self.overlay.get_chain_params().await?;
+                let mut new_params = old_params.clone();
+                if let Err(e) = changes
+                    .iter()
+                    .try_for_each(|change| new_params.set(&change.parameter, &change.value))
+                    .and_then(|()| new_params.check_valid())
+                {
+                    // The changes were valid when they were proposed, but may
+                    // conflict with a parameter change enacted since then.
+                    tracing::warn!(?proposal_id, ?e, "not enacting invalid parameter change");
+                    return Ok(());
+                }
+                tracing::debug!(?proposal_id, ?new_params, "changing chain parameters");
+                self.overlay.put_chain_params(new_params.clone()).await;
+
+                // Migrate any component state which depends on the changed parameters.
+                if new_params.signed_blocks_window_len != old_params.signed_blocks_window_len {
+                    self.overlay
+                        .resize_validator_uptimes(new_params.signed_blocks_window_len)
+                        .await?;
+                }
+
+                self.events
+                    .push(event::parameter_change(proposal_id, changes));
+            }
+            ProposalPayload::CommunityPoolSpend { outputs } => {
+                // The community pool may have been spent from since the
+                // proposal was submitted, so check its balance again.
+                let totals = total_spend(outputs)?;
+                for (asset_id, amount) in &totals {
+                    let balance = self.overlay.community_pool_balance(asset_id).await?;
+                    if balance < *amount {
+                        tracing::warn!(
+                            ?proposal_id,
+                            ?asset_id,
+                            ?balance,
+                            ?amount,
+                            "not enacting community pool spend exceeding the pool's balance"
+                        );
+                        return Ok(());
+                    }
+                }
+                for (asset_id, amount) in totals {
+                    self.overlay
+                        .community_pool_withdraw(Value { amount, asset_id })
+                        .await?;
+                }
+
+                // The ShieldedPool mints the payouts at the end of this block.
+                let height = self.overlay.get_block_height().await?;
+                let mut payouts = self
+                    .overlay
+                    .community_pool_payouts(height)
+                    .await?
+                    .unwrap_or_default();
+                payouts
+                    .payouts
+                    .extend(outputs.iter().map(|output| CommunityPoolPayout {
+                        proposal_id,
+                        value: output.value,
+                        address: output.address,
+                    }));
+                self.overlay
+                    .set_community_pool_payouts(height, payouts)
+                    .await;
+
+                self.events
+                    .push(event::community_pool_spend(proposal_id, outputs));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sums the value paid out by a community pool spend, per asset.
+fn total_spend(outputs: &[CommunityPoolOutput]) -> Result<BTreeMap<asset::Id, u64>> {
+    let mut totals = BTreeMap::new();
+    for output in outputs {
+        let total = totals.entry(output.value.asset_id).or_insert(0u64);
+        *total = total
+            .checked_add(output.value.amount)
+            .ok_or_else(|| anyhow!("overflow in community pool spend total"))?;
+    }
+    Ok(totals)
+}
+
+/// Extension trait providing read/write access to governance data.
+#[async_trait]
+pub trait View: OverlayExt {
+    async fn next_proposal_id(&self) -> Result<u64> {
+        self.get_proto("governance/next_proposal_id".into())
+            .await?
+            .ok_or_else(|| anyhow!("missing next proposal id"))
+    }
+
+    async fn set_next_proposal_id(&self, proposal_id: u64) {
+        self.put_proto("governance/next_proposal_id".into(), proposal_id)
+            .await
+    }
+
+    async fn proposal(&self, proposal_id: u64) -> Result<Option<Proposal>> {
+        self.get_domain(format!("governance/proposals/{}/proposal", proposal_id).into())
+            .await
+    }
+
+    async fn proposal_state(&self, proposal_id: u64) -> Result<Option<ProposalState>> {
+        self.get_domain(format!("governance/proposals/{}/state", proposal_id).into())
+            .await
+    }
+
+    async fn set_proposal_state(&self, proposal_id: u64, state: ProposalState) {
+        self.put_domain(
+            format!("governance/proposals/{}/state", proposal_id).into(),
+            state,
+        )
+        .await
+    }
+
+    /// The height of the block in which the proposal was submitted.
+    async fn proposal_start_height(&self, proposal_id: u64) -> Result<Option<u64>> {
+        self.get_proto(format!("governance/proposals/{}/start_height", proposal_id).into())
+            .await
+    }
+
+    /// The voting power of the validator when voting on the proposal began,
+    /// or `None` if the validator was not active then.
+    async fn proposal_validator_power(
+        &self,
+        proposal_id: u64,
+        identity_key: &IdentityKey,
+    ) -> Result<Option<u64>> {
+        self.get_proto(
+            format!(
+                "governance/proposals/{}/validator_power/{}",
+                proposal_id, identity_key
+            )
+            .into(),
+        )
+        .await
+    }
+
+    /// The supply of the validator's delegation tokens when voting on the
+    /// proposal began, or `None` if the validator was not active then.
+    async fn proposal_delegation_supply(
+        &self,
+        proposal_id: u64,
+        identity_key: &IdentityKey,
+    ) -> Result<Option<u64>> {
+        self.get_proto(
+            format!(
+                "governance/proposals/{}/delegation_supply/{}",
+                proposal_id, identity_key
+            )
+            .into(),
+        )
+        .await
+    }
+
+    async fn validator_vote(
+        &self,
+        proposal_id: u64,
+        identity_key: &IdentityKey,
+    ) -> Result<Option<Vote>> {
+        self.get_domain(
+            format!(
+                "governance/proposals/{}/validator_votes/{}",
+                proposal_id, identity_key
+            )
+            .into(),
+        )
+        .await
+    }
+
+    async fn set_validator_vote(&self, proposal_id: u64, identity_key: &IdentityKey, vote: Vote) {
+        self.put_domain(
+            format!(
+                "governance/proposals/{}/validator_votes/{}",
+                proposal_id, identity_key
+            )
+            .into(),
+            vote,
+        )
+        .await
+    }
+
+    /// The voting power cast by delegators to the validator, overriding the
+    /// validator's own vote.
+    async fn delegator_power(&self, proposal_id: u64, identity_key: &IdentityKey) -> Result<u64> {
+        Ok(self
+            .get_proto(
+                format!(
+                    "governance/proposals/{}/delegator_power/{}",
+                    proposal_id, identity_key
+                )
+                .into(),
+            )
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn delegator_tally(&self, proposal_id: u64) -> Result<Tally> {
+        Ok(self
+            .get_domain(format!("governance/proposals/{}/delegator_tally", proposal_id).into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn proposal_voted_nullifier(
+        &self,
+        proposal_id: u64,
+        nullifier: Nullifier,
+    ) -> Result<bool> {
+        Ok(self
+            .get_proto(
+                format!(
+                    "governance/proposals/{}/voted_nullifiers/{}",
+                    proposal_id, nullifier
+                )
+                .into(),
+            )
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// The proposals whose voting period ends with the given epoch.
+    async fn proposals_ending_in(&self, epoch_index: u64) -> Result<ProposalList> {
+        Ok(self
+            .get_domain(format!("governance/voting_end/{}", epoch_index).into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_proposals_ending_in(&self, epoch_index: u64, proposals: ProposalList) {
+        self.put_domain(
+            format!("governance/voting_end/{}", epoch_index).into(),
+            proposals,
+        )
+        .await
+    }
+
+    async fn deposit_refunds(&self, height: u64) -> Result<Option<DepositRefunds>> {
+        self.get_domain(format!("governance/deposit_refunds/{}", height).into())
+            .await
+    }
+
+    async fn set_deposit_refunds(&self, height: u64, refunds: DepositRefunds) {
+        self.put_domain(
+            format!("governance/deposit_refunds/{}", height).into(),
+            refunds,
+        )
+        .await
+    }
+
+    async fn delete_deposit_refunds(&self, height: u64) {
+        self.delete(format!("governance/deposit_refunds/{}", height).into())
+            .await
+    }
+}
+
+impl<T: OverlayExt + Send + Sync> View for T {}
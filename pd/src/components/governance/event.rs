@@ -0,0 +1,75 @@
+use penumbra_governance::{
+    action::{DelegatorVote, ValidatorVote},
+    CommunityPoolOutput, ParameterChange, ProposalOutcome,
+};
+use tendermint::abci::{Event, EventAttributeIndexExt};
+
+/// A proposal was submitted, opening voting on it.
+pub fn proposal(proposal_id: u64, end_epoch: u64) -> Event {
+    Event::new(
+        "action_proposal",
+        vec![
+            ("proposal_id", proposal_id.to_string()).index(),
+            ("end_epoch", end_epoch.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A validator voted on a proposal.
+pub fn validator_vote(vote: &ValidatorVote) -> Event {
+    Event::new(
+        "action_validator_vote",
+        vec![
+            ("proposal_id", vote.body.proposal_id.to_string()).index(),
+            ("validator", vote.body.identity_key.to_string()).index(),
+            ("vote", vote.body.vote.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A delegator voted on a proposal, with the given voting power.
+pub fn delegator_vote(vote: &DelegatorVote, power: u64) -> Event {
+    Event::new(
+        "action_delegator_vote",
+        vec![
+            ("proposal_id", vote.body.proposal_id.to_string()).index(),
+            ("validator", vote.body.identity_key.to_string()).index(),
+            ("vote", vote.body.vote.to_string()).no_index(),
+            ("power", power.to_string()).no_index(),
+        ],
+    )
+}
+
+/// Voting on a proposal finished.
+pub fn proposal_finished(proposal_id: u64, outcome: &ProposalOutcome) -> Event {
+    Event::new(
+        "proposal_finished",
+        vec![
+            ("proposal_id", proposal_id.to_string()).index(),
+            ("outcome", outcome.outcome.to_string()).index(),
+            ("yes", outcome.tally.yes.to_string()).no_index(),
+            ("no", outcome.tally.no.to_string()).no_index(),
+            ("abstain", outcome.tally.abstain.to_string()).no_index(),
+            ("no_with_veto", outcome.tally.no_with_veto.to_string()).no_index(),
+        ],
+    )
+}
+
+/// The chain parameters were changed by a proposal.
+pub fn parameter_change(proposal_id: u64, changes: &[ParameterChange]) -> Event {
+    let mut attributes = vec![("proposal_id", proposal_id.to_string()).index()];
+    for change in changes {
+        attributes.push((change.parameter.as_str(), change.value.clone()).index());
+    }
+    Event::new("parameter_change", attributes)
+}
+
+/// Funds were paid out of the community pool by a proposal.
+pub fn community_pool_spend(proposal_id: u64, outputs: &[CommunityPoolOutput]) -> Event {
+    let mut attributes = vec![("proposal_id", proposal_id.to_string()).index()];
+    for output in outputs {
+        attributes.push(("asset_id", output.value.asset_id.to_string()).index());
+        attributes.push(("amount", output.value.amount.to_string()).no_index());
+    }
+    Event::new("community_pool_spend", attributes)
+}
@@ -3,19 +3,32 @@
 // marked as unreachable only when not building in test configuration.
 #![allow(unreachable_patterns)]
 
-mod client;
+pub mod channel;
+pub mod client;
+pub mod connection;
+mod event;
+pub mod state_key;
+pub mod transfer;
 
 use crate::components::Component;
 use crate::{genesis, Overlay};
 use anyhow::Result;
 use async_trait::async_trait;
+use channel::ChannelComponent;
 use client::ClientComponent;
+use connection::ConnectionComponent;
 use penumbra_transaction::Transaction;
 use tendermint::abci;
 use tracing::instrument;
+use transfer::TransferComponent;
+
+pub use transfer::View;
 
 pub struct IBCComponent {
     client: client::ClientComponent,
+    connection: connection::ConnectionComponent,
+    channel: channel::ChannelComponent,
+    transfer: transfer::TransferComponent,
 }
 
 #[async_trait]
@@ -23,23 +36,40 @@ impl Component for IBCComponent {
     #[instrument(name = "ibc", skip(overlay))]
     async fn new(overlay: Overlay) -> Self {
         let client = ClientComponent::new(overlay.clone()).await;
+        let connection = ConnectionComponent::new(overlay.clone()).await;
+        let channel = ChannelComponent::new(overlay.clone()).await;
+        let transfer = TransferComponent::new(overlay.clone()).await;
 
-        Self { client }
+        Self {
+            client,
+            connection,
+            channel,
+            transfer,
+        }
     }
 
     #[instrument(name = "ibc", skip(self, app_state))]
     async fn init_chain(&mut self, app_state: &genesis::AppState) {
         self.client.init_chain(app_state).await;
+        self.connection.init_chain(app_state).await;
+        self.channel.init_chain(app_state).await;
+        self.transfer.init_chain(app_state).await;
     }
 
     #[instrument(name = "ibc", skip(self, begin_block))]
     async fn begin_block(&mut self, begin_block: &abci::request::BeginBlock) {
         self.client.begin_block(begin_block).await;
+        self.connection.begin_block(begin_block).await;
+        self.channel.begin_block(begin_block).await;
+        self.transfer.begin_block(begin_block).await;
     }
 
     #[instrument(name = "ibc", skip(tx))]
     fn check_tx_stateless(tx: &Transaction) -> Result<()> {
         client::ClientComponent::check_tx_stateless(tx)?;
+        connection::ConnectionComponent::check_tx_stateless(tx)?;
+        channel::ChannelComponent::check_tx_stateless(tx)?;
+        transfer::TransferComponent::check_tx_stateless(tx)?;
 
         Ok(())
     }
@@ -47,6 +77,9 @@ impl Component for IBCComponent {
     #[instrument(name = "ibc", skip(self, tx))]
     async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
         self.client.check_tx_stateful(tx).await?;
+        self.connection.check_tx_stateful(tx).await?;
+        self.channel.check_tx_stateful(tx).await?;
+        self.transfer.check_tx_stateful(tx).await?;
 
         Ok(())
     }
@@ -54,10 +87,24 @@ impl Component for IBCComponent {
     #[instrument(name = "ibc", skip(self, tx))]
     async fn execute_tx(&mut self, tx: &Transaction) {
         self.client.execute_tx(tx).await;
+        self.connection.execute_tx(tx).await;
+        self.channel.execute_tx(tx).await;
+        self.transfer.execute_tx(tx).await;
     }
 
     #[instrument(name = "ibc", skip(self, end_block))]
     async fn end_block(&mut self, end_block: &abci::request::EndBlock) {
         self.client.end_block(end_block).await;
+        self.connection.end_block(end_block).await;
+        self.channel.end_block(end_block).await;
+        self.transfer.end_block(end_block).await;
+    }
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        let mut events = self.client.take_events();
+        events.extend(self.connection.take_events());
+        events.extend(self.channel.take_events());
+        events.extend(self.transfer.take_events());
+        events
     }
 }
@@ -0,0 +1,1556 @@
+use std::{convert::TryFrom, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ibc::{
+    clients::ics07_tendermint::client_state::ClientState as TendermintClientState,
+    core::{
+        ics02_client::{client_state::AnyClientState, client_state::ClientState, height::Height},
+        ics03_connection::connection::{ConnectionEnd, State as ConnectionState},
+        ics04_channel::{
+            channel::{ChannelEnd, Counterparty, Order, State},
+            msgs::{
+                acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
+                chan_close_init::MsgChannelCloseInit, chan_open_ack::MsgChannelOpenAck,
+                chan_open_confirm::MsgChannelOpenConfirm, chan_open_init::MsgChannelOpenInit,
+                chan_open_try::MsgChannelOpenTry, recv_packet::MsgRecvPacket, timeout::MsgTimeout,
+            },
+            packet::{Packet, Sequence},
+        },
+        ics23_commitment::{
+            commitment::{CommitmentProofBytes, CommitmentRoot},
+            merkle::{apply_prefix, MerkleProof},
+        },
+        ics24_host::{
+            identifier::{ChainId, ChannelId, ConnectionId, PortId},
+            path::{
+                AcksPath, ChannelEndsPath, CommitmentsPath, ReceiptsPath, SeqAcksPath,
+                SeqRecvsPath, SeqSendsPath,
+            },
+        },
+    },
+    proofs::Proofs,
+};
+use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+use penumbra_ibc::{Channel, ChannelCounter, IBCAction, TRANSFER_PORT, TRANSFER_VERSION};
+use penumbra_proto::{
+    ibc::ibc_action::Action::{
+        Acknowledgement, ChannelCloseConfirm, ChannelCloseInit, ChannelOpenAck, ChannelOpenConfirm,
+        ChannelOpenInit, ChannelOpenTry, RecvPacket, Timeout,
+    },
+    Protobuf,
+};
+use penumbra_transaction::Transaction;
+use sha2::{Digest, Sha256};
+use tendermint::{abci, Time};
+use tracing::instrument;
+
+use super::{client::View as _, connection::View as _, event, state_key, transfer};
+use crate::{components::app::View as _, components::Component};
+use crate::{genesis, Overlay, OverlayExt};
+
+/// The longest we expect a block to take, used to convert a connection's delay period into a
+/// number of blocks.
+const MAX_EXPECTED_TIME_PER_BLOCK: Duration = Duration::from_secs(30);
+
+/// The Penumbra IBC channel component. Handles the ICS-04 channel handshake messages
+/// (MsgChannelOpenInit, MsgChannelOpenTry, MsgChannelOpenAck, MsgChannelOpenConfirm,
+/// MsgChannelCloseInit and MsgChannelCloseConfirm) and the packet lifecycle messages
+/// (MsgRecvPacket, MsgAcknowledgement and MsgTimeout). Packet commitments, receipts and
+/// acknowledgements are stored under their ICS-24 paths, so that counterparties can verify them.
+///
+/// The ICS-20 transfer application is the only one bound to a port, so channels can only be
+/// opened on the `transfer` port, and every packet is handed to the [`transfer`] module.
+pub struct ChannelComponent {
+    overlay: Overlay,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
+}
+
+#[async_trait]
+impl Component for ChannelComponent {
+    #[instrument(name = "ics4_channel", skip(overlay))]
+    async fn new(overlay: Overlay) -> Self {
+        Self {
+            overlay,
+            events: Vec::new(),
+        }
+    }
+
+    #[instrument(name = "ics4_channel", skip(self, _app_state))]
+    async fn init_chain(&mut self, _app_state: &genesis::AppState) {
+        // set the initial channel count
+        self.overlay.put_channel_counter(ChannelCounter(0)).await;
+    }
+
+    #[instrument(name = "ics4_channel", skip(self, _begin_block))]
+    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+
+    #[instrument(name = "ics4_channel", skip(tx))]
+    fn check_tx_stateless(tx: &Transaction) -> Result<()> {
+        for ibc_action in tx.ibc_actions() {
+            validate_ibc_action_stateless(ibc_action)?;
+        }
+        Ok(())
+    }
+
+    #[instrument(name = "ics4_channel", skip(self, tx))]
+    async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        for ibc_action in tx.ibc_actions() {
+            self.validate_ibc_action_stateful(ibc_action).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(name = "ics4_channel", skip(self, tx))]
+    async fn execute_tx(&mut self, tx: &Transaction) {
+        for ibc_action in tx.ibc_actions() {
+            self.execute_ibc_action(ibc_action).await;
+        }
+    }
+
+    #[instrument(name = "ics4_channel", skip(self, _end_block))]
+    async fn end_block(&mut self, _end_block: &abci::request::EndBlock) {}
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+// validates the given ibc action statelessly
+fn validate_ibc_action_stateless(ibc_action: &IBCAction) -> Result<()> {
+    match &ibc_action.action {
+        ChannelOpenInit(msg) => {
+            let msg = MsgChannelOpenInit::try_from(msg.clone())?;
+
+            if !msg.channel.state_matches(&State::Init) {
+                return Err(anyhow::anyhow!("channel must be in the INIT state"));
+            }
+            validate_connection_hops(&msg.channel)?;
+            // the counterparty channel ID is only known once the counterparty has executed
+            // ChannelOpenTry.
+            if msg.channel.counterparty().channel_id().is_some() {
+                return Err(anyhow::anyhow!(
+                    "counterparty channel ID must be empty in ChannelOpenInit"
+                ));
+            }
+        }
+        ChannelOpenTry(msg) => {
+            let msg = MsgChannelOpenTry::try_from(msg.clone())?;
+
+            if msg.previous_channel_id.is_some() {
+                return Err(anyhow::anyhow!(
+                    "resuming from a previous channel is not supported"
+                ));
+            }
+            if !msg.channel.state_matches(&State::TryOpen) {
+                return Err(anyhow::anyhow!("channel must be in the TRYOPEN state"));
+            }
+            validate_connection_hops(&msg.channel)?;
+            if msg.channel.counterparty().channel_id().is_none() {
+                return Err(anyhow::anyhow!(
+                    "counterparty channel ID must be set in ChannelOpenTry"
+                ));
+            }
+        }
+        ChannelOpenAck(msg) => {
+            MsgChannelOpenAck::try_from(msg.clone())?;
+        }
+        ChannelOpenConfirm(msg) => {
+            MsgChannelOpenConfirm::try_from(msg.clone())?;
+        }
+        ChannelCloseInit(msg) => {
+            MsgChannelCloseInit::try_from(msg.clone())?;
+        }
+        ChannelCloseConfirm(msg) => {
+            MsgChannelCloseConfirm::try_from(msg.clone())?;
+        }
+        RecvPacket(msg) => {
+            let msg = MsgRecvPacket::try_from(msg.clone())?;
+
+            validate_packet_stateless(&msg.packet)?;
+        }
+        Acknowledgement(msg) => {
+            let msg = MsgAcknowledgement::try_from(msg.clone())?;
+
+            validate_packet_stateless(&msg.packet)?;
+        }
+        Timeout(msg) => {
+            let msg = MsgTimeout::try_from(msg.clone())?;
+
+            validate_packet_stateless(&msg.packet)?;
+        }
+        _ => return Ok(()),
+    }
+
+    Ok(())
+}
+
+// multi-hop channels are not supported by ICS-04.
+fn validate_connection_hops(channel: &ChannelEnd) -> Result<()> {
+    if channel.connection_hops().len() != 1 {
+        return Err(anyhow::anyhow!(
+            "channels must have exactly one connection hop"
+        ));
+    }
+
+    Ok(())
+}
+
+// check that a packet has a nonzero sequence number and at least one timeout.
+fn validate_packet_stateless(packet: &Packet) -> Result<()> {
+    if u64::from(packet.sequence) == 0 {
+        return Err(anyhow::anyhow!("packet sequence must be nonzero"));
+    }
+    if packet.timeout_height.is_zero() && packet.timeout_timestamp.nanoseconds() == 0 {
+        return Err(anyhow::anyhow!(
+            "packet must have a timeout height or timestamp"
+        ));
+    }
+
+    Ok(())
+}
+
+impl ChannelComponent {
+    // validates the given IBC action statefully.
+    async fn validate_ibc_action_stateful(&self, ibc_action: &IBCAction) -> Result<()> {
+        match &ibc_action.action {
+            ChannelOpenInit(msg) => {
+                let msg = MsgChannelOpenInit::try_from(msg.clone())?;
+
+                self.validate_channel_open_init_stateful(&msg).await?;
+            }
+            ChannelOpenTry(msg) => {
+                let msg = MsgChannelOpenTry::try_from(msg.clone())?;
+
+                self.validate_channel_open_try_stateful(&msg).await?;
+            }
+            ChannelOpenAck(msg) => {
+                let msg = MsgChannelOpenAck::try_from(msg.clone())?;
+
+                self.validate_channel_open_ack_stateful(&msg).await?;
+            }
+            ChannelOpenConfirm(msg) => {
+                let msg = MsgChannelOpenConfirm::try_from(msg.clone())?;
+
+                self.validate_channel_open_confirm_stateful(&msg).await?;
+            }
+            ChannelCloseInit(msg) => {
+                let msg = MsgChannelCloseInit::try_from(msg.clone())?;
+
+                self.validate_channel_close_init_stateful(&msg).await?;
+            }
+            ChannelCloseConfirm(msg) => {
+                let msg = MsgChannelCloseConfirm::try_from(msg.clone())?;
+
+                self.validate_channel_close_confirm_stateful(&msg).await?;
+            }
+            RecvPacket(msg) => {
+                let msg = MsgRecvPacket::try_from(msg.clone())?;
+
+                self.validate_recv_packet_stateful(&msg).await?;
+            }
+            Acknowledgement(msg) => {
+                let msg = MsgAcknowledgement::try_from(msg.clone())?;
+
+                self.validate_acknowledgement_stateful(&msg).await?;
+            }
+            Timeout(msg) => {
+                let msg = MsgTimeout::try_from(msg.clone())?;
+
+                self.validate_timeout_stateful(&msg).await?;
+            }
+            _ => return Ok(()),
+        }
+
+        Ok(())
+    }
+
+    // executes the given IBC action, assuming that it has already been validated.
+    async fn execute_ibc_action(&mut self, ibc_action: &IBCAction) {
+        match &ibc_action.action {
+            ChannelOpenInit(raw_msg) => {
+                let msg = MsgChannelOpenInit::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_channel_open_init(msg).await;
+            }
+            ChannelOpenTry(raw_msg) => {
+                let msg = MsgChannelOpenTry::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_channel_open_try(msg).await;
+            }
+            ChannelOpenAck(raw_msg) => {
+                let msg = MsgChannelOpenAck::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_channel_open_ack(msg).await;
+            }
+            ChannelOpenConfirm(raw_msg) => {
+                let msg = MsgChannelOpenConfirm::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_channel_open_confirm(msg).await;
+            }
+            ChannelCloseInit(raw_msg) => {
+                let msg = MsgChannelCloseInit::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_channel_close_init(msg).await;
+            }
+            ChannelCloseConfirm(raw_msg) => {
+                let msg = MsgChannelCloseConfirm::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_channel_close_confirm(msg).await;
+            }
+            RecvPacket(raw_msg) => {
+                let msg = MsgRecvPacket::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_recv_packet(msg).await;
+            }
+            Acknowledgement(raw_msg) => {
+                let msg = MsgAcknowledgement::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_acknowledgement(msg).await;
+            }
+            Timeout(raw_msg) => {
+                let msg = MsgTimeout::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_timeout(msg).await;
+            }
+            _ => {}
+        }
+    }
+
+    // verify:
+    // - the channel is for the transfer application
+    // - the connection the channel is built on exists
+    async fn validate_channel_open_init_stateful(&self, msg: &MsgChannelOpenInit) -> Result<()> {
+        validate_transfer_channel(&msg.port_id, &msg.channel)?;
+
+        self.overlay
+            .get_connection(&msg.channel.connection_hops()[0])
+            .await?;
+
+        Ok(())
+    }
+
+    // verify:
+    // - the channel is for the transfer application
+    // - the connection the channel is built on is open
+    // - the counterparty has stored a channel end in the INIT state, pointing at our port
+    async fn validate_channel_open_try_stateful(&self, msg: &MsgChannelOpenTry) -> Result<()> {
+        validate_transfer_channel(&msg.port_id, &msg.channel)?;
+
+        let connection = self
+            .open_connection(&msg.channel.connection_hops()[0])
+            .await?;
+
+        let expected_counterparty_channel = ChannelEnd::new(
+            State::Init,
+            msg.channel.ordering().clone(),
+            Counterparty::new(msg.port_id.clone(), None),
+            vec![counterparty_connection_id(&connection)?],
+            msg.counterparty_version.clone(),
+        );
+
+        let counterparty = msg.channel.counterparty();
+        self.verify_membership(
+            &connection,
+            &msg.proofs,
+            ChannelEndsPath(
+                counterparty.port_id().clone(),
+                counterparty
+                    .channel_id()
+                    .expect("counterparty channel ID is checked statelessly")
+                    .clone(),
+            )
+            .to_string(),
+            Channel(expected_counterparty_channel).encode_to_vec(),
+        )
+        .await
+    }
+
+    // verify:
+    // - the channel exists and is in the INIT state
+    // - the connection the channel is built on is open
+    // - the counterparty has stored a channel end in the TRYOPEN state, pointing at our channel
+    async fn validate_channel_open_ack_stateful(&self, msg: &MsgChannelOpenAck) -> Result<()> {
+        let channel = self
+            .overlay
+            .get_channel(&msg.port_id, &msg.channel_id)
+            .await?;
+        if !channel.state_matches(&State::Init) {
+            return Err(anyhow::anyhow!("channel is not in the INIT state"));
+        }
+
+        let connection = self.open_connection(&channel.connection_hops()[0]).await?;
+
+        let expected_counterparty_channel = ChannelEnd::new(
+            State::TryOpen,
+            channel.ordering().clone(),
+            Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone())),
+            vec![counterparty_connection_id(&connection)?],
+            msg.counterparty_version.clone(),
+        );
+
+        self.verify_membership(
+            &connection,
+            &msg.proofs,
+            ChannelEndsPath(
+                channel.counterparty().port_id().clone(),
+                msg.counterparty_channel_id.clone(),
+            )
+            .to_string(),
+            Channel(expected_counterparty_channel).encode_to_vec(),
+        )
+        .await
+    }
+
+    // verify:
+    // - the channel exists and is in the TRYOPEN state
+    // - the connection the channel is built on is open
+    // - the counterparty has stored a channel end in the OPEN state, pointing at our channel
+    async fn validate_channel_open_confirm_stateful(
+        &self,
+        msg: &MsgChannelOpenConfirm,
+    ) -> Result<()> {
+        let channel = self
+            .overlay
+            .get_channel(&msg.port_id, &msg.channel_id)
+            .await?;
+        if !channel.state_matches(&State::TryOpen) {
+            return Err(anyhow::anyhow!("channel is not in the TRYOPEN state"));
+        }
+
+        let connection = self.open_connection(&channel.connection_hops()[0]).await?;
+
+        let expected_counterparty_channel = ChannelEnd::new(
+            State::Open,
+            channel.ordering().clone(),
+            Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone())),
+            vec![counterparty_connection_id(&connection)?],
+            channel.version().clone(),
+        );
+
+        self.verify_membership(
+            &connection,
+            &msg.proofs,
+            counterparty_channel_path(&channel)?,
+            Channel(expected_counterparty_channel).encode_to_vec(),
+        )
+        .await
+    }
+
+    // verify:
+    // - the channel exists and is not already closed
+    // - the connection the channel is built on is open
+    async fn validate_channel_close_init_stateful(&self, msg: &MsgChannelCloseInit) -> Result<()> {
+        let channel = self
+            .overlay
+            .get_channel(&msg.port_id, &msg.channel_id)
+            .await?;
+        if channel.state_matches(&State::Closed) {
+            return Err(anyhow::anyhow!("channel is already closed"));
+        }
+
+        self.open_connection(&channel.connection_hops()[0]).await?;
+
+        Ok(())
+    }
+
+    // verify:
+    // - the channel exists and is not already closed
+    // - the connection the channel is built on is open
+    // - the counterparty has closed its end of the channel
+    async fn validate_channel_close_confirm_stateful(
+        &self,
+        msg: &MsgChannelCloseConfirm,
+    ) -> Result<()> {
+        let channel = self
+            .overlay
+            .get_channel(&msg.port_id, &msg.channel_id)
+            .await?;
+        if channel.state_matches(&State::Closed) {
+            return Err(anyhow::anyhow!("channel is already closed"));
+        }
+
+        let connection = self.open_connection(&channel.connection_hops()[0]).await?;
+
+        let expected_counterparty_channel = ChannelEnd::new(
+            State::Closed,
+            channel.ordering().clone(),
+            Counterparty::new(msg.port_id.clone(), Some(msg.channel_id.clone())),
+            vec![counterparty_connection_id(&connection)?],
+            channel.version().clone(),
+        );
+
+        self.verify_membership(
+            &connection,
+            &msg.proofs,
+            counterparty_channel_path(&channel)?,
+            Channel(expected_counterparty_channel).encode_to_vec(),
+        )
+        .await
+    }
+
+    // verify:
+    // - the destination channel is open, and its counterparty is the packet's source
+    // - the packet has not timed out, according to our block height and time
+    // - the packet is the next one expected on an ordered channel, or has not already been
+    //   received on an unordered channel
+    // - the counterparty has committed to the packet
+    async fn validate_recv_packet_stateful(&self, msg: &MsgRecvPacket) -> Result<()> {
+        let packet = &msg.packet;
+        let channel = self
+            .open_channel(&packet.destination_port, &packet.destination_channel)
+            .await?;
+        if channel.counterparty().port_id() != &packet.source_port
+            || channel.counterparty().channel_id() != Some(&packet.source_channel)
+        {
+            return Err(anyhow::anyhow!(
+                "packet source does not match the channel counterparty"
+            ));
+        }
+
+        let connection = self.open_connection(&channel.connection_hops()[0]).await?;
+
+        let chain_id = self.overlay.get_chain_params().await?.chain_id;
+        let height = Height::new(
+            ChainId::chain_version(&chain_id),
+            self.overlay.get_block_height().await?,
+        );
+        if !packet.timeout_height.is_zero() && height >= packet.timeout_height {
+            return Err(anyhow::anyhow!(
+                "packet timed out at height {}",
+                packet.timeout_height
+            ));
+        }
+        let now = self.overlay.get_block_timestamp().await?;
+        let timeout_timestamp = packet.timeout_timestamp.nanoseconds();
+        if timeout_timestamp != 0 && unix_nanos(now)? >= timeout_timestamp {
+            return Err(anyhow::anyhow!(
+                "packet timed out at timestamp {}",
+                timeout_timestamp
+            ));
+        }
+
+        let sequence = u64::from(packet.sequence);
+        match channel.ordering() {
+            Order::Ordered => {
+                let next_sequence_recv = self
+                    .overlay
+                    .next_sequence_recv(&packet.destination_port, &packet.destination_channel)
+                    .await?;
+                if sequence != next_sequence_recv {
+                    return Err(anyhow::anyhow!(
+                        "packet sequence {} does not match the next receive sequence {}",
+                        sequence,
+                        next_sequence_recv
+                    ));
+                }
+            }
+            _ => {
+                if self
+                    .overlay
+                    .packet_receipt(
+                        &packet.destination_port,
+                        &packet.destination_channel,
+                        packet.sequence,
+                    )
+                    .await?
+                {
+                    return Err(anyhow::anyhow!("packet {} was already received", sequence));
+                }
+            }
+        }
+
+        self.verify_membership(
+            &connection,
+            &msg.proofs,
+            CommitmentsPath {
+                port_id: packet.source_port.clone(),
+                channel_id: packet.source_channel.clone(),
+                sequence: packet.sequence,
+            }
+            .to_string(),
+            commit_packet(packet),
+        )
+        .await
+    }
+
+    // verify:
+    // - the source channel is open, and its counterparty is the packet's destination
+    // - we committed to this packet, and have not yet received an acknowledgement or timeout
+    // - on an ordered channel, the packet is the next one to be acknowledged
+    // - the counterparty has written the acknowledgement
+    async fn validate_acknowledgement_stateful(&self, msg: &MsgAcknowledgement) -> Result<()> {
+        let packet = &msg.packet;
+        let channel = self.sent_packet_channel(packet).await?;
+        let connection = self.open_connection(&channel.connection_hops()[0]).await?;
+
+        if channel.ordering() == &Order::Ordered {
+            let next_sequence_ack = self
+                .overlay
+                .next_sequence_ack(&packet.source_port, &packet.source_channel)
+                .await?;
+            if u64::from(packet.sequence) != next_sequence_ack {
+                return Err(anyhow::anyhow!(
+                    "packet sequence {} does not match the next acknowledgement sequence {}",
+                    packet.sequence,
+                    next_sequence_ack
+                ));
+            }
+        }
+
+        self.verify_membership(
+            &connection,
+            &msg.proofs,
+            AcksPath {
+                port_id: packet.destination_port.clone(),
+                channel_id: packet.destination_channel.clone(),
+                sequence: packet.sequence,
+            }
+            .to_string(),
+            commit_acknowledgement(&msg.acknowledgement),
+        )
+        .await
+    }
+
+    // verify:
+    // - the source channel is open, and its counterparty is the packet's destination
+    // - we committed to this packet, and have not yet received an acknowledgement or timeout
+    // - the counterparty chain has passed the packet's timeout height or timestamp, as of the
+    //   height of the proof
+    // - the counterparty has not received the packet
+    async fn validate_timeout_stateful(&self, msg: &MsgTimeout) -> Result<()> {
+        let packet = &msg.packet;
+        let channel = self.sent_packet_channel(packet).await?;
+        let connection = self.open_connection(&channel.connection_hops()[0]).await?;
+
+        let proof_height = msg.proofs.height();
+        let height_timed_out =
+            !packet.timeout_height.is_zero() && proof_height >= packet.timeout_height;
+        let timeout_timestamp = packet.timeout_timestamp.nanoseconds();
+        let timestamp_timed_out = if timeout_timestamp != 0 {
+            let counterparty_time = self
+                .overlay
+                .get_verified_consensus_state(proof_height, connection.client_id().clone())
+                .await?
+                .as_tendermint()?
+                .timestamp;
+            unix_nanos(counterparty_time)? >= timeout_timestamp
+        } else {
+            false
+        };
+        if !height_timed_out && !timestamp_timed_out {
+            return Err(anyhow::anyhow!("packet has not timed out"));
+        }
+
+        match channel.ordering() {
+            Order::Ordered => {
+                if u64::from(msg.next_sequence_recv) > u64::from(packet.sequence) {
+                    return Err(anyhow::anyhow!("packet was already received"));
+                }
+                self.verify_membership(
+                    &connection,
+                    &msg.proofs,
+                    SeqRecvsPath(
+                        packet.destination_port.clone(),
+                        packet.destination_channel.clone(),
+                    )
+                    .to_string(),
+                    u64::from(msg.next_sequence_recv).to_be_bytes().to_vec(),
+                )
+                .await
+            }
+            _ => {
+                self.verify_non_membership(
+                    &connection,
+                    &msg.proofs,
+                    ReceiptsPath {
+                        port_id: packet.destination_port.clone(),
+                        channel_id: packet.destination_channel.clone(),
+                        sequence: packet.sequence,
+                    }
+                    .to_string(),
+                )
+                .await
+            }
+        }
+    }
+
+    // get the channel a packet was sent on, checking that it is open, that the packet's
+    // destination is its counterparty, and that we still hold a commitment to the packet.
+    async fn sent_packet_channel(&self, packet: &Packet) -> Result<ChannelEnd> {
+        let channel = self
+            .open_channel(&packet.source_port, &packet.source_channel)
+            .await?;
+        if channel.counterparty().port_id() != &packet.destination_port
+            || channel.counterparty().channel_id() != Some(&packet.destination_channel)
+        {
+            return Err(anyhow::anyhow!(
+                "packet destination does not match the channel counterparty"
+            ));
+        }
+
+        let commitment = self
+            .overlay
+            .packet_commitment(&packet.source_port, &packet.source_channel, packet.sequence)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no commitment to packet {}", packet.sequence))?;
+        if commitment != commit_packet(packet) {
+            return Err(anyhow::anyhow!(
+                "packet does not match the commitment to packet {}",
+                packet.sequence
+            ));
+        }
+
+        Ok(channel)
+    }
+
+    async fn open_channel(&self, port_id: &PortId, channel_id: &ChannelId) -> Result<ChannelEnd> {
+        let channel = self.overlay.get_channel(port_id, channel_id).await?;
+        if !channel.state_matches(&State::Open) {
+            return Err(anyhow::anyhow!("channel {} is not open", channel_id));
+        }
+
+        Ok(channel)
+    }
+
+    async fn open_connection(&self, connection_id: &ConnectionId) -> Result<ConnectionEnd> {
+        let connection = self.overlay.get_connection(connection_id).await?;
+        if !connection.state_matches(&ConnectionState::Open) {
+            return Err(anyhow::anyhow!("connection {} is not open", connection_id));
+        }
+
+        Ok(connection)
+    }
+
+    // verify a proof that the counterparty of `connection` has stored `value` under `path`, as of
+    // the proof height.
+    async fn verify_membership(
+        &self,
+        connection: &ConnectionEnd,
+        proofs: &Proofs,
+        path: String,
+        value: Vec<u8>,
+    ) -> Result<()> {
+        let (client_state, root, proof) = self
+            .counterparty_proof(connection, proofs.height(), proofs.object_proof())
+            .await?;
+        self.verify_delay_passed(connection, proofs.height())
+            .await?;
+
+        proof
+            .verify_membership(
+                &client_state.proof_specs,
+                root.into(),
+                apply_prefix(connection.counterparty().prefix(), vec![path]),
+                value,
+                0,
+            )
+            .map_err(|e| anyhow::anyhow!("could not verify counterparty state: {}", e))
+    }
+
+    // verify a proof that the counterparty of `connection` has nothing stored under `path`, as
+    // of the proof height.
+    async fn verify_non_membership(
+        &self,
+        connection: &ConnectionEnd,
+        proofs: &Proofs,
+        path: String,
+    ) -> Result<()> {
+        let (client_state, root, proof) = self
+            .counterparty_proof(connection, proofs.height(), proofs.object_proof())
+            .await?;
+        self.verify_delay_passed(connection, proofs.height())
+            .await?;
+
+        proof
+            .verify_non_membership(
+                &client_state.proof_specs,
+                root.into(),
+                apply_prefix(connection.counterparty().prefix(), vec![path]),
+            )
+            .map_err(|e| anyhow::anyhow!("could not verify counterparty state: {}", e))
+    }
+
+    // check that the delay period of `connection` has passed since the consensus state at
+    // `proof_height` was processed, so that misbehaviour can be submitted before a proof against
+    // it is accepted.
+    async fn verify_delay_passed(
+        &self,
+        connection: &ConnectionEnd,
+        proof_height: Height,
+    ) -> Result<()> {
+        let (processed_time, processed_height) = self
+            .overlay
+            .get_verified_consensus_state_processed(proof_height, connection.client_id().clone())
+            .await?;
+        let now = self.overlay.get_block_timestamp().await?;
+        let height = self.overlay.get_block_height().await?;
+
+        delay_period_passed(
+            connection.delay_period(),
+            (processed_time, processed_height),
+            (now, height),
+        )
+    }
+
+    // look up the client state and the trusted commitment root needed to verify a proof of the
+    // counterparty's state at `proof_height`.
+    async fn counterparty_proof(
+        &self,
+        connection: &ConnectionEnd,
+        proof_height: Height,
+        proof: &CommitmentProofBytes,
+    ) -> Result<(TendermintClientState, CommitmentRoot, MerkleProof)> {
+        let client_data = self.overlay.get_client_data(connection.client_id()).await?;
+        if client_data.client_state.0.is_frozen() {
+            return Err(anyhow::anyhow!(
+                "client {} is frozen",
+                connection.client_id()
+            ));
+        }
+        let client_state = match client_data.client_state.0 {
+            AnyClientState::Tendermint(tm_state) => tm_state,
+            _ => return Err(anyhow::anyhow!("unsupported client type")),
+        };
+
+        let root = self
+            .overlay
+            .get_verified_consensus_state(proof_height, connection.client_id().clone())
+            .await?
+            .as_tendermint()?
+            .root;
+
+        let proof: MerkleProof = RawMerkleProof::try_from(proof.clone())
+            .map_err(|e| anyhow::anyhow!("could not decode merkle proof: {}", e))?
+            .into();
+
+        Ok((client_state, root, proof))
+    }
+
+    // execute ChannelOpenInit, creating a new channel in the INIT state.
+    async fn execute_channel_open_init(&mut self, msg: MsgChannelOpenInit) {
+        let channel_id = self.add_channel(&msg.port_id, msg.channel.clone()).await;
+        self.events.push(event::channel_open_init(
+            &msg.port_id,
+            &channel_id,
+            &msg.channel,
+        ));
+    }
+
+    // execute ChannelOpenTry, creating a new channel in the TRYOPEN state.
+    async fn execute_channel_open_try(&mut self, msg: MsgChannelOpenTry) {
+        let channel_id = self.add_channel(&msg.port_id, msg.channel.clone()).await;
+        self.events.push(event::channel_open_try(
+            &msg.port_id,
+            &channel_id,
+            &msg.channel,
+        ));
+    }
+
+    // execute ChannelOpenAck, opening a channel in the INIT state.
+    async fn execute_channel_open_ack(&mut self, msg: MsgChannelOpenAck) {
+        let channel = self
+            .overlay
+            .get_channel(&msg.port_id, &msg.channel_id)
+            .await
+            .unwrap();
+
+        let channel = ChannelEnd::new(
+            State::Open,
+            channel.ordering().clone(),
+            Counterparty::new(
+                channel.counterparty().port_id().clone(),
+                Some(msg.counterparty_channel_id),
+            ),
+            channel.connection_hops().clone(),
+            msg.counterparty_version,
+        );
+
+        self.overlay
+            .put_channel(&msg.port_id, &msg.channel_id, channel.clone())
+            .await;
+        self.events.push(event::channel_open_ack(
+            &msg.port_id,
+            &msg.channel_id,
+            &channel,
+        ));
+    }
+
+    // execute ChannelOpenConfirm, opening a channel in the TRYOPEN state.
+    async fn execute_channel_open_confirm(&mut self, msg: MsgChannelOpenConfirm) {
+        let channel = self
+            .set_channel_state(&msg.port_id, &msg.channel_id, State::Open)
+            .await;
+        self.events.push(event::channel_open_confirm(
+            &msg.port_id,
+            &msg.channel_id,
+            &channel,
+        ));
+    }
+
+    // execute ChannelCloseInit, closing our end of a channel.
+    async fn execute_channel_close_init(&mut self, msg: MsgChannelCloseInit) {
+        let channel = self
+            .set_channel_state(&msg.port_id, &msg.channel_id, State::Closed)
+            .await;
+        self.events.push(event::channel_close_init(
+            &msg.port_id,
+            &msg.channel_id,
+            &channel,
+        ));
+    }
+
+    // execute ChannelCloseConfirm, closing our end of a channel the counterparty has closed.
+    async fn execute_channel_close_confirm(&mut self, msg: MsgChannelCloseConfirm) {
+        let channel = self
+            .set_channel_state(&msg.port_id, &msg.channel_id, State::Closed)
+            .await;
+        self.events.push(event::channel_close_confirm(
+            &msg.port_id,
+            &msg.channel_id,
+            &channel,
+        ));
+    }
+
+    // execute RecvPacket, recording that the packet was received and writing its
+    // acknowledgement.
+    async fn execute_recv_packet(&mut self, msg: MsgRecvPacket) {
+        let packet = msg.packet;
+        let port_id = &packet.destination_port;
+        let channel_id = &packet.destination_channel;
+        let channel = self.overlay.get_channel(port_id, channel_id).await.unwrap();
+
+        match channel.ordering() {
+            Order::Ordered => {
+                let next_sequence_recv = self
+                    .overlay
+                    .next_sequence_recv(port_id, channel_id)
+                    .await
+                    .unwrap();
+                self.overlay
+                    .put_next_sequence_recv(port_id, channel_id, next_sequence_recv + 1)
+                    .await;
+            }
+            _ => {
+                self.overlay
+                    .put_packet_receipt(port_id, channel_id, packet.sequence)
+                    .await;
+            }
+        }
+        self.events.push(event::recv_packet(&packet, &channel));
+
+        // A packet the transfer application can't handle is still received, but
+        // is acknowledged with an error, so that the sender can refund it.
+        let ack = match transfer::recv_packet(&mut self.overlay, &packet).await {
+            Ok(()) => transfer::success_acknowledgement(),
+            Err(e) => {
+                tracing::info!(?e, sequence = %packet.sequence, "rejecting ics20 transfer");
+                transfer::error_acknowledgement(&e)
+            }
+        };
+        self.overlay
+            .put_packet_acknowledgement(
+                port_id,
+                channel_id,
+                packet.sequence,
+                commit_acknowledgement(&ack),
+            )
+            .await;
+        self.events
+            .push(event::write_acknowledgement(&packet, &channel, &ack));
+    }
+
+    // execute Acknowledgement, deleting the commitment to the acknowledged packet.
+    async fn execute_acknowledgement(&mut self, msg: MsgAcknowledgement) {
+        let packet = msg.packet;
+        let port_id = &packet.source_port;
+        let channel_id = &packet.source_channel;
+        let channel = self.overlay.get_channel(port_id, channel_id).await.unwrap();
+
+        self.overlay
+            .delete_packet_commitment(port_id, channel_id, packet.sequence)
+            .await;
+        if channel.ordering() == &Order::Ordered {
+            let next_sequence_ack = self
+                .overlay
+                .next_sequence_ack(port_id, channel_id)
+                .await
+                .unwrap();
+            self.overlay
+                .put_next_sequence_ack(port_id, channel_id, next_sequence_ack + 1)
+                .await;
+        }
+        if let Err(e) =
+            transfer::acknowledge_packet(&mut self.overlay, &packet, &msg.acknowledgement).await
+        {
+            tracing::warn!(?e, sequence = %packet.sequence, "could not handle ics20 acknowledgement");
+        }
+        self.events
+            .push(event::acknowledge_packet(&packet, &channel));
+    }
+
+    // execute Timeout, deleting the commitment to the timed out packet. A timeout closes an
+    // ordered channel, since no later packet can be received on it.
+    async fn execute_timeout(&mut self, msg: MsgTimeout) {
+        let packet = msg.packet;
+        let port_id = &packet.source_port;
+        let channel_id = &packet.source_channel;
+        let channel = self.overlay.get_channel(port_id, channel_id).await.unwrap();
+
+        self.overlay
+            .delete_packet_commitment(port_id, channel_id, packet.sequence)
+            .await;
+        if channel.ordering() == &Order::Ordered {
+            let channel = self
+                .set_channel_state(port_id, channel_id, State::Closed)
+                .await;
+            self.events
+                .push(event::channel_close_confirm(port_id, channel_id, &channel));
+        }
+        if let Err(e) = transfer::refund_packet(&mut self.overlay, &packet).await {
+            tracing::warn!(?e, sequence = %packet.sequence, "could not refund ics20 transfer");
+        }
+        self.events.push(event::timeout_packet(&packet, &channel));
+    }
+
+    // store a new channel under the next channel ID, initializing its sequence numbers.
+    async fn add_channel(&mut self, port_id: &PortId, channel: ChannelEnd) -> ChannelId {
+        let counter = self.overlay.channel_counter().await.unwrap();
+        let channel_id = ChannelId::new(counter.0);
+
+        tracing::info!("creating channel {:?} on port {:?}", channel_id, port_id);
+
+        self.overlay
+            .put_channel(port_id, &channel_id, channel)
+            .await;
+        self.overlay
+            .put_next_sequence_send(port_id, &channel_id, 1)
+            .await;
+        self.overlay
+            .put_next_sequence_recv(port_id, &channel_id, 1)
+            .await;
+        self.overlay
+            .put_next_sequence_ack(port_id, &channel_id, 1)
+            .await;
+        self.overlay
+            .put_channel_counter(ChannelCounter(counter.0 + 1))
+            .await;
+
+        channel_id
+    }
+
+    async fn set_channel_state(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        state: State,
+    ) -> ChannelEnd {
+        let mut channel = self.overlay.get_channel(port_id, channel_id).await.unwrap();
+        channel.set_state(state);
+
+        self.overlay
+            .put_channel(port_id, channel_id, channel.clone())
+            .await;
+
+        channel
+    }
+}
+
+// check that a channel being opened is for the transfer application.
+fn validate_transfer_channel(port_id: &PortId, channel: &ChannelEnd) -> Result<()> {
+    if port_id.as_str() != TRANSFER_PORT {
+        return Err(anyhow::anyhow!(
+            "no application is bound to port {}",
+            port_id
+        ));
+    }
+    if channel.version().to_string() != TRANSFER_VERSION {
+        return Err(anyhow::anyhow!(
+            "unsupported transfer channel version {}",
+            channel.version()
+        ));
+    }
+
+    Ok(())
+}
+
+fn counterparty_connection_id(connection: &ConnectionEnd) -> Result<ConnectionId> {
+    connection
+        .counterparty()
+        .connection_id()
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("counterparty connection ID is not set"))
+}
+
+fn counterparty_channel_path(channel: &ChannelEnd) -> Result<String> {
+    let counterparty = channel.counterparty();
+    let channel_id = counterparty
+        .channel_id()
+        .ok_or_else(|| anyhow::anyhow!("counterparty channel ID is not set"))?;
+
+    Ok(ChannelEndsPath(counterparty.port_id().clone(), channel_id.clone()).to_string())
+}
+
+// check that a delay period has passed between when a consensus state was processed and now, both
+// in time and in blocks. the block delay is the number of blocks expected within the delay period,
+// as in ICS-03.
+fn delay_period_passed(
+    delay_period: Duration,
+    (processed_time, processed_height): (Time, u64),
+    (now, height): (Time, u64),
+) -> Result<()> {
+    let earliest_time = unix_nanos(processed_time)?.saturating_add(delay_period.as_nanos() as u64);
+    if unix_nanos(now)? < earliest_time {
+        return Err(anyhow::anyhow!(
+            "connection delay period has not passed since the consensus state was processed"
+        ));
+    }
+
+    let time_per_block = MAX_EXPECTED_TIME_PER_BLOCK.as_nanos();
+    let block_delay = ((delay_period.as_nanos() + time_per_block - 1) / time_per_block) as u64;
+    if height < processed_height.saturating_add(block_delay) {
+        return Err(anyhow::anyhow!(
+            "connection delay period of {} blocks has not passed since the consensus state was processed",
+            block_delay
+        ));
+    }
+
+    Ok(())
+}
+
+fn unix_nanos(time: Time) -> Result<u64> {
+    Ok(time.duration_since(Time::unix_epoch())?.as_nanos() as u64)
+}
+
+/// The ICS-04 commitment to a packet, which is stored by the sending chain and proven to the
+/// receiving chain.
+pub fn commit_packet(packet: &Packet) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(packet.timeout_timestamp.nanoseconds().to_be_bytes());
+    hasher.update(packet.timeout_height.revision_number.to_be_bytes());
+    hasher.update(packet.timeout_height.revision_height.to_be_bytes());
+    hasher.update(Sha256::digest(&packet.data));
+    hasher.finalize().to_vec()
+}
+
+/// The ICS-04 commitment to a packet acknowledgement.
+pub fn commit_acknowledgement(ack: &[u8]) -> Vec<u8> {
+    Sha256::digest(ack).to_vec()
+}
+
+#[async_trait]
+pub trait View: OverlayExt + Send + Sync {
+    async fn put_channel_counter(&mut self, counter: ChannelCounter) {
+        self.put_domain(state_key::channel_counter().into(), counter)
+            .await;
+    }
+    async fn channel_counter(&self) -> Result<ChannelCounter> {
+        self.get_domain(state_key::channel_counter().into())
+            .await
+            .map(|counter| counter.unwrap_or(ChannelCounter(0)))
+    }
+    async fn put_channel(&mut self, port_id: &PortId, channel_id: &ChannelId, channel: ChannelEnd) {
+        self.put_domain(
+            state_key::channel(port_id, channel_id).into(),
+            Channel(channel),
+        )
+        .await;
+    }
+    async fn get_channel(&self, port_id: &PortId, channel_id: &ChannelId) -> Result<ChannelEnd> {
+        self.get_domain::<Channel, _>(state_key::channel(port_id, channel_id).into())
+            .await?
+            .map(|channel| channel.0)
+            .ok_or_else(|| anyhow::anyhow!("channel {} on port {} not found", channel_id, port_id))
+    }
+
+    async fn next_sequence_send(&self, port_id: &PortId, channel_id: &ChannelId) -> Result<u64> {
+        self.get_proto::<u64>(state_key::next_sequence_send(port_id, channel_id).into())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no send sequence for channel {}", channel_id))
+    }
+    async fn put_next_sequence_send(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: u64,
+    ) {
+        self.put_proto(
+            state_key::next_sequence_send(port_id, channel_id).into(),
+            sequence,
+        )
+        .await;
+    }
+    async fn next_sequence_recv(&self, port_id: &PortId, channel_id: &ChannelId) -> Result<u64> {
+        self.get_proto::<u64>(state_key::next_sequence_recv(port_id, channel_id).into())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no receive sequence for channel {}", channel_id))
+    }
+    async fn put_next_sequence_recv(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: u64,
+    ) {
+        self.put_proto(
+            state_key::next_sequence_recv(port_id, channel_id).into(),
+            sequence,
+        )
+        .await;
+    }
+    async fn next_sequence_ack(&self, port_id: &PortId, channel_id: &ChannelId) -> Result<u64> {
+        self.get_proto::<u64>(state_key::next_sequence_ack(port_id, channel_id).into())
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("no acknowledgement sequence for channel {}", channel_id)
+            })
+    }
+    async fn put_next_sequence_ack(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: u64,
+    ) {
+        self.put_proto(
+            state_key::next_sequence_ack(port_id, channel_id).into(),
+            sequence,
+        )
+        .await;
+    }
+
+    // the commitment to a sent packet, until it is acknowledged or times out. Deleted
+    // commitments are stored as empty.
+    async fn packet_commitment(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .get_proto::<Vec<u8>>(
+                state_key::packet_commitment(port_id, channel_id, sequence).into(),
+            )
+            .await?
+            .filter(|commitment| !commitment.is_empty()))
+    }
+    async fn put_packet_commitment(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        commitment: Vec<u8>,
+    ) {
+        self.put_proto(
+            state_key::packet_commitment(port_id, channel_id, sequence).into(),
+            commitment,
+        )
+        .await;
+    }
+    async fn delete_packet_commitment(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) {
+        self.put_packet_commitment(port_id, channel_id, sequence, Vec::new())
+            .await;
+    }
+
+    async fn packet_receipt(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<bool> {
+        Ok(self
+            .get_proto::<bool>(state_key::packet_receipt(port_id, channel_id, sequence).into())
+            .await?
+            .unwrap_or(false))
+    }
+    async fn put_packet_receipt(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) {
+        self.put_proto(
+            state_key::packet_receipt(port_id, channel_id, sequence).into(),
+            true,
+        )
+        .await;
+    }
+
+    async fn packet_acknowledgement(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+    ) -> Result<Option<Vec<u8>>> {
+        self.get_proto::<Vec<u8>>(
+            state_key::packet_acknowledgement(port_id, channel_id, sequence).into(),
+        )
+        .await
+    }
+    async fn put_packet_acknowledgement(
+        &mut self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        sequence: Sequence,
+        commitment: Vec<u8>,
+    ) {
+        self.put_proto(
+            state_key::packet_acknowledgement(port_id, channel_id, sequence).into(),
+            commitment,
+        )
+        .await;
+    }
+}
+
+impl<T: OverlayExt + Send + Sync> View for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::app::View as _, Storage};
+    use ibc::{
+        core::{
+            ics03_connection::{connection::Counterparty as ConnectionCounterparty, version},
+            ics04_channel::Version,
+            ics23_commitment::commitment::CommitmentPrefix,
+            ics24_host::identifier::ClientId,
+        },
+        signer::Signer,
+        timestamp::Timestamp,
+    };
+    use penumbra_chain::params::ChainParams;
+    use tempfile::tempdir;
+
+    // a chain ID with revision number 1.
+    const PENUMBRA_CHAIN_ID: &str = "penumbra-1";
+
+    // an overlay with an open connection and an open transfer channel with the given ordering,
+    // whose counterparty is channel-0 on the counterparty's transfer port, at height 1-10.
+    async fn open_channel_overlay(ordering: Order) -> (tempfile::TempDir, Overlay) {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("ibc-testing.db"))
+            .await
+            .unwrap();
+        let mut overlay = storage.overlay().await.unwrap();
+
+        overlay
+            .put_chain_params(ChainParams {
+                chain_id: PENUMBRA_CHAIN_ID.to_string(),
+                ..Default::default()
+            })
+            .await;
+        overlay.put_block_height(10).await;
+        overlay
+            .put_block_timestamp(
+                Time::parse_from_rfc3339("2022-02-11T17:30:50.425417198Z").unwrap(),
+            )
+            .await;
+
+        let client_id = ClientId::new(
+            ibc::core::ics02_client::client_type::ClientType::Tendermint,
+            0,
+        )
+        .unwrap();
+        overlay
+            .put_connection(
+                &ConnectionId::new(0),
+                ConnectionEnd::new(
+                    ConnectionState::Open,
+                    client_id.clone(),
+                    ConnectionCounterparty::new(
+                        client_id,
+                        Some(ConnectionId::new(0)),
+                        CommitmentPrefix::from(b"ibc".to_vec()),
+                    ),
+                    vec![version::Version::default()],
+                    Duration::from_secs(0),
+                ),
+            )
+            .await;
+        overlay
+            .put_channel(
+                &transfer::transfer_port(),
+                &ChannelId::new(0),
+                ChannelEnd::new(
+                    State::Open,
+                    ordering,
+                    Counterparty::new(transfer::transfer_port(), Some(ChannelId::new(0))),
+                    vec![ConnectionId::new(0)],
+                    Version::new(TRANSFER_VERSION.to_string()),
+                ),
+            )
+            .await;
+
+        (dir, overlay)
+    }
+
+    // a packet over channel-0 in both directions, so that it can be received or timed out.
+    fn packet(sequence: u64, timeout_height: Height) -> Packet {
+        Packet {
+            sequence: Sequence::from(sequence),
+            source_port: transfer::transfer_port(),
+            source_channel: ChannelId::new(0),
+            destination_port: transfer::transfer_port(),
+            destination_channel: ChannelId::new(0),
+            data: b"packet data".to_vec(),
+            timeout_height,
+            timeout_timestamp: Timestamp::none(),
+        }
+    }
+
+    fn proofs(height: Height) -> Proofs {
+        Proofs::new(vec![1, 2, 3].try_into().unwrap(), None, None, None, height).unwrap()
+    }
+
+    fn recv_packet(packet: Packet) -> MsgRecvPacket {
+        MsgRecvPacket {
+            packet,
+            proofs: proofs(Height::new(0, 20)),
+            signer: Signer::new("signer"),
+        }
+    }
+
+    // test that received packets time out according to our full height, including the revision.
+    #[tokio::test]
+    async fn test_recv_packet_timeout_height() {
+        let (_dir, overlay) = open_channel_overlay(Order::Unordered).await;
+        let component = ChannelComponent::new(overlay).await;
+
+        // a higher revision height at an earlier revision has passed.
+        let timed_out = component
+            .validate_recv_packet_stateful(&recv_packet(packet(1, Height::new(0, 20))))
+            .await
+            .unwrap_err();
+        assert!(timed_out.to_string().contains("packet timed out"));
+
+        // a lower revision height at a later revision has not.
+        let not_timed_out = component
+            .validate_recv_packet_stateful(&recv_packet(packet(1, Height::new(2, 5))))
+            .await
+            .unwrap_err();
+        assert!(!not_timed_out.to_string().contains("timed out"));
+    }
+
+    // test that ordered channels receive packets in sequence, and unordered channels receive each
+    // packet only once.
+    #[tokio::test]
+    async fn test_recv_packet_sequence() {
+        let (_dir, mut overlay) = open_channel_overlay(Order::Ordered).await;
+        overlay
+            .put_next_sequence_recv(&transfer::transfer_port(), &ChannelId::new(0), 2)
+            .await;
+        let component = ChannelComponent::new(overlay).await;
+
+        let out_of_order = component
+            .validate_recv_packet_stateful(&recv_packet(packet(1, Height::zero())))
+            .await
+            .unwrap_err();
+        assert!(out_of_order
+            .to_string()
+            .contains("does not match the next receive sequence 2"));
+        let in_order = component
+            .validate_recv_packet_stateful(&recv_packet(packet(2, Height::zero())))
+            .await
+            .unwrap_err();
+        assert!(!in_order.to_string().contains("sequence"));
+
+        let (_dir, mut overlay) = open_channel_overlay(Order::Unordered).await;
+        overlay
+            .put_packet_receipt(
+                &transfer::transfer_port(),
+                &ChannelId::new(0),
+                Sequence::from(1),
+            )
+            .await;
+        let component = ChannelComponent::new(overlay).await;
+
+        let received = component
+            .validate_recv_packet_stateful(&recv_packet(packet(1, Height::zero())))
+            .await
+            .unwrap_err();
+        assert!(received
+            .to_string()
+            .contains("packet 1 was already received"));
+        let not_received = component
+            .validate_recv_packet_stateful(&recv_packet(packet(2, Height::zero())))
+            .await
+            .unwrap_err();
+        assert!(!not_received.to_string().contains("already received"));
+    }
+
+    // test that a timeout is only accepted once the counterparty has passed the packet's timeout
+    // height, including the revision, and only for packets it has not received.
+    #[tokio::test]
+    async fn test_timeout_height() {
+        let (_dir, mut overlay) = open_channel_overlay(Order::Ordered).await;
+        let packet = packet(1, Height::new(1, 10));
+        overlay
+            .put_packet_commitment(
+                &transfer::transfer_port(),
+                &ChannelId::new(0),
+                packet.sequence,
+                commit_packet(&packet),
+            )
+            .await;
+        let component = ChannelComponent::new(overlay).await;
+        let timeout = |next_sequence_recv: u64, proof_height: Height| MsgTimeout {
+            packet: packet.clone(),
+            next_sequence_recv: Sequence::from(next_sequence_recv),
+            proofs: proofs(proof_height),
+            signer: Signer::new("signer"),
+        };
+
+        let not_timed_out = component
+            .validate_timeout_stateful(&timeout(1, Height::new(0, 20)))
+            .await
+            .unwrap_err();
+        assert!(not_timed_out
+            .to_string()
+            .contains("packet has not timed out"));
+
+        let received = component
+            .validate_timeout_stateful(&timeout(2, Height::new(1, 10)))
+            .await
+            .unwrap_err();
+        assert!(received.to_string().contains("packet was already received"));
+    }
+
+    #[test]
+    fn test_delay_period_passed() {
+        let processed_time = Time::parse_from_rfc3339("2022-02-11T17:30:00Z").unwrap();
+        let time = |rfc3339| Time::parse_from_rfc3339(rfc3339).unwrap();
+
+        // without a delay period, proofs can be used as soon as the consensus state is processed.
+        delay_period_passed(
+            Duration::from_secs(0),
+            (processed_time, 10),
+            (processed_time, 10),
+        )
+        .unwrap();
+
+        // a 60 second delay is also two blocks.
+        let delay = Duration::from_secs(60);
+        delay_period_passed(
+            delay,
+            (processed_time, 10),
+            (time("2022-02-11T17:31:00Z"), 12),
+        )
+        .unwrap();
+        assert!(delay_period_passed(
+            delay,
+            (processed_time, 10),
+            (time("2022-02-11T17:30:59Z"), 12)
+        )
+        .is_err());
+        assert!(delay_period_passed(
+            delay,
+            (processed_time, 10),
+            (time("2022-02-11T17:31:00Z"), 11)
+        )
+        .is_err());
+    }
+}
@@ -14,13 +14,22 @@ use ibc::{
             client_state::{AnyClientState, ClientState},
             header::AnyHeader,
             height::Height,
-            msgs::{create_client::MsgCreateAnyClient, update_client::MsgUpdateAnyClient},
+            misbehaviour::AnyMisbehaviour,
+            msgs::{
+                create_client::MsgCreateAnyClient, misbehavior::MsgSubmitAnyMisbehaviour,
+                update_client::MsgUpdateAnyClient, upgrade_client::MsgUpgradeAnyClient,
+            },
         },
+        ics23_commitment::{commitment::CommitmentRoot, merkle::MerkleProof},
         ics24_host::identifier::ClientId,
     },
 };
+use ibc_proto::ibc::core::commitment::v1::{MerklePath, MerkleProof as RawMerkleProof};
 use penumbra_ibc::{ClientCounter, ClientData, ConsensusState, IBCAction, VerifiedHeights};
-use penumbra_proto::ibc::ibc_action::Action::{CreateClient, UpdateClient};
+use penumbra_proto::{
+    ibc::ibc_action::Action::{CreateClient, SubmitMisbehaviour, UpdateClient, UpgradeClient},
+    Message,
+};
 use penumbra_transaction::Transaction;
 use tendermint::{abci, Time};
 use tendermint_light_client_verifier::{
@@ -29,6 +38,7 @@ use tendermint_light_client_verifier::{
 };
 use tracing::instrument;
 
+use super::{event, state_key};
 use crate::{components::app::View as _, components::Component};
 use crate::{genesis, Overlay, OverlayExt};
 
@@ -36,15 +46,24 @@ use crate::{genesis, Overlay, OverlayExt};
 /// MsgUpdateClient, MsgUpgradeClient, and MsgSubmitMisbehaviour. The core responsibility of the
 /// client component is tracking light clients for IBC, creating new light clients and verifying
 /// state updates. Currently, only Tendermint light clients are supported.
+///
+/// A client that is shown to have followed a misbehaving counterparty, either through
+/// conflicting headers or headers that break the monotonicity of BFT time, is frozen, and can no
+/// longer be updated or used to verify counterparty state.
 pub struct ClientComponent {
     overlay: Overlay,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
 }
 
 #[async_trait]
 impl Component for ClientComponent {
     #[instrument(name = "ics2_client", skip(overlay))]
     async fn new(overlay: Overlay) -> Self {
-        Self { overlay }
+        Self {
+            overlay,
+            events: Vec::new(),
+        }
     }
 
     #[instrument(name = "ics2_client", skip(self, _app_state))]
@@ -53,8 +72,33 @@ impl Component for ClientComponent {
         self.overlay.put_client_counter(ClientCounter(0)).await;
     }
 
-    #[instrument(name = "ics2_client", skip(self, _begin_block))]
-    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+    #[instrument(name = "ics2_client", skip(self, begin_block))]
+    async fn begin_block(&mut self, begin_block: &abci::request::BeginBlock) {
+        // Record Penumbra's own consensus state at this height, which the
+        // counterparty's client of Penumbra is checked against during the
+        // connection handshake.
+        let height = begin_block.header.height.value();
+        self.overlay
+            .put_penumbra_consensus_state(
+                height,
+                ConsensusState(AnyConsensusState::Tendermint(
+                    TendermintConsensusState::from(begin_block.header.clone()),
+                )),
+            )
+            .await;
+
+        // A counterparty's client of Penumbra can't be trusted once its
+        // latest height is more than an unbonding period old, so only keep
+        // our consensus states for the heights within the unbonding period.
+        let chain_params = self.overlay.get_chain_params().await.unwrap();
+        let unbonding_blocks = chain_params
+            .epoch_duration
+            .saturating_mul(chain_params.unbonding_epochs);
+        self.overlay
+            .prune_penumbra_consensus_states(height.saturating_sub(unbonding_blocks))
+            .await
+            .unwrap();
+    }
 
     #[instrument(name = "ics2_client", skip(tx))]
     fn check_tx_stateless(tx: &Transaction) -> Result<()> {
@@ -82,6 +126,10 @@ impl Component for ClientComponent {
 
     #[instrument(name = "ics2_client", skip(self, _end_block))]
     async fn end_block(&mut self, _end_block: &abci::request::EndBlock) {}
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
 }
 
 // validates the given ibc action statelessly
@@ -97,6 +145,16 @@ fn validate_ibc_action_stateless(ibc_action: &IBCAction) -> Result<(), anyhow::E
 
             validate_update_client_stateless(&msg_update_client)?;
         }
+        UpgradeClient(msg) => {
+            let msg_upgrade_client = MsgUpgradeAnyClient::try_from(msg.clone())?;
+
+            validate_upgrade_client_stateless(&msg_upgrade_client)?;
+        }
+        SubmitMisbehaviour(msg) => {
+            let msg_submit_misbehaviour = MsgSubmitAnyMisbehaviour::try_from(msg.clone())?;
+
+            validate_submit_misbehaviour_stateless(&msg_submit_misbehaviour)?;
+        }
         _ => return Ok(()),
     }
 
@@ -142,6 +200,104 @@ fn validate_update_client_stateless(
     Ok(())
 }
 
+// check that both the upgraded client and its consensus state are Tendermint
+fn validate_upgrade_client_stateless(
+    upgrade_client: &MsgUpgradeAnyClient,
+) -> Result<(), anyhow::Error> {
+    match upgrade_client.client_state {
+        AnyClientState::Tendermint(_) => {}
+        _ => {
+            return Err(anyhow::anyhow!(
+                "only Tendermint clients are supported at this time"
+            ))
+        }
+    }
+    match upgrade_client.consensus_state {
+        AnyConsensusState::Tendermint(_) => {}
+        _ => {
+            return Err(anyhow::anyhow!(
+                "only Tendermint consensus is supported at this time"
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+// check that the misbehaviour is Tendermint, and that its two headers are evidence of
+// misbehaviour: either conflicting headers at the same height, or headers that break the
+// monotonicity of BFT time.
+fn validate_submit_misbehaviour_stateless(
+    submit_misbehaviour: &MsgSubmitAnyMisbehaviour,
+) -> Result<(), anyhow::Error> {
+    let misbehaviour = match &submit_misbehaviour.misbehaviour {
+        AnyMisbehaviour::Tendermint(misbehaviour) => misbehaviour,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "only Tendermint clients are supported at this time"
+            ))
+        }
+    };
+    if misbehaviour.client_id != submit_misbehaviour.client_id {
+        return Err(anyhow::anyhow!(
+            "misbehaviour is for client {}, not {}",
+            misbehaviour.client_id,
+            submit_misbehaviour.client_id
+        ));
+    }
+
+    let header_1 = misbehaviour.header1.signed_header.header();
+    let header_2 = misbehaviour.header2.signed_header.header();
+    if header_1.chain_id != header_2.chain_id {
+        return Err(anyhow::anyhow!(
+            "misbehaviour headers are for different chains"
+        ));
+    }
+
+    // by convention, the first header is the higher one.
+    if misbehaviour.header1.height() < misbehaviour.header2.height() {
+        return Err(anyhow::anyhow!(
+            "first misbehaviour header is lower than the second"
+        ));
+    }
+    if misbehaviour.header1.height() == misbehaviour.header2.height() {
+        if header_1.hash() == header_2.hash() {
+            return Err(anyhow::anyhow!(
+                "misbehaviour headers at the same height do not conflict"
+            ));
+        }
+    } else if header_1.time > header_2.time {
+        return Err(anyhow::anyhow!(
+            "misbehaviour headers do not break BFT time monotonicity"
+        ));
+    }
+
+    Ok(())
+}
+
+// the merkle path under which a counterparty commits to an upgraded client or consensus state,
+// identified by `key`, at the last height before the upgrade.
+fn upgrade_merkle_path(
+    client_state: &TendermintClientState,
+    last_height: Height,
+    key: &str,
+) -> Result<MerklePath> {
+    let mut key_path = client_state.upgrade_path.clone();
+    let last_key = key_path
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("client has no upgrade path"))?;
+    key_path.push(format!(
+        "{}/{}/{}",
+        last_key, last_height.revision_height, key
+    ));
+
+    Ok(MerklePath { key_path })
+}
+
+// The commitment root of an upgraded consensus state. The counterparty can't commit to its own
+// root before the upgrade, so the client must be updated before it can verify any proofs.
+const SENTINEL_ROOT: &[u8] = b"sentinel_root";
+
 impl ClientComponent {
     // validates the given IBC action statefully.
     async fn validate_ibc_action_stateful(&self, ibc_action: &IBCAction) -> Result<()> {
@@ -158,6 +314,18 @@ impl ClientComponent {
                 self.validate_update_client_stateful(msg_update_client)
                     .await?;
             }
+            UpgradeClient(msg) => {
+                let msg_upgrade_client = MsgUpgradeAnyClient::try_from(msg.clone())?;
+
+                self.validate_upgrade_client_stateful(msg_upgrade_client)
+                    .await?;
+            }
+            SubmitMisbehaviour(msg) => {
+                let msg_submit_misbehaviour = MsgSubmitAnyMisbehaviour::try_from(msg.clone())?;
+
+                self.validate_submit_misbehaviour_stateful(msg_submit_misbehaviour)
+                    .await?;
+            }
             _ => return Ok(()),
         }
 
@@ -179,6 +347,20 @@ impl ClientComponent {
 
                 self.execute_update_client(msg_update_client).await;
             }
+            UpgradeClient(raw_msg_upgrade_client) => {
+                let msg_upgrade_client =
+                    MsgUpgradeAnyClient::try_from(raw_msg_upgrade_client.clone()).unwrap();
+
+                self.execute_upgrade_client(msg_upgrade_client).await;
+            }
+            SubmitMisbehaviour(raw_msg_submit_misbehaviour) => {
+                let msg_submit_misbehaviour =
+                    MsgSubmitAnyMisbehaviour::try_from(raw_msg_submit_misbehaviour.clone())
+                        .unwrap();
+
+                self.execute_submit_misbehaviour(msg_submit_misbehaviour)
+                    .await;
+            }
             _ => {}
         }
     }
@@ -253,6 +435,145 @@ impl ClientComponent {
         Ok(())
     }
 
+    // validate IBC UpgradeClient, which lets a client follow its counterparty across a chain
+    // upgrade that would otherwise break light client verification.
+    //
+    // verify:
+    // - we have a client corresponding to the UpgradeClient's client_id
+    // - the stored client is not frozen
+    // - the upgraded client is at a greater height than the stored client
+    // - the counterparty committed to the upgraded client and consensus states under the stored
+    //   client's upgrade path, as of the stored client's latest height
+    async fn validate_upgrade_client_stateful(
+        &self,
+        msg_upgrade_client: MsgUpgradeAnyClient,
+    ) -> Result<()> {
+        let client_data = self
+            .overlay
+            .get_client_data(&msg_upgrade_client.client_id)
+            .await?;
+
+        if client_data.client_state.0.is_frozen() {
+            return Err(anyhow::anyhow!("client is frozen"));
+        }
+
+        let tm_client_state = match client_data.client_state.0 {
+            AnyClientState::Tendermint(tm_state) => tm_state,
+            _ => return Err(anyhow::anyhow!("unsupported client type")),
+        };
+        let upgraded_tm_client_state = match &msg_upgrade_client.client_state {
+            AnyClientState::Tendermint(tm_state) => tm_state,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "upgraded client is not a Tendermint client"
+                ))
+            }
+        };
+
+        let last_height = tm_client_state.latest_height();
+        if upgraded_tm_client_state.latest_height() <= last_height {
+            return Err(anyhow::anyhow!(
+                "upgraded client height {} is not greater than current client height {}",
+                upgraded_tm_client_state.latest_height(),
+                last_height
+            ));
+        }
+        if tm_client_state.trusting_period >= upgraded_tm_client_state.unbonding_period {
+            return Err(anyhow::anyhow!(
+                "client trusting period is not less than the upgraded unbonding period"
+            ));
+        }
+
+        let root = self
+            .overlay
+            .get_verified_consensus_state(last_height, msg_upgrade_client.client_id.clone())
+            .await?
+            .as_tendermint()?
+            .root;
+
+        self.verify_upgrade_proof(
+            &tm_client_state,
+            &root,
+            msg_upgrade_client.proof_upgrade_client.clone(),
+            upgrade_merkle_path(&tm_client_state, last_height, "upgradedClient")?,
+            prost_types::Any::from(msg_upgrade_client.client_state.clone()).encode_to_vec(),
+        )?;
+        self.verify_upgrade_proof(
+            &tm_client_state,
+            &root,
+            msg_upgrade_client.proof_upgrade_consensus_state.clone(),
+            upgrade_merkle_path(&tm_client_state, last_height, "upgradedConsState")?,
+            prost_types::Any::from(msg_upgrade_client.consensus_state.clone()).encode_to_vec(),
+        )?;
+
+        Ok(())
+    }
+
+    // verify a proof that the counterparty committed to `value` under the upgrade `path`.
+    fn verify_upgrade_proof(
+        &self,
+        client_state: &TendermintClientState,
+        root: &CommitmentRoot,
+        proof: RawMerkleProof,
+        path: MerklePath,
+        value: Vec<u8>,
+    ) -> Result<()> {
+        MerkleProof::from(proof)
+            .verify_membership(
+                &client_state.proof_specs,
+                root.clone().into(),
+                path,
+                value,
+                0,
+            )
+            .map_err(|e| anyhow::anyhow!("could not verify upgrade proof: {}", e))
+    }
+
+    // validate IBC SubmitMisbehaviour.
+    //
+    // verify:
+    // - we have a client corresponding to the SubmitMisbehaviour's client_id
+    // - the stored client is not already frozen
+    // - both conflicting headers verify against the consensus states stored at their trusted
+    //   heights, using the semantics of the light client's header verification fn
+    async fn validate_submit_misbehaviour_stateful(
+        &self,
+        msg_submit_misbehaviour: MsgSubmitAnyMisbehaviour,
+    ) -> Result<()> {
+        let client_data = self
+            .overlay
+            .get_client_data(&msg_submit_misbehaviour.client_id)
+            .await?;
+
+        if client_data.client_state.0.is_frozen() {
+            return Err(anyhow::anyhow!("client is already frozen"));
+        }
+
+        let tm_client_state = match client_data.client_state.0 {
+            AnyClientState::Tendermint(tm_state) => tm_state,
+            _ => return Err(anyhow::anyhow!("unsupported client type")),
+        };
+        let misbehaviour = match msg_submit_misbehaviour.misbehaviour {
+            AnyMisbehaviour::Tendermint(misbehaviour) => misbehaviour,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "misbehaviour is not Tendermint misbehaviour"
+                ))
+            }
+        };
+
+        for header in [misbehaviour.header1, misbehaviour.header2] {
+            self.verify_tendermint_header(
+                msg_submit_misbehaviour.client_id.clone(),
+                tm_client_state.clone(),
+                header,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
     async fn validate_create_client_stateful(
         &self,
         msg_create_client: MsgCreateAnyClient,
@@ -300,6 +621,11 @@ impl ClientComponent {
             now.to_rfc3339(),
             height,
         );
+        self.events.push(event::update_client(
+            &msg_update_client.client_id,
+            next_client_data.client_state.0.client_type(),
+            tm_header.height(),
+        ));
         self.overlay.put_client_data(next_client_data).await;
         self.overlay
             .put_verified_consensus_state(
@@ -311,6 +637,117 @@ impl ClientComponent {
             .unwrap();
     }
 
+    // execute a UpgradeClient IBC action, assuming that it has already been validated. the
+    // upgraded client keeps the parameters that were chosen by its creator (trust level,
+    // trusting period, clock drift), and takes the rest from the counterparty.
+    async fn execute_upgrade_client(&mut self, msg_upgrade_client: MsgUpgradeAnyClient) {
+        let client_data = self
+            .overlay
+            .get_client_data(&msg_upgrade_client.client_id)
+            .await
+            .unwrap();
+
+        let tm_client_state = match client_data.clone().client_state.0 {
+            AnyClientState::Tendermint(tm_state) => tm_state,
+            _ => panic!("unsupported client type"),
+        };
+        let upgraded_tm_client_state = match msg_upgrade_client.client_state {
+            AnyClientState::Tendermint(tm_state) => tm_state,
+            _ => panic!("upgraded client is not a Tendermint client"),
+        };
+        let upgraded_tm_consensus_state = match msg_upgrade_client.consensus_state {
+            AnyConsensusState::Tendermint(tm_state) => tm_state,
+            _ => panic!("upgraded consensus state is not a Tendermint consensus state"),
+        };
+
+        let next_tm_client_state = TendermintClientState {
+            chain_id: upgraded_tm_client_state.chain_id,
+            unbonding_period: upgraded_tm_client_state.unbonding_period,
+            latest_height: upgraded_tm_client_state.latest_height,
+            proof_specs: upgraded_tm_client_state.proof_specs,
+            upgrade_path: upgraded_tm_client_state.upgrade_path,
+            frozen_height: None,
+            ..tm_client_state
+        };
+        let next_tm_consensus_state = TendermintConsensusState::new(
+            CommitmentRoot::from_bytes(SENTINEL_ROOT),
+            upgraded_tm_consensus_state.timestamp,
+            upgraded_tm_consensus_state.next_validators_hash,
+        );
+        let upgrade_height = next_tm_client_state.latest_height();
+
+        tracing::info!(
+            "upgrading client {:?} to height {}",
+            msg_upgrade_client.client_id,
+            upgrade_height
+        );
+
+        let height = self.overlay.get_block_height().await.unwrap();
+        let now = self.overlay.get_block_timestamp().await.unwrap();
+        let next_client_data = client_data.with_new_client_state(
+            AnyClientState::Tendermint(next_tm_client_state),
+            now.to_rfc3339(),
+            height,
+        );
+        self.events.push(event::upgrade_client(
+            &msg_upgrade_client.client_id,
+            next_client_data.client_state.0.client_type(),
+            upgrade_height,
+        ));
+        self.overlay.put_client_data(next_client_data).await;
+        self.overlay
+            .put_verified_consensus_state(
+                upgrade_height,
+                msg_upgrade_client.client_id.clone(),
+                ConsensusState(AnyConsensusState::Tendermint(next_tm_consensus_state)),
+            )
+            .await
+            .unwrap();
+    }
+
+    // execute a SubmitMisbehaviour IBC action, assuming that it has already been validated,
+    // freezing the client at the height of the misbehaviour.
+    async fn execute_submit_misbehaviour(
+        &mut self,
+        msg_submit_misbehaviour: MsgSubmitAnyMisbehaviour,
+    ) {
+        let client_data = self
+            .overlay
+            .get_client_data(&msg_submit_misbehaviour.client_id)
+            .await
+            .unwrap();
+
+        let tm_client_state = match client_data.clone().client_state.0 {
+            AnyClientState::Tendermint(tm_state) => tm_state,
+            _ => panic!("unsupported client type"),
+        };
+        let misbehaviour = match msg_submit_misbehaviour.misbehaviour {
+            AnyMisbehaviour::Tendermint(misbehaviour) => misbehaviour,
+            _ => panic!("misbehaviour is not Tendermint misbehaviour"),
+        };
+        let frozen_height = misbehaviour.header1.height();
+
+        tracing::info!(
+            "freezing client {:?} at height {}",
+            msg_submit_misbehaviour.client_id,
+            frozen_height
+        );
+
+        let height = self.overlay.get_block_height().await.unwrap();
+        let now = self.overlay.get_block_timestamp().await.unwrap();
+        let next_client_data = client_data.with_new_client_state(
+            AnyClientState::Tendermint(tm_client_state.with_frozen_height(frozen_height).unwrap()),
+            now.to_rfc3339(),
+            height,
+        );
+        self.events.push(event::client_misbehaviour(
+            &msg_submit_misbehaviour.client_id,
+            next_client_data.client_state.0.client_type(),
+            frozen_height,
+        ));
+        self.overlay.put_client_data(next_client_data).await;
+    }
+
     // execute IBC CreateClient.
     //
     //  we compute the client's ID (a concatenation of a monotonically increasing integer, the
@@ -338,6 +775,11 @@ impl ClientComponent {
 
         // store the client data
         self.overlay.put_client_data(data.clone()).await;
+        self.events.push(event::create_client(
+            &client_id,
+            data.client_state.0.client_type(),
+            data.client_state.0.latest_height(),
+        ));
 
         // store the genesis consensus state
         self.overlay
@@ -451,18 +893,6 @@ impl ClientComponent {
     ) -> Result<()> {
         let untrusted_consensus_state = TendermintConsensusState::from(untrusted_header.clone());
 
-        if untrusted_header.height().revision_number != trusted_client_state.chain_id.version() {
-            return Err(anyhow::anyhow!(
-                "client update revision number does not match client state"
-            ));
-        }
-
-        if untrusted_header.height() <= untrusted_header.trusted_height {
-            return Err(anyhow::anyhow!(
-                "client update height is not greater than trusted height"
-            ));
-        }
-
         // check if we already have a consensus state for this height, if we do, check that it is
         // the same as this update, if it is, return early.
         if let Some(stored_consensus_state) = self
@@ -477,6 +907,30 @@ impl ClientComponent {
             }
         }
 
+        self.verify_tendermint_header(client_id, trusted_client_state, untrusted_header)
+            .await
+    }
+
+    // verify a tendermint header against the consensus state stored at its trusted height,
+    // given a trusted client state.
+    async fn verify_tendermint_header(
+        &self,
+        client_id: ClientId,
+        trusted_client_state: TendermintClientState,
+        untrusted_header: TendermintHeader,
+    ) -> Result<()> {
+        if untrusted_header.height().revision_number != trusted_client_state.chain_id.version() {
+            return Err(anyhow::anyhow!(
+                "client update revision number does not match client state"
+            ));
+        }
+
+        if untrusted_header.height() <= untrusted_header.trusted_height {
+            return Err(anyhow::anyhow!(
+                "client update height is not greater than trusted height"
+            ));
+        }
+
         let last_trusted_consensus_state = self
             .overlay
             .get_verified_consensus_state(untrusted_header.trusted_height, client_id.clone())
@@ -547,48 +1001,58 @@ impl ClientComponent {
 #[async_trait]
 pub trait View: OverlayExt + Send + Sync {
     async fn put_client_counter(&mut self, counter: ClientCounter) {
-        self.put_domain("ibc/ics02-client/client_counter".into(), counter)
+        self.put_domain(state_key::client_counter().into(), counter)
             .await;
     }
     async fn client_counter(&self) -> Result<ClientCounter> {
-        self.get_domain("ibc/ics02-client/client_counter".into())
+        self.get_domain(state_key::client_counter().into())
             .await
             .map(|counter| counter.unwrap_or(ClientCounter(0)))
     }
     async fn put_client_data(&mut self, data: ClientData) {
+        // The client state is stored on its own at its ICS-24 path, so that
+        // counterparties can verify it.
         self.put_domain(
-            format!(
-                "ibc/ics02-client/clients/{}",
-                hex::encode(data.client_id.as_bytes())
-            )
-            .into(),
-            data,
+            state_key::client_state(&data.client_id).into(),
+            data.client_state,
+        )
+        .await;
+        self.put_proto(
+            state_key::client_processed_time(&data.client_id).into(),
+            data.processed_time,
+        )
+        .await;
+        self.put_proto(
+            state_key::client_processed_height(&data.client_id).into(),
+            data.processed_height,
         )
         .await;
     }
     async fn get_client_data(&self, client_id: &ClientId) -> Result<ClientData> {
-        let client_data = self
-            .get_domain(
-                format!(
-                    "ibc/ics02-client/clients/{}",
-                    hex::encode(client_id.as_bytes())
-                )
-                .into(),
-            )
-            .await?;
-
-        client_data.ok_or(anyhow::anyhow!("client not found"))
+        let client_state = self
+            .get_domain(state_key::client_state(client_id).into())
+            .await?
+            .ok_or(anyhow::anyhow!("client not found"))?;
+        let processed_time = self
+            .get_proto(state_key::client_processed_time(client_id).into())
+            .await?
+            .ok_or(anyhow::anyhow!("client not found"))?;
+        let processed_height = self
+            .get_proto(state_key::client_processed_height(client_id).into())
+            .await?
+            .ok_or(anyhow::anyhow!("client not found"))?;
+
+        Ok(ClientData {
+            client_id: client_id.clone(),
+            client_state,
+            processed_time,
+            processed_height,
+        })
     }
 
     async fn get_verified_heights(&self, client_id: &ClientId) -> Result<Option<VerifiedHeights>> {
-        self.get_domain(
-            format!(
-                "ibc/ics02-client/clients/{}/verified_heights",
-                hex::encode(client_id.as_bytes())
-            )
-            .into(),
-        )
-        .await
+        self.get_domain(state_key::verified_heights(client_id).into())
+            .await
     }
 
     async fn put_verified_heights(
@@ -597,11 +1061,7 @@ pub trait View: OverlayExt + Send + Sync {
         verified_heights: VerifiedHeights,
     ) {
         self.put_domain(
-            format!(
-                "ibc/ics02-client/clients/{}/verified_heights",
-                hex::encode(client_id.as_bytes())
-            )
-            .into(),
+            state_key::verified_heights(client_id).into(),
             verified_heights,
         )
         .await;
@@ -612,16 +1072,12 @@ pub trait View: OverlayExt + Send + Sync {
         height: Height,
         client_id: ClientId,
     ) -> Result<ConsensusState> {
-        self.get_domain(
-            format!(
-                "ibc/ics02-client/clients/{}/consensus_state/{}",
-                hex::encode(client_id.as_bytes()),
-                height
-            )
-            .into(),
+        self.get_proto::<prost_types::Any>(
+            state_key::verified_consensus_state(&client_id, &height).into(),
         )
         .await?
-        .ok_or(anyhow::anyhow!("consensus state not found"))
+        .ok_or(anyhow::anyhow!("consensus state not found"))?
+        .try_into()
     }
 
     async fn put_verified_consensus_state(
@@ -630,14 +1086,26 @@ pub trait View: OverlayExt + Send + Sync {
         client_id: ClientId,
         consensus_state: ConsensusState,
     ) -> Result<()> {
-        self.put_domain(
-            format!(
-                "ibc/ics02-client/clients/{}/consensus_state/{}",
-                hex::encode(client_id.as_bytes()),
-                height
-            )
-            .into(),
-            consensus_state,
+        // Consensus states are stored as `Any`s at their ICS-24 path, so
+        // that counterparties can verify them.
+        self.put_proto(
+            state_key::verified_consensus_state(&client_id, &height).into(),
+            prost_types::Any::from(consensus_state),
+        )
+        .await;
+
+        // record when the consensus state was processed, from which the delay period of
+        // connections built on this client is measured.
+        let processed_height = self.get_block_height().await?;
+        let processed_time = self.get_block_timestamp().await?;
+        self.put_proto(
+            state_key::verified_consensus_state_processed_time(&client_id, &height).into(),
+            processed_time.to_rfc3339(),
+        )
+        .await;
+        self.put_proto(
+            state_key::verified_consensus_state_processed_height(&client_id, &height).into(),
+            processed_height,
         )
         .await;
 
@@ -657,6 +1125,70 @@ pub trait View: OverlayExt + Send + Sync {
         Ok(())
     }
 
+    /// The time and block height at which the consensus state at `height` was processed.
+    async fn get_verified_consensus_state_processed(
+        &self,
+        height: Height,
+        client_id: ClientId,
+    ) -> Result<(Time, u64)> {
+        let processed_time: String = self
+            .get_proto(
+                state_key::verified_consensus_state_processed_time(&client_id, &height).into(),
+            )
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("consensus state processed time not found"))?;
+        let processed_height = self
+            .get_proto(
+                state_key::verified_consensus_state_processed_height(&client_id, &height).into(),
+            )
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("consensus state processed height not found"))?;
+
+        Ok((Time::parse_from_rfc3339(&processed_time)?, processed_height))
+    }
+
+    async fn put_penumbra_consensus_state(&self, height: u64, consensus_state: ConsensusState) {
+        self.put_domain(
+            state_key::penumbra_consensus_state(height).into(),
+            consensus_state,
+        )
+        .await;
+    }
+
+    /// Penumbra's own consensus state at the given height.
+    async fn get_penumbra_consensus_state(&self, height: u64) -> Result<ConsensusState> {
+        self.get_domain(state_key::penumbra_consensus_state(height).into())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no penumbra consensus state at height {}", height))
+    }
+
+    /// Deletes Penumbra's own consensus states below `min_height`.
+    ///
+    /// The oldest height that hasn't been pruned is recorded, so that every
+    /// older state is deleted even if the window has shrunk since the last
+    /// call.
+    async fn prune_penumbra_consensus_states(&self, min_height: u64) -> Result<()> {
+        // Block heights start at 1.
+        let oldest_height = self
+            .get_proto::<u64>(state_key::oldest_penumbra_consensus_state_height().into())
+            .await?
+            .unwrap_or(1);
+        if min_height <= oldest_height {
+            return Ok(());
+        }
+
+        for height in oldest_height..min_height {
+            self.delete(state_key::penumbra_consensus_state(height).into())
+                .await;
+        }
+        self.put_proto(
+            state_key::oldest_penumbra_consensus_state_height().into(),
+            min_height,
+        )
+        .await;
+        Ok(())
+    }
+
     // returns the lowest verified consensus state that is higher than the given height, if it
     // exists.
     async fn next_verified_consensus_state(
@@ -708,6 +1240,7 @@ pub trait View: OverlayExt + Send + Sync {
         if let Some(prev_height) = verified_heights
             .heights
             .iter()
+            .rev()
             .find(|&verified_height| verified_height < &height)
         {
             let prev_cons_state = self
@@ -726,15 +1259,97 @@ impl<T: OverlayExt + Send + Sync> View for T {}
 mod tests {
     use super::*;
     use crate::Storage;
+    use ibc::{
+        clients::ics07_tendermint::misbehaviour::Misbehaviour as TendermintMisbehaviour,
+        core::ics02_client::client_type::ClientType, signer::Signer,
+    };
     use ibc_proto::ibc::core::client::v1::MsgCreateClient as RawMsgCreateClient;
     use ibc_proto::ibc::core::client::v1::MsgUpdateClient as RawMsgUpdateClient;
     use penumbra_crypto::merkle;
-    use penumbra_crypto::{Fq, Zero};
     use penumbra_proto::ibc::ibc_action::Action as IBCActionInner;
     use penumbra_proto::Message;
     use penumbra_transaction::{Action, Fee, Transaction, TransactionBody};
     use std::fs;
-    use tempfile::tempdir;
+    use tempfile::{tempdir, TempDir};
+    use tendermint::block::signed_header::SignedHeader;
+
+    fn read_msg(path: &str) -> Vec<u8> {
+        base64::decode(fs::read_to_string(path).unwrap().replace('\n', "")).unwrap()
+    }
+
+    // the MsgCreateClient used to create the stargaze light client on the cosmos hub.
+    fn stargaze_create_client() -> MsgCreateAnyClient {
+        MsgCreateAnyClient::try_from(
+            RawMsgCreateClient::decode(read_msg("../ibc/test/create_client.msg").as_slice())
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    // the header of one of the updates to the stargaze light client on the cosmos hub.
+    fn stargaze_header(path: &str) -> TendermintHeader {
+        let msg = MsgUpdateAnyClient::try_from(
+            RawMsgUpdateClient::decode(read_msg(path).as_slice()).unwrap(),
+        )
+        .unwrap();
+        match msg.header {
+            AnyHeader::Tendermint(header) => header,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("stargaze header is a tendermint header"),
+        }
+    }
+
+    // a copy of `header` with a different time, and hence a different hash.
+    fn with_time(header: &TendermintHeader, time: &str) -> TendermintHeader {
+        let mut tm_header = header.signed_header.header().clone();
+        tm_header.time = Time::parse_from_rfc3339(time).unwrap();
+        TendermintHeader {
+            signed_header: SignedHeader::new(tm_header, header.signed_header.commit().clone())
+                .unwrap(),
+            ..header.clone()
+        }
+    }
+
+    fn submit_misbehaviour(
+        header1: TendermintHeader,
+        header2: TendermintHeader,
+    ) -> MsgSubmitAnyMisbehaviour {
+        let client_id = ClientId::new(ClientType::Tendermint, 0).unwrap();
+        MsgSubmitAnyMisbehaviour {
+            client_id: client_id.clone(),
+            misbehaviour: AnyMisbehaviour::Tendermint(TendermintMisbehaviour {
+                client_id,
+                header1,
+                header2,
+            }),
+            signer: Signer::new("signer"),
+        }
+    }
+
+    // a client component with the stargaze client created in its state.
+    async fn stargaze_client_component() -> (TempDir, ClientComponent) {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("ibc-testing.db"))
+            .await
+            .unwrap();
+        let overlay = storage.overlay().await.unwrap();
+        overlay
+            .put_block_timestamp(
+                Time::parse_from_rfc3339("2022-02-11T17:30:50.425417198Z").unwrap(),
+            )
+            .await;
+        overlay.put_block_height(0).await;
+
+        let mut client_component = ClientComponent::new(overlay).await;
+        client_component
+            .init_chain(&genesis::AppState::default())
+            .await;
+        client_component
+            .execute_create_client(stargaze_create_client())
+            .await;
+
+        (dir, client_component)
+    }
 
     // test that we can create and update a light client.
     #[tokio::test]
@@ -795,7 +1410,7 @@ mod tests {
         let create_client_tx = Transaction {
             transaction_body: TransactionBody {
                 actions: vec![Action::IBCAction(create_client_action)],
-                merkle_root: merkle::Root(Fq::zero()),
+                merkle_root: merkle::NoteCommitmentTree::new().root(),
                 expiry_height: 0,
                 chain_id: "".to_string(),
                 fee: Fee(0),
@@ -809,7 +1424,7 @@ mod tests {
         let update_client_tx = Transaction {
             transaction_body: TransactionBody {
                 actions: vec![Action::IBCAction(update_client_action)],
-                merkle_root: merkle::Root(Fq::zero()),
+                merkle_root: merkle::NoteCommitmentTree::new().root(),
                 expiry_height: 0,
                 chain_id: "".to_string(),
                 fee: Fee(0),
@@ -858,7 +1473,7 @@ mod tests {
         let second_update_client_tx = Transaction {
             transaction_body: TransactionBody {
                 actions: vec![Action::IBCAction(second_update_client_action)],
-                merkle_root: merkle::Root(Fq::zero()),
+                merkle_root: merkle::NoteCommitmentTree::new().root(),
                 expiry_height: 0,
                 chain_id: "".to_string(),
                 fee: Fee(0),
@@ -875,4 +1490,254 @@ mod tests {
         // save the next tm state
         client_component.execute_tx(&second_update_client_tx).await;
     }
+
+    // test that misbehaviour is only accepted as evidence when its headers conflict at the same
+    // height, or break the monotonicity of BFT time.
+    #[test]
+    fn test_validate_submit_misbehaviour_stateless() {
+        let lower = stargaze_header("../ibc/test/update_client_1.msg");
+        let higher = stargaze_header("../ibc/test/update_client_2.msg");
+        assert!(higher.height() > lower.height());
+        assert!(higher.signed_header.header().time > lower.signed_header.header().time);
+
+        // the same header twice doesn't conflict.
+        let same = validate_submit_misbehaviour_stateless(&submit_misbehaviour(
+            lower.clone(),
+            lower.clone(),
+        ))
+        .unwrap_err();
+        assert!(same.to_string().contains("do not conflict"));
+
+        // two different headers at the same height do.
+        let conflicting = with_time(&lower, "2000-01-01T00:00:00Z");
+        validate_submit_misbehaviour_stateless(&submit_misbehaviour(lower.clone(), conflicting))
+            .unwrap();
+
+        // the higher header comes first.
+        let out_of_order = validate_submit_misbehaviour_stateless(&submit_misbehaviour(
+            lower.clone(),
+            higher.clone(),
+        ))
+        .unwrap_err();
+        assert!(out_of_order.to_string().contains("lower than the second"));
+
+        // headers whose times increase with their heights are not misbehaviour...
+        let monotonic = validate_submit_misbehaviour_stateless(&submit_misbehaviour(
+            higher.clone(),
+            lower.clone(),
+        ))
+        .unwrap_err();
+        assert!(monotonic.to_string().contains("BFT time monotonicity"));
+
+        // ... but a higher header with an earlier time is.
+        validate_submit_misbehaviour_stateless(&submit_misbehaviour(
+            with_time(&higher, "2000-01-01T00:00:00Z"),
+            lower,
+        ))
+        .unwrap();
+    }
+
+    // test the path under which a counterparty commits to its upgraded client and consensus
+    // states.
+    #[test]
+    fn test_upgrade_merkle_path() {
+        let mut client_state = match stargaze_create_client().client_state {
+            AnyClientState::Tendermint(client_state) => client_state,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("stargaze client is a tendermint client"),
+        };
+        client_state.upgrade_path = vec!["upgrade".to_string(), "upgradedIBCState".to_string()];
+
+        let path =
+            upgrade_merkle_path(&client_state, Height::new(1, 100), "upgradedClient").unwrap();
+        assert_eq!(
+            path.key_path,
+            vec![
+                "upgrade".to_string(),
+                "upgradedIBCState/100/upgradedClient".to_string()
+            ]
+        );
+
+        client_state.upgrade_path = Vec::new();
+        assert!(upgrade_merkle_path(&client_state, Height::new(1, 100), "upgradedClient").is_err());
+    }
+
+    // test that an upgrade must move the client forward and be proven by the counterparty, and
+    // that executing it replaces the client state and its latest consensus state.
+    #[tokio::test]
+    async fn test_upgrade_client() {
+        let (_dir, mut client_component) = stargaze_client_component().await;
+        let client_id = ClientId::new(ClientType::Tendermint, 0).unwrap();
+
+        // the counterparty commits to its upgrades under the stored client's upgrade path.
+        let client_data = client_component
+            .overlay
+            .get_client_data(&client_id)
+            .await
+            .unwrap();
+        let mut client_state = match client_data.client_state.0.clone() {
+            AnyClientState::Tendermint(client_state) => client_state,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("stargaze client is a tendermint client"),
+        };
+        client_state.upgrade_path = vec!["upgrade".to_string(), "upgradedIBCState".to_string()];
+        client_component
+            .overlay
+            .put_client_data(client_data.with_new_client_state(
+                AnyClientState::Tendermint(client_state),
+                client_data.processed_time.clone(),
+                client_data.processed_height,
+            ))
+            .await;
+
+        let create_client = stargaze_create_client();
+        let mut upgraded_client_state = match create_client.client_state {
+            AnyClientState::Tendermint(client_state) => client_state,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("stargaze client is a tendermint client"),
+        };
+        upgraded_client_state.upgrade_path =
+            vec!["upgrade".to_string(), "upgradedIBCState".to_string()];
+        let last_height = upgraded_client_state.latest_height;
+        let msg = |upgraded_client_state: TendermintClientState| MsgUpgradeAnyClient {
+            client_id: client_id.clone(),
+            client_state: AnyClientState::Tendermint(upgraded_client_state),
+            consensus_state: create_client.consensus_state.clone(),
+            proof_upgrade_client: RawMerkleProof { proofs: Vec::new() },
+            proof_upgrade_consensus_state: RawMerkleProof { proofs: Vec::new() },
+            signer: Signer::new("signer"),
+        };
+
+        let not_greater = client_component
+            .validate_upgrade_client_stateful(msg(upgraded_client_state.clone()))
+            .await
+            .unwrap_err();
+        assert!(not_greater.to_string().contains("is not greater"));
+
+        upgraded_client_state.latest_height = last_height.increment();
+        let unproven = client_component
+            .validate_upgrade_client_stateful(msg(upgraded_client_state.clone()))
+            .await
+            .unwrap_err();
+        assert!(unproven
+            .to_string()
+            .contains("could not verify upgrade proof"));
+
+        client_component
+            .execute_upgrade_client(msg(upgraded_client_state.clone()))
+            .await;
+        let client_data = client_component
+            .overlay
+            .get_client_data(&client_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            client_data.client_state.0.latest_height(),
+            last_height.increment()
+        );
+        assert!(!client_data.client_state.0.is_frozen());
+        let consensus_state = client_component
+            .overlay
+            .get_verified_consensus_state(last_height.increment(), client_id)
+            .await
+            .unwrap()
+            .as_tendermint()
+            .unwrap();
+        assert_eq!(
+            consensus_state.root,
+            CommitmentRoot::from_bytes(SENTINEL_ROOT)
+        );
+    }
+
+    // test that the neighbouring verified consensus states of a height are found regardless of
+    // the order in which they were verified.
+    #[tokio::test]
+    async fn test_prev_and_next_verified_consensus_state() {
+        let (_dir, mut client_component) = stargaze_client_component().await;
+        let client_id = ClientId::new(ClientType::Tendermint, 1).unwrap();
+
+        let base = match stargaze_create_client().consensus_state {
+            AnyConsensusState::Tendermint(consensus_state) => consensus_state,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("stargaze consensus state is a tendermint consensus state"),
+        };
+        let root = |height: u64| CommitmentRoot::from_bytes(&height.to_be_bytes());
+        for height in [10u64, 5, 20] {
+            let mut consensus_state = base.clone();
+            consensus_state.root = root(height);
+            client_component
+                .overlay
+                .put_verified_consensus_state(
+                    Height::new(0, height),
+                    client_id.clone(),
+                    ConsensusState(AnyConsensusState::Tendermint(consensus_state)),
+                )
+                .await
+                .unwrap();
+        }
+
+        let overlay = &client_component.overlay;
+        let prev = |height: u64| {
+            let client_id = client_id.clone();
+            async move {
+                overlay
+                    .prev_verified_consensus_state(&client_id, Height::new(0, height))
+                    .await
+                    .unwrap()
+                    .map(|consensus_state| consensus_state.as_tendermint().unwrap().root)
+            }
+        };
+        let next = |height: u64| {
+            let client_id = client_id.clone();
+            async move {
+                overlay
+                    .next_verified_consensus_state(&client_id, Height::new(0, height))
+                    .await
+                    .unwrap()
+                    .map(|consensus_state| consensus_state.as_tendermint().unwrap().root)
+            }
+        };
+
+        // the previous state is the highest one below the height, not the lowest.
+        assert_eq!(prev(15).await, Some(root(10)));
+        assert_eq!(prev(25).await, Some(root(20)));
+        assert_eq!(prev(5).await, None);
+        // the next state is the lowest one above the height.
+        assert_eq!(next(4).await, Some(root(5)));
+        assert_eq!(next(10).await, Some(root(20)));
+        assert_eq!(next(20).await, None);
+    }
+
+    // test that penumbra's own consensus states are only kept above the pruning height, even if
+    // the window shrinks between blocks.
+    #[tokio::test]
+    async fn test_prune_penumbra_consensus_states() {
+        let (_dir, client_component) = stargaze_client_component().await;
+        let overlay = &client_component.overlay;
+
+        let consensus_state = ConsensusState(stargaze_create_client().consensus_state);
+        for height in 1..=10 {
+            overlay
+                .put_penumbra_consensus_state(height, consensus_state.clone())
+                .await;
+        }
+        let kept =
+            |height: u64| async move { overlay.get_penumbra_consensus_state(height).await.is_ok() };
+
+        overlay.prune_penumbra_consensus_states(4).await.unwrap();
+        assert!(!kept(1).await);
+        assert!(!kept(3).await);
+        assert!(kept(4).await);
+
+        // pruning below the oldest kept height is a no-op...
+        overlay.prune_penumbra_consensus_states(2).await.unwrap();
+        assert!(kept(4).await);
+
+        // ... and every state below a later pruning height is deleted.
+        overlay.prune_penumbra_consensus_states(8).await.unwrap();
+        assert!(!kept(4).await);
+        assert!(!kept(7).await);
+        assert!(kept(8).await);
+        assert!(kept(10).await);
+    }
 }
@@ -0,0 +1,877 @@
+use std::convert::TryFrom;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ibc::{
+    core::{
+        ics02_client::{
+            client_def::{AnyClient, ClientDef},
+            client_state::{AnyClientState, ClientState},
+            height::Height,
+        },
+        ics03_connection::{
+            connection::{ConnectionEnd, Counterparty, State},
+            msgs::{
+                conn_open_ack::MsgConnectionOpenAck, conn_open_confirm::MsgConnectionOpenConfirm,
+                conn_open_init::MsgConnectionOpenInit, conn_open_try::MsgConnectionOpenTry,
+            },
+            version::{get_compatible_versions, pick_version},
+        },
+        ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+        ics24_host::identifier::{ChainId, ClientId, ConnectionId},
+    },
+    proofs::Proofs,
+};
+use penumbra_ibc::{ClientConnections, Connection, ConnectionCounter, IBCAction};
+use penumbra_proto::ibc::ibc_action::Action::{
+    ConnectionOpenAck, ConnectionOpenConfirm, ConnectionOpenInit, ConnectionOpenTry,
+};
+use penumbra_transaction::Transaction;
+use tendermint::abci;
+use tracing::instrument;
+
+use super::{client::View as _, event};
+use crate::{components::app::View as _, components::Component};
+use crate::{genesis, Overlay, OverlayExt};
+
+/// The prefix under which Penumbra commits to its IBC state, which counterparties use to
+/// verify proofs of our connection ends: every key they verify is this prefix followed by its
+/// ICS-24 path.
+pub const COMMITMENT_PREFIX: &[u8] = b"ibc";
+
+/// The Penumbra IBC connection component. Handles the four ICS-03 connection handshake messages:
+/// MsgConnectionOpenInit, MsgConnectionOpenTry, MsgConnectionOpenAck and
+/// MsgConnectionOpenConfirm. Each step after the first is checked against a proof of the
+/// counterparty's connection end, verified using the light client the connection is built on.
+/// The OpenTry and OpenAck steps also check proofs that the counterparty's client of Penumbra,
+/// and its consensus state for Penumbra, match Penumbra's own.
+pub struct ConnectionComponent {
+    overlay: Overlay,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
+}
+
+#[async_trait]
+impl Component for ConnectionComponent {
+    #[instrument(name = "ics3_connection", skip(overlay))]
+    async fn new(overlay: Overlay) -> Self {
+        Self {
+            overlay,
+            events: Vec::new(),
+        }
+    }
+
+    #[instrument(name = "ics3_connection", skip(self, _app_state))]
+    async fn init_chain(&mut self, _app_state: &genesis::AppState) {
+        // set the initial connection count
+        self.overlay
+            .put_connection_counter(ConnectionCounter(0))
+            .await;
+    }
+
+    #[instrument(name = "ics3_connection", skip(self, _begin_block))]
+    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+
+    #[instrument(name = "ics3_connection", skip(tx))]
+    fn check_tx_stateless(tx: &Transaction) -> Result<()> {
+        for ibc_action in tx.ibc_actions() {
+            validate_ibc_action_stateless(ibc_action)?;
+        }
+        Ok(())
+    }
+
+    #[instrument(name = "ics3_connection", skip(self, tx))]
+    async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        for ibc_action in tx.ibc_actions() {
+            self.validate_ibc_action_stateful(ibc_action).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(name = "ics3_connection", skip(self, tx))]
+    async fn execute_tx(&mut self, tx: &Transaction) {
+        for ibc_action in tx.ibc_actions() {
+            self.execute_ibc_action(ibc_action).await;
+        }
+    }
+
+    #[instrument(name = "ics3_connection", skip(self, _end_block))]
+    async fn end_block(&mut self, _end_block: &abci::request::EndBlock) {}
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+// validates the given ibc action statelessly
+fn validate_ibc_action_stateless(ibc_action: &IBCAction) -> Result<()> {
+    match &ibc_action.action {
+        ConnectionOpenInit(msg) => {
+            let msg = MsgConnectionOpenInit::try_from(msg.clone())?;
+
+            // the counterparty connection ID is only known once the counterparty has executed
+            // ConnectionOpenTry.
+            if msg.counterparty.connection_id().is_some() {
+                return Err(anyhow::anyhow!(
+                    "counterparty connection ID must be empty in ConnectionOpenInit"
+                ));
+            }
+        }
+        ConnectionOpenTry(msg) => {
+            let msg = MsgConnectionOpenTry::try_from(msg.clone())?;
+
+            // we don't support resuming a handshake from a previous connection, which was
+            // deprecated in ICS-03.
+            if msg.previous_connection_id.is_some() {
+                return Err(anyhow::anyhow!(
+                    "resuming from a previous connection is not supported"
+                ));
+            }
+            // the counterparty connection ID is needed to verify the counterparty's
+            // connection end.
+            if msg.counterparty.connection_id().is_none() {
+                return Err(anyhow::anyhow!(
+                    "counterparty connection ID must be set in ConnectionOpenTry"
+                ));
+            }
+            if msg.counterparty_versions.is_empty() {
+                return Err(anyhow::anyhow!("no counterparty versions were supplied"));
+            }
+        }
+        ConnectionOpenAck(msg) => {
+            MsgConnectionOpenAck::try_from(msg.clone())?;
+        }
+        ConnectionOpenConfirm(msg) => {
+            MsgConnectionOpenConfirm::try_from(msg.clone())?;
+        }
+        _ => return Ok(()),
+    }
+
+    Ok(())
+}
+
+impl ConnectionComponent {
+    // validates the given IBC action statefully.
+    async fn validate_ibc_action_stateful(&self, ibc_action: &IBCAction) -> Result<()> {
+        match &ibc_action.action {
+            ConnectionOpenInit(msg) => {
+                let msg = MsgConnectionOpenInit::try_from(msg.clone())?;
+
+                self.validate_connection_open_init_stateful(&msg).await?;
+            }
+            ConnectionOpenTry(msg) => {
+                let msg = MsgConnectionOpenTry::try_from(msg.clone())?;
+
+                self.validate_connection_open_try_stateful(&msg).await?;
+            }
+            ConnectionOpenAck(msg) => {
+                let msg = MsgConnectionOpenAck::try_from(msg.clone())?;
+
+                self.validate_connection_open_ack_stateful(&msg).await?;
+            }
+            ConnectionOpenConfirm(msg) => {
+                let msg = MsgConnectionOpenConfirm::try_from(msg.clone())?;
+
+                self.validate_connection_open_confirm_stateful(&msg).await?;
+            }
+            _ => return Ok(()),
+        }
+
+        Ok(())
+    }
+
+    // executes the given IBC action, assuming that it has already been validated.
+    async fn execute_ibc_action(&mut self, ibc_action: &IBCAction) {
+        match &ibc_action.action {
+            ConnectionOpenInit(raw_msg) => {
+                let msg = MsgConnectionOpenInit::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_connection_open_init(msg).await;
+            }
+            ConnectionOpenTry(raw_msg) => {
+                let msg = MsgConnectionOpenTry::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_connection_open_try(msg).await;
+            }
+            ConnectionOpenAck(raw_msg) => {
+                let msg = MsgConnectionOpenAck::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_connection_open_ack(msg).await;
+            }
+            ConnectionOpenConfirm(raw_msg) => {
+                let msg = MsgConnectionOpenConfirm::try_from(raw_msg.clone()).unwrap();
+
+                self.execute_connection_open_confirm(msg).await;
+            }
+            _ => {}
+        }
+    }
+
+    // verify:
+    // - the client the connection is built on exists
+    // - if a version is proposed, it is one we support
+    async fn validate_connection_open_init_stateful(
+        &self,
+        msg: &MsgConnectionOpenInit,
+    ) -> Result<()> {
+        self.overlay.get_client_data(&msg.client_id).await?;
+
+        if let Some(version) = &msg.version {
+            if !get_compatible_versions().contains(version) {
+                return Err(anyhow::anyhow!("unsupported connection version"));
+            }
+        }
+
+        Ok(())
+    }
+
+    // verify:
+    // - the counterparty's view of our chain is not ahead of our chain
+    // - we support one of the counterparty's proposed versions
+    // - the counterparty's client of Penumbra, and its consensus state for Penumbra, match ours
+    // - the counterparty has stored a connection end in the INIT state, pointing at the client
+    //   this connection is built on
+    async fn validate_connection_open_try_stateful(
+        &self,
+        msg: &MsgConnectionOpenTry,
+    ) -> Result<()> {
+        self.validate_consensus_height(&msg.proofs).await?;
+
+        pick_version(get_compatible_versions(), msg.counterparty_versions.clone())?;
+
+        self.verify_counterparty_client(
+            &msg.client_id,
+            &msg.proofs,
+            msg.counterparty.prefix(),
+            msg.counterparty.client_id(),
+            msg.client_state.as_ref(),
+        )
+        .await?;
+
+        let expected_counterparty_connection = ConnectionEnd::new(
+            State::Init,
+            msg.counterparty.client_id().clone(),
+            Counterparty::new(msg.client_id.clone(), None, commitment_prefix()),
+            msg.counterparty_versions.clone(),
+            msg.delay_period,
+        );
+
+        self.verify_connection_state(
+            &msg.client_id,
+            msg.proofs.height(),
+            msg.counterparty.prefix(),
+            msg.proofs.object_proof(),
+            msg.counterparty
+                .connection_id()
+                .expect("counterparty connection ID is checked statelessly"),
+            &expected_counterparty_connection,
+        )
+        .await
+    }
+
+    // verify:
+    // - the connection exists and is in the INIT state
+    // - the counterparty's view of our chain is not ahead of our chain
+    // - the version chosen by the counterparty is one we proposed
+    // - the counterparty's client of Penumbra, and its consensus state for Penumbra, match ours
+    // - the counterparty has stored a connection end in the TRYOPEN state, pointing at our
+    //   connection
+    async fn validate_connection_open_ack_stateful(
+        &self,
+        msg: &MsgConnectionOpenAck,
+    ) -> Result<()> {
+        let connection = self.overlay.get_connection(&msg.connection_id).await?;
+        if !connection.state_matches(&State::Init) {
+            return Err(anyhow::anyhow!("connection is not in the INIT state"));
+        }
+
+        self.validate_consensus_height(&msg.proofs).await?;
+
+        if !connection.versions().contains(&msg.version) {
+            return Err(anyhow::anyhow!(
+                "counterparty chose a version that was not proposed"
+            ));
+        }
+
+        self.verify_counterparty_client(
+            connection.client_id(),
+            &msg.proofs,
+            connection.counterparty().prefix(),
+            connection.counterparty().client_id(),
+            msg.client_state.as_ref(),
+        )
+        .await?;
+
+        let expected_counterparty_connection = ConnectionEnd::new(
+            State::TryOpen,
+            connection.counterparty().client_id().clone(),
+            Counterparty::new(
+                connection.client_id().clone(),
+                Some(msg.connection_id.clone()),
+                commitment_prefix(),
+            ),
+            vec![msg.version.clone()],
+            connection.delay_period(),
+        );
+
+        self.verify_connection_state(
+            connection.client_id(),
+            msg.proofs.height(),
+            connection.counterparty().prefix(),
+            msg.proofs.object_proof(),
+            &msg.counterparty_connection_id,
+            &expected_counterparty_connection,
+        )
+        .await
+    }
+
+    // verify:
+    // - the connection exists and is in the TRYOPEN state
+    // - the counterparty has stored a connection end in the OPEN state, pointing at our
+    //   connection
+    async fn validate_connection_open_confirm_stateful(
+        &self,
+        msg: &MsgConnectionOpenConfirm,
+    ) -> Result<()> {
+        let connection = self.overlay.get_connection(&msg.connection_id).await?;
+        if !connection.state_matches(&State::TryOpen) {
+            return Err(anyhow::anyhow!("connection is not in the TRYOPEN state"));
+        }
+
+        let counterparty_connection_id = connection
+            .counterparty()
+            .connection_id()
+            .ok_or_else(|| anyhow::anyhow!("counterparty connection ID is not set"))?;
+
+        let expected_counterparty_connection = ConnectionEnd::new(
+            State::Open,
+            connection.counterparty().client_id().clone(),
+            Counterparty::new(
+                connection.client_id().clone(),
+                Some(msg.connection_id.clone()),
+                commitment_prefix(),
+            ),
+            connection.versions().to_vec(),
+            connection.delay_period(),
+        );
+
+        self.verify_connection_state(
+            connection.client_id(),
+            msg.proofs.height(),
+            connection.counterparty().prefix(),
+            msg.proofs.object_proof(),
+            counterparty_connection_id,
+            &expected_counterparty_connection,
+        )
+        .await
+    }
+
+    // check that the height at which the counterparty claims to have verified our consensus
+    // state is in the past.
+    async fn validate_consensus_height(&self, proofs: &Proofs) -> Result<()> {
+        if let Some(consensus_proof) = proofs.consensus_proof() {
+            let height = self.overlay.get_block_height().await?;
+            if consensus_proof.height().revision_height >= height {
+                return Err(anyhow::anyhow!(
+                    "consensus height {} is not lower than the current height {}",
+                    consensus_proof.height(),
+                    height
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // verify proofs that the counterparty's client of Penumbra, stored under
+    // `counterparty_client_id`, is `counterparty_client_state`, which must be a valid client of
+    // Penumbra, and that its consensus state at the consensus height is Penumbra's own consensus
+    // state at that height.
+    async fn verify_counterparty_client(
+        &self,
+        client_id: &ClientId,
+        proofs: &Proofs,
+        counterparty_prefix: &CommitmentPrefix,
+        counterparty_client_id: &ClientId,
+        counterparty_client_state: Option<&AnyClientState>,
+    ) -> Result<()> {
+        let counterparty_client_state = counterparty_client_state
+            .ok_or_else(|| anyhow::anyhow!("counterparty client state is required"))?;
+        let client_proof = proofs
+            .client_proof()
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("counterparty client state proof is required"))?;
+        let consensus_proof = proofs
+            .consensus_proof()
+            .ok_or_else(|| anyhow::anyhow!("counterparty consensus state proof is required"))?;
+
+        let chain_id = self.overlay.get_chain_id().await?;
+        let height = self.overlay.get_block_height().await?;
+        validate_penumbra_client_state(counterparty_client_state, &chain_id, height)?;
+
+        let (client_state, root) = self.trusted_client(client_id, proofs.height()).await?;
+        let client = AnyClient::from_client_type(client_state.client_type());
+
+        client
+            .verify_client_full_state(
+                &client_state,
+                proofs.height(),
+                counterparty_prefix,
+                client_proof,
+                &root,
+                counterparty_client_id,
+                counterparty_client_state,
+            )
+            .map_err(|e| anyhow::anyhow!("could not verify counterparty client state: {}", e))?;
+
+        let expected_consensus_state = self
+            .overlay
+            .get_penumbra_consensus_state(consensus_proof.height().revision_height)
+            .await?;
+        client
+            .verify_client_consensus_state(
+                &client_state,
+                proofs.height(),
+                counterparty_prefix,
+                consensus_proof.proof(),
+                &root,
+                counterparty_client_id,
+                consensus_proof.height(),
+                &expected_consensus_state.0,
+            )
+            .map_err(|e| anyhow::anyhow!("could not verify counterparty consensus state: {}", e))
+    }
+
+    // verify a proof that the counterparty has stored `expected_connection` under
+    // `counterparty_connection_id`, against the consensus state of `client_id` at `proof_height`.
+    async fn verify_connection_state(
+        &self,
+        client_id: &ClientId,
+        proof_height: Height,
+        counterparty_prefix: &CommitmentPrefix,
+        proof: &CommitmentProofBytes,
+        counterparty_connection_id: &ConnectionId,
+        expected_connection: &ConnectionEnd,
+    ) -> Result<()> {
+        let (client_state, root) = self.trusted_client(client_id, proof_height).await?;
+
+        AnyClient::from_client_type(client_state.client_type())
+            .verify_connection_state(
+                &client_state,
+                proof_height,
+                counterparty_prefix,
+                proof,
+                &root,
+                counterparty_connection_id,
+                expected_connection,
+            )
+            .map_err(|e| anyhow::anyhow!("could not verify counterparty connection: {}", e))
+    }
+
+    // the state of the (unfrozen) client `client_id`, and the commitment root of its consensus
+    // state at `proof_height`, against which counterparty proofs are verified.
+    async fn trusted_client(
+        &self,
+        client_id: &ClientId,
+        proof_height: Height,
+    ) -> Result<(AnyClientState, CommitmentRoot)> {
+        let client_data = self.overlay.get_client_data(client_id).await?;
+        if client_data.client_state.0.is_frozen() {
+            return Err(anyhow::anyhow!("client {} is frozen", client_id));
+        }
+
+        let trusted_consensus_state = self
+            .overlay
+            .get_verified_consensus_state(proof_height, client_id.clone())
+            .await?
+            .as_tendermint()?;
+
+        Ok((client_data.client_state.0, trusted_consensus_state.root))
+    }
+
+    // execute ConnectionOpenInit, creating a new connection in the INIT state.
+    async fn execute_connection_open_init(&mut self, msg: MsgConnectionOpenInit) {
+        let versions = match msg.version {
+            Some(version) => vec![version],
+            None => get_compatible_versions(),
+        };
+        let connection = ConnectionEnd::new(
+            State::Init,
+            msg.client_id,
+            msg.counterparty,
+            versions,
+            msg.delay_period,
+        );
+
+        let connection_id = self.add_connection(connection.clone()).await;
+        self.events
+            .push(event::connection_open_init(&connection_id, &connection));
+    }
+
+    // execute ConnectionOpenTry, creating a new connection in the TRYOPEN state.
+    async fn execute_connection_open_try(&mut self, msg: MsgConnectionOpenTry) {
+        let version = pick_version(get_compatible_versions(), msg.counterparty_versions)
+            .expect("version is checked statefully");
+        let connection = ConnectionEnd::new(
+            State::TryOpen,
+            msg.client_id,
+            msg.counterparty,
+            vec![version],
+            msg.delay_period,
+        );
+
+        let connection_id = self.add_connection(connection.clone()).await;
+        self.events
+            .push(event::connection_open_try(&connection_id, &connection));
+    }
+
+    // execute ConnectionOpenAck, opening a connection in the INIT state.
+    async fn execute_connection_open_ack(&mut self, msg: MsgConnectionOpenAck) {
+        let connection = self
+            .overlay
+            .get_connection(&msg.connection_id)
+            .await
+            .unwrap();
+
+        let connection = ConnectionEnd::new(
+            State::Open,
+            connection.client_id().clone(),
+            Counterparty::new(
+                connection.counterparty().client_id().clone(),
+                Some(msg.counterparty_connection_id),
+                connection.counterparty().prefix().clone(),
+            ),
+            vec![msg.version],
+            connection.delay_period(),
+        );
+
+        self.overlay
+            .put_connection(&msg.connection_id, connection.clone())
+            .await;
+        self.events
+            .push(event::connection_open_ack(&msg.connection_id, &connection));
+    }
+
+    // execute ConnectionOpenConfirm, opening a connection in the TRYOPEN state.
+    async fn execute_connection_open_confirm(&mut self, msg: MsgConnectionOpenConfirm) {
+        let mut connection = self
+            .overlay
+            .get_connection(&msg.connection_id)
+            .await
+            .unwrap();
+        connection.set_state(State::Open);
+
+        self.overlay
+            .put_connection(&msg.connection_id, connection.clone())
+            .await;
+        self.events.push(event::connection_open_confirm(
+            &msg.connection_id,
+            &connection,
+        ));
+    }
+
+    // store a new connection under the next connection ID, and record it in the list of
+    // connections of its client.
+    async fn add_connection(&mut self, connection: ConnectionEnd) -> ConnectionId {
+        let counter = self.overlay.connection_counter().await.unwrap();
+        let connection_id = ConnectionId::new(counter.0);
+
+        tracing::info!("creating connection {:?}", connection_id);
+
+        self.overlay
+            .put_connection(&connection_id, connection.clone())
+            .await;
+        self.overlay
+            .add_client_connection(connection.client_id(), connection_id.clone())
+            .await
+            .unwrap();
+        self.overlay
+            .put_connection_counter(ConnectionCounter(counter.0 + 1))
+            .await;
+
+        connection_id
+    }
+}
+
+/// Checks that a counterparty's client of Penumbra is a Tendermint client of this chain, which is
+/// not frozen, and which has not been updated past the current `height`.
+fn validate_penumbra_client_state(
+    client_state: &AnyClientState,
+    chain_id: &str,
+    height: u64,
+) -> Result<()> {
+    let client_state = match client_state {
+        AnyClientState::Tendermint(client_state) => client_state,
+        #[allow(unreachable_patterns)]
+        _ => {
+            return Err(anyhow::anyhow!(
+                "client of penumbra must be a tendermint client"
+            ))
+        }
+    };
+
+    let chain_id = ChainId::from_string(chain_id);
+    if client_state.chain_id != chain_id {
+        return Err(anyhow::anyhow!(
+            "client of penumbra is for chain {}, not {}",
+            client_state.chain_id,
+            chain_id
+        ));
+    }
+    if client_state.is_frozen() {
+        return Err(anyhow::anyhow!("client of penumbra is frozen"));
+    }
+    if client_state.latest_height.revision_number != chain_id.version()
+        || client_state.latest_height.revision_height >= height
+    {
+        return Err(anyhow::anyhow!(
+            "client of penumbra has height {}, but the current height is {}",
+            client_state.latest_height,
+            height
+        ));
+    }
+
+    Ok(())
+}
+
+/// The commitment prefix of the Penumbra IBC state.
+pub fn commitment_prefix() -> CommitmentPrefix {
+    CommitmentPrefix::from(COMMITMENT_PREFIX.to_vec())
+}
+
+#[async_trait]
+pub trait View: OverlayExt + Send + Sync {
+    async fn put_connection_counter(&mut self, counter: ConnectionCounter) {
+        self.put_domain(state_key::connection_counter().into(), counter)
+            .await;
+    }
+    async fn connection_counter(&self) -> Result<ConnectionCounter> {
+        self.get_domain(state_key::connection_counter().into())
+            .await
+            .map(|counter| counter.unwrap_or(ConnectionCounter(0)))
+    }
+    async fn put_connection(&mut self, connection_id: &ConnectionId, connection: ConnectionEnd) {
+        self.put_domain(
+            state_key::connection(connection_id).into(),
+            Connection(connection),
+        )
+        .await;
+    }
+    async fn get_connection(&self, connection_id: &ConnectionId) -> Result<ConnectionEnd> {
+        self.get_domain::<Connection, _>(state_key::connection(connection_id).into())
+            .await?
+            .map(|connection| connection.0)
+            .ok_or_else(|| anyhow::anyhow!("connection {} not found", connection_id))
+    }
+    async fn get_client_connections(&self, client_id: &ClientId) -> Result<ClientConnections> {
+        self.get_domain(state_key::client_connections(client_id).into())
+            .await
+            .map(|connections| connections.unwrap_or_default())
+    }
+    async fn add_client_connection(
+        &mut self,
+        client_id: &ClientId,
+        connection_id: ConnectionId,
+    ) -> Result<()> {
+        let mut connections = self.get_client_connections(client_id).await?;
+        connections.connection_ids.push(connection_id);
+
+        self.put_domain(state_key::client_connections(client_id).into(), connections)
+            .await;
+
+        Ok(())
+    }
+}
+
+impl<T: OverlayExt + Send + Sync> View for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::client::ClientComponent;
+    use super::*;
+    use crate::Storage;
+    use ibc::{
+        core::{
+            ics02_client::msgs::create_client::MsgCreateAnyClient,
+            ics03_connection::version::Version,
+        },
+        proofs::ConsensusProof,
+        signer::Signer,
+    };
+    use ibc_proto::ibc::core::client::v1::MsgCreateClient as RawMsgCreateClient;
+    use penumbra_chain::params::ChainParams;
+    use penumbra_crypto::merkle;
+    use penumbra_proto::{ibc::ibc_action::Action as IBCActionInner, Message};
+    use penumbra_transaction::{Action, Fee, TransactionBody};
+    use std::{fs, time::Duration};
+    use tempfile::tempdir;
+    use tendermint::Time;
+
+    const PENUMBRA_CHAIN_ID: &str = "penumbra-testnet";
+
+    // the stargaze client used in the client component test.
+    fn stargaze_client_msg() -> RawMsgCreateClient {
+        let raw = base64::decode(
+            fs::read_to_string("../ibc/test/create_client.msg")
+                .unwrap()
+                .replace('\n', ""),
+        )
+        .unwrap();
+        RawMsgCreateClient::decode(raw.as_slice()).unwrap()
+    }
+
+    // a client of penumbra at the given height, as the counterparty would store it.
+    fn penumbra_client_state(height: u64) -> AnyClientState {
+        let msg = MsgCreateAnyClient::try_from(stargaze_client_msg()).unwrap();
+        match msg.client_state {
+            AnyClientState::Tendermint(mut client_state) => {
+                client_state.chain_id = ChainId::from_string(PENUMBRA_CHAIN_ID);
+                client_state.latest_height = Height::new(0, height);
+                AnyClientState::Tendermint(client_state)
+            }
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("stargaze client is a tendermint client"),
+        }
+    }
+
+    fn proof_bytes() -> CommitmentProofBytes {
+        vec![1, 2, 3].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_validate_penumbra_client_state() {
+        validate_penumbra_client_state(&penumbra_client_state(5), PENUMBRA_CHAIN_ID, 10).unwrap();
+
+        // a client of some other chain
+        assert!(
+            validate_penumbra_client_state(&penumbra_client_state(5), "penumbra-other", 10)
+                .is_err()
+        );
+        // a client that is ahead of the chain
+        assert!(
+            validate_penumbra_client_state(&penumbra_client_state(10), PENUMBRA_CHAIN_ID, 10)
+                .is_err()
+        );
+    }
+
+    // test that ConnectionOpenTry checks the counterparty's client of penumbra, and the proofs of
+    // its client and consensus states.
+    #[tokio::test]
+    async fn test_connection_open_try_verifies_counterparty_client() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("ibc-testing.db"))
+            .await
+            .unwrap();
+        let overlay = storage.overlay().await.unwrap();
+
+        overlay
+            .put_chain_params(ChainParams {
+                chain_id: PENUMBRA_CHAIN_ID.to_string(),
+                ..Default::default()
+            })
+            .await;
+        overlay
+            .put_block_timestamp(
+                Time::parse_from_rfc3339("2022-02-11T17:30:50.425417198Z").unwrap(),
+            )
+            .await;
+        overlay.put_block_height(10).await;
+
+        // create the stargaze client, which the connection is built on.
+        let mut client_component = ClientComponent::new(overlay.clone()).await;
+        client_component
+            .init_chain(&genesis::AppState::default())
+            .await;
+        let create_client_tx = Transaction {
+            transaction_body: TransactionBody {
+                actions: vec![Action::IBCAction(IBCAction {
+                    action: IBCActionInner::CreateClient(stargaze_client_msg()),
+                })],
+                merkle_root: merkle::NoteCommitmentTree::new().root(),
+                expiry_height: 0,
+                chain_id: "".to_string(),
+                fee: Fee(0),
+            },
+            binding_sig: [0u8; 64].into(),
+        };
+        client_component
+            .check_tx_stateful(&create_client_tx)
+            .await
+            .unwrap();
+        client_component.execute_tx(&create_client_tx).await;
+
+        let client_id = ClientId::new(
+            ibc::core::ics02_client::client_type::ClientType::Tendermint,
+            0,
+        )
+        .unwrap();
+        let proof_height = overlay
+            .get_client_data(&client_id)
+            .await
+            .unwrap()
+            .client_state
+            .0
+            .latest_height();
+
+        // any consensus state will do as penumbra's own, since the proofs are never checked
+        // against it.
+        let consensus_state = overlay
+            .get_verified_consensus_state(proof_height, client_id.clone())
+            .await
+            .unwrap();
+        overlay
+            .put_penumbra_consensus_state(5, consensus_state)
+            .await;
+
+        let connection_component = ConnectionComponent::new(overlay.clone()).await;
+        let msg = |client_state: Option<AnyClientState>| MsgConnectionOpenTry {
+            previous_connection_id: None,
+            client_id: client_id.clone(),
+            client_state,
+            counterparty: Counterparty::new(
+                client_id.clone(),
+                Some(ConnectionId::new(0)),
+                CommitmentPrefix::from(b"ibc".to_vec()),
+            ),
+            counterparty_versions: vec![Version::default()],
+            proofs: Proofs::new(
+                proof_bytes(),
+                Some(proof_bytes()),
+                Some(ConsensusProof::new(proof_bytes(), Height::new(0, 5)).unwrap()),
+                None,
+                proof_height,
+            )
+            .unwrap(),
+            delay_period: Duration::from_secs(0),
+            signer: Signer::new("signer"),
+        };
+
+        let missing_client = connection_component
+            .validate_connection_open_try_stateful(&msg(None))
+            .await
+            .unwrap_err();
+        assert!(missing_client
+            .to_string()
+            .contains("counterparty client state is required"));
+
+        let wrong_chain = connection_component
+            .validate_connection_open_try_stateful(&msg(Some(
+                MsgCreateAnyClient::try_from(stargaze_client_msg())
+                    .unwrap()
+                    .client_state,
+            )))
+            .await
+            .unwrap_err();
+        assert!(wrong_chain
+            .to_string()
+            .contains("client of penumbra is for chain"));
+
+        let bad_proof = connection_component
+            .validate_connection_open_try_stateful(&msg(Some(penumbra_client_state(5))))
+            .await
+            .unwrap_err();
+        assert!(bad_proof
+            .to_string()
+            .contains("could not verify counterparty client state"));
+    }
+}
@@ -0,0 +1,255 @@
+use ibc::core::{
+    ics02_client::{client_type::ClientType, height::Height},
+    ics03_connection::connection::ConnectionEnd,
+    ics04_channel::{channel::ChannelEnd, packet::Packet},
+    ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+};
+use tendermint::abci::{Event, EventAttributeIndexExt};
+
+// These events follow the format used by the Cosmos SDK's IBC module, so that
+// existing relayers and indexers can understand them.
+
+/// A new light client was created.
+pub fn create_client(client_id: &ClientId, client_type: ClientType, height: Height) -> Event {
+    Event::new(
+        "create_client",
+        vec![
+            ("client_id", client_id.to_string()).index(),
+            ("client_type", client_type.as_str().to_string()).index(),
+            ("consensus_height", height.to_string()).index(),
+        ],
+    )
+}
+
+/// An existing light client was updated with a new header.
+pub fn update_client(client_id: &ClientId, client_type: ClientType, height: Height) -> Event {
+    Event::new(
+        "update_client",
+        vec![
+            ("client_id", client_id.to_string()).index(),
+            ("client_type", client_type.as_str().to_string()).index(),
+            ("consensus_height", height.to_string()).index(),
+        ],
+    )
+}
+
+/// An existing light client was upgraded to follow its counterparty across a chain upgrade.
+pub fn upgrade_client(client_id: &ClientId, client_type: ClientType, height: Height) -> Event {
+    Event::new(
+        "upgrade_client",
+        vec![
+            ("client_id", client_id.to_string()).index(),
+            ("client_type", client_type.as_str().to_string()).index(),
+            ("consensus_height", height.to_string()).index(),
+        ],
+    )
+}
+
+/// A light client was frozen after evidence of misbehaviour was submitted.
+pub fn client_misbehaviour(client_id: &ClientId, client_type: ClientType, height: Height) -> Event {
+    Event::new(
+        "client_misbehaviour",
+        vec![
+            ("client_id", client_id.to_string()).index(),
+            ("client_type", client_type.as_str().to_string()).index(),
+            ("consensus_height", height.to_string()).index(),
+        ],
+    )
+}
+
+/// A connection handshake step, identified by `kind`, was executed on the given connection.
+fn connection_event(kind: &str, connection_id: &ConnectionId, connection: &ConnectionEnd) -> Event {
+    Event::new(
+        kind,
+        vec![
+            ("connection_id", connection_id.to_string()).index(),
+            ("client_id", connection.client_id().to_string()).index(),
+            (
+                "counterparty_client_id",
+                connection.counterparty().client_id().to_string(),
+            )
+                .index(),
+            (
+                "counterparty_connection_id",
+                connection
+                    .counterparty()
+                    .connection_id()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            )
+                .index(),
+        ],
+    )
+}
+
+/// A new connection was initialized by `ConnectionOpenInit`.
+pub fn connection_open_init(connection_id: &ConnectionId, connection: &ConnectionEnd) -> Event {
+    connection_event("connection_open_init", connection_id, connection)
+}
+
+/// A new connection was created in response to a counterparty's `ConnectionOpenInit`.
+pub fn connection_open_try(connection_id: &ConnectionId, connection: &ConnectionEnd) -> Event {
+    connection_event("connection_open_try", connection_id, connection)
+}
+
+/// A connection we initialized was opened by `ConnectionOpenAck`.
+pub fn connection_open_ack(connection_id: &ConnectionId, connection: &ConnectionEnd) -> Event {
+    connection_event("connection_open_ack", connection_id, connection)
+}
+
+/// A connection the counterparty initialized was opened by `ConnectionOpenConfirm`.
+pub fn connection_open_confirm(connection_id: &ConnectionId, connection: &ConnectionEnd) -> Event {
+    connection_event("connection_open_confirm", connection_id, connection)
+}
+
+/// A channel handshake step, identified by `kind`, was executed on the given channel.
+fn channel_event(
+    kind: &str,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    channel: &ChannelEnd,
+) -> Event {
+    Event::new(
+        kind,
+        vec![
+            ("port_id", port_id.to_string()).index(),
+            ("channel_id", channel_id.to_string()).index(),
+            (
+                "counterparty_port_id",
+                channel.counterparty().port_id().to_string(),
+            )
+                .index(),
+            (
+                "counterparty_channel_id",
+                channel
+                    .counterparty()
+                    .channel_id()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            )
+                .index(),
+            (
+                "connection_id",
+                channel
+                    .connection_hops()
+                    .first()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            )
+                .index(),
+        ],
+    )
+}
+
+/// A new channel was initialized by `ChannelOpenInit`.
+pub fn channel_open_init(port_id: &PortId, channel_id: &ChannelId, channel: &ChannelEnd) -> Event {
+    channel_event("channel_open_init", port_id, channel_id, channel)
+}
+
+/// A new channel was created in response to a counterparty's `ChannelOpenInit`.
+pub fn channel_open_try(port_id: &PortId, channel_id: &ChannelId, channel: &ChannelEnd) -> Event {
+    channel_event("channel_open_try", port_id, channel_id, channel)
+}
+
+/// A channel we initialized was opened by `ChannelOpenAck`.
+pub fn channel_open_ack(port_id: &PortId, channel_id: &ChannelId, channel: &ChannelEnd) -> Event {
+    channel_event("channel_open_ack", port_id, channel_id, channel)
+}
+
+/// A channel the counterparty initialized was opened by `ChannelOpenConfirm`.
+pub fn channel_open_confirm(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    channel: &ChannelEnd,
+) -> Event {
+    channel_event("channel_open_confirm", port_id, channel_id, channel)
+}
+
+/// A channel was closed by `ChannelCloseInit`.
+pub fn channel_close_init(port_id: &PortId, channel_id: &ChannelId, channel: &ChannelEnd) -> Event {
+    channel_event("channel_close_init", port_id, channel_id, channel)
+}
+
+/// A channel was closed in response to the counterparty closing it.
+pub fn channel_close_confirm(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    channel: &ChannelEnd,
+) -> Event {
+    channel_event("channel_close_confirm", port_id, channel_id, channel)
+}
+
+/// A packet lifecycle step, identified by `kind`, was executed on the given packet.
+fn packet_event(kind: &str, packet: &Packet, channel: &ChannelEnd) -> Event {
+    Event::new(
+        kind,
+        packet_attributes(packet, channel)
+            .into_iter()
+            .map(|(key, value)| (key, value).index())
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn packet_attributes(packet: &Packet, channel: &ChannelEnd) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "packet_data",
+            String::from_utf8_lossy(&packet.data).into_owned(),
+        ),
+        ("packet_timeout_height", packet.timeout_height.to_string()),
+        (
+            "packet_timeout_timestamp",
+            packet.timeout_timestamp.nanoseconds().to_string(),
+        ),
+        ("packet_sequence", packet.sequence.to_string()),
+        ("packet_src_port", packet.source_port.to_string()),
+        ("packet_src_channel", packet.source_channel.to_string()),
+        ("packet_dst_port", packet.destination_port.to_string()),
+        ("packet_dst_channel", packet.destination_channel.to_string()),
+        (
+            "packet_channel_ordering",
+            channel.ordering().as_str().to_string(),
+        ),
+        (
+            "packet_connection",
+            channel
+                .connection_hops()
+                .first()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        ),
+    ]
+}
+
+/// A packet was sent over a channel.
+pub fn send_packet(packet: &Packet, channel: &ChannelEnd) -> Event {
+    packet_event("send_packet", packet, channel)
+}
+
+/// A packet sent by the counterparty was received.
+pub fn recv_packet(packet: &Packet, channel: &ChannelEnd) -> Event {
+    packet_event("recv_packet", packet, channel)
+}
+
+/// An acknowledgement was written for a received packet.
+pub fn write_acknowledgement(packet: &Packet, channel: &ChannelEnd, ack: &[u8]) -> Event {
+    let mut attributes = packet_attributes(packet, channel);
+    attributes.push(("packet_ack", String::from_utf8_lossy(ack).into_owned()));
+    Event::new(
+        "write_acknowledgement",
+        attributes
+            .into_iter()
+            .map(|(key, value)| (key, value).index())
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// The counterparty acknowledged a packet we sent.
+pub fn acknowledge_packet(packet: &Packet, channel: &ChannelEnd) -> Event {
+    packet_event("acknowledge_packet", packet, channel)
+}
+
+/// A packet we sent timed out before the counterparty received it.
+pub fn timeout_packet(packet: &Packet, channel: &ChannelEnd) -> Event {
+    packet_event("timeout_packet", packet, channel)
+}
@@ -0,0 +1,143 @@
+//! The JMT keys under which the IBC components store their state.
+//!
+//! These are shared with the IBC query service, which proves their values to relayers.
+//! Everything a counterparty chain verifies is stored at its ICS-24 path under
+//! the `ibc` [commitment prefix](super::connection::commitment_prefix).
+
+use ibc::core::{
+    ics02_client::height::Height,
+    ics04_channel::packet::Sequence,
+    ics24_host::{
+        identifier::{ChannelId, ClientId, ConnectionId, PortId},
+        path::{
+            AcksPath, ChannelEndsPath, ClientConnectionsPath, ClientConsensusStatePath,
+            ClientStatePath, CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqAcksPath,
+            SeqRecvsPath, SeqSendsPath,
+        },
+    },
+};
+
+pub fn client_counter() -> String {
+    "ibc/ics02-client/client_counter".to_string()
+}
+
+pub fn client_state(client_id: &ClientId) -> String {
+    format!("ibc/{}", ClientStatePath(client_id.clone()))
+}
+
+pub fn client_processed_time(client_id: &ClientId) -> String {
+    format!("ibc/clients/{}/processedTime", client_id)
+}
+
+pub fn client_processed_height(client_id: &ClientId) -> String {
+    format!("ibc/clients/{}/processedHeight", client_id)
+}
+
+pub fn verified_heights(client_id: &ClientId) -> String {
+    format!("ibc/clients/{}/verifiedHeights", client_id)
+}
+
+pub fn verified_consensus_state(client_id: &ClientId, height: &Height) -> String {
+    format!(
+        "ibc/{}",
+        ClientConsensusStatePath {
+            client_id: client_id.clone(),
+            epoch: height.revision_number,
+            height: height.revision_height,
+        }
+    )
+}
+
+pub fn verified_consensus_state_processed_time(client_id: &ClientId, height: &Height) -> String {
+    format!(
+        "ibc/clients/{}/consensusStates/{}/processedTime",
+        client_id, height
+    )
+}
+
+pub fn verified_consensus_state_processed_height(client_id: &ClientId, height: &Height) -> String {
+    format!(
+        "ibc/clients/{}/consensusStates/{}/processedHeight",
+        client_id, height
+    )
+}
+
+pub fn penumbra_consensus_state(height: u64) -> String {
+    format!("ibc/penumbra_consensus_state/{}", height)
+}
+
+pub fn oldest_penumbra_consensus_state_height() -> String {
+    "ibc/penumbra_consensus_state_oldest_height".to_string()
+}
+
+pub fn client_connections(client_id: &ClientId) -> String {
+    format!("ibc/{}", ClientConnectionsPath(client_id.clone()))
+}
+
+pub fn connection_counter() -> String {
+    "ibc/ics03-connection/connection_counter".to_string()
+}
+
+pub fn connection(connection_id: &ConnectionId) -> String {
+    format!("ibc/{}", ConnectionsPath(connection_id.clone()))
+}
+
+pub fn channel_counter() -> String {
+    "ibc/ics04-channel/channel_counter".to_string()
+}
+
+pub fn channel(port_id: &PortId, channel_id: &ChannelId) -> String {
+    format!(
+        "ibc/{}",
+        ChannelEndsPath(port_id.clone(), channel_id.clone())
+    )
+}
+
+pub fn next_sequence_send(port_id: &PortId, channel_id: &ChannelId) -> String {
+    format!("ibc/{}", SeqSendsPath(port_id.clone(), channel_id.clone()))
+}
+
+pub fn next_sequence_recv(port_id: &PortId, channel_id: &ChannelId) -> String {
+    format!("ibc/{}", SeqRecvsPath(port_id.clone(), channel_id.clone()))
+}
+
+pub fn next_sequence_ack(port_id: &PortId, channel_id: &ChannelId) -> String {
+    format!("ibc/{}", SeqAcksPath(port_id.clone(), channel_id.clone()))
+}
+
+pub fn packet_commitment(port_id: &PortId, channel_id: &ChannelId, sequence: Sequence) -> String {
+    format!(
+        "ibc/{}",
+        CommitmentsPath {
+            port_id: port_id.clone(),
+            channel_id: channel_id.clone(),
+            sequence,
+        }
+    )
+}
+
+pub fn packet_receipt(port_id: &PortId, channel_id: &ChannelId, sequence: Sequence) -> String {
+    format!(
+        "ibc/{}",
+        ReceiptsPath {
+            port_id: port_id.clone(),
+            channel_id: channel_id.clone(),
+            sequence,
+        }
+    )
+}
+
+pub fn packet_acknowledgement(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    sequence: Sequence,
+) -> String {
+    format!(
+        "ibc/{}",
+        AcksPath {
+            port_id: port_id.clone(),
+            channel_id: channel_id.clone(),
+            sequence,
+        }
+    )
+}
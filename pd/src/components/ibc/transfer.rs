@@ -0,0 +1,705 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ibc::{
+    core::{
+        ics02_client::{client_state::ClientState, height::Height},
+        ics04_channel::{
+            channel::State,
+            packet::{Packet, Sequence},
+        },
+        ics24_host::identifier::{ChannelId, PortId},
+    },
+    timestamp::Timestamp,
+};
+use penumbra_crypto::{
+    asset::{self, Denom},
+    Address, Value,
+};
+use penumbra_ibc::{
+    FungibleTokenPacketData, Ics20Mint, Ics20Mints, Ics20Withdrawal, TRANSFER_PORT,
+};
+use penumbra_transaction::Transaction;
+use tendermint::abci;
+use tracing::instrument;
+
+use super::{
+    channel::{commit_packet, View as _},
+    client::View as _,
+    connection::View as _,
+    event,
+};
+use crate::components::{app::View as _, shielded_pool::View as _, Component};
+use crate::{genesis, Overlay, OverlayExt};
+
+/// The Penumbra ICS-20 transfer component. Handles outbound transfers made by `Ics20Withdrawal`
+/// actions, which escrow Penumbra-native assets (or burn assets being returned to their native
+/// chain) and send a packet over the `transfer` port. Inbound packets, acknowledgements and
+/// timeouts are handed to this module by the channel component.
+pub struct TransferComponent {
+    overlay: Overlay,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
+}
+
+#[async_trait]
+impl Component for TransferComponent {
+    #[instrument(name = "ics20_transfer", skip(overlay))]
+    async fn new(overlay: Overlay) -> Self {
+        Self {
+            overlay,
+            events: Vec::new(),
+        }
+    }
+
+    #[instrument(name = "ics20_transfer", skip(self, _app_state))]
+    async fn init_chain(&mut self, _app_state: &genesis::AppState) {}
+
+    #[instrument(name = "ics20_transfer", skip(self, _begin_block))]
+    async fn begin_block(&mut self, _begin_block: &abci::request::BeginBlock) {}
+
+    #[instrument(name = "ics20_transfer", skip(tx))]
+    fn check_tx_stateless(tx: &Transaction) -> Result<()> {
+        for withdrawal in tx.ics20_withdrawals() {
+            if withdrawal.amount == 0 {
+                return Err(anyhow::anyhow!("ics20 withdrawal amount must be nonzero"));
+            }
+            if withdrawal.destination_chain_address.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "ics20 withdrawal must have a destination address"
+                ));
+            }
+            if withdrawal.timeout_height == 0 && withdrawal.timeout_time == 0 {
+                return Err(anyhow::anyhow!(
+                    "ics20 withdrawal must have a timeout height or time"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(name = "ics20_transfer", skip(self, tx))]
+    async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        if tx.ics20_withdrawals().next().is_none() {
+            return Ok(());
+        }
+
+        let chain_params = self.overlay.get_chain_params().await?;
+        if !chain_params.outbound_ics20_transfers_enabled {
+            return Err(anyhow::anyhow!("outbound ics20 transfers are disabled"));
+        }
+
+        for withdrawal in tx.ics20_withdrawals() {
+            let channel = self
+                .overlay
+                .get_channel(&transfer_port(), &withdrawal.source_channel)
+                .await?;
+            if !channel.state_matches(&State::Open) {
+                return Err(anyhow::anyhow!(
+                    "channel {} is not open",
+                    withdrawal.source_channel
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "ics20_transfer", skip(self, tx))]
+    async fn execute_tx(&mut self, tx: &Transaction) {
+        for withdrawal in tx.ics20_withdrawals() {
+            self.withdraw(withdrawal).await.unwrap();
+        }
+    }
+
+    #[instrument(name = "ics20_transfer", skip(self, _end_block))]
+    async fn end_block(&mut self, _end_block: &abci::request::EndBlock) {}
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl TransferComponent {
+    // take the withdrawn value out of circulation, and send a packet transferring it to the
+    // counterparty.
+    async fn withdraw(&mut self, withdrawal: &Ics20Withdrawal) -> Result<()> {
+        let port_id = transfer_port();
+        let channel_id = &withdrawal.source_channel;
+        let channel = self.overlay.get_channel(&port_id, channel_id).await?;
+
+        // The withdrawn value was consumed from the transaction's balance.  If
+        // the asset is native to Penumbra, it's held in escrow until it
+        // returns; if it's being returned to the chain it came from, it's
+        // simply burned.
+        let value = withdrawal.value();
+        self.overlay
+            .update_token_supply(&value.asset_id, -(value.amount as i64))
+            .await?;
+        if !is_returning(&withdrawal.denom, &port_id, channel_id) {
+            self.overlay.escrow(channel_id, value).await?;
+        }
+
+        // Counterparty heights are interpreted relative to the revision of the
+        // counterparty chain that our light client is tracking.
+        let connection = self
+            .overlay
+            .get_connection(&channel.connection_hops()[0])
+            .await?;
+        let revision_number = self
+            .overlay
+            .get_client_data(connection.client_id())
+            .await?
+            .client_state
+            .0
+            .latest_height()
+            .revision_number;
+
+        let sequence = self
+            .overlay
+            .next_sequence_send(&port_id, channel_id)
+            .await?;
+        let packet = Packet {
+            sequence: Sequence::from(sequence),
+            source_port: port_id.clone(),
+            source_channel: channel_id.clone(),
+            destination_port: channel.counterparty().port_id().clone(),
+            destination_channel: channel
+                .counterparty()
+                .channel_id()
+                .ok_or_else(|| anyhow::anyhow!("counterparty channel ID is not set"))?
+                .clone(),
+            data: serde_json::to_vec(&withdrawal.packet_data())?,
+            timeout_height: match withdrawal.timeout_height {
+                0 => Height::zero(),
+                height => Height::new(revision_number, height),
+            },
+            timeout_timestamp: Timestamp::from_nanoseconds(withdrawal.timeout_time)
+                .map_err(|e| anyhow::anyhow!("invalid timeout time: {}", e))?,
+        };
+
+        self.overlay
+            .put_packet_commitment(
+                &port_id,
+                channel_id,
+                packet.sequence,
+                commit_packet(&packet),
+            )
+            .await;
+        self.overlay
+            .put_next_sequence_send(&port_id, channel_id, sequence + 1)
+            .await;
+        self.events.push(event::send_packet(&packet, &channel));
+
+        Ok(())
+    }
+}
+
+/// Handles an ICS-20 packet received from a counterparty, scheduling a note
+/// to be minted to its receiver.
+///
+/// Assets native to the counterparty are minted under a denomination prefixed
+/// with our port and channel, while assets native to Penumbra that are being
+/// returned are released from escrow.  Any error is reported to the sender in
+/// an error acknowledgement.
+pub async fn recv_packet(overlay: &mut Overlay, packet: &Packet) -> Result<()> {
+    let chain_params = overlay.get_chain_params().await?;
+    if !chain_params.inbound_ics20_transfers_enabled {
+        return Err(anyhow::anyhow!("inbound ics20 transfers are disabled"));
+    }
+
+    let data: FungibleTokenPacketData = serde_json::from_slice(&packet.data)?;
+    let amount: u64 = data.amount.parse()?;
+    if amount == 0 {
+        return Err(anyhow::anyhow!("transfer amount must be nonzero"));
+    }
+    let receiver = Address::from_str(&data.receiver)?;
+
+    let source_prefix = format!("{}/{}/", packet.source_port, packet.source_channel);
+    let value = if let Some(native_denom) = data.denom.strip_prefix(&source_prefix) {
+        // The asset left Penumbra over this channel, so release it from escrow.
+        let value = parse_denom(native_denom)?.value(amount);
+        overlay
+            .release_escrow(&packet.destination_channel, value)
+            .await?;
+        value
+    } else {
+        let denom = parse_denom(&format!(
+            "{}/{}/{}",
+            packet.destination_port, packet.destination_channel, data.denom
+        ))?;
+        overlay.register_denom(&denom).await?;
+        denom.value(amount)
+    };
+
+    overlay
+        .add_ics20_mint(Ics20Mint {
+            value,
+            address: receiver,
+            channel_id: packet.destination_channel.clone(),
+            sequence: u64::from(packet.sequence),
+        })
+        .await
+}
+
+/// Handles the acknowledgement of an ICS-20 packet we sent, refunding the
+/// transfer if the counterparty rejected it.
+pub async fn acknowledge_packet(overlay: &mut Overlay, packet: &Packet, ack: &[u8]) -> Result<()> {
+    let ack: serde_json::Value = serde_json::from_slice(ack)?;
+    if ack.get("result").is_some() {
+        return Ok(());
+    }
+
+    tracing::info!(?ack, sequence = %packet.sequence, "ics20 transfer was rejected");
+    refund_packet(overlay, packet).await
+}
+
+/// Refunds an ICS-20 packet we sent, which timed out or was rejected by the
+/// counterparty, to its sender.
+pub async fn refund_packet(overlay: &mut Overlay, packet: &Packet) -> Result<()> {
+    let data: FungibleTokenPacketData = serde_json::from_slice(&packet.data)?;
+    let denom = parse_denom(&data.denom)?;
+    let value = denom.value(data.amount.parse()?);
+    let sender = Address::from_str(&data.sender)?;
+
+    if !is_returning(&denom, &packet.source_port, &packet.source_channel) {
+        overlay
+            .release_escrow(&packet.source_channel, value)
+            .await?;
+    }
+
+    overlay
+        .add_ics20_mint(Ics20Mint {
+            value,
+            address: sender,
+            channel_id: packet.source_channel.clone(),
+            sequence: u64::from(packet.sequence),
+        })
+        .await
+}
+
+/// The acknowledgement written for an ICS-20 packet that was received successfully.
+pub fn success_acknowledgement() -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({ "result": "AQ==" })).unwrap()
+}
+
+/// The acknowledgement written for an ICS-20 packet that could not be received.
+pub fn error_acknowledgement(error: &anyhow::Error) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({ "error": error.to_string() })).unwrap()
+}
+
+pub fn transfer_port() -> PortId {
+    PortId::from_str(TRANSFER_PORT).expect("transfer port is a valid port ID")
+}
+
+// whether an asset sent over the given channel is being returned to the chain it came from,
+// rather than leaving Penumbra, its native chain.
+fn is_returning(denom: &Denom, port_id: &PortId, channel_id: &ChannelId) -> bool {
+    denom
+        .to_string()
+        .starts_with(&format!("{}/{}/", port_id, channel_id))
+}
+
+fn parse_denom(denom: &str) -> Result<Denom> {
+    asset::REGISTRY
+        .parse_denom(denom)
+        .ok_or_else(|| anyhow::anyhow!("invalid denomination {}", denom))
+}
+
+#[async_trait]
+pub trait View: OverlayExt + Send + Sync {
+    async fn escrow_balance(&self, channel_id: &ChannelId, asset_id: &asset::Id) -> Result<u64> {
+        Ok(self
+            .get_proto(format!("ibc/ics20-transfer/escrow/{}/{}", channel_id, asset_id).into())
+            .await?
+            .unwrap_or(0))
+    }
+    async fn set_escrow_balance(
+        &mut self,
+        channel_id: &ChannelId,
+        asset_id: &asset::Id,
+        amount: u64,
+    ) {
+        self.put_proto(
+            format!("ibc/ics20-transfer/escrow/{}/{}", channel_id, asset_id).into(),
+            amount,
+        )
+        .await;
+    }
+    async fn escrow(&mut self, channel_id: &ChannelId, value: Value) -> Result<()> {
+        let balance = self.escrow_balance(channel_id, &value.asset_id).await?;
+        let balance = balance
+            .checked_add(value.amount)
+            .ok_or_else(|| anyhow::anyhow!("overflow escrowing {:?}", value))?;
+        self.set_escrow_balance(channel_id, &value.asset_id, balance)
+            .await;
+        Ok(())
+    }
+    async fn release_escrow(&mut self, channel_id: &ChannelId, value: Value) -> Result<()> {
+        let balance = self.escrow_balance(channel_id, &value.asset_id).await?;
+        let balance = balance.checked_sub(value.amount).ok_or_else(|| {
+            anyhow::anyhow!(
+                "insufficient escrow on channel {} to release {:?}",
+                channel_id,
+                value
+            )
+        })?;
+        self.set_escrow_balance(channel_id, &value.asset_id, balance)
+            .await;
+        Ok(())
+    }
+
+    async fn ics20_mints(&self, height: u64) -> Result<Option<Ics20Mints>> {
+        self.get_domain(format!("ibc/ics20-transfer/mints/{}", height).into())
+            .await
+    }
+    async fn set_ics20_mints(&mut self, height: u64, mints: Ics20Mints) {
+        self.put_domain(format!("ibc/ics20-transfer/mints/{}", height).into(), mints)
+            .await;
+    }
+    async fn delete_ics20_mints(&mut self, height: u64) {
+        self.delete(format!("ibc/ics20-transfer/mints/{}", height).into())
+            .await;
+    }
+    // schedules a note to be minted by the shielded pool at the end of the current block.
+    async fn add_ics20_mint(&mut self, mint: Ics20Mint) -> Result<()> {
+        let height = self.get_block_height().await?;
+        let mut mints = self.ics20_mints(height).await?.unwrap_or_default();
+        mints.mints.push(mint);
+        self.set_ics20_mints(height, mints).await;
+        Ok(())
+    }
+}
+
+impl<T: OverlayExt + Send + Sync> View for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Storage;
+    use penumbra_chain::params::ChainParams;
+    use penumbra_crypto::keys::{SpendKey, SpendSeed};
+    use tempfile::tempdir;
+
+    // an overlay at height 10 accepting inbound transfers.
+    async fn transfer_overlay() -> (tempfile::TempDir, Overlay) {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("ibc-testing.db"))
+            .await
+            .unwrap();
+        let mut overlay = storage.overlay().await.unwrap();
+
+        overlay
+            .put_chain_params(ChainParams {
+                chain_id: "penumbra-1".to_string(),
+                inbound_ics20_transfers_enabled: true,
+                ..Default::default()
+            })
+            .await;
+        overlay.put_block_height(10).await;
+
+        (dir, overlay)
+    }
+
+    fn penumbra_address() -> Address {
+        let spend_key = SpendKey::from(SpendSeed([7u8; 32]));
+        spend_key
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into())
+            .0
+    }
+
+    fn denom(denom: &str) -> Denom {
+        parse_denom(denom).unwrap()
+    }
+
+    // a packet sent from our transfer channel-0 to the counterparty's transfer channel-7.
+    fn outbound_packet(sequence: u64, data: FungibleTokenPacketData) -> Packet {
+        Packet {
+            sequence: Sequence::from(sequence),
+            source_port: transfer_port(),
+            source_channel: ChannelId::new(0),
+            destination_port: transfer_port(),
+            destination_channel: ChannelId::new(7),
+            data: serde_json::to_vec(&data).unwrap(),
+            timeout_height: Height::new(0, 100),
+            timeout_timestamp: Timestamp::none(),
+        }
+    }
+
+    // a packet sent from the counterparty's transfer channel-7 to our transfer channel-0.
+    fn inbound_packet(sequence: u64, data: FungibleTokenPacketData) -> Packet {
+        Packet {
+            sequence: Sequence::from(sequence),
+            source_port: transfer_port(),
+            source_channel: ChannelId::new(7),
+            destination_port: transfer_port(),
+            destination_channel: ChannelId::new(0),
+            data: serde_json::to_vec(&data).unwrap(),
+            timeout_height: Height::new(0, 100),
+            timeout_timestamp: Timestamp::none(),
+        }
+    }
+
+    fn packet_data(
+        denom: &str,
+        amount: u64,
+        sender: String,
+        receiver: String,
+    ) -> FungibleTokenPacketData {
+        FungibleTokenPacketData {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+            sender,
+            receiver,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_escrow_and_release() {
+        let (_dir, mut overlay) = transfer_overlay().await;
+        let channel = ChannelId::new(0);
+        let upenumbra = denom("upenumbra");
+
+        overlay
+            .escrow(&channel, upenumbra.value(100))
+            .await
+            .unwrap();
+        overlay.escrow(&channel, upenumbra.value(50)).await.unwrap();
+        assert_eq!(
+            overlay
+                .escrow_balance(&channel, &upenumbra.id())
+                .await
+                .unwrap(),
+            150
+        );
+
+        // escrow is tracked per channel.
+        assert_eq!(
+            overlay
+                .escrow_balance(&ChannelId::new(1), &upenumbra.id())
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(overlay
+            .release_escrow(&ChannelId::new(1), upenumbra.value(1))
+            .await
+            .is_err());
+
+        overlay
+            .release_escrow(&channel, upenumbra.value(120))
+            .await
+            .unwrap();
+        assert!(overlay
+            .release_escrow(&channel, upenumbra.value(31))
+            .await
+            .is_err());
+        assert_eq!(
+            overlay
+                .escrow_balance(&channel, &upenumbra.id())
+                .await
+                .unwrap(),
+            30
+        );
+    }
+
+    // test that refunding a packet of a Penumbra-native asset releases it from escrow and
+    // mints it back to its sender.
+    #[tokio::test]
+    async fn test_refund_native_packet() {
+        let (_dir, mut overlay) = transfer_overlay().await;
+        let sender = penumbra_address();
+        let upenumbra = denom("upenumbra");
+        overlay
+            .escrow(&ChannelId::new(0), upenumbra.value(100))
+            .await
+            .unwrap();
+
+        let packet = outbound_packet(
+            3,
+            packet_data(
+                "upenumbra",
+                60,
+                sender.to_string(),
+                "cosmos1receiver".to_string(),
+            ),
+        );
+        refund_packet(&mut overlay, &packet).await.unwrap();
+
+        assert_eq!(
+            overlay
+                .escrow_balance(&ChannelId::new(0), &upenumbra.id())
+                .await
+                .unwrap(),
+            40
+        );
+        let mints = overlay.ics20_mints(10).await.unwrap().unwrap().mints;
+        assert_eq!(mints.len(), 1);
+        assert_eq!(mints[0].value, upenumbra.value(60));
+        assert_eq!(mints[0].address, sender);
+        assert_eq!(mints[0].channel_id, ChannelId::new(0));
+        assert_eq!(mints[0].sequence, 3);
+
+        // a refund can't release more than was escrowed.
+        let packet = outbound_packet(
+            4,
+            packet_data(
+                "upenumbra",
+                41,
+                sender.to_string(),
+                "cosmos1receiver".to_string(),
+            ),
+        );
+        assert!(refund_packet(&mut overlay, &packet).await.is_err());
+    }
+
+    // test that only error acknowledgements refund the packet.
+    #[tokio::test]
+    async fn test_acknowledge_packet() {
+        let (_dir, mut overlay) = transfer_overlay().await;
+        let sender = penumbra_address();
+        let upenumbra = denom("upenumbra");
+        overlay
+            .escrow(&ChannelId::new(0), upenumbra.value(100))
+            .await
+            .unwrap();
+        let packet = outbound_packet(
+            1,
+            packet_data(
+                "upenumbra",
+                100,
+                sender.to_string(),
+                "cosmos1receiver".to_string(),
+            ),
+        );
+
+        acknowledge_packet(&mut overlay, &packet, &success_acknowledgement())
+            .await
+            .unwrap();
+        assert_eq!(
+            overlay
+                .escrow_balance(&ChannelId::new(0), &upenumbra.id())
+                .await
+                .unwrap(),
+            100
+        );
+        assert!(overlay.ics20_mints(10).await.unwrap().is_none());
+
+        let error = error_acknowledgement(&anyhow::anyhow!("rejected"));
+        acknowledge_packet(&mut overlay, &packet, &error)
+            .await
+            .unwrap();
+        assert_eq!(
+            overlay
+                .escrow_balance(&ChannelId::new(0), &upenumbra.id())
+                .await
+                .unwrap(),
+            0
+        );
+        let mints = overlay.ics20_mints(10).await.unwrap().unwrap().mints;
+        assert_eq!(mints.len(), 1);
+        assert_eq!(mints[0].value, upenumbra.value(100));
+        assert_eq!(mints[0].address, sender);
+    }
+
+    // test that assets native to the counterparty are minted under our prefix without touching
+    // escrow, and that Penumbra-native assets coming back are released from escrow.
+    #[tokio::test]
+    async fn test_returning_denom_round_trip() {
+        let (_dir, mut overlay) = transfer_overlay().await;
+        let address = penumbra_address();
+        let upenumbra = denom("upenumbra");
+        let voucher = denom("transfer/channel-0/uatom");
+
+        // uatom arrives from the counterparty and is minted as a voucher.
+        let packet = inbound_packet(
+            1,
+            packet_data(
+                "uatom",
+                25,
+                "cosmos1sender".to_string(),
+                address.to_string(),
+            ),
+        );
+        recv_packet(&mut overlay, &packet).await.unwrap();
+        assert_eq!(
+            overlay
+                .escrow_balance(&ChannelId::new(0), &voucher.id())
+                .await
+                .unwrap(),
+            0
+        );
+
+        // the voucher is sent back, but times out: refunding it doesn't touch escrow either.
+        let packet = outbound_packet(
+            1,
+            packet_data(
+                "transfer/channel-0/uatom",
+                25,
+                address.to_string(),
+                "cosmos1sender".to_string(),
+            ),
+        );
+        refund_packet(&mut overlay, &packet).await.unwrap();
+        assert_eq!(
+            overlay
+                .escrow_balance(&ChannelId::new(0), &voucher.id())
+                .await
+                .unwrap(),
+            0
+        );
+
+        // upenumbra escrowed when it left over channel-0 comes back, and is released.
+        overlay
+            .escrow(&ChannelId::new(0), upenumbra.value(70))
+            .await
+            .unwrap();
+        let packet = inbound_packet(
+            2,
+            packet_data(
+                "transfer/channel-7/upenumbra",
+                70,
+                "cosmos1sender".to_string(),
+                address.to_string(),
+            ),
+        );
+        recv_packet(&mut overlay, &packet).await.unwrap();
+        assert_eq!(
+            overlay
+                .escrow_balance(&ChannelId::new(0), &upenumbra.id())
+                .await
+                .unwrap(),
+            0
+        );
+
+        // receiving it again would mint upenumbra that was never escrowed.
+        let packet = inbound_packet(
+            3,
+            packet_data(
+                "transfer/channel-7/upenumbra",
+                70,
+                "cosmos1sender".to_string(),
+                address.to_string(),
+            ),
+        );
+        assert!(recv_packet(&mut overlay, &packet).await.is_err());
+
+        let mints = overlay.ics20_mints(10).await.unwrap().unwrap().mints;
+        let minted = mints
+            .iter()
+            .map(|mint| (mint.value, mint.channel_id.clone(), mint.sequence))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            minted,
+            vec![
+                (voucher.value(25), ChannelId::new(0), 1),
+                (voucher.value(25), ChannelId::new(0), 1),
+                (upenumbra.value(70), ChannelId::new(0), 2),
+            ]
+        );
+        assert!(mints.iter().all(|mint| mint.address == address));
+    }
+}
@@ -1,30 +1,42 @@
 use std::collections::BTreeSet;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, Result};
 use ark_ff::PrimeField;
 use async_trait::async_trait;
 use decaf377::{Fq, Fr};
-use penumbra_chain::{sync::CompactBlock, KnownAssets, NoteSource};
+use penumbra_chain::{sync::CompactBlock, KnownAssets, NoteSource, Quarantined};
 use penumbra_crypto::{
     asset::{self, Asset, Denom},
     ka,
-    merkle::{self, Frontier, NoteCommitmentTree, TreeExt},
-    note, Address, Note, Nullifier, One, Value,
+    merkle::{self, NoteCommitmentTree},
+    note,
+    proofs::{OutputPublicInputs, SpendPublicInputs},
+    Address, Note, Nullifier, One, Value,
 };
-use penumbra_stake::{Epoch, STAKING_TOKEN_ASSET_ID};
+use penumbra_stake::{Epoch, IdentityKey, Recipient, STAKING_TOKEN_ASSET_ID};
 use penumbra_transaction::{action::output, Action, Transaction};
 use tendermint::abci;
 use tracing::instrument;
 
-use super::{app::View as _, staking::View as _, Component};
+use super::{
+    app::View as _, community_pool::View as _, governance::View as _, ibc::View as _,
+    staking::View as _, Component,
+};
 use crate::{genesis, Overlay, OverlayExt};
 
+mod event;
+mod verify;
+
+use verify::{ProofCheck, SignatureBatch};
+
 // Stub component
 pub struct ShieldedPool {
     overlay: Overlay,
     note_commitment_tree: NoteCommitmentTree,
     /// The in-progress CompactBlock representation of the ShieldedPool changes
     compact_block: CompactBlock,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
 }
 
 #[async_trait]
@@ -37,11 +49,18 @@ impl Component for ShieldedPool {
             overlay,
             note_commitment_tree,
             compact_block: Default::default(),
+            events: Vec::new(),
         }
     }
 
     #[instrument(name = "shielded_pool", skip(self, app_state))]
     async fn init_chain(&mut self, app_state: &genesis::AppState) {
+        // Open the genesis block explicitly, so that the NCT block and epoch
+        // structure always follows the chain's, even without any allocations.
+        self.note_commitment_tree
+            .insert_block(merkle::Block::new())
+            .unwrap();
+
         for allocation in &app_state.allocations {
             tracing::info!(?allocation, "processing allocation");
 
@@ -84,54 +103,49 @@ impl Component for ShieldedPool {
     fn check_tx_stateless(tx: &Transaction) -> Result<()> {
         // TODO: add a check that ephemeral_key is not identity to prevent scanning dos attack ?
         let sighash = tx.transaction_body().sighash();
+        let anchor = tx.transaction_body.merkle_root;
 
-        // 1. Check binding signature.
-        tx.binding_verification_key()
-            .verify(&sighash, tx.binding_sig())
-            .context("binding signature failed to verify")?;
+        // 1. Queue the binding signature, and all spend auth signatures using
+        // the provided spend auth keys, to be verified as a batch.
+        let mut signatures = SignatureBatch::default();
+        signatures.queue_binding(tx.binding_verification_key(), *tx.binding_sig(), &sighash);
 
-        // 2. Check all spend auth signatures using provided spend auth keys
-        // and check all proofs verify. If any action does not verify, the entire
+        // 2. Collect all proofs along with their public inputs, to be
+        // verified in parallel. If any action does not verify, the entire
         // transaction has failed.
+        let mut proofs = Vec::new();
         let mut spent_nullifiers = BTreeSet::<Nullifier>::new();
 
-        for action in tx.transaction_body().actions {
+        for (index, action) in tx.actions().enumerate() {
             match action {
                 Action::Output(output) => {
-                    if output
-                        .proof
-                        .verify(
-                            output.value_commitment,
-                            output.body.note_commitment,
-                            output.body.ephemeral_key,
-                        )
-                        .is_err()
-                    {
-                        // TODO should the verification error be bubbled up here?
-                        return Err(anyhow::anyhow!("An output proof did not verify"));
-                    }
+                    proofs.push((
+                        index,
+                        ProofCheck::Output(
+                            &output.proof,
+                            OutputPublicInputs {
+                                value_commitment: output.value_commitment,
+                                note_commitment: output.body.note_commitment,
+                                epk: output.body.ephemeral_key,
+                            },
+                        ),
+                    ));
                 }
                 Action::Spend(spend) => {
-                    spend
-                        .body
-                        .rk
-                        .verify(&sighash, &spend.auth_sig)
-                        .context("spend auth signature failed to verify")?;
-
-                    if spend
-                        .body
-                        .proof
-                        .verify(
-                            tx.transaction_body().merkle_root,
-                            spend.body.value_commitment,
-                            spend.body.nullifier.clone(),
-                            spend.body.rk,
-                        )
-                        .is_err()
-                    {
-                        // TODO should the verification error be bubbled up here?
-                        return Err(anyhow::anyhow!("A spend proof did not verify"));
-                    }
+                    signatures.queue_spend_auth(index, spend.body.rk, spend.auth_sig, &sighash);
+
+                    proofs.push((
+                        index,
+                        ProofCheck::Spend(
+                            &spend.body.proof,
+                            SpendPublicInputs {
+                                anchor,
+                                value_commitment: spend.body.value_commitment,
+                                nullifier: spend.body.nullifier.clone(),
+                                rk: spend.body.rk,
+                            },
+                        ),
+                    ));
 
                     // Check nullifier has not been revealed already in this transaction.
                     if spent_nullifiers.contains(&spend.body.nullifier.clone()) {
@@ -149,6 +163,57 @@ impl Component for ShieldedPool {
                 Action::ValidatorDefinition(_validator) => {
                     // Handled in the `Staking` component.
                 }
+                Action::Proposal(_proposal) => {
+                    // Handled in the `Governance` component.
+                }
+                Action::ValidatorVote(_vote) => {
+                    // Handled in the `Governance` component.
+                }
+                Action::DelegatorVote(_vote) => {
+                    // Handled in the `Governance` component.
+                }
+                Action::CommunityPoolDeposit(_deposit) => {
+                    // Handled in the `CommunityPool` component.
+                }
+                Action::IBCAction(_action) => {
+                    // Handled in the `IBC` component.
+                }
+                Action::Ics20Withdrawal(_withdrawal) => {
+                    // Handled in the `IBC` component.
+                }
+                Action::Swap(swap) => {
+                    proofs.push((index, ProofCheck::Swap(&swap.proof, swap.public_inputs())));
+                }
+                Action::SwapClaim(claim) => {
+                    signatures.queue_spend_auth(index, claim.body.rk, claim.auth_sig, &sighash);
+
+                    // The claim spends the swap NFT, without revealing it.
+                    proofs.push((
+                        index,
+                        ProofCheck::SwapClaim(&claim.body.proof, claim.body.public_inputs(anchor)),
+                    ));
+
+                    if spent_nullifiers.contains(&claim.body.nullifier.clone()) {
+                        return Err(anyhow::anyhow!("Double spend"));
+                    }
+
+                    spent_nullifiers.insert(claim.body.nullifier.clone());
+                }
+                Action::PositionOpen(_open) => {
+                    // Handled in the `Dex` component.
+                }
+                Action::PositionClose(_close) => {
+                    // Handled in the `Dex` component.
+                }
+                Action::PositionWithdraw(_withdraw) => {
+                    // Handled in the `Dex` component.
+                }
+                Action::DkgMessage(_message) => {
+                    // Handled in the `FlowEncryption` component.
+                }
+                Action::FlowDecryption(_decryption) => {
+                    // Handled in the `FlowEncryption` component.
+                }
                 #[allow(unreachable_patterns)]
                 _ => {
                     return Err(anyhow::anyhow!("unsupported action"));
@@ -156,7 +221,9 @@ impl Component for ShieldedPool {
             }
         }
 
-        Ok(())
+        // 3. Verify the signatures as a batch, then the proofs in parallel.
+        signatures.verify()?;
+        verify::verify_proofs(proofs)
     }
 
     #[instrument(name = "shielded_pool", skip(self, tx))]
@@ -166,42 +233,72 @@ impl Component for ShieldedPool {
             .check_claimed_anchor(&tx.transaction_body.merkle_root)
             .await?;
 
+        // Check that every proof uses the proof system the chain currently
+        // requires.
+        let proof_system = self.overlay.get_chain_params().await?.proof_system;
+        for action in tx.actions() {
+            let action_proof_system = match action {
+                Action::Spend(spend) => spend.body.proof.proof_system(),
+                Action::Output(output) => output.proof.proof_system(),
+                Action::Swap(swap) => swap.proof.proof_system(),
+                Action::SwapClaim(claim) => claim.body.proof.proof_system(),
+                Action::DelegatorVote(vote) => vote.body.proof.proof_system(),
+                _ => continue,
+            };
+            if action_proof_system != proof_system {
+                return Err(anyhow::anyhow!(
+                    "proof uses the {} proof system, but the chain requires {}",
+                    action_proof_system,
+                    proof_system
+                ));
+            }
+        }
+
         for spent_nullifier in tx.spent_nullifiers() {
             self.overlay
                 .check_nullifier_unspent(spent_nullifier)
                 .await?;
+            // Nullifiers held in quarantine by a pending undelegation can't be
+            // spent until the quarantine is released or rolled back.
+            self.overlay
+                .check_nullifier_unquarantined(spent_nullifier)
+                .await?;
         }
 
-        // TODO: handle quarantine
         Ok(())
     }
 
     #[instrument(name = "shielded_pool", skip(self, tx))]
     async fn execute_tx(&mut self, tx: &Transaction) {
-        let _should_quarantine = tx
-            .transaction_body
-            .actions
-            .iter()
-            .any(|action| matches!(action, Action::Undelegate { .. }));
-
         let source = NoteSource::Transaction { id: tx.id() };
 
-        /*
-        if should_quarantine {
-            tracing::warn!("skipping processing, TODO: implement");
-        } else {
-         */
-        for compact_output in tx.output_bodies() {
+        // Transactions containing undelegations are quarantined until the end
+        // of the unbonding period, so that the undelegated stake remains
+        // slashable.  The Staking component ensures that a transaction
+        // undelegates from at most one validator.
+        if let Some(identity_key) = tx
+            .undelegations()
+            .next()
+            .map(|u| u.validator_identity.clone())
+        {
+            self.quarantine_tx(tx, identity_key, source).await.unwrap();
+            return;
+        }
+
+        let height = self.overlay.get_block_height().await.unwrap();
+        for (compact_output, source) in note_sources(tx, source) {
             self.add_note(compact_output, source).await;
         }
         for spent_nullifier in tx.spent_nullifiers() {
             // We need to record the nullifier as spent in the JMT (to prevent
             // double spends), as well as in the CompactBlock (so that clients
             // can learn that their note was spent).
-            self.overlay.spend_nullifier(spent_nullifier, source).await;
+            self.overlay
+                .spend_nullifier(spent_nullifier, source, height)
+                .await;
             self.compact_block.nullifiers.push(spent_nullifier);
+            self.events.push(event::spend(&spent_nullifier));
         }
-        //}
     }
 
     #[instrument(name = "shielded_pool", skip(self, end_block))]
@@ -209,13 +306,31 @@ impl Component for ShieldedPool {
         // Set the height of the compact block, now that we got it in end_block
         self.compact_block.height = end_block.height as u64;
 
-        // Handle any pending reward notes from the Staking component
-        let notes = self
+        // Roll back the quarantined undelegations of any validators slashed in
+        // this block, before releasing any quarantined undelegations whose
+        // unbonding period ends with this block.
+        self.rollback_slashed_quarantines().await.unwrap();
+        let current_epoch = self.overlay.get_current_epoch().await.unwrap();
+        if current_epoch.is_epoch_end(self.compact_block.height) {
+            self.release_quarantine(current_epoch.index).await.unwrap();
+        }
+
+        // Handle any pending reward notes from the Staking component, deleting
+        // them from the state once they're minted.
+        let notes = match self
             .overlay
             .commission_amounts(self.compact_block.height)
             .await
             .unwrap()
-            .unwrap_or_default();
+        {
+            Some(notes) => {
+                self.overlay
+                    .delete_commission_amounts(self.compact_block.height)
+                    .await;
+                notes
+            }
+            None => Default::default(),
+        };
 
         // TODO: should we calculate this here or include it directly within the PendingRewardNote
         // to prevent a potential mismatch between Staking and ShieldedPool?
@@ -228,20 +343,110 @@ impl Component for ShieldedPool {
         };
 
         for note in notes.notes {
-            self.mint_note(
-                Value {
-                    amount: note.amount,
-                    asset_id: *STAKING_TOKEN_ASSET_ID,
-                },
-                &note.destination,
-                source,
-            )
+            let value = Value {
+                amount: note.amount,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            };
+            match note.destination {
+                Recipient::Address(address) => {
+                    self.mint_note(value, &address, source).await.unwrap();
+                }
+                // Funds held by the community pool aren't part of the token
+                // supply, so they're credited to it without minting a note.
+                Recipient::CommunityPool => {
+                    self.overlay.community_pool_deposit(value).await.unwrap();
+                }
+            }
+        }
+
+        // Likewise, return the deposits of any proposals whose voting finished
+        // in this block.
+        if let Some(refunds) = self
+            .overlay
+            .deposit_refunds(self.compact_block.height)
             .await
-            .unwrap();
+            .unwrap()
+        {
+            self.overlay
+                .delete_deposit_refunds(self.compact_block.height)
+                .await;
+            for refund in refunds.refunds {
+                self.mint_note(
+                    Value {
+                        amount: refund.amount,
+                        asset_id: *STAKING_TOKEN_ASSET_ID,
+                    },
+                    &refund.address,
+                    NoteSource::ProposalDepositRefund {
+                        proposal_id: refund.proposal_id,
+                    },
+                )
+                .await
+                .unwrap();
+            }
+        }
+
+        // And pay out any community pool spends enacted in this block.
+        if let Some(payouts) = self
+            .overlay
+            .community_pool_payouts(self.compact_block.height)
+            .await
+            .unwrap()
+        {
+            self.overlay
+                .delete_community_pool_payouts(self.compact_block.height)
+                .await;
+            for payout in payouts.payouts {
+                self.mint_note(
+                    payout.value,
+                    &payout.address,
+                    NoteSource::CommunityPoolSpend {
+                        proposal_id: payout.proposal_id,
+                    },
+                )
+                .await
+                .unwrap();
+            }
+        }
+
+        // And mint any notes for ICS-20 transfers received or refunded in this block.
+        if let Some(mints) = self
+            .overlay
+            .ics20_mints(self.compact_block.height)
+            .await
+            .unwrap()
+        {
+            self.overlay
+                .delete_ics20_mints(self.compact_block.height)
+                .await;
+            for mint in mints.mints {
+                // Mints are only recorded for transfers over our own channels,
+                // whose IDs we generated from the channel counter.
+                let channel = mint
+                    .channel_id
+                    .as_str()
+                    .strip_prefix("channel-")
+                    .and_then(|index| index.parse().ok())
+                    .expect("penumbra channel IDs are of the form channel-<index>");
+                self.mint_note(
+                    mint.value,
+                    &mint.address,
+                    NoteSource::Ics20Transfer {
+                        channel,
+                        sequence: mint.sequence,
+                    },
+                )
+                .await
+                .unwrap();
+            }
         }
 
         self.write_compactblock_and_nct().await.unwrap();
     }
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
 }
 
 impl ShieldedPool {
@@ -259,36 +464,11 @@ impl ShieldedPool {
         //
         // Hashing the current NCT root is sufficient, since it will change every time
         // we insert a new note.
-
-        // However, in our current implementation, computing the NCT root is very slow...
-        /*
         let blinding_factor = Fq::from_le_bytes_mod_order(
             blake2b_simd::Params::default()
                 .personal(b"PenumbraMint")
                 .to_state()
-                .update(&self.note_commitment_tree.root2().to_bytes())
-                .finalize()
-                .as_bytes(),
-        );
-        */
-
-        // ... so just hash the current position instead.
-        let position = self
-            .note_commitment_tree
-            .bridges()
-            .last()
-            .map(|b| b.frontier().position().into())
-            // If there are no bridges, the tree is empty
-            .unwrap_or(0u64);
-        tracing::debug!(
-            ?position,
-            "using NCT position for uniqueness in minted note"
-        );
-        let blinding_factor = Fq::from_le_bytes_mod_order(
-            blake2b_simd::Params::default()
-                .personal(b"PenumbraMint")
-                .to_state()
-                .update(&position.to_le_bytes())
+                .update(&<[u8; 32]>::from(self.note_commitment_tree.root()))
                 .finalize()
                 .as_bytes(),
         );
@@ -327,29 +507,232 @@ impl ShieldedPool {
         Ok(())
     }
 
+    /// Holds the outputs and nullifiers of an undelegating transaction in
+    /// quarantine until the end of its unbonding epoch.
+    #[instrument(skip(self, tx, source))]
+    async fn quarantine_tx(
+        &mut self,
+        tx: &Transaction,
+        identity_key: IdentityKey,
+        source: NoteSource,
+    ) -> Result<()> {
+        let unbonding_epoch = self.overlay.unbonding_epoch_for(&identity_key).await?;
+        tracing::debug!(?unbonding_epoch, "quarantining undelegation");
+
+        let (delegation_amount, unbonded_amount) =
+            tx.undelegations()
+                .fold((0, 0), |(delegation, unbonded), u| {
+                    (
+                        delegation + u.delegation_amount,
+                        unbonded + u.unbonded_amount,
+                    )
+                });
+
+        // Record the note sources now, since we won't have the transaction
+        // when the quarantine is released.
+        let outputs = tx.output_bodies();
+        for (output, source) in note_sources(tx, source) {
+            self.overlay
+                .set_note_source(&output.note_commitment, source)
+                .await;
+        }
+        // Lock the nullifiers, so they can't be spent by another transaction
+        // while the quarantine is pending.
+        let nullifiers = tx.spent_nullifiers();
+        for nullifier in &nullifiers {
+            self.overlay.quarantine_nullifier(*nullifier, source).await;
+        }
+
+        let mut quarantined = Quarantined::default();
+        quarantined.schedule(
+            unbonding_epoch,
+            identity_key.clone(),
+            outputs,
+            nullifiers,
+            delegation_amount,
+            unbonded_amount,
+        );
+
+        // Schedule the release in the JMT, and tell clients about it in the
+        // CompactBlock.
+        let mut scheduled = self.overlay.scheduled_quarantine(unbonding_epoch).await?;
+        scheduled.extend(quarantined.clone());
+        self.overlay
+            .set_scheduled_quarantine(unbonding_epoch, scheduled)
+            .await;
+        if unbonding_epoch > self.overlay.latest_quarantine_epoch().await? {
+            self.overlay
+                .set_latest_quarantine_epoch(unbonding_epoch)
+                .await;
+        }
+        self.compact_block.quarantined.extend(quarantined);
+        self.events
+            .push(event::quarantine(&identity_key, unbonding_epoch));
+
+        Ok(())
+    }
+
+    /// Releases the quarantined undelegations whose unbonding period ends with
+    /// the given epoch, adding their notes to the NCT and revealing their nullifiers.
+    #[instrument(skip(self))]
+    async fn release_quarantine(&mut self, epoch_index: u64) -> Result<()> {
+        let scheduled = self.overlay.scheduled_quarantine(epoch_index).await?;
+        for group in scheduled.groups() {
+            tracing::debug!(identity_key = ?group.identity_key, "releasing quarantined undelegations");
+            for output in &group.outputs {
+                let source = self
+                    .overlay
+                    .note_source(&output.note_commitment)
+                    .await?
+                    .ok_or_else(|| anyhow!("missing source for quarantined note"))?;
+                self.add_note(output.clone(), source).await;
+            }
+            for nullifier in &group.nullifiers {
+                let source = self
+                    .overlay
+                    .quarantined_nullifier_source(*nullifier)
+                    .await?
+                    .ok_or_else(|| anyhow!("missing source for quarantined nullifier"))?;
+                self.overlay.unquarantine_nullifier(*nullifier).await;
+                self.overlay
+                    .spend_nullifier(*nullifier, source, self.compact_block.height)
+                    .await;
+                self.compact_block.nullifiers.push(*nullifier);
+                self.events.push(event::spend(nullifier));
+            }
+        }
+        // The release is complete, so clear the schedule.
+        self.overlay
+            .set_scheduled_quarantine(epoch_index, Quarantined::default())
+            .await;
+
+        Ok(())
+    }
+
+    /// Rolls back the pending quarantined undelegations of all validators
+    /// slashed in this block.
+    ///
+    /// The quarantined notes are discarded, and the quarantined nullifiers are
+    /// unlocked, so that the delegators keep their (now slashed) delegation tokens.
+    #[instrument(skip(self))]
+    async fn rollback_slashed_quarantines(&mut self) -> Result<()> {
+        let slashed = self
+            .overlay
+            .slashed_validators(self.compact_block.height)
+            .await?;
+        if slashed.is_empty() {
+            return Ok(());
+        }
+
+        // Pending quarantines are scheduled to be released no later than the
+        // latest unbonding epoch scheduled so far.  This can be more than
+        // `unbonding_epochs` epochs in the future, if governance has since
+        // shortened the unbonding period.
+        let current_epoch = self.overlay.get_current_epoch().await?.index;
+        let latest_epoch = self.overlay.latest_quarantine_epoch().await?;
+
+        for epoch_index in current_epoch..=latest_epoch {
+            let mut scheduled = self.overlay.scheduled_quarantine(epoch_index).await?;
+            if scheduled.is_empty() {
+                continue;
+            }
+
+            let mut rolled_back = false;
+            for identity_key in &slashed {
+                for group in scheduled.remove_validator(identity_key) {
+                    tracing::debug!(
+                        ?identity_key,
+                        ?epoch_index,
+                        "rolling back quarantined undelegations"
+                    );
+                    rolled_back = true;
+                    for nullifier in group.nullifiers {
+                        self.overlay.unquarantine_nullifier(nullifier).await;
+                    }
+                    // The undelegations were (or will be, at the end of this
+                    // epoch) applied to the token supply by the Staking
+                    // component, so reverse them here.
+                    self.overlay
+                        .update_token_supply(
+                            &identity_key.delegation_token().id(),
+                            group.delegation_amount as i64,
+                        )
+                        .await?;
+                    self.overlay
+                        .update_token_supply(
+                            &STAKING_TOKEN_ASSET_ID,
+                            -(group.unbonded_amount as i64),
+                        )
+                        .await?;
+                    self.events
+                        .push(event::quarantine_rollback(identity_key, epoch_index));
+                }
+            }
+
+            if rolled_back {
+                self.overlay
+                    .set_scheduled_quarantine(epoch_index, scheduled)
+                    .await;
+            }
+        }
+
+        // The ShieldedPool is the only reader of the slashed validators, so
+        // delete them now that they've been handled.
+        self.overlay
+            .delete_slashed_validators(self.compact_block.height)
+            .await;
+        self.compact_block.slashed = slashed;
+
+        Ok(())
+    }
+
     #[instrument(skip(self, source, output_body))]
     async fn add_note(&mut self, output_body: output::Body, source: NoteSource) {
         tracing::debug!(commitment = ?output_body.note_commitment, "appending to NCT in component");
-        // 1. Insert it into the NCT
+        // 1. Insert it into the NCT. The chain never needs to produce inclusion
+        // proofs, so it can forget the commitment as soon as it's inserted.
         self.note_commitment_tree
-            .append(&output_body.note_commitment);
+            .insert(merkle::Forget, output_body.note_commitment)
+            .expect("note commitment tree is not full");
         // 2. Record its source in the JMT
         self.overlay
             .set_note_source(&output_body.note_commitment, source)
             .await;
-        // 3. Finally, record it in the pending compact block.
+        // 3. Emit an event so indexers can learn about the new note.
+        self.events
+            .push(event::output(&output_body.note_commitment));
+        // 4. Finally, record it in the pending compact block.
         self.compact_block.outputs.push(output_body);
     }
 
     #[instrument(skip(self))]
     async fn write_compactblock_and_nct(&mut self) -> Result<()> {
+        let height = self.compact_block.height;
+
+        // Record which NCT block holds this block's notes, before closing it,
+        // so that swap claims can prove their swap NFT was created in it.
+        let position = u64::from(self.note_commitment_tree.position());
+        self.overlay
+            .set_nct_block(height, (position >> 16) as u32)
+            .await;
+
+        // Close this block in the NCT (and this epoch, if the block ends it),
+        // so that the next block's notes are inserted into a fresh block:
+        let epoch_duration = self.overlay.get_epoch_duration().await?;
+        if Epoch::from_height(height, epoch_duration).is_epoch_end(height) {
+            self.note_commitment_tree
+                .insert_epoch(merkle::Epoch::new())?;
+        }
+        self.note_commitment_tree
+            .insert_block(merkle::Block::new())?;
+
         // Write the CompactBlock:
         self.overlay
             .set_compact_block(std::mem::take(&mut self.compact_block))
             .await;
         // and the note commitment tree data and anchor:
         self.overlay
-            .set_nct_anchor(self.compact_block.height, self.note_commitment_tree.root2())
+            .set_nct_anchor(height, self.note_commitment_tree.root())
             .await;
         self.put_nct().await?;
 
@@ -357,8 +740,8 @@ impl ShieldedPool {
     }
 
     /// This is not part of the View trait because the NCT isn't a domain
-    /// type, and we'll be replacing it anyways, so there's not much point
-    /// implementing one now.  When switching to the TCT we should revisit.
+    /// type.  Since the chain forgets every commitment as soon as it's
+    /// inserted, the serialized NCT is just its frontier, so it's small.
     async fn put_nct(&mut self) -> Result<()> {
         let nct_data = bincode::serialize(&self.note_commitment_tree)?;
         self.overlay
@@ -370,8 +753,6 @@ impl ShieldedPool {
 
     /// This is an associated function rather than a method,
     /// so that we can call it in the constructor to get the NCT.
-    /// NOTE: we may not need that any more now that we can use an
-    /// Overlay on an empty database.
     async fn get_nct(overlay: &Overlay) -> Result<NoteCommitmentTree> {
         if let Ok(Some(bytes)) = overlay
             .lock()
@@ -381,7 +762,7 @@ impl ShieldedPool {
         {
             bincode::deserialize(&bytes).map_err(Into::into)
         } else {
-            Ok(NoteCommitmentTree::new(0))
+            Ok(NoteCommitmentTree::new())
         }
     }
 }
@@ -389,6 +770,38 @@ impl ShieldedPool {
 /// Extension trait providing read/write access to shielded pool data.
 ///
 /// TODO: should this be split into Read and Write traits?
+/// The notes created by a transaction, in the order of `Transaction::output_bodies`, with their
+/// sources.
+///
+/// The notes of a swap claim come from the batch the swap was cleared in: they're refunds if
+/// nothing in the batch was filled, and outputs otherwise.  Every other note comes from the
+/// transaction itself.
+fn note_sources(tx: &Transaction, source: NoteSource) -> Vec<(output::Body, NoteSource)> {
+    tx.actions()
+        .flat_map(|action| match action {
+            Action::Output(output) => vec![(output.body.clone(), source)],
+            Action::Swap(swap) => vec![(swap.body.swap_nft.clone(), source)],
+            Action::SwapClaim(claim) => {
+                let output_data = &claim.body.output_data;
+                let source = if output_data.lambda_1 == 0 && output_data.lambda_2 == 0 {
+                    NoteSource::SwapRefund {
+                        height: output_data.height,
+                    }
+                } else {
+                    NoteSource::SwapOutput {
+                        height: output_data.height,
+                    }
+                };
+                vec![
+                    (claim.body.output_1.clone(), source),
+                    (claim.body.output_2.clone(), source),
+                ]
+            }
+            _ => vec![],
+        })
+        .collect()
+}
+
 #[async_trait]
 pub trait View: OverlayExt {
     async fn token_supply(&self, asset_id: &asset::Id) -> Result<Option<u64>> {
@@ -519,6 +932,26 @@ pub trait View: OverlayExt {
         .await;
     }
 
+    async fn nct_anchor_by_height(&self, height: u64) -> Result<Option<merkle::Root>> {
+        self.get_domain(format!("shielded_pool/nct_anchor/{}", height).into())
+            .await
+    }
+
+    async fn set_nct_block(&self, height: u64, nct_block: u32) {
+        self.put_proto(
+            format!("shielded_pool/nct_block/{}", height).into(),
+            nct_block,
+        )
+        .await
+    }
+
+    /// The index of the NCT block holding the notes created at the given
+    /// height, as its epoch and block indices.
+    async fn nct_block(&self, height: u64) -> Result<Option<u32>> {
+        self.get_proto(format!("shielded_pool/nct_block/{}", height).into())
+            .await
+    }
+
     /// Checks whether a claimed NCT anchor is a previous valid state root.
     async fn check_claimed_anchor(&self, anchor: &merkle::Root) -> Result<()> {
         if let Some(anchor_height) = self
@@ -536,7 +969,7 @@ pub trait View: OverlayExt {
     }
 
     #[instrument(skip(self))]
-    async fn spend_nullifier(&self, nullifier: Nullifier, source: NoteSource) {
+    async fn spend_nullifier(&self, nullifier: Nullifier, source: NoteSource, height: u64) {
         self.put_proto(
             format!("shielded_pool/spent_nullifiers/{}", nullifier).into(),
             // We don't use the value for validity checks, but writing the source
@@ -545,6 +978,103 @@ pub trait View: OverlayExt {
             source.to_bytes().to_vec(),
         )
         .await;
+        // Governance needs to know whether a note was spent before voting on
+        // a proposal began.
+        self.put_proto(
+            format!("shielded_pool/spent_nullifier_heights/{}", nullifier).into(),
+            height,
+        )
+        .await;
+    }
+
+    /// Returns the height at which the nullifier was spent, if it was.
+    async fn nullifier_spend_height(&self, nullifier: Nullifier) -> Result<Option<u64>> {
+        self.get_proto(format!("shielded_pool/spent_nullifier_heights/{}", nullifier).into())
+            .await
+    }
+
+    async fn scheduled_quarantine(&self, epoch_index: u64) -> Result<Quarantined> {
+        Ok(self
+            .get_domain(format!("shielded_pool/quarantined/{}", epoch_index).into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_scheduled_quarantine(&self, epoch_index: u64, quarantined: Quarantined) {
+        self.put_domain(
+            format!("shielded_pool/quarantined/{}", epoch_index).into(),
+            quarantined,
+        )
+        .await
+    }
+
+    /// Returns the latest epoch any quarantine has been scheduled to be
+    /// released at.
+    async fn latest_quarantine_epoch(&self) -> Result<u64> {
+        Ok(self
+            .get_proto("shielded_pool/latest_quarantine_epoch".into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_latest_quarantine_epoch(&self, epoch_index: u64) {
+        self.put_proto("shielded_pool/latest_quarantine_epoch".into(), epoch_index)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn quarantine_nullifier(&self, nullifier: Nullifier, source: NoteSource) {
+        self.put_proto(
+            format!("shielded_pool/quarantined_nullifiers/{}", nullifier).into(),
+            source.to_bytes().to_vec(),
+        )
+        .await;
+    }
+
+    #[instrument(skip(self))]
+    async fn unquarantine_nullifier(&self, nullifier: Nullifier) {
+        // The JMT doesn't support deletions, so an empty value marks a
+        // nullifier that is no longer quarantined.
+        self.put_proto(
+            format!("shielded_pool/quarantined_nullifiers/{}", nullifier).into(),
+            Vec::<u8>::new(),
+        )
+        .await;
+    }
+
+    /// Returns the source of the transaction holding the nullifier in
+    /// quarantine, if any.
+    async fn quarantined_nullifier_source(
+        &self,
+        nullifier: Nullifier,
+    ) -> Result<Option<NoteSource>> {
+        match self
+            .get_proto::<Vec<u8>>(
+                format!("shielded_pool/quarantined_nullifiers/{}", nullifier).into(),
+            )
+            .await?
+        {
+            Some(source_bytes) if !source_bytes.is_empty() => {
+                let source_bytes: [u8; 32] = source_bytes
+                    .try_into()
+                    .map_err(|_| anyhow!("invalid note source encoding"))?;
+                Ok(Some(NoteSource::try_from(source_bytes)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn check_nullifier_unquarantined(&self, nullifier: Nullifier) -> Result<()> {
+        if let Some(source) = self.quarantined_nullifier_source(nullifier).await? {
+            Err(anyhow!(
+                "Nullifier {} is quarantined by the undelegation in {:?}",
+                nullifier,
+                source
+            ))
+        } else {
+            Ok(())
+        }
     }
 
     #[instrument(skip(self))]
@@ -0,0 +1,42 @@
+use penumbra_crypto::{note, Nullifier};
+use penumbra_stake::IdentityKey;
+use tendermint::abci::{Event, EventAttributeIndexExt};
+
+/// A note was spent, revealing its nullifier.
+pub fn spend(nullifier: &Nullifier) -> Event {
+    Event::new(
+        "action_spend",
+        vec![("nullifier", nullifier.to_string()).index()],
+    )
+}
+
+/// A new note was added to the note commitment tree.
+pub fn output(note_commitment: &note::Commitment) -> Event {
+    Event::new(
+        "action_output",
+        vec![("note_commitment", note_commitment.to_string()).index()],
+    )
+}
+
+/// The notes and nullifiers of an undelegation were quarantined until the end
+/// of the unbonding epoch.
+pub fn quarantine(identity_key: &IdentityKey, unbonding_epoch: u64) -> Event {
+    Event::new(
+        "quarantine",
+        vec![
+            ("validator", identity_key.to_string()).index(),
+            ("unbonding_epoch", unbonding_epoch.to_string()).no_index(),
+        ],
+    )
+}
+
+/// Quarantined undelegations were rolled back because the validator was slashed.
+pub fn quarantine_rollback(identity_key: &IdentityKey, unbonding_epoch: u64) -> Event {
+    Event::new(
+        "quarantine_rollback",
+        vec![
+            ("validator", identity_key.to_string()).index(),
+            ("unbonding_epoch", unbonding_epoch.to_string()).no_index(),
+        ],
+    )
+}
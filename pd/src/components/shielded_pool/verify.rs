@@ -0,0 +1,239 @@
+//! Batched signature verification and parallel proof verification for the
+//! stateless checks.
+
+use anyhow::{anyhow, Result};
+use penumbra_crypto::{
+    proofs::{OutputProof, OutputPublicInputs, Proof, SpendProof, SpendPublicInputs},
+    rdsa::{batch, Binding, Signature, SpendAuth, VerificationKey, VerificationKeyBytes},
+};
+use penumbra_dex::proofs::{SwapClaimProof, SwapClaimPublicInputs, SwapProof, SwapPublicInputs};
+use rand_core::OsRng;
+use rayon::prelude::*;
+
+/// What a queued signature authorizes, used to report which one failed.
+#[derive(Clone, Copy, Debug)]
+enum Signer {
+    Binding,
+    /// The spend authorization signature of the action with this index.
+    SpendAuth(usize),
+}
+
+/// A batch of the binding and spend authorization signatures of a
+/// transaction, verified all at once.
+#[derive(Default)]
+pub struct SignatureBatch {
+    verifier: batch::Verifier,
+    items: Vec<(Signer, batch::Item)>,
+}
+
+impl SignatureBatch {
+    /// Queues the transaction's binding signature.
+    pub fn queue_binding(
+        &mut self,
+        vk: VerificationKey<Binding>,
+        sig: Signature<Binding>,
+        sighash: &[u8; 64],
+    ) {
+        let item = batch::Item::from((VerificationKeyBytes::from(vk), sig, sighash));
+        self.queue(Signer::Binding, item);
+    }
+
+    /// Queues the spend authorization signature of the action at `index`.
+    pub fn queue_spend_auth(
+        &mut self,
+        index: usize,
+        rk: VerificationKey<SpendAuth>,
+        sig: Signature<SpendAuth>,
+        sighash: &[u8; 64],
+    ) {
+        let item = batch::Item::from((VerificationKeyBytes::from(rk), sig, sighash));
+        self.queue(Signer::SpendAuth(index), item);
+    }
+
+    fn queue(&mut self, signer: Signer, item: batch::Item) {
+        self.verifier.queue(item.clone());
+        self.items.push((signer, item));
+    }
+
+    /// Verifies every queued signature.
+    ///
+    /// A failed batch doesn't say which signature was invalid, so in that
+    /// case we fall back to checking each signature individually, and report
+    /// the first one that failed.
+    pub fn verify(self) -> Result<()> {
+        if self.verifier.verify(OsRng).is_ok() {
+            return Ok(());
+        }
+
+        for (signer, item) in self.items {
+            if item.verify_single().is_err() {
+                return Err(match signer {
+                    Signer::Binding => anyhow!("binding signature failed to verify"),
+                    Signer::SpendAuth(index) => {
+                        anyhow!("spend auth signature for action {} failed to verify", index)
+                    }
+                });
+            }
+        }
+
+        // Batch verification only fails if one of the signatures is invalid.
+        Err(anyhow!("signature batch failed to verify"))
+    }
+}
+
+/// A proof from one of the actions of a transaction, together with the public
+/// inputs it should be verified against.
+pub enum ProofCheck<'a> {
+    Spend(&'a SpendProof, SpendPublicInputs),
+    Output(&'a OutputProof, OutputPublicInputs),
+    Swap(&'a SwapProof, SwapPublicInputs),
+    SwapClaim(&'a SwapClaimProof, SwapClaimPublicInputs),
+}
+
+impl<'a> ProofCheck<'a> {
+    fn kind(&self) -> &'static str {
+        match self {
+            ProofCheck::Spend(..) => "spend",
+            ProofCheck::Output(..) => "output",
+            ProofCheck::Swap(..) => "swap",
+            ProofCheck::SwapClaim(..) => "swap claim",
+        }
+    }
+
+    fn verify(&self) -> Result<()> {
+        match self {
+            ProofCheck::Spend(proof, inputs) => proof.verify(inputs),
+            ProofCheck::Output(proof, inputs) => proof.verify(inputs),
+            ProofCheck::Swap(proof, inputs) => proof.verify(inputs),
+            ProofCheck::SwapClaim(proof, inputs) => proof.verify(inputs),
+        }
+    }
+}
+
+/// Verifies the proofs of a transaction in parallel on the rayon thread pool,
+/// returning an error for the first action (in order) whose proof did not
+/// verify.
+pub fn verify_proofs(proofs: Vec<(usize, ProofCheck)>) -> Result<()> {
+    let results = proofs
+        .par_iter()
+        .map(|(index, check)| {
+            check.verify().map_err(|e| {
+                anyhow!(
+                    "{} proof for action {} did not verify: {}",
+                    check.kind(),
+                    index,
+                    e
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    results.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use penumbra_crypto::{
+        asset, ka,
+        keys::{SpendKey, SpendSeed},
+        proofs::ProofSystem,
+        rdsa::SigningKey,
+        Fr, Note, Value,
+    };
+
+    use super::*;
+
+    // a spend auth signing key, with its verification key and signature over `sighash`.
+    fn spend_auth_signature(
+        sighash: &[u8; 64],
+    ) -> (VerificationKey<SpendAuth>, Signature<SpendAuth>) {
+        let sk = SigningKey::<SpendAuth>::new(OsRng);
+        let sig = sk.sign(OsRng, sighash);
+        (sk.into(), sig)
+    }
+
+    // a transparent output proof, with the public inputs it proves.
+    fn output_proof() -> (OutputProof, OutputPublicInputs) {
+        let mut rng = OsRng;
+        let sk = SpendKey::from(SpendSeed([1u8; 32]));
+        let (dest, _dtk_d) = sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+        let value = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let note = Note::generate(&mut rng, &dest, value);
+        let v_blinding = Fr::rand(&mut rng);
+        let esk = ka::Secret::new(&mut rng);
+
+        let inputs = OutputPublicInputs {
+            value_commitment: -value.commit(v_blinding),
+            note_commitment: note.commit(),
+            epk: esk.diversified_public(&note.diversified_generator()),
+        };
+        let proof = OutputProof::prove(ProofSystem::Transparent, &mut rng, &note, v_blinding, &esk)
+            .unwrap();
+        (proof, inputs)
+    }
+
+    #[test]
+    fn signature_batch_reports_failed_action() {
+        let sighash = [7u8; 64];
+
+        let mut batch = SignatureBatch::default();
+        for index in 0..4 {
+            let (rk, sig) = spend_auth_signature(&sighash);
+            batch.queue_spend_auth(index, rk, sig, &sighash);
+        }
+        assert!(batch.verify().is_ok());
+
+        // a signature over a different sighash is reported with its action's index.
+        let mut batch = SignatureBatch::default();
+        for index in 0..4 {
+            let (rk, sig) = if index == 2 {
+                spend_auth_signature(&[8u8; 64])
+            } else {
+                spend_auth_signature(&sighash)
+            };
+            batch.queue_spend_auth(index, rk, sig, &sighash);
+        }
+        let error = batch.verify().unwrap_err().to_string();
+        assert_eq!(error, "spend auth signature for action 2 failed to verify");
+    }
+
+    #[test]
+    fn verify_proofs_reports_first_failed_action() {
+        let outputs = (0..4).map(|_| output_proof()).collect::<Vec<_>>();
+
+        let checks = outputs
+            .iter()
+            .enumerate()
+            .map(|(index, (proof, inputs))| (index, ProofCheck::Output(proof, inputs.clone())))
+            .collect();
+        assert!(verify_proofs(checks).is_ok());
+
+        // actions 1 and 3 are checked against another output's public inputs, and only the
+        // first of them is reported.
+        let checks = outputs
+            .iter()
+            .enumerate()
+            .map(|(index, (proof, inputs))| {
+                let inputs = if index % 2 == 1 {
+                    outputs[0].1.clone()
+                } else {
+                    inputs.clone()
+                };
+                (index, ProofCheck::Output(proof, inputs))
+            })
+            .collect();
+        let error = verify_proofs(checks).unwrap_err().to_string();
+        assert!(
+            error.starts_with("output proof for action 1 did not verify"),
+            "{}",
+            error
+        );
+    }
+}
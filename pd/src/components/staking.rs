@@ -7,8 +7,8 @@ use penumbra_stake::{
     action::{Delegate, Undelegate},
     rate::{BaseRateData, RateData},
     validator::{self, Validator},
-    CommissionAmount, CommissionAmounts, DelegationChanges, Epoch, IdentityKey, Uptime,
-    STAKING_TOKEN_ASSET_ID,
+    CommissionAmount, CommissionAmounts, DelegationChanges, Epoch, FundingStreams, IdentityKey,
+    Uptime, STAKING_TOKEN_ASSET_ID,
 };
 use penumbra_transaction::{Action, Transaction};
 
@@ -25,6 +25,8 @@ use tracing::instrument;
 use super::{app::View as _, shielded_pool::View as _, Component};
 use crate::{genesis, Overlay, OverlayExt};
 
+mod event;
+
 // Max validator power is 1152921504606846975 (i64::MAX / 8)
 // https://github.com/tendermint/tendermint/blob/master/types/validator_set.go#L25
 const MAX_VOTING_POWER: i64 = 1152921504606846975;
@@ -36,6 +38,14 @@ pub struct Staking {
     /// persisted at the end of the block for processing at the end of the next
     /// epoch.
     delegation_changes: DelegationChanges,
+    /// Validators slashed over the course of this block, to be persisted at
+    /// the end of the block so that the ShieldedPool can roll back their
+    /// quarantined undelegations.
+    slashed_validators: Vec<IdentityKey>,
+    /// Transaction fees collected over the course of this block.
+    fees: u64,
+    /// Events recorded since the last call to `take_events`.
+    events: Vec<abci::Event>,
 }
 
 impl Staking {
@@ -46,10 +56,11 @@ impl Staking {
         let mut delegations_by_validator = BTreeMap::<IdentityKey, Vec<Delegate>>::new();
         let mut undelegations_by_validator = BTreeMap::<IdentityKey, Vec<Undelegate>>::new();
         for height in epoch_to_end.start_height().value()..=epoch_to_end.end_height().value() {
-            let changes = self
-                .overlay
-                .delegation_changes(height.try_into().unwrap())
-                .await?;
+            let height = height.try_into().unwrap();
+            let changes = self.overlay.delegation_changes(height).await?;
+            // The changes are only needed until the end of the epoch, so
+            // delete them rather than keeping them in the state forever.
+            self.overlay.delete_delegation_changes(height).await;
             for d in changes.delegations {
                 delegations_by_validator
                     .entry(d.validator_identity.clone())
@@ -95,6 +106,9 @@ impl Staking {
             .await;
 
         let mut commission_amounts = Vec::new();
+        // The voting power and funding streams of the validators that were
+        // Active during the ending epoch, which share the fees collected in it.
+        let mut fee_recipients = Vec::new();
         let validator_list = self.overlay.validator_list().await?;
         for v in &validator_list {
             let validator = self.overlay.validator(v).await?.ok_or_else(|| {
@@ -112,6 +126,11 @@ impl Staking {
 
             let funding_streams = validator.funding_streams;
 
+            if validator_state == validator::State::Active {
+                let power = self.overlay.validator_power(v).await?.unwrap_or(0);
+                fee_recipients.push((power, funding_streams.clone()));
+            }
+
             let next_rate =
                 current_rate.next(&next_base_rate, funding_streams.as_ref(), &validator_state);
             assert!(next_rate.epoch_index == epoch_to_end.index + 2);
@@ -189,7 +208,7 @@ impl Staking {
                     // JMT here so it can be processed during the ShieldedPool's end_block phase.
                     commission_amounts.push(CommissionAmount {
                         amount: commission_reward_amount,
-                        destination: stream.address,
+                        destination: stream.recipient,
                     });
                 }
             }
@@ -203,6 +222,8 @@ impl Staking {
             tracing::debug!(?delegation_denom);
         }
 
+        commission_amounts.extend(self.distribute_fees(fee_recipients).await?);
+
         // Now that all the voting power has been calculated for the upcoming epoch,
         // we can determine which validators are Active for the next epoch.
         self.process_epoch_transitions(epoch_to_end, active_validator_limit, unbonding_epochs)
@@ -225,6 +246,47 @@ impl Staking {
         Ok(())
     }
 
+    /// Distributes the fees collected during the ending epoch to the funding
+    /// streams of the validators that were Active in it.
+    ///
+    /// Fees are burned from the staking token supply as they are collected.
+    /// Each validator's share of the fees is proportional to its voting power,
+    /// and each funding stream receives its `rate_bps` of that share, to be
+    /// minted by the ShieldedPool along with the commission rewards.  Whatever
+    /// isn't distributed stays burned.
+    async fn distribute_fees(
+        &mut self,
+        fee_recipients: Vec<(u64, FundingStreams)>,
+    ) -> Result<Vec<CommissionAmount>> {
+        let collected_fees = self.overlay.collected_fees().await?;
+        self.overlay.set_collected_fees(0).await;
+
+        let total_power = fee_recipients
+            .iter()
+            .map(|(power, _)| *power as u128)
+            .sum::<u128>();
+        tracing::debug!(?collected_fees, ?total_power, "distributing fees");
+        if collected_fees == 0 || total_power == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut fee_amounts = Vec::new();
+        for (power, funding_streams) in fee_recipients {
+            let validator_fees = collected_fees as u128 * power as u128 / total_power;
+            for stream in funding_streams {
+                let amount = (validator_fees * stream.rate_bps as u128 / 10_000) as u64;
+                if amount > 0 {
+                    fee_amounts.push(CommissionAmount {
+                        amount,
+                        destination: stream.recipient,
+                    });
+                }
+            }
+        }
+
+        Ok(fee_amounts)
+    }
+
     /// Called during `end_epoch`. Will perform state transitions to validators based
     /// on changes to voting power that occurred in this epoch.
     pub async fn process_epoch_transitions(
@@ -278,9 +340,12 @@ impl Staking {
                 // on voting power and the delegation pool has a nonzero balance (meaning non-zero voting power),
                 // then the validator should be moved to the Active state.
                 if top_validators.contains(&vp.identity_key) && vp.power > 0 {
-                    self.overlay
-                        .set_validator_state(&vp.identity_key, validator::State::Active)
-                        .await;
+                    self.transition_validator_state(
+                        &vp.identity_key,
+                        &vp.state,
+                        validator::State::Active,
+                    )
+                    .await;
                     // Start tracking the validator's uptime as it becomes active
                     let uptime = Uptime::new(
                         self.overlay.get_block_height().await?,
@@ -299,14 +364,14 @@ impl Staking {
                     self.overlay
                         .set_validator_power(&vp.identity_key, 0)
                         .await?;
-                    self.overlay
-                        .set_validator_state(
-                            &vp.identity_key,
-                            validator::State::Unbonding {
-                                unbonding_epoch: unbonding_epochs,
-                            },
-                        )
-                        .await;
+                    self.transition_validator_state(
+                        &vp.identity_key,
+                        &vp.state,
+                        validator::State::Unbonding {
+                            unbonding_epoch: epoch_to_end.index + unbonding_epochs,
+                        },
+                    )
+                    .await;
                 }
             }
 
@@ -314,9 +379,12 @@ impl Staking {
             // and the validator is still in Unbonding state
             if let validator::State::Unbonding { unbonding_epoch } = vp.state {
                 if unbonding_epoch <= epoch_to_end.index {
-                    self.overlay
-                        .set_validator_state(&vp.identity_key, validator::State::Inactive)
-                        .await;
+                    self.transition_validator_state(
+                        &vp.identity_key,
+                        &vp.state,
+                        validator::State::Inactive,
+                    )
+                    .await;
                 }
             };
         }
@@ -324,6 +392,20 @@ impl Staking {
         Ok(())
     }
 
+    /// Sets the state of a validator, recording an event for the transition.
+    async fn transition_validator_state(
+        &mut self,
+        identity_key: &IdentityKey,
+        old_state: &validator::State,
+        new_state: validator::State,
+    ) {
+        self.events
+            .push(event::state_change(identity_key, old_state, &new_state));
+        self.overlay
+            .set_validator_state(identity_key, new_state)
+            .await;
+    }
+
     // Returns the list of validator updates formatted for inclusion in the Tendermint `EndBlockResponse`
     pub async fn tm_validator_updates(&self) -> Result<Vec<ValidatorUpdate>> {
         // Return the voting power for all known validators.
@@ -365,7 +447,7 @@ impl Staking {
     }
 
     #[instrument(skip(self, last_commit_info))]
-    async fn track_uptime(&self, last_commit_info: &LastCommitInfo) -> Result<()> {
+    async fn track_uptime(&mut self, last_commit_info: &LastCommitInfo) -> Result<()> {
         tracing::debug!(?last_commit_info);
 
         // Note: this probably isn't the correct height for the LastCommitInfo,
@@ -418,6 +500,12 @@ impl Staking {
                     self.overlay
                         .slash_validator(info.validator, params.slashing_penalty_downtime_bps)
                         .await?;
+                    self.events.push(event::slash(
+                        v,
+                        "downtime",
+                        params.slashing_penalty_downtime_bps,
+                    ));
+                    self.slashed_validators.push(v.clone());
                 } else {
                     self.overlay.set_validator_uptime(v, uptime).await;
                 }
@@ -435,6 +523,9 @@ impl Component for Staking {
         Self {
             overlay,
             delegation_changes: Default::default(),
+            slashed_validators: Vec::new(),
+            fees: 0,
+            events: Vec::new(),
         }
     }
 
@@ -542,10 +633,17 @@ impl Component for Staking {
         // For each validator identified as byzantine by tendermint, update its
         // state to be slashed.
         for evidence in begin_block.byzantine_validators.iter() {
-            self.overlay
+            let (validator, penalty) = self
+                .overlay
                 .slash_validator_by_evidence(evidence)
                 .await
                 .unwrap();
+            self.events.push(event::slash(
+                &validator.identity_key,
+                "misbehavior",
+                penalty,
+            ));
+            self.slashed_validators.push(validator.identity_key);
         }
 
         self.track_uptime(&begin_block.last_commit_info)
@@ -611,6 +709,20 @@ impl Component for Staking {
 
     #[instrument(name = "staking", skip(self, tx))]
     async fn check_tx_stateful(&self, tx: &Transaction) -> Result<()> {
+        // The fees collected in a block are burned from the staking token
+        // supply, so their total must fit in an `i64`.
+        let fee = tx.transaction_body.fee.0;
+        self.fees
+            .checked_add(fee)
+            .filter(|fees| i64::try_from(*fees).is_ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "transaction fee {} overflows the {} in fees already collected in this block",
+                    fee,
+                    self.fees
+                )
+            })?;
+
         // Tally the delegations and undelegations
         let mut delegation_changes = BTreeMap::new();
         for d in tx.delegations() {
@@ -757,15 +869,23 @@ impl Component for Staking {
 
     #[instrument(name = "staking", skip(self, tx))]
     async fn execute_tx(&mut self, tx: &Transaction) {
+        // Collect the transaction fee, for distribution at the end of the epoch.
+        self.fees = self
+            .fees
+            .checked_add(tx.transaction_body.fee.0)
+            .expect("fees were checked not to overflow in check_tx_stateful");
+
         // Queue any (un)delegations for processing at the next epoch boundary.
         for action in &tx.transaction_body.actions {
             match action {
                 Action::Delegate(d) => {
                     tracing::debug!(?d, "queuing delegation for next epoch");
+                    self.events.push(event::delegate(d));
                     self.delegation_changes.delegations.push(d.clone());
                 }
                 Action::Undelegate(u) => {
                     tracing::debug!(?u, "queuing undelegation for next epoch");
+                    self.events.push(event::undelegate(u));
                     self.delegation_changes.undelegations.push(u.clone());
                 }
                 _ => {}
@@ -777,6 +897,7 @@ impl Component for Staking {
         let cur_epoch = self.overlay.get_current_epoch().await.unwrap();
 
         for v in definitions {
+            self.events.push(event::validator_definition(&v.validator));
             if self
                 .overlay
                 .validator(&v.validator.identity_key)
@@ -834,6 +955,31 @@ impl Component for Staking {
             )
             .await;
 
+        // Burn the fees collected in this block from the staking token supply,
+        // and record them for distribution at the end of the epoch.
+        let fees = std::mem::take(&mut self.fees);
+        if fees > 0 {
+            let burned = i64::try_from(fees)
+                .expect("fees were checked to fit in an i64 in check_tx_stateful");
+            self.overlay
+                .update_token_supply(&STAKING_TOKEN_ASSET_ID, -burned)
+                .await
+                .unwrap();
+            let collected_fees = self.overlay.collected_fees().await.unwrap();
+            self.overlay.set_collected_fees(collected_fees + fees).await;
+        }
+
+        // Write the validators slashed in this block, if any, so that the
+        // ShieldedPool can roll back their quarantined undelegations.
+        if !self.slashed_validators.is_empty() {
+            self.overlay
+                .set_slashed_validators(
+                    end_block.height.try_into().unwrap(),
+                    std::mem::take(&mut self.slashed_validators),
+                )
+                .await;
+        }
+
         // If this is an epoch boundary, updated rates need to be calculated and set.
         let cur_epoch = self.overlay.get_current_epoch().await.unwrap();
         let cur_height = self.overlay.get_block_height().await.unwrap();
@@ -842,6 +988,10 @@ impl Component for Staking {
             self.end_epoch(cur_epoch).await.unwrap();
         }
     }
+
+    fn take_events(&mut self) -> Vec<abci::Event> {
+        std::mem::take(&mut self.events)
+    }
 }
 
 /// Extension trait providing read/write access to staking data.
@@ -960,7 +1110,9 @@ pub trait View: OverlayExt {
 
     // TODO: move out of view? this seems more like business logic
     // TODO: sort of messy, clean up slashing logic?
-    async fn slash_validator_by_evidence(&self, evidence: &Evidence) -> Result<()> {
+    /// Slashes the validator identified by the given evidence, returning the
+    /// slashed validator and the penalty applied.
+    async fn slash_validator_by_evidence(&self, evidence: &Evidence) -> Result<(Validator, u64)> {
         let ck = tendermint::PublicKey::from_raw_ed25519(&evidence.validator.address)
             .ok_or_else(|| anyhow::anyhow!("invalid ed25519 consensus pubkey from tendermint"))
             .unwrap();
@@ -975,7 +1127,10 @@ pub trait View: OverlayExt {
             .await?
             .slashing_penalty_misbehavior_bps;
 
-        self.slash_validator(validator, slashing_penalty).await
+        self.slash_validator(validator.clone(), slashing_penalty)
+            .await?;
+
+        Ok((validator, slashing_penalty))
     }
 
     async fn slash_validator(&self, validator: Validator, slashing_penalty: u64) -> Result<()> {
@@ -1140,6 +1295,68 @@ pub trait View: OverlayExt {
         .await
     }
 
+    async fn delete_delegation_changes(&self, height: block::Height) {
+        self.delete(format!("staking/delegation_changes/{}", height.value()).into())
+            .await
+    }
+
+    async fn slashed_validators(&self, height: u64) -> Result<Vec<IdentityKey>> {
+        Ok(self
+            .get_domain(format!("staking/slashed_validators/{}", height).into())
+            .await?
+            .map(|list: validator::List| list.0)
+            .unwrap_or_default())
+    }
+
+    async fn set_slashed_validators(&self, height: u64, validators: Vec<IdentityKey>) {
+        self.put_domain(
+            format!("staking/slashed_validators/{}", height).into(),
+            validator::List(validators),
+        )
+        .await
+    }
+
+    async fn delete_slashed_validators(&self, height: u64) {
+        self.delete(format!("staking/slashed_validators/{}", height).into())
+            .await
+    }
+
+    /// Computes the index of the epoch at whose end an undelegation from the
+    /// given validator, made in the current epoch, finishes unbonding.
+    async fn unbonding_epoch_for(&self, identity_key: &IdentityKey) -> Result<u64> {
+        let current_epoch = self.get_current_epoch().await?.index;
+        let unbonding_epochs = self.get_chain_params().await?.unbonding_epochs;
+        let state = self
+            .validator_state(identity_key)
+            .await?
+            .ok_or_else(|| anyhow!("missing state for validator {}", identity_key))?;
+
+        Ok(match state {
+            // Stake bonded to an active validator must wait out the full unbonding period...
+            validator::State::Active => current_epoch + unbonding_epochs,
+            // ... but if the validator is already unbonding, its stake is
+            // released once the validator finishes unbonding.
+            validator::State::Unbonding { unbonding_epoch } => {
+                unbonding_epoch.min(current_epoch + unbonding_epochs)
+            }
+            // Stake that isn't bonded can be released at the end of this epoch.
+            validator::State::Inactive | validator::State::Slashed => current_epoch,
+        })
+    }
+
+    /// Gets the transaction fees collected so far in the current epoch.
+    async fn collected_fees(&self) -> Result<u64> {
+        Ok(self
+            .get_proto("staking/collected_fees".into())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_collected_fees(&self, amount: u64) {
+        self.put_proto("staking/collected_fees".into(), amount)
+            .await
+    }
+
     async fn commission_amounts(&self, height: u64) -> Result<Option<CommissionAmounts>> {
         self.get_domain(format!("staking/commission_amounts/{}", height).into())
             .await
@@ -1153,6 +1370,11 @@ pub trait View: OverlayExt {
         .await
     }
 
+    async fn delete_commission_amounts(&self, height: u64) {
+        self.delete(format!("staking/commission_amounts/{}", height).into())
+            .await
+    }
+
     async fn validator_uptime(&self, identity_key: &IdentityKey) -> Result<Option<Uptime>> {
         self.get_domain(format!("staking/validator_uptime/{}", identity_key).into())
             .await
@@ -1166,6 +1388,19 @@ pub trait View: OverlayExt {
         .await
     }
 
+    /// Migrates the uptime trackers of all validators to a new signed blocks
+    /// window length, after a change to the chain parameters.
+    #[instrument(skip(self))]
+    async fn resize_validator_uptimes(&self, signed_blocks_window_len: u64) -> Result<()> {
+        for identity_key in self.validator_list().await? {
+            if let Some(mut uptime) = self.validator_uptime(&identity_key).await? {
+                uptime.resize(signed_blocks_window_len as usize);
+                self.set_validator_uptime(&identity_key, uptime).await;
+            }
+        }
+        Ok(())
+    }
+
     async fn signed_blocks_window_len(&self) -> Result<u64> {
         Ok(self.get_chain_params().await?.signed_blocks_window_len)
     }
@@ -1176,3 +1411,110 @@ pub trait View: OverlayExt {
 }
 
 impl<T: OverlayExt + Send + Sync> View for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Storage;
+    use penumbra_crypto::{
+        keys::{SpendKey, SpendSeed},
+        merkle,
+    };
+    use penumbra_stake::{FundingStream, Recipient};
+    use penumbra_transaction::{Fee, TransactionBody};
+    use tempfile::tempdir;
+
+    fn recipient(seed: u8) -> Recipient {
+        let sk = SpendKey::from(SpendSeed([seed; 32]));
+        Recipient::Address(
+            sk.full_viewing_key()
+                .incoming()
+                .payment_address(0u64.into())
+                .0,
+        )
+    }
+
+    fn funding_streams(streams: Vec<(Recipient, u16)>) -> FundingStreams {
+        streams
+            .into_iter()
+            .map(|(recipient, rate_bps)| FundingStream {
+                recipient,
+                rate_bps,
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    // test that collected fees are split between validators in proportion to their voting power,
+    // and between each validator's funding streams by their rates, rounding down.
+    #[tokio::test]
+    async fn test_distribute_fees() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("staking-testing.db"))
+            .await
+            .unwrap();
+        let overlay = storage.overlay().await.unwrap();
+        overlay.set_collected_fees(1000).await;
+        let mut staking = Staking::new(overlay.clone()).await;
+
+        let fee_recipients = vec![
+            (
+                3,
+                funding_streams(vec![(recipient(1), 5000), (Recipient::CommunityPool, 2500)]),
+            ),
+            (1, funding_streams(vec![(recipient(2), 10_000)])),
+            (0, funding_streams(vec![(recipient(3), 10_000)])),
+        ];
+        let fee_amounts = staking
+            .distribute_fees(fee_recipients.clone())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|amount| (amount.amount, amount.destination))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fee_amounts,
+            vec![
+                (375, recipient(1)),
+                (187, Recipient::CommunityPool),
+                (250, recipient(2)),
+            ]
+        );
+
+        // the collected fees are only distributed once.
+        assert_eq!(overlay.collected_fees().await.unwrap(), 0);
+        assert!(staking
+            .distribute_fees(fee_recipients)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    // test that a transaction is rejected if its fee would overflow the fees burned at the end of
+    // the block.
+    #[tokio::test]
+    async fn test_fee_overflow() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("staking-testing.db"))
+            .await
+            .unwrap();
+        let mut staking = Staking::new(storage.overlay().await.unwrap()).await;
+        staking.fees = i64::MAX as u64 - 1;
+
+        let tx = |fee: u64| Transaction {
+            transaction_body: TransactionBody {
+                actions: vec![],
+                merkle_root: merkle::NoteCommitmentTree::new().root(),
+                expiry_height: 0,
+                chain_id: "penumbra-test".to_string(),
+                fee: Fee(fee),
+            },
+            binding_sig: [0u8; 64].into(),
+        };
+
+        assert!(staking.check_tx_stateful(&tx(1)).await.is_ok());
+        assert!(staking.check_tx_stateful(&tx(2)).await.is_err());
+        assert!(staking.check_tx_stateful(&tx(u64::MAX)).await.is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use penumbra_stake::{
+    action::{Delegate, Undelegate},
+    validator, IdentityKey,
+};
+use tendermint::abci::{Event, EventAttributeIndexExt};
+
+/// Stake was delegated to a validator.
+pub fn delegate(delegate: &Delegate) -> Event {
+    Event::new(
+        "action_delegate",
+        vec![
+            ("validator", delegate.validator_identity.to_string()).index(),
+            ("amount", delegate.unbonded_amount.to_string()).no_index(),
+        ],
+    )
+}
+
+/// Stake was undelegated from a validator.
+pub fn undelegate(undelegate: &Undelegate) -> Event {
+    Event::new(
+        "action_undelegate",
+        vec![
+            ("validator", undelegate.validator_identity.to_string()).index(),
+            ("amount", undelegate.unbonded_amount.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A validator definition was uploaded, either defining a new validator or
+/// updating an existing one.
+pub fn validator_definition(validator: &validator::Validator) -> Event {
+    Event::new(
+        "action_validator_definition",
+        vec![
+            ("validator", validator.identity_key.to_string()).index(),
+            ("sequence_number", validator.sequence_number.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A validator was slashed.
+pub fn slash(identity_key: &IdentityKey, reason: &str, penalty_bps: u64) -> Event {
+    Event::new(
+        "validator_slashed",
+        vec![
+            ("validator", identity_key.to_string()).index(),
+            ("reason", reason.to_string()).no_index(),
+            ("penalty_bps", penalty_bps.to_string()).no_index(),
+        ],
+    )
+}
+
+/// A validator transitioned from one state to another.
+pub fn state_change(
+    identity_key: &IdentityKey,
+    old_state: &validator::State,
+    new_state: &validator::State,
+) -> Event {
+    Event::new(
+        "validator_state_change",
+        vec![
+            ("validator", identity_key.to_string()).index(),
+            ("old_state", old_state.to_string()).no_index(),
+            ("new_state", new_state.to_string()).index(),
+        ],
+    )
+}
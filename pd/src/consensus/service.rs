@@ -14,7 +14,7 @@ use tokio_util::sync::PollSender;
 use tower_abci::BoxError;
 
 use super::{Message, Worker};
-use crate::{RequestExt, Storage};
+use crate::{pruning, RequestExt, Storage};
 
 #[derive(Clone)]
 pub struct Consensus {
@@ -22,7 +22,10 @@ pub struct Consensus {
 }
 
 impl Consensus {
-    pub async fn new(storage: Storage) -> anyhow::Result<(Self, watch::Receiver<block::Height>)> {
+    pub async fn new(
+        storage: Storage,
+        pruning: pruning::Config,
+    ) -> anyhow::Result<(Self, watch::Receiver<block::Height>)> {
         let (queue_tx, queue_rx) = mpsc::channel(10);
         let initial_height = match storage.latest_version().await? {
             Some(version) => version.try_into().unwrap(),
@@ -30,9 +33,12 @@ impl Consensus {
         };
         let (height_tx, height_rx) = watch::channel(initial_height);
 
-        tokio::task::Builder::new()
-            .name("consensus::Worker")
-            .spawn(Worker::new(storage, queue_rx, height_tx).await?.run());
+        tokio::task::Builder::new().name("consensus::Worker").spawn(
+            Worker::new(storage.clone(), queue_rx, height_tx, pruning)
+                .await?
+                .run(),
+        );
+        pruning::spawn(storage, pruning, height_rx.clone());
 
         Ok((
             Self {
@@ -11,13 +11,17 @@ use tokio::sync::{mpsc, watch};
 use tracing::Instrument;
 
 use super::Message;
-use crate::{genesis, App, Component, Storage};
+use crate::{genesis, pruning, App, Component, Storage};
 
 pub struct Worker {
     queue: mpsc::Receiver<Message>,
     height_tx: watch::Sender<block::Height>,
     storage: Storage,
     app: App,
+    /// The storage version the `app`'s overlay was created on top of.
+    version: Option<jmt::Version>,
+    /// Determines the retain height reported to Tendermint on commit.
+    pruning: pruning::Config,
 }
 
 impl Worker {
@@ -25,14 +29,18 @@ impl Worker {
         storage: Storage,
         queue: mpsc::Receiver<Message>,
         height_tx: watch::Sender<block::Height>,
+        pruning: pruning::Config,
     ) -> Result<Self> {
         let app = App::new(storage.overlay().await?).await;
+        let version = storage.latest_version().await?;
 
         Ok(Self {
             queue,
             height_tx,
             storage,
             app,
+            version,
+            pruning,
         })
     }
 
@@ -61,7 +69,10 @@ impl Worker {
                 ),
                 Request::DeliverTx(deliver_tx) => {
                     Response::DeliverTx(match self.deliver_tx(deliver_tx).instrument(span).await {
-                        Ok(()) => abci::response::DeliverTx::default(),
+                        Ok(events) => abci::response::DeliverTx {
+                            events,
+                            ..Default::default()
+                        },
                         Err(e) => abci::response::DeliverTx {
                             code: 1,
                             log: e.to_string(),
@@ -105,7 +116,8 @@ impl Worker {
         }
         self.app.init_chain(&app_state).await;
         // Note: App::commit resets internal components, so we don't need to do that ourselves.
-        let (jmt_root, _) = self.app.commit(self.storage.clone()).await?;
+        let (jmt_root, version) = self.app.commit(self.storage.clone()).await?;
+        self.version = Some(version);
 
         let app_hash = jmt_root.0.to_vec();
 
@@ -135,9 +147,21 @@ impl Worker {
         &mut self,
         begin_block: abci::request::BeginBlock,
     ) -> Result<abci::response::BeginBlock> {
+        // If the state was restored from a snapshot, the storage has moved on
+        // from the version our overlay was created on, so we need to rebuild
+        // the app on top of the restored state.
+        let version = self.storage.latest_version().await?;
+        if version != self.version {
+            tracing::info!(?version, "storage version changed, resetting app state");
+            self.app = App::new(self.storage.overlay().await?).await;
+            self.version = version;
+        }
+
         self.app.begin_block(&begin_block).await;
-        // TODO(events): consider creating + returning Events to Tendermint here.
-        Ok(Default::default())
+
+        Ok(abci::response::BeginBlock {
+            events: self.app.take_events(),
+        })
     }
 
     /// Perform full transaction validation via `DeliverTx`.
@@ -147,18 +171,27 @@ impl Worker {
     /// We must perform all checks again here even though they are performed in `CheckTx`, as a
     /// Byzantine node may propose a block containing double spends or other disallowed behavior,
     /// so it is not safe to assume all checks performed in `CheckTx` were done.
-    async fn deliver_tx(&mut self, deliver_tx: abci::request::DeliverTx) -> Result<()> {
+    ///
+    /// Returns the events recorded while executing the transaction.
+    async fn deliver_tx(
+        &mut self,
+        deliver_tx: abci::request::DeliverTx,
+    ) -> Result<Vec<abci::Event>> {
         // Verify the transaction is well-formed...
         let transaction = Transaction::decode(deliver_tx.tx)?;
-        // ... and statelessly valid...
-        App::check_tx_stateless(&transaction)?;
+        // ... and statelessly valid, verifying its signatures and proofs on
+        // the blocking thread pool so as not to stall the runtime...
+        let transaction = tokio::task::spawn_blocking(move || {
+            App::check_tx_stateless(&transaction).map(|()| transaction)
+        })
+        .await??;
         // ... and statefully valid.
         self.app.check_tx_stateful(&transaction).await?;
         // Now execute the transaction. It's important to panic on error here, since if
         // we fail to execute the transaction here, it's because of an internal
         // error and we may have left the chain in an inconsistent state.
         self.app.execute_tx(&transaction).await;
-        Ok(())
+        Ok(self.app.take_events())
     }
 
     async fn end_block(
@@ -181,7 +214,7 @@ impl Worker {
         Ok(abci::response::EndBlock {
             validator_updates,
             consensus_param_updates: None,
-            events: Vec::new(),
+            events: self.app.take_events(),
         })
     }
 
@@ -189,7 +222,8 @@ impl Worker {
         // Begin sidecar code
 
         // Note: App::commit resets internal components, so we don't need to do that ourselves.
-        let (jmt_root, _) = self.app.commit(self.storage.clone()).await?;
+        let (jmt_root, version) = self.app.commit(self.storage.clone()).await?;
+        self.version = Some(version);
         let app_hash = jmt_root.0.to_vec();
         let _ = self.height_tx.send(
             self.storage
@@ -200,11 +234,19 @@ impl Worker {
                 .unwrap(),
         );
 
-        tracing::info!(app_hash = ?hex::encode(&app_hash), "finished block commit");
+        // Let Tendermint prune the blocks we no longer keep the state for.
+        // A retain height of 0 means that nothing is pruned.
+        let retain_height = self.pruning.retain_version(version).unwrap_or(0);
+
+        tracing::info!(
+            app_hash = ?hex::encode(&app_hash),
+            retain_height,
+            "finished block commit"
+        );
 
         Ok(abci::response::Commit {
             data: app_hash.into(),
-            retain_height: 0u32.into(),
+            retain_height: retain_height.try_into()?,
         })
     }
 }
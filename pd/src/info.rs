@@ -4,26 +4,41 @@ use std::{
     task::{Context, Poll},
 };
 
+use anyhow::anyhow;
 use futures::FutureExt;
-use tendermint::abci::{self, response::Echo, InfoRequest, InfoResponse};
+use tendermint::{
+    abci::{self, response::Echo, InfoRequest, InfoResponse},
+    block, merkle,
+};
+use tokio::sync::watch;
 use tower_abci::BoxError;
 use tracing::Instrument;
 
 use crate::{RequestExt, Storage};
 
+mod ibc;
 mod oblivious;
 mod specific;
 
 const ABCI_INFO_VERSION: &str = env!("VERGEN_GIT_SEMVER");
 
+/// The proof type used for the bincode-encoded JMT `SparseMerkleProof`s
+/// returned by `state/key` queries.
+const JMT_PROOF_TYPE: &str = "jmt:v1";
+
+/// The ABCI info service, which also implements the `ObliviousQuery` and
+/// `SpecificQuery` gRPC services, and the IBC client, connection and channel
+/// query services used by relayers.
 #[derive(Clone, Debug)]
 pub struct Info {
     storage: Storage,
+    /// Used to notify subscribers of newly committed blocks.
+    height_rx: watch::Receiver<block::Height>,
 }
 
 impl Info {
-    pub fn new(storage: Storage) -> Self {
-        Self { storage }
+    pub fn new(storage: Storage, height_rx: watch::Receiver<block::Height>) -> Self {
+        Self { storage, height_rx }
     }
 
     async fn info(&self, info: abci::request::Info) -> Result<abci::response::Info, anyhow::Error> {
@@ -47,13 +62,66 @@ impl Info {
         })
     }
 
+    /// Answers key/value queries against the JMT.
+    ///
+    /// The only supported path is `state/key`, with the raw key (e.g.,
+    /// `staking/validators/list`) as the query data.  If `prove` is set, the
+    /// response includes a JMT proof of existence or non-existence, which can
+    /// be verified against the app hash for the queried height.
     async fn query(
         &self,
         query: abci::request::Query,
     ) -> Result<abci::response::Query, anyhow::Error> {
-        tracing::warn!(?query, "unhandled query");
-        // TODO: implement (#22)
-        Ok(Default::default())
+        tracing::debug!(?query);
+
+        match query.path.as_str() {
+            "state/key" => {}
+            path => return Err(anyhow!("unknown query path {}", path)),
+        }
+
+        let latest_version = self
+            .storage
+            .latest_version()
+            .await?
+            .ok_or_else(|| anyhow!("no state has been committed yet"))?;
+        // A height of 0 means "the latest height".
+        let version = match query.height.value() {
+            0 => latest_version,
+            height if height <= latest_version => height,
+            height => {
+                return Err(anyhow!(
+                    "requested height {} is greater than the latest height {}",
+                    height,
+                    latest_version
+                ))
+            }
+        };
+
+        let key_hash = jmt::KeyHash::from(query.data.as_ref());
+        let (value, proof) = jmt::JellyfishMerkleTree::new(&self.storage)
+            .get_with_proof(key_hash, version)
+            .await?;
+
+        let proof = if query.prove {
+            Some(merkle::Proof {
+                ops: vec![merkle::proof::ProofOp {
+                    field_type: JMT_PROOF_TYPE.to_string(),
+                    key: query.data.to_vec(),
+                    data: bincode::serialize(&proof)?,
+                }],
+            })
+        } else {
+            None
+        };
+
+        Ok(abci::response::Query {
+            code: 0,
+            key: query.data,
+            value: value.unwrap_or_default().into(),
+            proof,
+            height: version.try_into()?,
+            ..Default::default()
+        })
     }
 }
 
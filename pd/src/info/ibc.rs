@@ -0,0 +1,1142 @@
+use std::str::FromStr;
+
+use ibc::core::{
+    ics02_client::{client_state::ClientState, client_type::ClientType, height::Height},
+    ics03_connection::connection::IdentifiedConnectionEnd,
+    ics04_channel::{
+        channel::{IdentifiedChannelEnd, Order},
+        packet::Sequence,
+    },
+    ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+};
+use ibc_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+use ibc_proto::ibc::core::{
+    channel::v1::{
+        query_server::Query as ChannelQuery, PacketState, QueryChannelClientStateRequest,
+        QueryChannelClientStateResponse, QueryChannelConsensusStateRequest,
+        QueryChannelConsensusStateResponse, QueryChannelRequest, QueryChannelResponse,
+        QueryChannelsRequest, QueryChannelsResponse, QueryConnectionChannelsRequest,
+        QueryConnectionChannelsResponse, QueryNextSequenceReceiveRequest,
+        QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementRequest,
+        QueryPacketAcknowledgementResponse, QueryPacketAcknowledgementsRequest,
+        QueryPacketAcknowledgementsResponse, QueryPacketCommitmentRequest,
+        QueryPacketCommitmentResponse, QueryPacketCommitmentsRequest,
+        QueryPacketCommitmentsResponse, QueryPacketReceiptRequest, QueryPacketReceiptResponse,
+        QueryUnreceivedAcksRequest, QueryUnreceivedAcksResponse, QueryUnreceivedPacketsRequest,
+        QueryUnreceivedPacketsResponse,
+    },
+    client::v1::{
+        query_server::Query as ClientQuery, ConsensusStateWithHeight, Height as RawHeight,
+        IdentifiedClientState, Params as ClientParams, QueryClientParamsRequest,
+        QueryClientParamsResponse, QueryClientStateRequest, QueryClientStateResponse,
+        QueryClientStatesRequest, QueryClientStatesResponse, QueryClientStatusRequest,
+        QueryClientStatusResponse, QueryConsensusStateRequest, QueryConsensusStateResponse,
+        QueryConsensusStatesRequest, QueryConsensusStatesResponse, QueryUpgradedClientStateRequest,
+        QueryUpgradedClientStateResponse, QueryUpgradedConsensusStateRequest,
+        QueryUpgradedConsensusStateResponse,
+    },
+    connection::v1::{
+        query_server::Query as ConnectionQuery, QueryClientConnectionsRequest,
+        QueryClientConnectionsResponse, QueryConnectionClientStateRequest,
+        QueryConnectionClientStateResponse, QueryConnectionConsensusStateRequest,
+        QueryConnectionConsensusStateResponse, QueryConnectionRequest, QueryConnectionResponse,
+        QueryConnectionsRequest, QueryConnectionsResponse,
+    },
+};
+use ibc_proto::{
+    ibc::core::commitment::v1::MerkleProof,
+    ics23::{CommitmentProof, ProofSpec},
+};
+use penumbra_ibc::ClientData;
+use prost::Message;
+use tendermint::Time;
+use tonic::Status;
+use tracing::instrument;
+
+use super::Info;
+use crate::components::{
+    app::View as _,
+    ibc::{
+        channel::View as _, client::View as _, connection::View as _, state_key,
+        transfer::transfer_port,
+    },
+};
+use crate::Overlay;
+
+// The IBC query services answer every request against a single pinned version of the state,
+// so that all the values in a response, and their proofs, are consistent with the same height.
+//
+// Proofs are encoded `MerkleProof`s holding a single ICS-23 commitment proof of the full JMT key
+// (including the `ibc` commitment prefix) against the app hash, verifiable with the spec returned
+// by `jmt_proof_spec`.
+//
+// List queries honour the pagination in the request; the `next_key` of a page is the offset of
+// the following page, and clients should treat it as opaque.
+
+/// The number of results in a page, when the request doesn't set a limit.
+const DEFAULT_PAGE_LIMIT: u64 = 100;
+
+/// The ICS-23 proof spec for the JMT, which counterparty clients of Penumbra need among their
+/// `proof_specs` to verify the proofs returned by the IBC query services.
+pub fn jmt_proof_spec() -> ProofSpec {
+    ProofSpec::decode(jmt::ics23_spec().encode_to_vec().as_slice())
+        .expect("the JMT proof spec is a valid ICS-23 proof spec")
+}
+
+/// A version of the state, pinned to answer an IBC query.
+struct IbcState {
+    overlay: Overlay,
+    version: jmt::Version,
+    /// The version of the state, as an IBC height.
+    height: RawHeight,
+}
+
+impl Info {
+    async fn ibc_state(&self) -> Result<IbcState, Status> {
+        let version = self
+            .storage
+            .latest_version()
+            .await
+            .map_err(|_| Status::unavailable("database error"))?
+            .ok_or_else(|| Status::unavailable("no state has been committed yet"))?;
+        let overlay = self
+            .storage
+            .overlay_at(version)
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?;
+        let chain_id = overlay
+            .get_chain_params()
+            .await
+            .map_err(|_| Status::unavailable("database error"))?
+            .chain_id;
+
+        Ok(IbcState {
+            overlay,
+            version,
+            height: RawHeight {
+                revision_number: ChainId::chain_version(&chain_id),
+                revision_height: version,
+            },
+        })
+    }
+
+    // prove the value stored under the given JMT key, or its absence, as of the given version.
+    async fn prove(&self, key: String, version: jmt::Version) -> Result<Vec<u8>, Status> {
+        let proof = jmt::JellyfishMerkleTree::new(&self.storage)
+            .get_with_ics23_proof(key.into_bytes(), version)
+            .await
+            .map_err(|_| Status::unavailable("database error"))?;
+        let proof = CommitmentProof::decode(proof.encode_to_vec().as_slice())
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(MerkleProof {
+            proofs: vec![proof],
+        }
+        .encode_to_vec())
+    }
+}
+
+#[tonic::async_trait]
+impl ClientQuery for Info {
+    #[instrument(skip(self, request))]
+    async fn client_state(
+        &self,
+        request: tonic::Request<QueryClientStateRequest>,
+    ) -> Result<tonic::Response<QueryClientStateResponse>, Status> {
+        let client_id = parse_client_id(&request.get_ref().client_id)?;
+        let state = self.ibc_state().await?;
+
+        let client_data = state
+            .overlay
+            .get_client_data(&client_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let proof = self
+            .prove(state_key::client_state(&client_id), state.version)
+            .await?;
+
+        Ok(tonic::Response::new(QueryClientStateResponse {
+            client_state: Some(client_data.client_state.0.into()),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn client_states(
+        &self,
+        request: tonic::Request<QueryClientStatesRequest>,
+    ) -> Result<tonic::Response<QueryClientStatesResponse>, Status> {
+        let state = self.ibc_state().await?;
+
+        let (client_ids, pagination) = paginate(
+            client_ids(&state.overlay).await?,
+            request.into_inner().pagination,
+        )?;
+        let mut client_states = Vec::new();
+        for client_id in client_ids {
+            let client_data = state
+                .overlay
+                .get_client_data(&client_id)
+                .await
+                .map_err(|_| Status::unavailable("database error"))?;
+            client_states.push(IdentifiedClientState {
+                client_id: client_id.to_string(),
+                client_state: Some(client_data.client_state.0.into()),
+            });
+        }
+
+        Ok(tonic::Response::new(QueryClientStatesResponse {
+            client_states,
+            pagination,
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn consensus_state(
+        &self,
+        request: tonic::Request<QueryConsensusStateRequest>,
+    ) -> Result<tonic::Response<QueryConsensusStateResponse>, Status> {
+        let request = request.into_inner();
+        let client_id = parse_client_id(&request.client_id)?;
+        let state = self.ibc_state().await?;
+
+        let height = if request.latest_height {
+            state
+                .overlay
+                .get_client_data(&client_id)
+                .await
+                .map_err(|e| Status::not_found(e.to_string()))?
+                .client_state
+                .0
+                .latest_height()
+        } else {
+            Height::new(request.revision_number, request.revision_height)
+        };
+        let consensus_state = state
+            .overlay
+            .get_verified_consensus_state(height, client_id.clone())
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let proof = self
+            .prove(
+                state_key::verified_consensus_state(&client_id, &height),
+                state.version,
+            )
+            .await?;
+
+        Ok(tonic::Response::new(QueryConsensusStateResponse {
+            consensus_state: Some(consensus_state.0.into()),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn consensus_states(
+        &self,
+        request: tonic::Request<QueryConsensusStatesRequest>,
+    ) -> Result<tonic::Response<QueryConsensusStatesResponse>, Status> {
+        let request = request.into_inner();
+        let client_id = parse_client_id(&request.client_id)?;
+        let state = self.ibc_state().await?;
+
+        let mut heights = state
+            .overlay
+            .get_verified_heights(&client_id)
+            .await
+            .map_err(|_| Status::unavailable("database error"))?
+            .map(|verified_heights| verified_heights.heights)
+            .unwrap_or_default();
+        heights.sort();
+
+        let (heights, pagination) = paginate(heights, request.pagination)?;
+        let mut consensus_states = Vec::new();
+        for height in heights {
+            let consensus_state = state
+                .overlay
+                .get_verified_consensus_state(height, client_id.clone())
+                .await
+                .map_err(|_| Status::unavailable("database error"))?;
+            consensus_states.push(ConsensusStateWithHeight {
+                height: Some(height.into()),
+                consensus_state: Some(consensus_state.0.into()),
+            });
+        }
+
+        Ok(tonic::Response::new(QueryConsensusStatesResponse {
+            consensus_states,
+            pagination,
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn client_status(
+        &self,
+        request: tonic::Request<QueryClientStatusRequest>,
+    ) -> Result<tonic::Response<QueryClientStatusResponse>, Status> {
+        let client_id = parse_client_id(&request.get_ref().client_id)?;
+        let state = self.ibc_state().await?;
+
+        let client_data = state
+            .overlay
+            .get_client_data(&client_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let status = client_status(&state.overlay, &client_data)
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?;
+
+        Ok(tonic::Response::new(QueryClientStatusResponse {
+            status: status.to_string(),
+        }))
+    }
+
+    #[instrument(skip(self, _request))]
+    async fn client_params(
+        &self,
+        _request: tonic::Request<QueryClientParamsRequest>,
+    ) -> Result<tonic::Response<QueryClientParamsResponse>, Status> {
+        Ok(tonic::Response::new(QueryClientParamsResponse {
+            params: Some(ClientParams {
+                allowed_clients: vec![ClientType::Tendermint.as_str().to_string()],
+            }),
+        }))
+    }
+
+    #[instrument(skip(self, _request))]
+    async fn upgraded_client_state(
+        &self,
+        _request: tonic::Request<QueryUpgradedClientStateRequest>,
+    ) -> Result<tonic::Response<QueryUpgradedClientStateResponse>, Status> {
+        Err(Status::not_found("no upgrade is scheduled"))
+    }
+
+    #[instrument(skip(self, _request))]
+    async fn upgraded_consensus_state(
+        &self,
+        _request: tonic::Request<QueryUpgradedConsensusStateRequest>,
+    ) -> Result<tonic::Response<QueryUpgradedConsensusStateResponse>, Status> {
+        Err(Status::not_found("no upgrade is scheduled"))
+    }
+}
+
+#[tonic::async_trait]
+impl ConnectionQuery for Info {
+    #[instrument(skip(self, request))]
+    async fn connection(
+        &self,
+        request: tonic::Request<QueryConnectionRequest>,
+    ) -> Result<tonic::Response<QueryConnectionResponse>, Status> {
+        let connection_id = parse_connection_id(&request.get_ref().connection_id)?;
+        let state = self.ibc_state().await?;
+
+        let connection = state
+            .overlay
+            .get_connection(&connection_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let proof = self
+            .prove(state_key::connection(&connection_id), state.version)
+            .await?;
+
+        Ok(tonic::Response::new(QueryConnectionResponse {
+            connection: Some(connection.into()),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn connections(
+        &self,
+        request: tonic::Request<QueryConnectionsRequest>,
+    ) -> Result<tonic::Response<QueryConnectionsResponse>, Status> {
+        let state = self.ibc_state().await?;
+
+        let counter = state
+            .overlay
+            .connection_counter()
+            .await
+            .map_err(|_| Status::unavailable("database error"))?;
+        let (connection_ids, pagination) = paginate(
+            (0..counter.0).map(ConnectionId::new).collect(),
+            request.into_inner().pagination,
+        )?;
+        let mut connections = Vec::new();
+        for connection_id in connection_ids {
+            let connection = state
+                .overlay
+                .get_connection(&connection_id)
+                .await
+                .map_err(|_| Status::unavailable("database error"))?;
+            connections.push(IdentifiedConnectionEnd::new(connection_id, connection).into());
+        }
+
+        Ok(tonic::Response::new(QueryConnectionsResponse {
+            connections,
+            pagination,
+            height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn client_connections(
+        &self,
+        request: tonic::Request<QueryClientConnectionsRequest>,
+    ) -> Result<tonic::Response<QueryClientConnectionsResponse>, Status> {
+        let client_id = parse_client_id(&request.get_ref().client_id)?;
+        let state = self.ibc_state().await?;
+
+        let connections = state
+            .overlay
+            .get_client_connections(&client_id)
+            .await
+            .map_err(|_| Status::unavailable("database error"))?;
+        let proof = self
+            .prove(state_key::client_connections(&client_id), state.version)
+            .await?;
+
+        Ok(tonic::Response::new(QueryClientConnectionsResponse {
+            connection_paths: connections
+                .connection_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn connection_client_state(
+        &self,
+        request: tonic::Request<QueryConnectionClientStateRequest>,
+    ) -> Result<tonic::Response<QueryConnectionClientStateResponse>, Status> {
+        let connection_id = parse_connection_id(&request.get_ref().connection_id)?;
+        let state = self.ibc_state().await?;
+
+        let connection = state
+            .overlay
+            .get_connection(&connection_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let (identified_client_state, proof) = self
+            .identified_client_state(&state, connection.client_id())
+            .await?;
+
+        Ok(tonic::Response::new(QueryConnectionClientStateResponse {
+            identified_client_state: Some(identified_client_state),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn connection_consensus_state(
+        &self,
+        request: tonic::Request<QueryConnectionConsensusStateRequest>,
+    ) -> Result<tonic::Response<QueryConnectionConsensusStateResponse>, Status> {
+        let request = request.into_inner();
+        let connection_id = parse_connection_id(&request.connection_id)?;
+        let state = self.ibc_state().await?;
+
+        let connection = state
+            .overlay
+            .get_connection(&connection_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let height = Height::new(request.revision_number, request.revision_height);
+        let (consensus_state, proof) = self
+            .consensus_state_with_proof(&state, connection.client_id(), height)
+            .await?;
+
+        Ok(tonic::Response::new(
+            QueryConnectionConsensusStateResponse {
+                consensus_state: Some(consensus_state),
+                client_id: connection.client_id().to_string(),
+                proof,
+                proof_height: Some(state.height),
+            },
+        ))
+    }
+}
+
+#[tonic::async_trait]
+impl ChannelQuery for Info {
+    #[instrument(skip(self, request))]
+    async fn channel(
+        &self,
+        request: tonic::Request<QueryChannelRequest>,
+    ) -> Result<tonic::Response<QueryChannelResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        let channel = state
+            .overlay
+            .get_channel(&port_id, &channel_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let proof = self
+            .prove(state_key::channel(&port_id, &channel_id), state.version)
+            .await?;
+
+        Ok(tonic::Response::new(QueryChannelResponse {
+            channel: Some(channel.into()),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn channels(
+        &self,
+        request: tonic::Request<QueryChannelsRequest>,
+    ) -> Result<tonic::Response<QueryChannelsResponse>, Status> {
+        let state = self.ibc_state().await?;
+
+        let (channels, pagination) = paginate(
+            channels(&state.overlay).await?,
+            request.into_inner().pagination,
+        )?;
+
+        Ok(tonic::Response::new(QueryChannelsResponse {
+            channels: channels.into_iter().map(Into::into).collect(),
+            pagination,
+            height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn connection_channels(
+        &self,
+        request: tonic::Request<QueryConnectionChannelsRequest>,
+    ) -> Result<tonic::Response<QueryConnectionChannelsResponse>, Status> {
+        let request = request.into_inner();
+        let connection_id = parse_connection_id(&request.connection)?;
+        let state = self.ibc_state().await?;
+
+        let channels = channels(&state.overlay)
+            .await?
+            .into_iter()
+            .filter(|channel| {
+                channel
+                    .channel_end
+                    .connection_hops()
+                    .contains(&connection_id)
+            })
+            .collect();
+        let (channels, pagination) = paginate(channels, request.pagination)?;
+
+        Ok(tonic::Response::new(QueryConnectionChannelsResponse {
+            channels: channels.into_iter().map(Into::into).collect(),
+            pagination,
+            height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn channel_client_state(
+        &self,
+        request: tonic::Request<QueryChannelClientStateRequest>,
+    ) -> Result<tonic::Response<QueryChannelClientStateResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        let client_id = self
+            .channel_client_id(&state, &port_id, &channel_id)
+            .await?;
+        let (identified_client_state, proof) =
+            self.identified_client_state(&state, &client_id).await?;
+
+        Ok(tonic::Response::new(QueryChannelClientStateResponse {
+            identified_client_state: Some(identified_client_state),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn channel_consensus_state(
+        &self,
+        request: tonic::Request<QueryChannelConsensusStateRequest>,
+    ) -> Result<tonic::Response<QueryChannelConsensusStateResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        let client_id = self
+            .channel_client_id(&state, &port_id, &channel_id)
+            .await?;
+        let height = Height::new(request.revision_number, request.revision_height);
+        let (consensus_state, proof) = self
+            .consensus_state_with_proof(&state, &client_id, height)
+            .await?;
+
+        Ok(tonic::Response::new(QueryChannelConsensusStateResponse {
+            consensus_state: Some(consensus_state),
+            client_id: client_id.to_string(),
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn packet_commitment(
+        &self,
+        request: tonic::Request<QueryPacketCommitmentRequest>,
+    ) -> Result<tonic::Response<QueryPacketCommitmentResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let sequence = Sequence::from(request.sequence);
+        let state = self.ibc_state().await?;
+
+        let commitment = state
+            .overlay
+            .packet_commitment(&port_id, &channel_id, sequence)
+            .await
+            .map_err(|_| Status::unavailable("database error"))?
+            .ok_or_else(|| Status::not_found("packet commitment not found"))?;
+        let proof = self
+            .prove(
+                state_key::packet_commitment(&port_id, &channel_id, sequence),
+                state.version,
+            )
+            .await?;
+
+        Ok(tonic::Response::new(QueryPacketCommitmentResponse {
+            commitment,
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn packet_commitments(
+        &self,
+        request: tonic::Request<QueryPacketCommitmentsRequest>,
+    ) -> Result<tonic::Response<QueryPacketCommitmentsResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        let next_sequence_send = state
+            .overlay
+            .next_sequence_send(&port_id, &channel_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let mut commitments = Vec::new();
+        for sequence in 1..next_sequence_send {
+            if let Some(commitment) = state
+                .overlay
+                .packet_commitment(&port_id, &channel_id, Sequence::from(sequence))
+                .await
+                .map_err(|_| Status::unavailable("database error"))?
+            {
+                commitments.push(packet_state(&port_id, &channel_id, sequence, commitment));
+            }
+        }
+
+        let (commitments, pagination) = paginate(commitments, request.pagination)?;
+
+        Ok(tonic::Response::new(QueryPacketCommitmentsResponse {
+            commitments,
+            pagination,
+            height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn packet_receipt(
+        &self,
+        request: tonic::Request<QueryPacketReceiptRequest>,
+    ) -> Result<tonic::Response<QueryPacketReceiptResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let sequence = Sequence::from(request.sequence);
+        let state = self.ibc_state().await?;
+
+        let received = state
+            .overlay
+            .packet_receipt(&port_id, &channel_id, sequence)
+            .await
+            .map_err(|_| Status::unavailable("database error"))?;
+        let proof = self
+            .prove(
+                state_key::packet_receipt(&port_id, &channel_id, sequence),
+                state.version,
+            )
+            .await?;
+
+        Ok(tonic::Response::new(QueryPacketReceiptResponse {
+            received,
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn packet_acknowledgement(
+        &self,
+        request: tonic::Request<QueryPacketAcknowledgementRequest>,
+    ) -> Result<tonic::Response<QueryPacketAcknowledgementResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let sequence = Sequence::from(request.sequence);
+        let state = self.ibc_state().await?;
+
+        let acknowledgement = state
+            .overlay
+            .packet_acknowledgement(&port_id, &channel_id, sequence)
+            .await
+            .map_err(|_| Status::unavailable("database error"))?
+            .ok_or_else(|| Status::not_found("packet acknowledgement not found"))?;
+        let proof = self
+            .prove(
+                state_key::packet_acknowledgement(&port_id, &channel_id, sequence),
+                state.version,
+            )
+            .await?;
+
+        Ok(tonic::Response::new(QueryPacketAcknowledgementResponse {
+            acknowledgement,
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn packet_acknowledgements(
+        &self,
+        request: tonic::Request<QueryPacketAcknowledgementsRequest>,
+    ) -> Result<tonic::Response<QueryPacketAcknowledgementsResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        // unordered channels don't track the highest sequence they've received, so
+        // without a list of sequences we can only enumerate acknowledgements on
+        // ordered channels.
+        let sequences = if request.packet_commitment_sequences.is_empty() {
+            let next_sequence_recv = state
+                .overlay
+                .next_sequence_recv(&port_id, &channel_id)
+                .await
+                .map_err(|e| Status::not_found(e.to_string()))?;
+            (1..next_sequence_recv).collect()
+        } else {
+            request.packet_commitment_sequences
+        };
+
+        let mut acknowledgements = Vec::new();
+        for sequence in sequences {
+            if let Some(acknowledgement) = state
+                .overlay
+                .packet_acknowledgement(&port_id, &channel_id, Sequence::from(sequence))
+                .await
+                .map_err(|_| Status::unavailable("database error"))?
+            {
+                acknowledgements.push(packet_state(
+                    &port_id,
+                    &channel_id,
+                    sequence,
+                    acknowledgement,
+                ));
+            }
+        }
+
+        let (acknowledgements, pagination) = paginate(acknowledgements, request.pagination)?;
+
+        Ok(tonic::Response::new(QueryPacketAcknowledgementsResponse {
+            acknowledgements,
+            pagination,
+            height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn unreceived_packets(
+        &self,
+        request: tonic::Request<QueryUnreceivedPacketsRequest>,
+    ) -> Result<tonic::Response<QueryUnreceivedPacketsResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        let channel = state
+            .overlay
+            .get_channel(&port_id, &channel_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let next_sequence_recv = state
+            .overlay
+            .next_sequence_recv(&port_id, &channel_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+
+        let mut sequences = Vec::new();
+        for sequence in request.packet_commitment_sequences {
+            let received = if channel.ordering() == &Order::Ordered {
+                sequence < next_sequence_recv
+            } else {
+                state
+                    .overlay
+                    .packet_receipt(&port_id, &channel_id, Sequence::from(sequence))
+                    .await
+                    .map_err(|_| Status::unavailable("database error"))?
+            };
+            if !received {
+                sequences.push(sequence);
+            }
+        }
+
+        Ok(tonic::Response::new(QueryUnreceivedPacketsResponse {
+            sequences,
+            height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn unreceived_acks(
+        &self,
+        request: tonic::Request<QueryUnreceivedAcksRequest>,
+    ) -> Result<tonic::Response<QueryUnreceivedAcksResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        // a packet's commitment is deleted once its acknowledgement is received.
+        let mut sequences = Vec::new();
+        for sequence in request.packet_ack_sequences {
+            if state
+                .overlay
+                .packet_commitment(&port_id, &channel_id, Sequence::from(sequence))
+                .await
+                .map_err(|_| Status::unavailable("database error"))?
+                .is_some()
+            {
+                sequences.push(sequence);
+            }
+        }
+
+        Ok(tonic::Response::new(QueryUnreceivedAcksResponse {
+            sequences,
+            height: Some(state.height),
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn next_sequence_receive(
+        &self,
+        request: tonic::Request<QueryNextSequenceReceiveRequest>,
+    ) -> Result<tonic::Response<QueryNextSequenceReceiveResponse>, Status> {
+        let request = request.into_inner();
+        let (port_id, channel_id) = parse_channel(&request.port_id, &request.channel_id)?;
+        let state = self.ibc_state().await?;
+
+        let next_sequence_receive = state
+            .overlay
+            .next_sequence_recv(&port_id, &channel_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let proof = self
+            .prove(
+                state_key::next_sequence_recv(&port_id, &channel_id),
+                state.version,
+            )
+            .await?;
+
+        Ok(tonic::Response::new(QueryNextSequenceReceiveResponse {
+            next_sequence_receive,
+            proof,
+            proof_height: Some(state.height),
+        }))
+    }
+}
+
+impl Info {
+    async fn identified_client_state(
+        &self,
+        state: &IbcState,
+        client_id: &ClientId,
+    ) -> Result<(IdentifiedClientState, Vec<u8>), Status> {
+        let client_data = state
+            .overlay
+            .get_client_data(client_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let proof = self
+            .prove(state_key::client_state(client_id), state.version)
+            .await?;
+
+        Ok((
+            IdentifiedClientState {
+                client_id: client_id.to_string(),
+                client_state: Some(client_data.client_state.0.into()),
+            },
+            proof,
+        ))
+    }
+
+    async fn consensus_state_with_proof(
+        &self,
+        state: &IbcState,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<(prost_types::Any, Vec<u8>), Status> {
+        let consensus_state = state
+            .overlay
+            .get_verified_consensus_state(height, client_id.clone())
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let proof = self
+            .prove(
+                state_key::verified_consensus_state(client_id, &height),
+                state.version,
+            )
+            .await?;
+
+        Ok((consensus_state.0.into(), proof))
+    }
+
+    // the client underlying the connection a channel is built on.
+    async fn channel_client_id(
+        &self,
+        state: &IbcState,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+    ) -> Result<ClientId, Status> {
+        let channel = state
+            .overlay
+            .get_channel(port_id, channel_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        let connection_id = channel
+            .connection_hops()
+            .first()
+            .ok_or_else(|| Status::internal("channel has no connection hops"))?;
+        let connection = state
+            .overlay
+            .get_connection(connection_id)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+
+        Ok(connection.client_id().clone())
+    }
+}
+
+// the identifiers of every client, all of which are Tendermint clients.
+async fn client_ids(overlay: &Overlay) -> Result<Vec<ClientId>, Status> {
+    let counter = overlay
+        .client_counter()
+        .await
+        .map_err(|_| Status::unavailable("database error"))?;
+
+    (0..counter.0)
+        .map(|n| {
+            ClientId::new(ClientType::Tendermint, n).map_err(|e| Status::internal(e.to_string()))
+        })
+        .collect()
+}
+
+// every channel, all of which are bound to the transfer port.
+async fn channels(overlay: &Overlay) -> Result<Vec<IdentifiedChannelEnd>, Status> {
+    let port_id = transfer_port();
+    let counter = overlay
+        .channel_counter()
+        .await
+        .map_err(|_| Status::unavailable("database error"))?;
+
+    let mut channels = Vec::new();
+    for channel_id in (0..counter.0).map(ChannelId::new) {
+        let channel = overlay
+            .get_channel(&port_id, &channel_id)
+            .await
+            .map_err(|_| Status::unavailable("database error"))?;
+        channels.push(IdentifiedChannelEnd::new(
+            port_id.clone(),
+            channel_id,
+            channel,
+        ));
+    }
+
+    Ok(channels)
+}
+
+// the status of a client, as reported by the Cosmos SDK: frozen clients can't be used at all,
+// and expired clients can't be updated.
+async fn client_status(
+    overlay: &Overlay,
+    client_data: &ClientData,
+) -> anyhow::Result<&'static str> {
+    let client_state = &client_data.client_state.0;
+    if client_state.is_frozen() {
+        return Ok("Frozen");
+    }
+
+    let latest_consensus_state = overlay
+        .get_verified_consensus_state(client_state.latest_height(), client_data.client_id.clone())
+        .await?
+        .as_tendermint()?;
+    let now = overlay.get_block_timestamp().await?;
+    let stamp = latest_consensus_state.timestamp.to_rfc3339();
+    let elapsed = now.duration_since(Time::parse_from_rfc3339(&stamp)?)?;
+    if client_state.expired(elapsed) {
+        return Ok("Expired");
+    }
+
+    Ok("Active")
+}
+
+// the page of `items` selected by the pagination in a request, and the response describing it.
+fn paginate<T>(
+    mut items: Vec<T>,
+    request: Option<PageRequest>,
+) -> Result<(Vec<T>, Option<PageResponse>), Status> {
+    let request = request.unwrap_or_default();
+    let total = items.len() as u64;
+    let offset = if request.key.is_empty() {
+        request.offset
+    } else {
+        let key: [u8; 8] = request
+            .key
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid pagination key"))?;
+        u64::from_be_bytes(key)
+    };
+    let limit = match request.limit {
+        0 => DEFAULT_PAGE_LIMIT,
+        limit => limit,
+    };
+
+    if request.reverse {
+        items.reverse();
+    }
+    let page: Vec<T> = items
+        .into_iter()
+        .skip(offset.try_into().unwrap_or(usize::MAX))
+        .take(limit.try_into().unwrap_or(usize::MAX))
+        .collect();
+    let next_offset = offset.saturating_add(page.len() as u64);
+    let next_key = if next_offset < total {
+        next_offset.to_be_bytes().to_vec()
+    } else {
+        Vec::new()
+    };
+
+    Ok((
+        page,
+        Some(PageResponse {
+            next_key,
+            total: if request.count_total { total } else { 0 },
+        }),
+    ))
+}
+
+fn packet_state(
+    port_id: &PortId,
+    channel_id: &ChannelId,
+    sequence: u64,
+    data: Vec<u8>,
+) -> PacketState {
+    PacketState {
+        port_id: port_id.to_string(),
+        channel_id: channel_id.to_string(),
+        sequence,
+        data,
+    }
+}
+
+fn parse_client_id(client_id: &str) -> Result<ClientId, Status> {
+    ClientId::from_str(client_id)
+        .map_err(|e| Status::invalid_argument(format!("invalid client ID: {}", e)))
+}
+
+fn parse_connection_id(connection_id: &str) -> Result<ConnectionId, Status> {
+    ConnectionId::from_str(connection_id)
+        .map_err(|e| Status::invalid_argument(format!("invalid connection ID: {}", e)))
+}
+
+fn parse_channel(port_id: &str, channel_id: &str) -> Result<(PortId, ChannelId), Status> {
+    let port_id = PortId::from_str(port_id)
+        .map_err(|e| Status::invalid_argument(format!("invalid port ID: {}", e)))?;
+    let channel_id = ChannelId::from_str(channel_id)
+        .map_err(|e| Status::invalid_argument(format!("invalid channel ID: {}", e)))?;
+
+    Ok((port_id, channel_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paginate_by_key_and_offset() {
+        let items: Vec<u64> = (0..5).collect();
+
+        let (page, response) = paginate(items.clone(), None).unwrap();
+        assert_eq!(page, items);
+        assert!(response.unwrap().next_key.is_empty());
+
+        let (page, response) = paginate(
+            items.clone(),
+            Some(PageRequest {
+                limit: 2,
+                count_total: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let response = response.unwrap();
+        assert_eq!(page, vec![0, 1]);
+        assert_eq!(response.total, 5);
+
+        // the next key picks up where the previous page left off.
+        let (page, response) = paginate(
+            items.clone(),
+            Some(PageRequest {
+                key: response.next_key,
+                limit: 2,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(page, vec![2, 3]);
+
+        let (page, response) = paginate(
+            items.clone(),
+            Some(PageRequest {
+                key: response.unwrap().next_key,
+                limit: 2,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(page, vec![4]);
+        assert!(response.unwrap().next_key.is_empty());
+
+        let (page, _) = paginate(
+            items.clone(),
+            Some(PageRequest {
+                offset: 1,
+                limit: 2,
+                reverse: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(page, vec![3, 2]);
+
+        assert!(paginate(
+            items,
+            Some(PageRequest {
+                key: vec![1, 2, 3],
+                ..Default::default()
+            }),
+        )
+        .is_err());
+    }
+}
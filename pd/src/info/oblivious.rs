@@ -22,23 +22,26 @@ use tracing::instrument;
 // (stable) std types.
 // use tracing_futures::Instrument;
 
+use super::Info;
 use crate::components::{app::View as _, shielded_pool::View as _, staking::View as _};
-use crate::Storage;
 
 #[tonic::async_trait]
-impl ObliviousQuery for Storage {
+impl ObliviousQuery for Info {
     type CompactBlockRangeStream =
         Pin<Box<dyn futures::Stream<Item = Result<CompactBlock, tonic::Status>> + Send>>;
 
     type ValidatorInfoStream =
         Pin<Box<dyn futures::Stream<Item = Result<ValidatorInfo, tonic::Status>> + Send>>;
 
-    #[instrument(skip(self, request))]
+    #[instrument(skip(self, request), fields(height = request.get_ref().height))]
     async fn chain_params(
         &self,
         request: tonic::Request<ChainParamsRequest>,
     ) -> Result<tonic::Response<ChainParams>, Status> {
-        let overlay = self.overlay_tonic().await?;
+        let overlay = self
+            .storage
+            .overlay_at_height_tonic(request.get_ref().height)
+            .await?;
         overlay.check_chain_id(&request.get_ref().chain_id).await?;
 
         let chain_params = overlay
@@ -54,7 +57,7 @@ impl ObliviousQuery for Storage {
         &self,
         request: tonic::Request<AssetListRequest>,
     ) -> Result<tonic::Response<KnownAssets>, Status> {
-        let overlay = self.overlay_tonic().await?;
+        let overlay = self.storage.overlay_tonic().await?;
         overlay.check_chain_id(&request.get_ref().chain_id).await?;
 
         let known_assets = overlay
@@ -64,12 +67,21 @@ impl ObliviousQuery for Storage {
         Ok(tonic::Response::new(known_assets.into()))
     }
 
-    #[instrument(skip(self, request), fields(show_inactive = request.get_ref().show_inactive))]
+    #[instrument(
+        skip(self, request),
+        fields(
+            show_inactive = request.get_ref().show_inactive,
+            height = request.get_ref().height,
+        ),
+    )]
     async fn validator_info(
         &self,
         request: tonic::Request<ValidatorInfoRequest>,
     ) -> Result<tonic::Response<Self::ValidatorInfoStream>, Status> {
-        let overlay = self.overlay_tonic().await?;
+        let overlay = self
+            .storage
+            .overlay_at_height_tonic(request.get_ref().height)
+            .await?;
         overlay.check_chain_id(&request.get_ref().chain_id).await?;
 
         let validators = overlay
@@ -104,18 +116,20 @@ impl ObliviousQuery for Storage {
         fields(
             start_height = request.get_ref().start_height,
             end_height = request.get_ref().end_height,
+            keep_alive = request.get_ref().keep_alive,
         ),
     )]
     async fn compact_block_range(
         &self,
         request: tonic::Request<CompactBlockRangeRequest>,
     ) -> Result<tonic::Response<Self::CompactBlockRangeStream>, Status> {
-        let overlay = self.overlay_tonic().await?;
+        let overlay = self.storage.overlay_tonic().await?;
         overlay.check_chain_id(&request.get_ref().chain_id).await?;
 
         let CompactBlockRangeRequest {
             start_height,
             end_height,
+            keep_alive,
             ..
         } = request.into_inner();
 
@@ -124,6 +138,10 @@ impl ObliviousQuery for Storage {
             .await
             .map_err(|_| tonic::Status::unavailable("database error"))?;
 
+        // Only keep the stream alive if the client asked to follow the chain,
+        // rather than for a fixed range.
+        let keep_alive = keep_alive && end_height == 0;
+
         // Treat end_height = 0 as end_height = current_height so that if the
         // end_height is unspecified in the proto, it will be treated as a
         // request to sync up to the current height.
@@ -133,6 +151,8 @@ impl ObliviousQuery for Storage {
             std::cmp::min(end_height, current_height)
         };
 
+        let storage = self.storage.clone();
+        let mut height_rx = self.height_rx.clone();
         let block_range = try_stream! {
             // It's useful to record the end height since we adjusted it,
             // but the start height is already recorded in the span.
@@ -147,6 +167,29 @@ impl ObliviousQuery for Storage {
                     .expect("compact block for in-range height must be present");
                 yield block.to_proto();
             }
+
+            if keep_alive {
+                // Having caught up, keep sending new blocks as consensus commits them.
+                let mut next_height = std::cmp::max(start_height, end_height);
+                loop {
+                    // Use a fresh overlay, since the one above is pinned to the
+                    // version that was current when the request was received.
+                    let overlay = storage.overlay().await?;
+                    let latest_height = overlay.get_block_height().await?;
+                    while next_height <= latest_height {
+                        let block = overlay.compact_block(next_height)
+                            .await?
+                            .expect("compact block for committed height must be present");
+                        yield block.to_proto();
+                        next_height += 1;
+                    }
+
+                    if height_rx.changed().await.is_err() {
+                        tracing::info!("consensus worker shut down, ending compact block stream");
+                        break;
+                    }
+                }
+            }
         };
 
         Ok(tonic::Response::new(
@@ -14,17 +14,17 @@ use tracing::instrument;
 // (stable) std types.
 //use tracing_futures::Instrument;
 
+use super::Info;
 use crate::components::{app::View as _, shielded_pool::View as _, staking::View as _};
-use crate::Storage;
 
 #[tonic::async_trait]
-impl SpecificQuery for Storage {
+impl SpecificQuery for Info {
     #[instrument(skip(self, request))]
     async fn transaction_by_note(
         &self,
         request: tonic::Request<NoteCommitment>,
     ) -> Result<tonic::Response<NoteSource>, Status> {
-        let overlay = self.overlay_tonic().await?;
+        let overlay = self.storage.overlay_tonic().await?;
         let cm = request
             .into_inner()
             .try_into()
@@ -39,12 +39,15 @@ impl SpecificQuery for Storage {
         Ok(tonic::Response::new(source.into()))
     }
 
-    #[instrument(skip(self, request))]
+    #[instrument(skip(self, request), fields(height = request.get_ref().height))]
     async fn validator_status(
         &self,
         request: tonic::Request<ValidatorStatusRequest>,
     ) -> Result<tonic::Response<proto::stake::ValidatorStatus>, Status> {
-        let overlay = self.overlay_tonic().await?;
+        let overlay = self
+            .storage
+            .overlay_at_height_tonic(request.get_ref().height)
+            .await?;
         overlay.check_chain_id(&request.get_ref().chain_id).await?;
 
         let id = request
@@ -68,7 +71,7 @@ impl SpecificQuery for Storage {
         &self,
         request: tonic::Request<proto::stake::IdentityKey>,
     ) -> Result<tonic::Response<proto::stake::RateData>, Status> {
-        let overlay = self.overlay_tonic().await?;
+        let overlay = self.storage.overlay_tonic().await?;
         let identity_key = request
             .into_inner()
             .try_into()
@@ -8,6 +8,7 @@ mod consensus;
 mod info;
 mod mempool;
 mod pd_metrics;
+mod pruning;
 mod request_ext;
 mod snapshot;
 mod storage;
@@ -23,5 +24,6 @@ pub use consensus::Consensus;
 pub use info::Info;
 pub use mempool::Mempool;
 pub use pd_metrics::register_all_metrics;
-pub use snapshot::Snapshot;
+pub use pruning::Config as PruningConfig;
+pub use snapshot::{Config as SnapshotConfig, Snapshot};
 pub use storage::{Overlay, OverlayExt, Storage};
@@ -5,18 +5,24 @@ use std::{
 };
 
 use anyhow::Context;
+use ibc_proto::ibc::core::{
+    channel::v1::query_server::QueryServer as ChannelQueryServer,
+    client::v1::query_server::QueryServer as ClientQueryServer,
+    connection::v1::query_server::QueryServer as ConnectionQueryServer,
+};
 use metrics_exporter_prometheus::PrometheusBuilder;
 use pd::genesis::Allocation;
 use penumbra_chain::params::ChainParams;
 use penumbra_crypto::{
     keys::{SpendKey, SpendSeed},
+    proofs::{groth16, ProofSystem},
     rdsa::{SigningKey, SpendAuth, VerificationKey},
 };
 use penumbra_proto::client::{
     oblivious::oblivious_query_server::ObliviousQueryServer,
     specific::specific_query_server::SpecificQueryServer,
 };
-use penumbra_stake::{validator::Validator, FundingStream, FundingStreams};
+use penumbra_stake::{validator::Validator, FundingStream, FundingStreams, Recipient};
 use rand_core::OsRng;
 use structopt::StructOpt;
 use tonic::transport::Server;
@@ -52,9 +58,30 @@ enum Command {
         /// Bind the specific query service to this port.
         #[structopt(short, long, default_value = "26667")]
         specific_query_port: u16,
+        /// Bind the IBC query service, used by relayers, to this port.
+        #[structopt(short, long, default_value = "26668")]
+        ibc_query_port: u16,
         /// Bind the metrics endpoint to this port.
         #[structopt(short, long, default_value = "9000")]
         metrics_port: u16,
+        /// The path used to store state snapshots [default: `snapshots` next to the Rocks database].
+        #[structopt(long)]
+        snapshot_path: Option<PathBuf>,
+        /// Create a state snapshot every this many blocks (0 disables snapshots).
+        #[structopt(long, default_value = "1000")]
+        snapshot_interval: u64,
+        /// The number of recent snapshots to keep.
+        #[structopt(long, default_value = "2")]
+        snapshot_keep_recent: usize,
+        /// Keep only this many recent versions of the state and blocks [default: keep everything, as an archive node].
+        #[structopt(long)]
+        pruning_keep_recent: Option<u64>,
+        /// Delete stale state from pruned versions every this many blocks.
+        #[structopt(long, default_value = "100")]
+        pruning_interval: u64,
+        /// Load the Groth16 spend, output, swap, swap claim and delegator vote parameters produced by a setup ceremony from this directory [default: none, and Groth16 proofs are rejected].
+        #[structopt(long)]
+        groth16_parameters: Option<PathBuf>,
     },
 
     /// Generates a directory structure containing necessary files to run a
@@ -69,6 +96,12 @@ enum Command {
         /// Maximum number of validators in the consensus set.
         #[structopt(long, default_value = "10")]
         active_validator_limit: u64,
+        /// Minimum fee for transactions, in units of the staking token.
+        #[structopt(long, default_value = "0")]
+        min_fee: u64,
+        /// Proof system for shielded proofs, `transparent` or `groth16`.
+        #[structopt(long, default_value = "transparent")]
+        proof_system: ProofSystem,
         /// Whether to preserve the chain ID (useful for public testnets) or append a random suffix (useful for dev/testing).
         #[structopt(long)]
         preserve_chain_id: bool,
@@ -114,32 +147,67 @@ async fn main() -> anyhow::Result<()> {
             abci_port,
             oblivious_query_port,
             specific_query_port,
+            ibc_query_port,
             metrics_port,
             rocks_path,
+            snapshot_path,
+            snapshot_interval,
+            snapshot_keep_recent,
+            pruning_keep_recent,
+            pruning_interval,
+            groth16_parameters,
         } => {
             tracing::info!(
                 ?host,
                 ?abci_port,
                 ?oblivious_query_port,
                 ?specific_query_port,
+                ?ibc_query_port,
                 "starting pd"
             );
 
+            if let Some(dir) = groth16_parameters {
+                groth16::load_parameters(&dir).context("Unable to load Groth16 parameters")?;
+                penumbra_dex::proofs::groth16::load_parameters(&dir)
+                    .context("Unable to load Groth16 swap parameters")?;
+                penumbra_governance::proofs::groth16::load_parameters(&dir)
+                    .context("Unable to load Groth16 delegator vote parameters")?;
+            } else {
+                tracing::warn!("no Groth16 parameters loaded, Groth16 proofs will be rejected");
+            }
+
+            let snapshot_config = pd::SnapshotConfig {
+                path: snapshot_path.unwrap_or_else(|| rocks_path.with_file_name("snapshots")),
+                interval: snapshot_interval,
+                keep_recent: snapshot_keep_recent,
+            };
+
+            if pruning_keep_recent == Some(0) {
+                return Err(anyhow::anyhow!("must keep at least one recent version"));
+            }
+            let pruning_config = pd::PruningConfig {
+                keep_recent: pruning_keep_recent,
+                interval: pruning_interval,
+                snapshot_interval,
+                snapshot_keep_recent,
+            };
+
             let storage = pd::Storage::load(rocks_path)
                 .await
                 .context("Unable to initialize RocksDB storage")?;
 
-            let (consensus, height_rx) = pd::Consensus::new(storage.clone()).await?;
-            let mempool = pd::Mempool::new(storage.clone(), height_rx).await?;
-            let info = pd::Info::new(storage.clone());
-            let snapshot = pd::Snapshot {};
+            let (consensus, height_rx) =
+                pd::Consensus::new(storage.clone(), pruning_config).await?;
+            let mempool = pd::Mempool::new(storage.clone(), height_rx.clone()).await?;
+            let info = pd::Info::new(storage.clone(), height_rx.clone());
+            let snapshot = pd::Snapshot::new(storage.clone(), snapshot_config, height_rx).await?;
 
             let abci_server = tokio::task::Builder::new().name("abci_server").spawn(
                 tower_abci::Server::builder()
                     .consensus(consensus)
                     .snapshot(snapshot)
                     .mempool(mempool)
-                    .info(info)
+                    .info(info.clone())
                     .finish()
                     .unwrap()
                     .listen(format!("{}:{}", host, abci_port)),
@@ -155,7 +223,7 @@ async fn main() -> anyhow::Result<()> {
                             }
                             None => tracing::error_span!("oblivious_query"),
                         })
-                        .add_service(ObliviousQueryServer::new(storage.clone()))
+                        .add_service(ObliviousQueryServer::new(info.clone()))
                         .serve(
                             format!("{}:{}", host, oblivious_query_port)
                                 .parse()
@@ -172,7 +240,7 @@ async fn main() -> anyhow::Result<()> {
                             }
                             None => tracing::error_span!("specific_query"),
                         })
-                        .add_service(SpecificQueryServer::new(storage.clone()))
+                        .add_service(SpecificQueryServer::new(info.clone()))
                         .serve(
                             format!("{}:{}", host, specific_query_port)
                                 .parse()
@@ -180,6 +248,24 @@ async fn main() -> anyhow::Result<()> {
                         ),
                 );
 
+            let ibc_server = tokio::task::Builder::new().name("ibc_query_server").spawn(
+                Server::builder()
+                    .trace_fn(|req| match remote_addr(req) {
+                        Some(remote_addr) => {
+                            tracing::error_span!("ibc_query", ?remote_addr)
+                        }
+                        None => tracing::error_span!("ibc_query"),
+                    })
+                    .add_service(ClientQueryServer::new(info.clone()))
+                    .add_service(ConnectionQueryServer::new(info.clone()))
+                    .add_service(ChannelQueryServer::new(info.clone()))
+                    .serve(
+                        format!("{}:{}", host, ibc_query_port)
+                            .parse()
+                            .expect("this is a valid address"),
+                    ),
+            );
+
             // This service lets Prometheus pull metrics from `pd`
             PrometheusBuilder::new()
                 .with_http_listener(
@@ -198,6 +284,7 @@ async fn main() -> anyhow::Result<()> {
                 x = abci_server => x?.map_err(|e| anyhow::anyhow!(e))?,
                 x = oblivious_server => x?.map_err(|e| anyhow::anyhow!(e))?,
                 x = specific_server => x?.map_err(|e| anyhow::anyhow!(e))?,
+                x = ibc_server => x?.map_err(|e| anyhow::anyhow!(e))?,
             };
         }
         Command::GenerateTestnet {
@@ -209,6 +296,8 @@ async fn main() -> anyhow::Result<()> {
             epoch_duration,
             unbonding_epochs,
             active_validator_limit,
+            min_fee,
+            proof_system,
             allocations_input_file,
             validators_input_file,
             output_dir,
@@ -393,11 +482,13 @@ async fn main() -> anyhow::Result<()> {
                                 .iter()
                                 .map(|fs| {
                                     Ok(FundingStream {
-                                        address: Address::from_str(&fs.address).map_err(|_| {
-                                            anyhow::anyhow!(
-                                                "invalid funding stream address in validators.json"
-                                            )
-                                        })?,
+                                        recipient: Recipient::Address(
+                                            Address::from_str(&fs.address).map_err(|_| {
+                                                anyhow::anyhow!(
+                                                    "invalid funding stream address in validators.json"
+                                                )
+                                            })?,
+                                        ),
                                         rate_bps: fs.rate_bps,
                                     })
                                 })
@@ -422,6 +513,8 @@ async fn main() -> anyhow::Result<()> {
                         epoch_duration,
                         unbonding_epochs,
                         active_validator_limit,
+                        min_fee,
+                        proof_system,
                         ..Default::default()
                     },
                     validators: validators.clone(),
@@ -1,11 +1,13 @@
 use anyhow::Result;
-use bytes::Bytes;
+use penumbra_transaction::Transaction;
 use tokio::sync::oneshot;
 use tracing::Span;
 
 #[derive(Debug)]
 pub struct Message {
-    pub tx_bytes: Bytes,
-    pub rsp_sender: oneshot::Sender<Result<()>>,
+    /// A transaction that has passed the stateless checks.
+    pub tx: Transaction,
+    /// Returns the transaction's fee, used to prioritize it in the mempool.
+    pub rsp_sender: oneshot::Sender<Result<u64>>,
     pub span: Span,
 }
@@ -1,10 +1,14 @@
 use std::{
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
+use bytes::Bytes;
 use futures::FutureExt;
+use penumbra_proto::Protobuf;
+use penumbra_transaction::Transaction;
 use tendermint::{
     abci::{
         request::CheckTx as CheckTxReq, response::CheckTx as CheckTxRsp, MempoolRequest,
@@ -12,16 +16,44 @@ use tendermint::{
     },
     block,
 };
-use tokio::sync::{mpsc, oneshot, watch};
-use tokio_util::sync::PollSender;
+use tokio::sync::{mpsc, oneshot, watch, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::PollSemaphore;
 use tower_abci::BoxError;
+use tracing::Instrument;
 
 use super::{Message, Worker};
-use crate::{RequestExt, Storage};
+use crate::{App, Component, RequestExt, Storage};
 
-#[derive(Clone)]
+/// The maximum number of transactions that can be undergoing stateless checks
+/// or waiting to be queued for the [`Worker`] at once.
+const MAX_CONCURRENT_CHECKS: usize = 16;
+
+/// The mempool service.
+///
+/// Stateless checks are performed concurrently, on the blocking thread pool,
+/// as requests arrive.  Transactions that pass them are queued for the
+/// [`Worker`], which performs the stateful checks and executes them one at a
+/// time against the ephemeral mempool state.
+///
+/// Each request holds a permit from `checks` until its transaction has been
+/// queued, so the service stops being ready once [`MAX_CONCURRENT_CHECKS`]
+/// requests are in flight, or the worker's queue is full.
 pub struct Mempool {
-    queue: PollSender<Message>,
+    queue: mpsc::Sender<Message>,
+    checks: PollSemaphore,
+    /// The permit acquired by `poll_ready` for the next call.
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Clone for Mempool {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+            checks: self.checks.clone(),
+            // Permits are acquired separately by each clone.
+            permit: None,
+        }
+    }
 }
 
 impl Mempool {
@@ -36,49 +68,79 @@ impl Mempool {
             .spawn(Worker::new(storage, queue_rx, height_rx).await?.run());
 
         Ok(Self {
-            queue: PollSender::new(queue_tx),
+            queue: queue_tx,
+            checks: PollSemaphore::new(Arc::new(Semaphore::new(MAX_CONCURRENT_CHECKS))),
+            permit: None,
         })
     }
 }
 
+/// Decodes a transaction and performs its stateless checks.
+async fn check_tx_stateless(tx_bytes: Bytes) -> anyhow::Result<Transaction> {
+    tokio::task::spawn_blocking(move || {
+        let tx = Transaction::decode(tx_bytes.as_ref())?;
+        App::check_tx_stateless(&tx)?;
+        Ok(tx)
+    })
+    .await?
+}
+
 impl tower::Service<MempoolRequest> for Mempool {
     type Response = MempoolResponse;
     type Error = BoxError;
     type Future = Pin<Box<dyn Future<Output = Result<MempoolResponse, BoxError>> + Send + 'static>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.queue.poll_reserve(cx).map_err(Into::into)
-    }
-
-    fn call(&mut self, req: MempoolRequest) -> Self::Future {
-        // Check if the worker has terminated. We do this again in `call`
-        // because the worker may have terminated *after* `poll_ready` reserved
-        // a send permit.
         if self.queue.is_closed() {
-            return async move {
-                Err(anyhow::anyhow!("mempool worker terminated or panicked").into())
+            return Poll::Ready(Err(anyhow::anyhow!(
+                "mempool worker terminated or panicked"
+            )
+            .into()));
+        }
+        if self.permit.is_none() {
+            match self.checks.poll_acquire(cx) {
+                Poll::Ready(Some(permit)) => self.permit = Some(permit),
+                Poll::Ready(None) => unreachable!("the semaphore is never closed"),
+                Poll::Pending => return Poll::Pending,
             }
-            .boxed();
         }
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: MempoolRequest) -> Self::Future {
+        let permit = self.permit.take().expect("called without `poll_ready`");
         let span = req.create_span();
-        let (tx, rx) = oneshot::channel();
+        let queue = self.queue.clone();
 
         let MempoolRequest::CheckTx(CheckTxReq { tx: tx_bytes, .. }) = req;
 
-        self.queue
-            .send_item(Message {
-                tx_bytes,
-                rsp_sender: tx,
-                span,
-            })
-            .expect("called without `poll_ready`");
-
         async move {
-            match rx
-                .await
-                .map_err(|_| anyhow::anyhow!("mempool worker terminated or panicked"))?
-            {
-                Ok(()) => Ok(MempoolResponse::CheckTx(CheckTxRsp::default())),
+            let rsp = match check_tx_stateless(tx_bytes).instrument(span.clone()).await {
+                Ok(tx) => {
+                    let (rsp_sender, rsp_receiver) = oneshot::channel();
+                    queue
+                        .send(Message {
+                            tx,
+                            rsp_sender,
+                            span,
+                        })
+                        .await
+                        .map_err(|_| anyhow::anyhow!("mempool worker terminated or panicked"))?;
+                    drop(permit);
+                    rsp_receiver
+                        .await
+                        .map_err(|_| anyhow::anyhow!("mempool worker terminated or panicked"))?
+                }
+                Err(e) => Err(e),
+            };
+
+            match rsp {
+                // Transactions paying higher fees are prioritized by
+                // Tendermint's prioritized mempool.
+                Ok(fee) => Ok(MempoolResponse::CheckTx(CheckTxRsp {
+                    priority: fee.try_into().unwrap_or(i64::MAX),
+                    ..Default::default()
+                })),
                 Err(e) => Ok(MempoolResponse::CheckTx(CheckTxRsp {
                     code: 1,
                     log: e.to_string(),
@@ -1,7 +1,4 @@
 use anyhow::Result;
-use bytes::Bytes;
-
-use penumbra_proto::Protobuf;
 use penumbra_transaction::Transaction;
 use tendermint::block;
 use tokio::sync::{mpsc, watch};
@@ -33,17 +30,22 @@ impl Worker {
         })
     }
 
-    /// Currently, we perform all stateless and stateful checks sequentially in
-    /// the mempool worker.  A possibly more performant design would be to only
-    /// perform the stateful checks in the worker, and have a frontend service
-    /// that performs the stateless checks.  However, this probably isn't
-    /// important to do until we know that it's a bottleneck.
-    async fn check_and_execute_tx(&mut self, tx_bytes: Bytes) -> Result<()> {
-        let tx = Transaction::decode(tx_bytes.as_ref())?;
-        App::check_tx_stateless(&tx)?;
+    /// Performs the stateful checks on a transaction that has already passed
+    /// the stateless checks in the [`Mempool`](super::Mempool) frontend, and
+    /// executes it against the ephemeral mempool state.
+    ///
+    /// Since accepted transactions are executed against the ephemeral state,
+    /// a transaction spending a note already spent by a pending transaction
+    /// is rejected by the stateful checks.
+    ///
+    /// Returns the transaction's fee, which Tendermint uses to order the mempool.
+    async fn check_and_execute_tx(&mut self, tx: Transaction) -> Result<u64> {
         self.app.check_tx_stateful(&tx).await?;
         self.app.execute_tx(&tx).await;
-        Ok(())
+        // Events are only reported for transactions included in a block, so
+        // discard any recorded while simulating execution in the mempool.
+        let _ = self.app.take_events();
+        Ok(tx.transaction_body.fee.0)
     }
 
     pub async fn run(mut self) -> Result<()> {
@@ -66,13 +68,13 @@ impl Worker {
                 }
                 message = self.queue.recv() => {
                     if let Some(Message {
-                        tx_bytes,
+                        tx,
                         rsp_sender,
                         span,
                     }) = message {
                         // ... and then execute it if it was valid.
                         let _ = rsp_sender.send(
-                            self.check_and_execute_tx(tx_bytes)
+                            self.check_and_execute_tx(tx)
                                 .instrument(span)
                                 .await
                         );
@@ -85,3 +87,115 @@ impl Worker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::app::View as _, OverlayExt};
+    use penumbra_chain::{params::ChainParams, NoteSource};
+    use penumbra_crypto::{
+        keys::{SpendKey, SpendSeed},
+        merkle::{Keep, NoteCommitmentTree},
+        Note, Value,
+    };
+    use penumbra_stake::STAKING_TOKEN_ASSET_ID;
+    use rand_core::OsRng;
+    use tempfile::tempdir;
+    use tokio::sync::oneshot;
+    use tracing::Span;
+
+    const CHAIN_ID: &str = "penumbra-test";
+
+    // a transaction spending `note` to pay its whole value as the fee.
+    fn spend_tx(nct: &NoteCommitmentTree, spend_key: &SpendKey, note: &Note) -> Transaction {
+        let mut builder = Transaction::build_with_root(nct.root());
+        builder
+            .add_spend(&mut OsRng, nct, spend_key, note.clone())
+            .unwrap()
+            .set_fee(note.value().amount)
+            .set_chain_id(CHAIN_ID.to_string());
+        builder.finalize(&mut OsRng).unwrap()
+    }
+
+    async fn submit(queue: &mpsc::Sender<Message>, tx: Transaction) -> Result<u64> {
+        let (rsp_sender, rsp_receiver) = oneshot::channel();
+        queue
+            .send(Message {
+                tx,
+                rsp_sender,
+                span: Span::current(),
+            })
+            .await
+            .unwrap();
+        rsp_receiver.await.unwrap()
+    }
+
+    // test that a transaction spending a nullifier already spent by a pending transaction is
+    // rejected by the stateful checks against the ephemeral state, until the mempool is reset by
+    // a new block.
+    #[tokio::test]
+    async fn test_pending_double_spend() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("mempool-testing.db"))
+            .await
+            .unwrap();
+
+        let spend_key = SpendKey::from(SpendSeed([3u8; 32]));
+        let (address, _dtk_d) = spend_key
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+        let note = Note::generate(
+            &mut OsRng,
+            &address,
+            Value {
+                amount: 10,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+        );
+        let mut nct = NoteCommitmentTree::new();
+        nct.insert(Keep, note.commit()).unwrap();
+
+        let overlay = storage.overlay().await.unwrap();
+        overlay
+            .put_chain_params(ChainParams {
+                chain_id: CHAIN_ID.to_string(),
+                ..Default::default()
+            })
+            .await;
+        overlay.put_block_height(10).await;
+        overlay
+            .put_proto(
+                format!("shielded_pool/valid_anchors/{}", nct.root()).into(),
+                10u64,
+            )
+            .await;
+        overlay.lock().await.commit(storage.clone()).await.unwrap();
+
+        let (queue_tx, queue_rx) = mpsc::channel(10);
+        let (height_tx, height_rx) = watch::channel(block::Height::from(10u32));
+        let worker = Worker::new(storage, queue_rx, height_rx).await.unwrap();
+        tokio::spawn(worker.run());
+
+        // two different transactions spending the same note.
+        let tx = spend_tx(&nct, &spend_key, &note);
+        let double_spend = spend_tx(&nct, &spend_key, &note);
+        assert_ne!(tx.id(), double_spend.id());
+
+        assert_eq!(submit(&queue_tx, tx.clone()).await.unwrap(), 10);
+        let error = submit(&queue_tx, double_spend.clone()).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Nullifier {} was already spent in {:?}",
+                tx.spent_nullifiers()[0],
+                NoteSource::Transaction { id: tx.id() }
+            )
+        );
+
+        // a block is committed without the pending transaction, so the note can be spent again.
+        height_tx.send(block::Height::from(11u32)).unwrap();
+        assert_eq!(submit(&queue_tx, double_spend).await.unwrap(), 10);
+        assert!(submit(&queue_tx, tx).await.is_err());
+    }
+}
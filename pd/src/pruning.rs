@@ -0,0 +1,156 @@
+use anyhow::Result;
+use tendermint::block;
+use tokio::sync::watch;
+
+use crate::Storage;
+
+/// Configuration for pruning old versions of the state.
+///
+/// Pruning is local to each node, so it only discards old *versions* of the
+/// state, never keys in the latest version, which would change the app hash.
+///
+/// Some per-height data is deliberately kept in the latest version: the
+/// compact blocks, which clients sync from genesis, and the NCT anchors and
+/// block indices, which spends, swap claims and delegator votes are checked
+/// against.  Transient per-height keys (delegation changes, slashed
+/// validators, commission amounts, deposit refunds, community pool payouts
+/// and ICS-20 mints) are instead deleted by the components once they're
+/// consumed, so that they only remain in old versions, which are pruned.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Config {
+    /// Keep only the `keep_recent` most recent versions of the state (and
+    /// blocks), or keep every version, if `None`.
+    pub keep_recent: Option<u64>,
+    /// Prune stale JMT nodes every `interval` blocks.
+    pub interval: u64,
+    /// The snapshot interval, so that the versions (and blocks) that recent
+    /// snapshots were taken at are retained while they're served to peers.
+    pub snapshot_interval: u64,
+    /// The number of recent snapshots kept on disk.
+    pub snapshot_keep_recent: usize,
+}
+
+impl Config {
+    /// Returns the oldest version that must be retained once `version` has
+    /// been committed, or `None` if nothing should be pruned.
+    ///
+    /// The retained versions always include the oldest snapshot still kept on
+    /// disk, since state sync needs its block to verify the snapshot, and the
+    /// most recent one may still be being created from its version.
+    pub fn retain_version(&self, version: jmt::Version) -> Option<jmt::Version> {
+        let keep_recent = self.keep_recent?;
+        let retain_version = (version + 1).saturating_sub(keep_recent);
+        let retain_version = match self.oldest_snapshot_height(version) {
+            Some(snapshot_height) => retain_version.min(snapshot_height),
+            None => retain_version,
+        };
+        match retain_version {
+            0 => None,
+            retain_version => Some(retain_version),
+        }
+    }
+
+    // the height of the oldest snapshot that may still be kept once `version`
+    // has been committed, or `None` if no snapshot has been taken.
+    fn oldest_snapshot_height(&self, version: jmt::Version) -> Option<jmt::Version> {
+        if self.snapshot_interval == 0 {
+            return None;
+        }
+        let latest = match version - version % self.snapshot_interval {
+            0 => return None,
+            latest => latest,
+        };
+        let keep_recent = self.snapshot_keep_recent.max(1) as u64;
+        let oldest =
+            latest.saturating_sub((keep_recent - 1).saturating_mul(self.snapshot_interval));
+        // no snapshot is taken at genesis.
+        Some(oldest.max(self.snapshot_interval))
+    }
+}
+
+/// Spawns a background worker that watches for newly committed blocks, and
+/// periodically deletes the JMT nodes that are only needed to read versions
+/// of the state older than the retention window.
+///
+/// Does nothing if the node is configured as an archive node.
+pub fn spawn(storage: Storage, config: Config, height_rx: watch::Receiver<block::Height>) {
+    if config.keep_recent.is_some() && config.interval > 0 {
+        tokio::task::Builder::new()
+            .name("pruning::Worker")
+            .spawn(run(storage, config, height_rx));
+    }
+}
+
+async fn run(
+    storage: Storage,
+    config: Config,
+    mut height_rx: watch::Receiver<block::Height>,
+) -> Result<()> {
+    let mut pruned_version = 0;
+    while height_rx.changed().await.is_ok() {
+        let height = height_rx.borrow().value();
+        if height % config.interval != 0 {
+            continue;
+        }
+        let retain_version = match config.retain_version(height) {
+            Some(retain_version) if retain_version > pruned_version => retain_version,
+            _ => continue,
+        };
+
+        let storage = storage.clone();
+        let result = tokio::task::Builder::new()
+            .name("pruning::prune")
+            .spawn_blocking(move || storage.prune(retain_version))
+            .await?;
+
+        match result {
+            Ok(deleted) => {
+                tracing::info!(retain_version, deleted, "pruned stale JMT nodes");
+                pruned_version = retain_version;
+            }
+            Err(e) => tracing::error!(?e, retain_version, "failed to prune stale JMT nodes"),
+        }
+    }
+
+    tracing::info!("consensus worker shut down, shutting down pruning worker");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retain_version_keeps_snapshots() {
+        let config = Config {
+            keep_recent: Some(10),
+            interval: 100,
+            snapshot_interval: 0,
+            snapshot_keep_recent: 2,
+        };
+        assert_eq!(config.retain_version(5), None);
+        assert_eq!(config.retain_version(9), None);
+        assert_eq!(config.retain_version(10), Some(1));
+        assert_eq!(config.retain_version(2500), Some(2491));
+
+        // the two most recent snapshots, at 2000 and 2500, are retained.
+        let config = Config {
+            snapshot_interval: 500,
+            ..config
+        };
+        assert_eq!(config.retain_version(2500), Some(2000));
+        assert_eq!(config.retain_version(2999), Some(2000));
+        assert_eq!(config.retain_version(3000), Some(2500));
+        // no snapshot older than 10 versions has been taken yet.
+        assert_eq!(config.retain_version(10), Some(1));
+        assert_eq!(config.retain_version(700), Some(500));
+
+        // the snapshot being created is retained even if none are kept.
+        let config = Config {
+            keep_recent: Some(1),
+            snapshot_keep_recent: 0,
+            ..config
+        };
+        assert_eq!(config.retain_version(2600), Some(2500));
+    }
+}
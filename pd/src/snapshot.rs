@@ -1,15 +1,300 @@
 use std::{
     future::Future,
+    path::PathBuf,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
+use anyhow::{anyhow, Result};
 use futures::FutureExt;
-use tendermint::abci::{SnapshotRequest, SnapshotResponse};
+use tendermint::{
+    abci::{
+        self,
+        response::{ApplySnapshotChunkResult, OfferSnapshot},
+        SnapshotRequest, SnapshotResponse,
+    },
+    block,
+};
+use tokio::sync::{watch, Mutex};
 use tower_abci::BoxError;
+use tracing::Instrument;
+
+use crate::{RequestExt, Storage};
+
+mod store;
+
+use store::{Manifest, Store, SNAPSHOT_FORMAT};
+
+/// Configuration for periodic state snapshots.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The directory snapshots are stored in.
+    pub path: PathBuf,
+    /// Create a snapshot every `interval` blocks, or never, if `interval` is 0.
+    pub interval: u64,
+    /// The number of recent snapshots to keep on disk.
+    pub keep_recent: usize,
+}
 
+/// The ABCI snapshot service, used by Tendermint's state sync.
+///
+/// Snapshots are created in the background every [`Config::interval`] blocks,
+/// and served to peers in chunks.  A new node can be bootstrapped by restoring
+/// a snapshot offered by a peer, rather than replaying the chain from genesis.
 #[derive(Clone, Debug)]
-pub struct Snapshot {}
+pub struct Snapshot {
+    storage: Storage,
+    store: Store,
+    restore: Arc<Mutex<Option<Restore>>>,
+}
+
+/// The state of an in-progress snapshot restoration.
+#[derive(Debug)]
+struct Restore {
+    height: jmt::Version,
+    app_hash: Vec<u8>,
+    chunk_hashes: Vec<[u8; 32]>,
+    next_chunk: u32,
+}
+
+impl Snapshot {
+    pub async fn new(
+        storage: Storage,
+        config: Config,
+        height_rx: watch::Receiver<block::Height>,
+    ) -> Result<Self> {
+        let store = Store::new(config.path.clone())?;
+
+        if config.interval > 0 {
+            tokio::task::Builder::new()
+                .name("snapshot::Worker")
+                .spawn(Self::run(storage.clone(), store.clone(), config, height_rx));
+        }
+
+        Ok(Self {
+            storage,
+            store,
+            restore: Default::default(),
+        })
+    }
+
+    /// Watches for newly committed blocks, creating a snapshot whenever the
+    /// height is a multiple of the snapshot interval.
+    async fn run(
+        storage: Storage,
+        store: Store,
+        config: Config,
+        mut height_rx: watch::Receiver<block::Height>,
+    ) -> Result<()> {
+        while height_rx.changed().await.is_ok() {
+            let height = height_rx.borrow().value();
+            if height == 0 || height % config.interval != 0 {
+                continue;
+            }
+
+            let storage = storage.clone();
+            let store = store.clone();
+            let keep_recent = config.keep_recent;
+            let result = tokio::task::Builder::new()
+                .name("snapshot::create")
+                .spawn_blocking(move || {
+                    store.create(&storage, height)?;
+                    store.prune(keep_recent)
+                })
+                .await?;
+
+            if let Err(e) = result {
+                tracing::error!(?e, height, "failed to create snapshot");
+            }
+        }
+
+        tracing::info!("consensus worker shut down, shutting down snapshot worker");
+        Ok(())
+    }
+
+    fn list_snapshots(&self) -> Result<abci::response::ListSnapshots> {
+        let snapshots = self
+            .store
+            .list()?
+            .into_iter()
+            .filter_map(|manifest| match manifest.height.try_into() {
+                Ok(height) => Some(abci::types::Snapshot {
+                    height,
+                    format: manifest.format,
+                    chunks: manifest.chunk_hashes.len() as u32,
+                    hash: manifest.hash().to_vec().into(),
+                    metadata: manifest.metadata().into(),
+                }),
+                Err(e) => {
+                    tracing::warn!(
+                        ?e,
+                        height = manifest.height,
+                        "skipping snapshot with invalid height"
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        Ok(abci::response::ListSnapshots { snapshots })
+    }
+
+    fn load_snapshot_chunk(
+        &self,
+        req: abci::request::LoadSnapshotChunk,
+    ) -> Result<abci::response::LoadSnapshotChunk> {
+        let chunk = self
+            .store
+            .load_chunk(req.height.value(), req.format, req.chunk)?;
+
+        Ok(abci::response::LoadSnapshotChunk {
+            chunk: chunk.into(),
+        })
+    }
+
+    async fn offer_snapshot(&self, req: abci::request::OfferSnapshot) -> Result<OfferSnapshot> {
+        let snapshot = req.snapshot;
+        tracing::info!(height = ?snapshot.height, format = snapshot.format, "offered snapshot");
+
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Ok(OfferSnapshot::RejectFormat);
+        }
+        // We can only restore a snapshot into an empty database.
+        if self.storage.latest_version().await?.is_some() {
+            tracing::warn!("rejecting snapshot offer, since the database is not empty");
+            return Ok(OfferSnapshot::Abort);
+        }
+
+        let chunk_hashes = match Manifest::chunk_hashes_from_metadata(&snapshot.metadata) {
+            Ok(chunk_hashes) => chunk_hashes,
+            Err(_) => return Ok(OfferSnapshot::Reject),
+        };
+        let manifest = Manifest {
+            height: snapshot.height.value(),
+            format: snapshot.format,
+            chunk_hashes,
+        };
+        if manifest.chunk_hashes.len() != snapshot.chunks as usize
+            || manifest.hash().as_ref() != snapshot.hash.as_ref()
+        {
+            return Ok(OfferSnapshot::Reject);
+        }
+
+        *self.restore.lock().await = Some(Restore {
+            height: manifest.height,
+            app_hash: req.app_hash.as_ref().to_vec(),
+            chunk_hashes: manifest.chunk_hashes,
+            next_chunk: 0,
+        });
+
+        Ok(OfferSnapshot::Accept)
+    }
+
+    async fn apply_snapshot_chunk(
+        &self,
+        req: abci::request::ApplySnapshotChunk,
+    ) -> Result<abci::response::ApplySnapshotChunk> {
+        use ApplySnapshotChunkResult as R;
+        let response = |result| abci::response::ApplySnapshotChunk {
+            result,
+            refetch_chunks: Vec::new(),
+            reject_senders: Vec::new(),
+        };
+
+        let mut guard = self.restore.lock().await;
+        let restore = guard
+            .as_mut()
+            .ok_or_else(|| anyhow!("received snapshot chunk without an accepted snapshot"))?;
+
+        // Chunks are applied in order, so anything else must be a stale retry.
+        if req.index != restore.next_chunk {
+            return Ok(response(R::Retry));
+        }
+        if store::chunk_hash(&req.chunk) != restore.chunk_hashes[req.index as usize] {
+            tracing::warn!(index = req.index, sender = ?req.sender, "snapshot chunk hash mismatch");
+            return Ok(abci::response::ApplySnapshotChunk {
+                result: R::Retry,
+                refetch_chunks: vec![req.index],
+                reject_senders: vec![req.sender],
+            });
+        }
+
+        // The chunk matches the snapshot's metadata, so if it's malformed then
+        // so is the whole snapshot, and the peer that sent it can't be trusted.
+        let entries = match store::decode_chunk(&req.chunk) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!(
+                    ?e,
+                    index = req.index,
+                    sender = ?req.sender,
+                    "malformed snapshot chunk, rejecting snapshot"
+                );
+                guard.take();
+                let storage = self.storage.clone();
+                tokio::task::spawn_blocking(move || storage.clear()).await??;
+                return Ok(abci::response::ApplySnapshotChunk {
+                    result: R::RejectSnapshot,
+                    refetch_chunks: Vec::new(),
+                    reject_senders: vec![req.sender],
+                });
+            }
+        };
+        let storage = self.storage.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || storage.import_nodes(entries)).await? {
+            tracing::warn!(
+                ?e,
+                index = req.index,
+                "could not import snapshot chunk, retrying"
+            );
+            return Ok(abci::response::ApplySnapshotChunk {
+                result: R::Retry,
+                refetch_chunks: vec![req.index],
+                reject_senders: Vec::new(),
+            });
+        }
+        restore.next_chunk += 1;
+        tracing::debug!(index = req.index, "applied snapshot chunk");
+
+        if (restore.next_chunk as usize) < restore.chunk_hashes.len() {
+            return Ok(response(R::Accept));
+        }
+
+        // All chunks have been applied, so check that we reconstructed the
+        // expected state before handing control back to Tendermint.
+        let restore = guard.take().expect("restore is in progress");
+        let root_hash = match jmt::JellyfishMerkleTree::new(&self.storage)
+            .get_root_hash_option(restore.height)
+            .await
+        {
+            Ok(root_hash) => root_hash.map(|rh| rh.0.to_vec()),
+            Err(e) => {
+                tracing::warn!(
+                    ?e,
+                    height = restore.height,
+                    "could not read restored root hash"
+                );
+                None
+            }
+        };
+
+        if root_hash.as_ref() == Some(&restore.app_hash) {
+            tracing::info!(height = restore.height, "finished restoring snapshot");
+            Ok(response(R::Accept))
+        } else {
+            tracing::warn!(
+                height = restore.height,
+                ?root_hash,
+                app_hash = ?hex::encode(&restore.app_hash),
+                "restored snapshot does not match app hash, discarding"
+            );
+            let storage = self.storage.clone();
+            tokio::task::spawn_blocking(move || storage.clear()).await??;
+            Ok(response(R::RejectSnapshot))
+        }
+    }
+}
 
 impl tower::Service<SnapshotRequest> for Snapshot {
     type Response = SnapshotResponse;
@@ -22,17 +307,161 @@ impl tower::Service<SnapshotRequest> for Snapshot {
     }
 
     fn call(&mut self, req: SnapshotRequest) -> Self::Future {
-        // No-op, we don't implement snapshot support
         use SnapshotRequest as Request;
         use SnapshotResponse as Response;
+
+        let span = req.create_span();
+        let self2 = self.clone();
+
         async move {
             Ok(match req {
-                Request::ListSnapshots => Response::ListSnapshots(Default::default()),
-                Request::OfferSnapshot(_) => Response::OfferSnapshot(Default::default()),
-                Request::LoadSnapshotChunk(_) => Response::LoadSnapshotChunk(Default::default()),
-                Request::ApplySnapshotChunk(_) => Response::ApplySnapshotChunk(Default::default()),
+                Request::ListSnapshots => Response::ListSnapshots(self2.list_snapshots()?),
+                Request::OfferSnapshot(offer) => {
+                    Response::OfferSnapshot(self2.offer_snapshot(offer).await?)
+                }
+                Request::LoadSnapshotChunk(load) => {
+                    Response::LoadSnapshotChunk(self2.load_snapshot_chunk(load)?)
+                }
+                Request::ApplySnapshotChunk(apply) => {
+                    Response::ApplySnapshotChunk(self2.apply_snapshot_chunk(apply).await?)
+                }
             })
         }
+        .instrument(span)
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::app::View as _;
+    use tempfile::tempdir;
+
+    // test that a snapshot restored into an empty database has the same app hash.
+    #[tokio::test]
+    async fn test_snapshot_roundtrip() {
+        let dir = tempdir().unwrap();
+        let source = Storage::load(dir.path().join("source.db")).await.unwrap();
+        let dest = Storage::load(dir.path().join("dest.db")).await.unwrap();
+
+        // commit a few versions of the tree
+        let mut root_hash = None;
+        for height in 0..3u64 {
+            let overlay = source.overlay().await.unwrap();
+            overlay.put_block_height(height).await;
+            let (hash, version) = overlay.lock().await.commit(source.clone()).await.unwrap();
+            assert_eq!(version, height);
+            root_hash = Some(hash);
+        }
+        let root_hash = root_hash.unwrap();
+
+        let store = Store::new(dir.path().join("snapshots")).unwrap();
+        let manifest = store.create(&source, 2).unwrap();
+
+        let (_height_tx, height_rx) = watch::channel(block::Height::default());
+        let config = Config {
+            path: dir.path().join("snapshots"),
+            interval: 0,
+            keep_recent: 1,
+        };
+        let snapshot = Snapshot::new(dest.clone(), config, height_rx).await.unwrap();
+
+        let offered = snapshot
+            .list_snapshots()
+            .unwrap()
+            .snapshots
+            .pop()
+            .unwrap();
+        assert_eq!(offered.height.value(), 2);
+        let offer = snapshot
+            .offer_snapshot(abci::request::OfferSnapshot {
+                snapshot: offered,
+                app_hash: root_hash.0.to_vec().into(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(offer, OfferSnapshot::Accept);
+
+        for index in 0..manifest.chunk_hashes.len() as u32 {
+            let chunk = store.load_chunk(2, SNAPSHOT_FORMAT, index).unwrap();
+            let rsp = snapshot
+                .apply_snapshot_chunk(abci::request::ApplySnapshotChunk {
+                    index,
+                    chunk: chunk.into(),
+                    sender: String::new(),
+                })
+                .await
+                .unwrap();
+            assert_eq!(rsp.result, ApplySnapshotChunkResult::Accept);
+        }
+
+        assert_eq!(
+            jmt::JellyfishMerkleTree::new(&dest)
+                .get_root_hash_option(2)
+                .await
+                .unwrap(),
+            Some(root_hash)
+        );
+        // only the tree at the snapshot height is restored, not its history.
+        assert_eq!(
+            jmt::JellyfishMerkleTree::new(&dest)
+                .get_root_hash_option(1)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    // test that a chunk which matches the snapshot's metadata, but isn't a list of JMT nodes,
+    // rejects the whole snapshot and the peer that sent it.
+    #[tokio::test]
+    async fn test_malformed_chunk_rejects_snapshot() {
+        let dir = tempdir().unwrap();
+        let dest = Storage::load(dir.path().join("dest.db")).await.unwrap();
+
+        let (_height_tx, height_rx) = watch::channel(block::Height::default());
+        let config = Config {
+            path: dir.path().join("snapshots"),
+            interval: 0,
+            keep_recent: 1,
+        };
+        let snapshot = Snapshot::new(dest.clone(), config, height_rx)
+            .await
+            .unwrap();
+
+        let chunk = b"not a chunk".to_vec();
+        let manifest = Manifest {
+            height: 2,
+            format: SNAPSHOT_FORMAT,
+            chunk_hashes: vec![store::chunk_hash(&chunk)],
+        };
+        let offer = snapshot
+            .offer_snapshot(abci::request::OfferSnapshot {
+                snapshot: abci::types::Snapshot {
+                    height: block::Height::from(2u32),
+                    format: manifest.format,
+                    chunks: 1,
+                    hash: manifest.hash().to_vec().into(),
+                    metadata: manifest.metadata().into(),
+                },
+                app_hash: vec![0; 32].into(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(offer, OfferSnapshot::Accept);
+
+        let rsp = snapshot
+            .apply_snapshot_chunk(abci::request::ApplySnapshotChunk {
+                index: 0,
+                chunk: chunk.into(),
+                sender: "peer".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(rsp.result, ApplySnapshotChunkResult::RejectSnapshot);
+        assert_eq!(rsp.reject_senders, vec!["peer".to_string()]);
+        assert!(snapshot.restore.lock().await.is_none());
+        assert!(dest.latest_version().await.unwrap().is_none());
+    }
+}
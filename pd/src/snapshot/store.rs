@@ -0,0 +1,198 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use jmt::{
+    storage::{Node, NodeKey},
+    Version,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::Storage;
+
+/// The format version of the snapshots produced by this module.
+///
+/// A snapshot is a dump of the RocksDB-backed JMT nodes making up the tree at
+/// the snapshot height, split into chunks.  Since JMT nodes are immutable once
+/// written, this dump is sufficient to reconstruct the tree (and hence the app
+/// hash) at the snapshot height.
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// The maximum size of a single snapshot chunk, in bytes.
+///
+/// Tendermint limits chunks to 16 MB, so we stay comfortably below that.
+const MAX_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// The contents of a single snapshot chunk: a list of raw RocksDB key/value pairs.
+pub type ChunkEntries = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Describes a complete snapshot stored on disk.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub height: Version,
+    pub format: u32,
+    /// The SHA256 hash of each chunk, in order.
+    pub chunk_hashes: Vec<[u8; 32]>,
+}
+
+impl Manifest {
+    /// The hash identifying the snapshot as a whole, computed over the chunk hashes.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for chunk_hash in &self.chunk_hashes {
+            hasher.update(chunk_hash);
+        }
+        hasher.finalize().into()
+    }
+
+    /// The opaque metadata sent to peers alongside the snapshot, which lets
+    /// them verify each chunk as it arrives.
+    pub fn metadata(&self) -> Vec<u8> {
+        bincode::serialize(&self.chunk_hashes).expect("can serialize chunk hashes")
+    }
+
+    /// Parses the chunk hashes out of the snapshot metadata sent by a peer.
+    pub fn chunk_hashes_from_metadata(metadata: &[u8]) -> Result<Vec<[u8; 32]>> {
+        bincode::deserialize(metadata).context("invalid snapshot metadata")
+    }
+}
+
+/// Decodes the entries of a chunk, checking that each one is a JMT node.
+pub fn decode_chunk(chunk: &[u8]) -> Result<ChunkEntries> {
+    let entries: ChunkEntries = bincode::deserialize(chunk).context("invalid snapshot chunk")?;
+    for (key, value) in &entries {
+        NodeKey::decode(key)?;
+        Node::decode(value)?;
+    }
+    Ok(entries)
+}
+
+/// Computes the hash of a single chunk.
+pub fn chunk_hash(chunk: &[u8]) -> [u8; 32] {
+    Sha256::digest(chunk).into()
+}
+
+/// An on-disk store of snapshots, laid out as
+///
+/// ```ascii,no_run
+/// <path>/<height>/chunk-<index>
+/// <path>/<height>/manifest
+/// ```
+///
+/// The manifest is written last, so that snapshots which were interrupted
+/// while being written are never offered to peers.
+#[derive(Clone, Debug)]
+pub struct Store {
+    path: PathBuf,
+}
+
+impl Store {
+    pub fn new(path: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&path)
+            .with_context(|| format!("could not create snapshot directory {:?}", path))?;
+        Ok(Self { path })
+    }
+
+    fn snapshot_dir(&self, height: Version) -> PathBuf {
+        self.path.join(height.to_string())
+    }
+
+    fn chunk_path(dir: &Path, index: u32) -> PathBuf {
+        dir.join(format!("chunk-{}", index))
+    }
+
+    /// Lists the manifests of all complete snapshots, ordered by height.
+    pub fn list(&self) -> Result<Vec<Manifest>> {
+        let mut manifests = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let manifest_path = entry?.path().join("manifest");
+            if !manifest_path.exists() {
+                continue;
+            }
+            let manifest: Manifest = bincode::deserialize(&fs::read(&manifest_path)?)?;
+            manifests.push(manifest);
+        }
+        manifests.sort_by_key(|m| m.height);
+        Ok(manifests)
+    }
+
+    /// Reads a single chunk of a stored snapshot.
+    pub fn load_chunk(&self, height: Version, format: u32, index: u32) -> Result<Vec<u8>> {
+        if format != SNAPSHOT_FORMAT {
+            return Err(anyhow!("unsupported snapshot format {}", format));
+        }
+        let path = Self::chunk_path(&self.snapshot_dir(height), index);
+        fs::read(&path).with_context(|| format!("could not read snapshot chunk {:?}", path))
+    }
+
+    /// Creates a snapshot of `storage` at the given `height`.
+    ///
+    /// This performs blocking IO, so it should be run on a blocking thread.
+    #[instrument(skip(self, storage))]
+    pub fn create(&self, storage: &Storage, height: Version) -> Result<Manifest> {
+        let dir = self.snapshot_dir(height);
+        if dir.exists() {
+            // Clear out any partially-written snapshot at this height.
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+
+        let mut chunk_hashes = Vec::new();
+        let mut write_chunk = |entries: &ChunkEntries| -> Result<()> {
+            let bytes = bincode::serialize(entries)?;
+            chunk_hashes.push(chunk_hash(&bytes));
+            fs::write(
+                Self::chunk_path(&dir, (chunk_hashes.len() - 1) as u32),
+                bytes,
+            )?;
+            Ok(())
+        };
+
+        // Stream the nodes into chunks as we walk the tree, rather than holding
+        // the whole tree in memory.
+        let mut chunk = ChunkEntries::new();
+        let mut chunk_size = 0;
+        storage.export_nodes(height, |key, value| {
+            chunk_size += key.len() + value.len();
+            chunk.push((key, value));
+            if chunk_size >= MAX_CHUNK_SIZE {
+                write_chunk(&chunk)?;
+                chunk.clear();
+                chunk_size = 0;
+            }
+            Ok(())
+        })?;
+        if !chunk.is_empty() || chunk_hashes.is_empty() {
+            write_chunk(&chunk)?;
+        }
+
+        let manifest = Manifest {
+            height,
+            format: SNAPSHOT_FORMAT,
+            chunk_hashes,
+        };
+        fs::write(dir.join("manifest"), bincode::serialize(&manifest)?)?;
+        tracing::info!(
+            height,
+            chunks = manifest.chunk_hashes.len(),
+            "created snapshot"
+        );
+
+        Ok(manifest)
+    }
+
+    /// Deletes all but the `keep_recent` most recent snapshots.
+    pub fn prune(&self, keep_recent: usize) -> Result<()> {
+        let manifests = self.list()?;
+        let num_to_prune = manifests.len().saturating_sub(keep_recent);
+        for manifest in &manifests[..num_to_prune] {
+            tracing::debug!(height = manifest.height, "pruning snapshot");
+            fs::remove_dir_all(self.snapshot_dir(manifest.height))?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use futures::future::BoxFuture;
 use jmt::{
     storage::{Node, NodeBatch, NodeKey, TreeReader, TreeWriter},
@@ -70,6 +70,154 @@ impl Storage {
             .await
             .map_err(|e| tonic::Status::internal(e.to_string()))
     }
+
+    /// Returns a new [`Overlay`] on top of the given version of the tree.
+    ///
+    /// Since the overlay is never committed, this can be used as a read-only
+    /// view of the historical state at `version`.
+    pub async fn overlay_at(&self, version: jmt::Version) -> Result<Overlay> {
+        let latest_version = self
+            .latest_version()
+            .await?
+            .ok_or_else(|| anyhow!("no state has been committed yet"))?;
+        if version > latest_version {
+            return Err(anyhow!(
+                "requested version {} is greater than the latest version {}",
+                version,
+                latest_version
+            ));
+        }
+        if jmt::JellyfishMerkleTree::new(self)
+            .get_root_hash_option(version)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow!("state at version {} is not available", version));
+        }
+
+        tracing::debug!("creating overlay for historical version {}", version);
+        Ok(Arc::new(Mutex::new(WriteOverlay::new(
+            self.clone(),
+            version,
+        ))))
+    }
+
+    /// Like [`Self::overlay_tonic`], but on top of the tree at the given
+    /// `height`, treating a height of 0 as a request for the latest version.
+    ///
+    /// This is used to answer gRPC requests with an optional height.
+    pub async fn overlay_at_height_tonic(
+        &self,
+        height: u64,
+    ) -> std::result::Result<Overlay, tonic::Status> {
+        if height == 0 {
+            self.overlay_tonic().await
+        } else {
+            self.overlay_at(height)
+                .await
+                .map_err(|e| tonic::Status::out_of_range(e.to_string()))
+        }
+    }
+
+    /// Calls `f` with the raw key/value pair of each JMT node in the tree at
+    /// `version`, walking down from its root.
+    ///
+    /// Since JMT nodes are never modified once written, these nodes are a
+    /// complete image of the tree at `version`, without the nodes that are
+    /// only part of older versions.  This method performs blocking IO and
+    /// should be called from a blocking thread.
+    pub fn export_nodes(
+        &self,
+        version: jmt::Version,
+        mut f: impl FnMut(Vec<u8>, Vec<u8>) -> Result<()>,
+    ) -> Result<()> {
+        // Read from a RocksDB snapshot, so that we get a consistent view
+        // even if new blocks are committed while we're walking the tree.
+        let snapshot = self.0.snapshot();
+        walk_tree(&snapshot, version, |_node_key, key_bytes, value| {
+            f(key_bytes, value)
+        })
+    }
+
+    /// Writes raw JMT node key/value pairs, as returned by
+    /// [`Storage::export_nodes`], directly into the database.
+    ///
+    /// The nodes are checked to be well-formed before being written.  This
+    /// method performs blocking IO and should be called from a blocking thread.
+    pub fn import_nodes(&self, nodes: Vec<(Vec<u8>, Vec<u8>)>) -> Result<()> {
+        let mut batch = rocksdb::WriteBatch::default();
+        for (key, value) in nodes {
+            NodeKey::decode(&key)?;
+            Node::decode(&value)?;
+            batch.put(key, value);
+        }
+        self.0.write(batch)?;
+        Ok(())
+    }
+
+    /// Deletes the JMT nodes that are only needed to read versions of the tree
+    /// older than `min_version`, returning the number of deleted nodes.
+    ///
+    /// Since JMT nodes are never modified once written, any node written
+    /// before `min_version` that is part of the tree at some later version
+    /// must also be part of the tree at `min_version`.  So the older nodes that
+    /// aren't reachable from the root at `min_version` are stale, and can be
+    /// deleted without affecting any retained version.  Keys in the latest
+    /// version are never removed; see [`crate::pruning::Config`] for the
+    /// per-height data that is kept there.  This method performs blocking IO
+    /// and should be called from a blocking thread.
+    pub fn prune(&self, min_version: jmt::Version) -> Result<usize> {
+        // Read from a RocksDB snapshot, so that we get a consistent view even
+        // if new blocks are committed while we're pruning.  New nodes are
+        // always written at later versions, so they're never deleted.
+        let snapshot = self.0.snapshot();
+
+        // Mark the older nodes that are still part of the tree at `min_version`...
+        let mut reachable = HashSet::new();
+        walk_tree(&snapshot, min_version, |node_key, key_bytes, _value| {
+            if node_key.version() < min_version {
+                reachable.insert(key_bytes);
+            }
+            Ok(())
+        })?;
+
+        // ... then sweep the ones that aren't.
+        let mut iter = snapshot.raw_iterator();
+        iter.seek_to_first();
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut deleted = 0;
+        while iter.valid() {
+            let key = iter.key().unwrap();
+            if NodeKey::decode(key)?.version() < min_version && !reachable.contains(key) {
+                batch.delete(key);
+                deleted += 1;
+            }
+            iter.next();
+        }
+        iter.status()?;
+        self.0.write(batch)?;
+
+        Ok(deleted)
+    }
+
+    /// Deletes every node in the database.
+    ///
+    /// This is used to back out of a failed snapshot restoration, and performs
+    /// blocking IO, so it should be called from a blocking thread.
+    pub fn clear(&self) -> Result<()> {
+        let mut iter = self.0.raw_iterator();
+        iter.seek_to_first();
+
+        let mut batch = rocksdb::WriteBatch::default();
+        while iter.valid() {
+            batch.delete(iter.key().unwrap());
+            iter.next();
+        }
+        iter.status()?;
+        self.0.write(batch)?;
+        Ok(())
+    }
 }
 
 impl TreeWriter for Storage {
@@ -175,3 +323,88 @@ impl TreeReader for Storage {
         })
     }
 }
+
+/// Calls `f` with the key, raw key and raw value of each node in the tree at
+/// `version`, walking down from its root.
+fn walk_tree(
+    snapshot: &rocksdb::Snapshot,
+    version: jmt::Version,
+    mut f: impl FnMut(&NodeKey, Vec<u8>, Vec<u8>) -> Result<()>,
+) -> Result<()> {
+    let mut pending = vec![NodeKey::new_empty_path(version)];
+    while let Some(node_key) = pending.pop() {
+        let key_bytes = node_key.encode()?;
+        let value = snapshot
+            .get(&key_bytes)?
+            .ok_or_else(|| anyhow!("missing node {:?} of tree at version {}", node_key, version))?;
+        if let Node::Internal(internal_node) = Node::decode(&value)? {
+            for (nibble, child) in internal_node.children_sorted() {
+                pending.push(node_key.gen_child_node_key(child.version, *nibble));
+            }
+        }
+        f(&node_key, key_bytes, value)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::app::View as _;
+    use tempfile::tempdir;
+
+    // test that historical overlays read the state as of their version.
+    #[tokio::test]
+    async fn test_overlay_at() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("test.db")).await.unwrap();
+
+        for height in 0..3u64 {
+            let overlay = storage.overlay().await.unwrap();
+            overlay.put_block_height(height * 10).await;
+            overlay.lock().await.commit(storage.clone()).await.unwrap();
+        }
+
+        for version in 0..3u64 {
+            let overlay = storage.overlay_at(version).await.unwrap();
+            assert_eq!(overlay.get_block_height().await.unwrap(), version * 10);
+        }
+        assert!(storage.overlay_at(3).await.is_err());
+    }
+
+    // test that pruning keeps the retained versions intact, and removes the older ones.
+    #[tokio::test]
+    async fn test_prune() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("test.db")).await.unwrap();
+
+        let mut root_hashes = Vec::new();
+        for height in 0..5u64 {
+            let overlay = storage.overlay().await.unwrap();
+            overlay.put_block_height(height * 10).await;
+            let (root_hash, _) = overlay.lock().await.commit(storage.clone()).await.unwrap();
+            root_hashes.push(root_hash);
+        }
+
+        let deleted = storage.prune(3).unwrap();
+        assert!(deleted > 0);
+
+        for version in 0..3u64 {
+            assert!(storage.overlay_at(version).await.is_err());
+        }
+        for version in 3..5u64 {
+            let overlay = storage.overlay_at(version).await.unwrap();
+            assert_eq!(overlay.get_block_height().await.unwrap(), version * 10);
+            assert_eq!(
+                jmt::JellyfishMerkleTree::new(&storage)
+                    .get_root_hash_option(version)
+                    .await
+                    .unwrap(),
+                Some(root_hashes[version as usize])
+            );
+        }
+        // pruning again at the same version has nothing left to delete.
+        assert_eq!(storage.prune(3).unwrap(), 0);
+    }
+}
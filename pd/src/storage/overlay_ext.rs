@@ -42,6 +42,9 @@ pub trait OverlayExt: Send + Sync + Sized + Clone + 'static {
     async fn put_proto<P>(&self, key: KeyHash, value: P)
     where
         P: Message + Debug;
+
+    /// Deletes a key from the overlay, so that later reads find no entry.
+    async fn delete(&self, key: KeyHash);
 }
 
 #[async_trait]
@@ -108,4 +111,10 @@ impl<R: TreeReader + Sync + 'static> OverlayExt for Arc<Mutex<WriteOverlay<R>>>
     {
         self.lock().await.put(key, value.encode_to_vec());
     }
+
+    #[instrument(skip(self, key))]
+    async fn delete(&self, key: KeyHash) {
+        tracing::trace!(?key, "deleting entry");
+        self.lock().await.delete(key);
+    }
 }
@@ -25,6 +25,7 @@ fn main() -> Result<()> {
         // byte arrays and then discarded.
         ".penumbra.chain.CompactOutput",
         ".penumbra.chain.CompactBlock",
+        ".penumbra.chain.QuarantineGroup",
     ]);
 
     for (path, attribute) in TYPE_ATTRIBUTES.iter() {
@@ -47,6 +48,10 @@ fn main() -> Result<()> {
             "proto/chain.proto",
             "proto/genesis.proto",
             "proto/ibc.proto",
+            "proto/governance.proto",
+            "proto/dex.proto",
+            "proto/flow_encryption.proto",
+            "proto/zk_proofs.proto",
         ],
         &["proto/", "ibc-go-vendor/"],
     )?;
@@ -73,6 +78,10 @@ fn main() -> Result<()> {
 static SERIALIZE: &str = r#"#[derive(::serde::Deserialize, ::serde::Serialize)]"#;
 /// Serializes newtype structs as if the inner field were serialized on its own.
 static SERDE_TRANSPARENT: &str = r#"#[serde(transparent)]"#;
+/// Serializes oneof variants with the snake_case names of their proto fields.
+static SERDE_SNAKE_CASE: &str = r#"#[serde(rename_all = "snake_case")]"#;
+/// Serializes a oneof field as if its variant were a field of the enclosing message.
+static SERDE_FLATTEN: &str = r#"#[serde(flatten)]"#;
 
 static AS_HEX: &str = r#"#[serde(with = "crate::serializers::hexstr")]"#;
 static AS_BASE64: &str = r#"#[serde(with = "crate::serializers::base64str")]"#;
@@ -86,6 +95,9 @@ static AS_BECH32_FULL_VIEWING_KEY: &str =
 static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.stake.Validator", SERIALIZE),
     (".penumbra.stake.FundingStream", SERIALIZE),
+    (".penumbra.stake.FundingStream.recipient", SERIALIZE),
+    (".penumbra.stake.FundingStream.recipient", SERDE_SNAKE_CASE),
+    (".penumbra.stake.CommunityPool", SERIALIZE),
     (".penumbra.stake.ValidatorDefinition", SERIALIZE),
     (".penumbra.stake.ValidatorInfo", SERIALIZE),
     (".penumbra.stake.ValidatorList", SERIALIZE),
@@ -100,6 +112,11 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.stake.Undelegate", SERIALIZE),
     (".penumbra.stake.DelegationChanges", SERIALIZE),
     (".penumbra.stake.CommissionAmount", SERIALIZE),
+    (".penumbra.stake.CommissionAmount.destination", SERIALIZE),
+    (
+        ".penumbra.stake.CommissionAmount.destination",
+        SERDE_SNAKE_CASE,
+    ),
     (".penumbra.stake.CommissionAmounts", SERIALIZE),
     (".penumbra.stake.Uptime", SERIALIZE),
     (".penumbra.crypto.Address", SERIALIZE),
@@ -121,7 +138,10 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.crypto.DiversifierIndex", SERIALIZE),
     (".penumbra.crypto.DiversifierIndex", SERDE_TRANSPARENT),
     (".penumbra.chain.ChainParams", SERIALIZE),
+    (".penumbra.zk_proofs.ProofSystem", SERIALIZE),
     (".penumbra.chain.CompactBlock", SERIALIZE),
+    (".penumbra.chain.Quarantined", SERIALIZE),
+    (".penumbra.chain.QuarantineGroup", SERIALIZE),
     (".penumbra.chain.KnownAssets", SERIALIZE),
     (".penumbra.chain.KnownAssets", SERDE_TRANSPARENT),
     (".penumbra.chain.NoteSource", SERIALIZE),
@@ -138,6 +158,13 @@ static FIELD_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.stake.ValidatorDefinition.auth_sig", AS_HEX),
     (".penumbra.stake.IdentityKey.ik", AS_BECH32_IDENTITY_KEY),
     (".penumbra.stake.Uptime.bitvec", AS_BASE64),
+    // Flattening the recipient keeps the JSON format of funding streams
+    // paying to an address the same as before the community pool existed.
+    (".penumbra.stake.FundingStream.recipient", SERDE_FLATTEN),
+    (
+        ".penumbra.stake.CommissionAmount.destination",
+        SERDE_FLATTEN,
+    ),
     (".penumbra.crypto.Address.inner", AS_BECH32_ADDRESS),
     (".penumbra.crypto.AssetId.inner", AS_BECH32_ASSET_ID),
     (".penumbra.crypto.NoteCommitment.inner", AS_HEX),
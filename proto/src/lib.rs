@@ -38,6 +38,21 @@ pub mod transaction {
     include!(concat!(env!("OUT_DIR"), "/penumbra.transaction.rs"));
 }
 
+/// Governance structures.
+pub mod governance {
+    include!(concat!(env!("OUT_DIR"), "/penumbra.governance.rs"));
+}
+
+/// Decentralized exchange structures.
+pub mod dex {
+    include!(concat!(env!("OUT_DIR"), "/penumbra.dex.rs"));
+}
+
+/// Flow encryption structures.
+pub mod flow_encryption {
+    include!(concat!(env!("OUT_DIR"), "/penumbra.flow_encryption.rs"));
+}
+
 /// Chain-related structures.
 pub mod chain {
     tonic::include_proto!("penumbra.chain");
@@ -68,7 +83,8 @@ pub mod sighash {
 
     use sig_hash_action::Action as SHAction;
 
-    use super::transaction::{action::Action as TxAction, Spend};
+    use super::governance::DelegatorVote;
+    use super::transaction::{action::Action as TxAction, Spend, SwapClaim};
 
     impl From<super::transaction::Action> for SigHashAction {
         fn from(action: super::transaction::Action) -> Self {
@@ -87,6 +103,37 @@ pub mod sighash {
                     ..
                 })) => Some(SHAction::Spend(spend_body)),
                 Some(TxAction::IbcAction(i)) => Some(SHAction::IbcAction(i)),
+                // Proposals don't contain any signatures.
+                Some(TxAction::Proposal(p)) => Some(SHAction::Proposal(p)),
+                // Like the `ValidatorDefinition`, the `ValidatorVote` is signed
+                // independently of the transaction, so its signature is included.
+                Some(TxAction::ValidatorVote(v)) => Some(SHAction::ValidatorVote(v)),
+                // Collapse delegator votes to their bodies, like spends.
+                Some(TxAction::DelegatorVote(DelegatorVote { body: None, .. })) => None,
+                Some(TxAction::DelegatorVote(DelegatorVote {
+                    body: Some(vote_body),
+                    ..
+                })) => Some(SHAction::DelegatorVote(vote_body)),
+                Some(TxAction::CommunityPoolDeposit(d)) => Some(SHAction::CommunityPoolDeposit(d)),
+                Some(TxAction::Ics20Withdrawal(w)) => Some(SHAction::Ics20Withdrawal(w)),
+                // Swaps don't contain any signatures.
+                Some(TxAction::Swap(s)) => Some(SHAction::Swap(s)),
+                // Collapse swap claims to their bodies, like spends.
+                Some(TxAction::SwapClaim(SwapClaim { body: None, .. })) => None,
+                Some(TxAction::SwapClaim(SwapClaim {
+                    body: Some(claim_body),
+                    ..
+                })) => Some(SHAction::SwapClaim(claim_body)),
+                // Position actions are authorized by the LP NFTs they consume,
+                // so they don't contain any signatures.
+                Some(TxAction::PositionOpen(p)) => Some(SHAction::PositionOpen(p)),
+                Some(TxAction::PositionClose(p)) => Some(SHAction::PositionClose(p)),
+                Some(TxAction::PositionWithdraw(p)) => Some(SHAction::PositionWithdraw(p)),
+                // Like the `ValidatorVote`, DKG messages are signed
+                // independently of the transaction, so their signatures are included.
+                Some(TxAction::DkgMessage(m)) => Some(SHAction::DkgMessage(m)),
+                // Likewise for flow decryptions.
+                Some(TxAction::FlowDecryption(d)) => Some(SHAction::FlowDecryption(d)),
                 None => None,
             };
             Self { action }
@@ -106,6 +153,11 @@ pub mod sighash {
     }
 }
 
+/// Zero-knowledge proofs.
+pub mod zk_proofs {
+    include!(concat!(env!("OUT_DIR"), "/penumbra.zk_proofs.rs"));
+}
+
 /// Transparent proofs.
 ///
 /// Note that these are protos for the "MVP" transparent version of Penumbra,
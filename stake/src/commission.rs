@@ -1,14 +1,15 @@
 use anyhow::Result;
-use penumbra_crypto::Address;
 use penumbra_proto::{stake as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 
+use crate::funding_stream::Recipient;
+
 /// A commission amount to be minted as part of processing the epoch transition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(try_from = "pb::CommissionAmount", into = "pb::CommissionAmount")]
 pub struct CommissionAmount {
     pub amount: u64,
-    pub destination: Address,
+    pub destination: Recipient,
 }
 
 impl Protobuf<pb::CommissionAmount> for CommissionAmount {}
@@ -17,7 +18,14 @@ impl From<CommissionAmount> for pb::CommissionAmount {
     fn from(note: CommissionAmount) -> pb::CommissionAmount {
         pb::CommissionAmount {
             amount: note.amount.into(),
-            destination: Some(note.destination.into()),
+            destination: Some(match note.destination {
+                Recipient::Address(address) => {
+                    pb::commission_amount::Destination::Address(address.into())
+                }
+                Recipient::CommunityPool => {
+                    pb::commission_amount::Destination::CommunityPool(pb::CommunityPool {})
+                }
+            }),
         }
     }
 }
@@ -27,7 +35,15 @@ impl TryFrom<pb::CommissionAmount> for CommissionAmount {
     fn try_from(note: pb::CommissionAmount) -> Result<CommissionAmount> {
         Ok(CommissionAmount {
             amount: note.amount.into(),
-            destination: note.destination.unwrap().try_into()?,
+            destination: match note
+                .destination
+                .ok_or_else(|| anyhow::anyhow!("missing commission amount destination"))?
+            {
+                pb::commission_amount::Destination::Address(address) => {
+                    Recipient::Address(address.try_into()?)
+                }
+                pb::commission_amount::Destination::CommunityPool(_) => Recipient::CommunityPool,
+            },
         })
     }
 }
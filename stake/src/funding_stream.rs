@@ -8,14 +8,23 @@ use crate::rate::BaseRateData;
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
 #[serde(try_from = "pb::FundingStream", into = "pb::FundingStream")]
 pub struct FundingStream {
-    /// The destinatination address for the funding stream..
-    pub address: Address,
+    /// The recipient of the funding stream.
+    pub recipient: Recipient,
 
     /// The portion (in terms of [basis points](https://en.wikipedia.org/wiki/Basis_point)) of the
     /// validator's total staking reward that goes to this funding stream.
     pub rate_bps: u16,
 }
 
+/// The recipient of a funding stream, or of any other funds paid out by the chain.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Recipient {
+    /// The funds are paid to an address, in a newly minted note.
+    Address(Address),
+    /// The funds are paid to the community pool.
+    CommunityPool,
+}
+
 impl FundingStream {
     /// Computes the amount of reward at the epoch specified by base_rate_data
     pub fn reward_amount(
@@ -42,7 +51,14 @@ impl Protobuf<pb::FundingStream> for FundingStream {}
 impl From<FundingStream> for pb::FundingStream {
     fn from(fs: FundingStream) -> Self {
         pb::FundingStream {
-            address: fs.address.to_string(),
+            recipient: Some(match fs.recipient {
+                Recipient::Address(address) => {
+                    pb::funding_stream::Recipient::Address(address.to_string())
+                }
+                Recipient::CommunityPool => {
+                    pb::funding_stream::Recipient::CommunityPool(pb::CommunityPool {})
+                }
+            }),
             rate_bps: fs.rate_bps as u32,
         }
     }
@@ -62,7 +78,15 @@ impl TryFrom<pb::FundingStream> for FundingStream {
         };
 
         Ok(FundingStream {
-            address: fs.address.parse()?,
+            recipient: match fs
+                .recipient
+                .ok_or_else(|| anyhow::anyhow!("missing funding stream recipient"))?
+            {
+                pb::funding_stream::Recipient::Address(address) => {
+                    Recipient::Address(address.parse()?)
+                }
+                pb::funding_stream::Recipient::CommunityPool(_) => Recipient::CommunityPool,
+            },
             rate_bps,
         })
     }
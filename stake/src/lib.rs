@@ -17,7 +17,7 @@ pub mod validator;
 pub use changes::DelegationChanges;
 pub use commission::{CommissionAmount, CommissionAmounts};
 pub use epoch::Epoch;
-pub use funding_stream::{FundingStream, FundingStreams};
+pub use funding_stream::{FundingStream, FundingStreams, Recipient};
 pub use identity_key::IdentityKey;
 pub use token::DelegationToken;
 pub use uptime::Uptime;
@@ -21,9 +21,6 @@ pub struct Uptime {
     // Note: tracking this means we *could* in principle answer queries by
     // height, they just might be surprising for new validators (we just report
     // *failures* to sign, not didn't sign)
-    //
-    // can also possibly extend this impl to support resizing the window when we
-    // get to that
     as_of_block_height: u64,
     signatures: BitVec<u8, Lsb0>,
 }
@@ -81,6 +78,29 @@ impl Uptime {
     pub fn num_missed_blocks(&self) -> usize {
         self.signatures.iter_zeros().len()
     }
+
+    /// The number of blocks in the window.
+    pub fn window_len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Resize the window to `signed_blocks_window_len` blocks, for use when the
+    /// chain parameter changes.
+    ///
+    /// The records for the most recent blocks are kept, up to the length of
+    /// the new window.  If the window grows, the older blocks it now covers
+    /// are treated as signed, just as for a new validator.
+    pub fn resize(&mut self, signed_blocks_window_len: usize) {
+        let mut signatures = bitvec![u8, Lsb0; 1; signed_blocks_window_len];
+        let retained = self.signatures.len().min(signed_blocks_window_len) as u64;
+        for height in
+            (self.as_of_block_height + 1).saturating_sub(retained)..=self.as_of_block_height
+        {
+            let signed = self.signatures[(height as usize) % self.signatures.len()];
+            signatures.set((height as usize) % signed_blocks_window_len, signed);
+        }
+        self.signatures = signatures;
+    }
 }
 
 impl Protobuf<pb::Uptime> for Uptime {}
@@ -139,6 +159,32 @@ mod tests {
         assert!(uptime.mark_height_as_signed(0, true).is_err());
     }
 
+    #[test]
+    fn resize_keeps_recent_blocks() {
+        let mut uptime = Uptime::new(0, 100);
+        // Miss the blocks at heights 50 and 90 through 99
+        for h in 1..100u64 {
+            uptime.mark_height_as_signed(h, h != 50 && h < 90).unwrap();
+        }
+        assert_eq!(uptime.num_missed_blocks(), 11);
+
+        // Shrinking the window forgets the older missed block...
+        let mut shrunk = uptime.clone();
+        shrunk.resize(20);
+        assert_eq!(shrunk.window_len(), 20);
+        assert_eq!(shrunk.num_missed_blocks(), 10);
+
+        // ... while growing it keeps all of them.
+        uptime.resize(300);
+        assert_eq!(uptime.num_missed_blocks(), 11);
+
+        // Tracking continues with the new window.
+        for h in 100..400u64 {
+            uptime.mark_height_as_signed(h, true).unwrap();
+        }
+        assert_eq!(uptime.num_missed_blocks(), 0);
+    }
+
     #[test]
     fn proto_round_trip() {
         // make a weird size window
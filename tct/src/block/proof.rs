@@ -118,9 +118,6 @@ impl Proof {
     }
 }
 
-// TODO: re-enable these impls once we adapt the protobuf crate:
-
-/*
 use penumbra_proto::transparent_proofs as pb;
 
 impl From<Proof> for pb::MerkleProof {
@@ -138,4 +135,3 @@ impl TryFrom<pb::MerkleProof> for Proof {
 }
 
 impl penumbra_proto::Protobuf<pb::MerkleProof> for Proof {}
-*/
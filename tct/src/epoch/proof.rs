@@ -183,9 +183,6 @@ impl Proof {
     }
 }
 
-// TODO: re-enable these impls once we adapt the protobuf crate:
-
-/*
 use penumbra_proto::transparent_proofs as pb;
 
 impl From<Proof> for pb::MerkleProof {
@@ -203,4 +200,3 @@ impl TryFrom<pb::MerkleProof> for Proof {
 }
 
 impl penumbra_proto::Protobuf<pb::MerkleProof> for Proof {}
-*/
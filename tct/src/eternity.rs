@@ -67,6 +67,22 @@ impl From<Root> for pb::MerkleRoot {
 
 impl Protobuf<pb::MerkleRoot> for Root {}
 
+impl From<Root> for [u8; 32] {
+    fn from(root: Root) -> Self {
+        Fq::from(root.0).to_bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for Root {
+    type Error = RootDecodeError;
+
+    fn try_from(slice: &[u8]) -> Result<Root, Self::Error> {
+        let bytes: [u8; 32] = slice.try_into().map_err(|_| RootDecodeError)?;
+        let inner = Fq::from_bytes(bytes).map_err(|_| RootDecodeError)?;
+        Ok(Root(Hash::new(inner)))
+    }
+}
+
 impl Display for Root {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", hex::encode(&Fq::from(self.0).to_bytes()))
@@ -217,9 +217,6 @@ mod test {
 #[error("could not decode authentication path")]
 pub struct PathDecodeError;
 
-// TODO: re-enable these protobuf impls once we adapt the protobuf crate to this crate:
-
-/*
 use std::collections::VecDeque;
 use decaf377::{FieldExt, Fq};
 use penumbra_proto::transparent_proofs as pb;
@@ -345,4 +342,3 @@ where
         <Node<Child>>::try_from(VecDeque::from(queue))
     }
 }
-*/
@@ -74,9 +74,6 @@ impl VerifyError {
 #[error("could not decode proof")]
 pub struct ProofDecodeError;
 
-// TODO: re-enable these protobuf impls once we adapt the protobuf crate to these types:
-
-/*
 use decaf377::{FieldExt, Fq};
 use penumbra_proto::transparent_proofs as pb;
 
@@ -119,4 +116,3 @@ where
         })
     }
 }
-*/
@@ -67,6 +67,7 @@ use internal::{
 
 #[doc(inline)]
 pub use crate::internal::{
+    hash::DOMAIN_SEPARATOR,
     path::PathDecodeError,
     proof::{ProofDecodeError, VerifyError},
 };
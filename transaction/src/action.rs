@@ -1,15 +1,22 @@
 use std::convert::{TryFrom, TryInto};
 
 use penumbra_crypto::value;
+use penumbra_dex::action as dex;
+use penumbra_flow_encryption::action as flow_encryption;
+use penumbra_governance::action as governance;
 use penumbra_ibc as ibc;
 use penumbra_proto::{transaction as pb, Protobuf};
 use penumbra_stake::action as stake;
 
 pub mod output;
 pub mod spend;
+pub mod swap;
+pub mod swap_claim;
 
 pub use output::Output;
 pub use spend::Spend;
+pub use swap::Swap;
+pub use swap_claim::SwapClaim;
 
 /// Supported actions in a Penumbra transaction.
 #[derive(Clone, Debug)]
@@ -20,6 +27,18 @@ pub enum Action {
     Undelegate(stake::Undelegate),
     ValidatorDefinition(stake::ValidatorDefinition),
     IBCAction(ibc::IBCAction),
+    Proposal(governance::Proposal),
+    ValidatorVote(governance::ValidatorVote),
+    DelegatorVote(governance::DelegatorVote),
+    CommunityPoolDeposit(governance::CommunityPoolDeposit),
+    Ics20Withdrawal(ibc::Ics20Withdrawal),
+    Swap(swap::Swap),
+    SwapClaim(swap_claim::SwapClaim),
+    PositionOpen(dex::PositionOpen),
+    PositionClose(dex::PositionClose),
+    PositionWithdraw(dex::PositionWithdraw),
+    DkgMessage(flow_encryption::DkgMessage),
+    FlowDecryption(flow_encryption::FlowDecryption),
 }
 
 impl Action {
@@ -34,6 +53,21 @@ impl Action {
             Action::ValidatorDefinition(_) => value::Commitment::default(),
             // TODO: should IBC actions have value commitments?
             Action::IBCAction(_) => value::Commitment::default(),
+            Action::Proposal(proposal) => proposal.value_commitment(),
+            // Votes don't move any value: delegator votes prove ownership of
+            // a note without spending it.
+            Action::ValidatorVote(_) => value::Commitment::default(),
+            Action::DelegatorVote(_) => value::Commitment::default(),
+            Action::CommunityPoolDeposit(deposit) => deposit.value_commitment(),
+            Action::Ics20Withdrawal(withdrawal) => withdrawal.value_commitment(),
+            Action::Swap(swap) => swap.value_commitment(),
+            // Swap claim outputs are paid out of the batch, not the transaction.
+            Action::SwapClaim(_) => value::Commitment::default(),
+            Action::PositionOpen(open) => open.value_commitment(),
+            Action::PositionClose(close) => close.value_commitment(),
+            Action::PositionWithdraw(withdraw) => withdraw.value_commitment(),
+            Action::DkgMessage(_) => value::Commitment::default(),
+            Action::FlowDecryption(_) => value::Commitment::default(),
         }
     }
 }
@@ -61,6 +95,42 @@ impl From<Action> for pb::Action {
             Action::IBCAction(inner) => pb::Action {
                 action: Some(pb::action::Action::IbcAction(inner.into())),
             },
+            Action::Proposal(inner) => pb::Action {
+                action: Some(pb::action::Action::Proposal(inner.into())),
+            },
+            Action::ValidatorVote(inner) => pb::Action {
+                action: Some(pb::action::Action::ValidatorVote(inner.into())),
+            },
+            Action::DelegatorVote(inner) => pb::Action {
+                action: Some(pb::action::Action::DelegatorVote(inner.into())),
+            },
+            Action::CommunityPoolDeposit(inner) => pb::Action {
+                action: Some(pb::action::Action::CommunityPoolDeposit(inner.into())),
+            },
+            Action::Ics20Withdrawal(inner) => pb::Action {
+                action: Some(pb::action::Action::Ics20Withdrawal(inner.into())),
+            },
+            Action::Swap(inner) => pb::Action {
+                action: Some(pb::action::Action::Swap(inner.into())),
+            },
+            Action::SwapClaim(inner) => pb::Action {
+                action: Some(pb::action::Action::SwapClaim(inner.into())),
+            },
+            Action::PositionOpen(inner) => pb::Action {
+                action: Some(pb::action::Action::PositionOpen(inner.into())),
+            },
+            Action::PositionClose(inner) => pb::Action {
+                action: Some(pb::action::Action::PositionClose(inner.into())),
+            },
+            Action::PositionWithdraw(inner) => pb::Action {
+                action: Some(pb::action::Action::PositionWithdraw(inner.into())),
+            },
+            Action::DkgMessage(inner) => pb::Action {
+                action: Some(pb::action::Action::DkgMessage(inner.into())),
+            },
+            Action::FlowDecryption(inner) => pb::Action {
+                action: Some(pb::action::Action::FlowDecryption(inner.into())),
+            },
         }
     }
 }
@@ -83,6 +153,32 @@ impl TryFrom<pb::Action> for Action {
                 Ok(Action::ValidatorDefinition(inner.try_into()?))
             }
             pb::action::Action::IbcAction(inner) => Ok(Action::IBCAction(inner.try_into()?)),
+            pb::action::Action::Proposal(inner) => Ok(Action::Proposal(inner.try_into()?)),
+            pb::action::Action::ValidatorVote(inner) => {
+                Ok(Action::ValidatorVote(inner.try_into()?))
+            }
+            pb::action::Action::DelegatorVote(inner) => {
+                Ok(Action::DelegatorVote(inner.try_into()?))
+            }
+            pb::action::Action::CommunityPoolDeposit(inner) => {
+                Ok(Action::CommunityPoolDeposit(inner.try_into()?))
+            }
+            pb::action::Action::Ics20Withdrawal(inner) => {
+                Ok(Action::Ics20Withdrawal(inner.try_into()?))
+            }
+            pb::action::Action::Swap(inner) => Ok(Action::Swap(inner.try_into()?)),
+            pb::action::Action::SwapClaim(inner) => Ok(Action::SwapClaim(inner.try_into()?)),
+            pb::action::Action::PositionOpen(inner) => Ok(Action::PositionOpen(inner.try_into()?)),
+            pb::action::Action::PositionClose(inner) => {
+                Ok(Action::PositionClose(inner.try_into()?))
+            }
+            pb::action::Action::PositionWithdraw(inner) => {
+                Ok(Action::PositionWithdraw(inner.try_into()?))
+            }
+            pb::action::Action::DkgMessage(inner) => Ok(Action::DkgMessage(inner.try_into()?)),
+            pb::action::Action::FlowDecryption(inner) => {
+                Ok(Action::FlowDecryption(inner.try_into()?))
+            }
         }
     }
 }
@@ -7,7 +7,7 @@ use penumbra_crypto::{
     keys::OutgoingViewingKey,
     memo::{MemoCiphertext, MemoPlaintext},
     note,
-    proofs::transparent::OutputProof,
+    proofs::{OutputProof, ProofSystem},
     value, Address, Fr, Note,
 };
 use penumbra_proto::{transaction as pb, Protobuf};
@@ -24,6 +24,7 @@ pub struct Output {
 
 impl Output {
     pub fn new<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
         rng: &mut R,
         note: Note,
         memo: MemoPlaintext,
@@ -32,9 +33,6 @@ impl Output {
         v_blinding: Fr,
     ) -> Output {
         let esk = ka::Secret::new(rng);
-        let diversified_generator = note.diversified_generator();
-        let transmission_key = note.transmission_key();
-
         // TODO: p. 43 Spec. Decide whether to do leadByte 0x01 method or 0x02 or other.
 
         // Outputs subtract from the transaction value balance, so commit to -value.
@@ -46,14 +44,8 @@ impl Output {
         let encrypted_memo = memo.encrypt(&esk, dest);
         let ovk_wrapped_key = note.encrypt_key(&esk, ovk, value_commitment);
 
-        let proof = OutputProof {
-            g_d: diversified_generator,
-            pk_d: transmission_key,
-            value: note.value(),
-            v_blinding,
-            note_blinding: note.note_blinding(),
-            esk: esk.clone(),
-        };
+        let proof = OutputProof::prove(proof_system, rng, &note, v_blinding, &esk)
+            .expect("can generate output proof");
 
         Self {
             body: Body {
@@ -4,11 +4,12 @@ use anyhow::Error;
 use bytes::Bytes;
 use penumbra_crypto::{
     keys, merkle,
-    proofs::transparent::SpendProof,
+    proofs::{ProofSystem, SpendProof},
     rdsa::{Signature, SigningKey, SpendAuth, VerificationKey},
     value, Fr, Note, Nullifier,
 };
 use penumbra_proto::{transaction, Message, Protobuf};
+use rand_core::{CryptoRng, RngCore};
 
 #[derive(Clone, Debug)]
 pub struct Spend {
@@ -59,11 +60,15 @@ pub struct Body {
 }
 
 impl Body {
-    pub fn new(
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
         value_commitment: value::Commitment,
         ask: SigningKey<SpendAuth>,
         spend_auth_randomizer: Fr,
-        merkle_path: merkle::Path,
+        anchor: merkle::Root,
+        note_commitment_proof: merkle::Proof,
         note: Note,
         v_blinding: Fr,
         nk: keys::NullifierKey,
@@ -71,22 +76,19 @@ impl Body {
         let rsk = ask.randomize(&spend_auth_randomizer);
         let rk = rsk.into();
         let note_commitment = note.commit();
-        let position = merkle_path.0.clone();
-        let proof = SpendProof {
-            // XXX: the position field duplicates data from the merkle path
-            // probably not worth fixing before we just make them snarks...
-            position,
-            merkle_path,
-            g_d: note.diversified_generator(),
-            pk_d: note.transmission_key(),
-            value: note.value(),
+        let position = note_commitment_proof.position();
+        let proof = SpendProof::prove(
+            proof_system,
+            rng,
+            anchor,
+            note_commitment_proof,
+            &note,
             v_blinding,
-            note_commitment,
-            note_blinding: note.note_blinding(),
             spend_auth_randomizer,
-            ak: ask.into(),
+            ask.into(),
             nk,
-        };
+        )
+        .expect("can generate spend proof");
         Body {
             value_commitment,
             nullifier: nk.derive_nullifier(position, &note_commitment),
@@ -0,0 +1,192 @@
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::Error;
+use bytes::Bytes;
+use penumbra_crypto::{ka, proofs::ProofSystem, value, Fr, Note};
+use penumbra_dex::{
+    proofs::{SwapProof, SwapPublicInputs},
+    SwapPlaintext, TradingPair,
+};
+use penumbra_flow_encryption::{Ciphertext, EncryptionKey, EncryptionRandomness};
+use penumbra_proto::{transaction as pb, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use super::output;
+
+/// Swaps one asset of a trading pair for the other.
+///
+/// The swap's inputs are burned, and it creates a swap NFT committing to its
+/// contents, which is spent by a later [`SwapClaim`](super::SwapClaim) to
+/// claim the swap's outputs once its batch has been cleared.  The amounts are
+/// only revealed as part of the batch totals, which the validators decrypt.
+#[derive(Clone, Debug)]
+pub struct Swap {
+    pub body: Body,
+    /// The proof that the swap is well-formed.
+    pub proof: SwapProof,
+}
+
+/// The body of a swap.
+#[derive(Clone, Debug)]
+pub struct Body {
+    pub trading_pair: TradingPair,
+    /// The commitment to the swap's inputs, which it burns.
+    pub value_commitment: value::Commitment,
+    /// The flow encryption key the amounts are encrypted to.
+    pub encryption_key: EncryptionKey,
+    pub encrypted_delta_1: Ciphertext,
+    pub encrypted_delta_2: Ciphertext,
+    /// The swap NFT, committing to the contents of the swap.
+    pub swap_nft: output::Body,
+    /// The contents of the swap, encrypted to the claim address.
+    pub encrypted_swap: Vec<u8>,
+}
+
+impl Swap {
+    /// Creates a swap of `swap_plaintext`, encrypting its amounts to the flow
+    /// encryption key, with `v_blinding` blinding the commitment to its inputs.
+    pub fn new<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        swap_plaintext: &SwapPlaintext,
+        encryption_key: EncryptionKey,
+        v_blinding: Fr,
+    ) -> Swap {
+        let note = Note::generate(
+            rng,
+            &swap_plaintext.claim_address,
+            swap_plaintext.nft_value(),
+        );
+        let esk = ka::Secret::new(rng);
+        let randomness_1 = EncryptionRandomness::new(&mut *rng);
+        let randomness_2 = EncryptionRandomness::new(&mut *rng);
+
+        let proof = SwapProof::prove(
+            proof_system,
+            rng,
+            swap_plaintext,
+            &encryption_key,
+            randomness_1,
+            randomness_2,
+            v_blinding,
+            note.note_blinding(),
+            &esk,
+        )
+        .expect("can generate swap proof");
+
+        Swap {
+            body: Body {
+                trading_pair: swap_plaintext.trading_pair,
+                value_commitment: swap_plaintext.input_commitment(v_blinding),
+                encryption_key,
+                encrypted_delta_1: encryption_key
+                    .encrypt_with_randomness(swap_plaintext.delta_1, &randomness_1),
+                encrypted_delta_2: encryption_key
+                    .encrypt_with_randomness(swap_plaintext.delta_2, &randomness_2),
+                swap_nft: output::Body {
+                    note_commitment: note.commit(),
+                    ephemeral_key: esk.diversified_public(&note.diversified_generator()),
+                    encrypted_note: note.encrypt(&esk),
+                },
+                encrypted_swap: swap_plaintext.encrypt(&esk),
+            },
+            proof,
+        }
+    }
+
+    /// The value commitment of the swap's inputs, which it consumes from the
+    /// transaction's balance.
+    ///
+    /// The swap NFT is not part of the balance: it's created by the swap.
+    pub fn value_commitment(&self) -> value::Commitment {
+        self.body.value_commitment
+    }
+
+    /// The public inputs the proof is checked against.
+    pub fn public_inputs(&self) -> SwapPublicInputs {
+        SwapPublicInputs {
+            trading_pair: self.body.trading_pair,
+            value_commitment: self.body.value_commitment,
+            encryption_key: self.body.encryption_key,
+            encrypted_delta_1: self.body.encrypted_delta_1,
+            encrypted_delta_2: self.body.encrypted_delta_2,
+            note_commitment: self.body.swap_nft.note_commitment,
+            epk: self.body.swap_nft.ephemeral_key,
+        }
+    }
+}
+
+impl Protobuf<pb::Swap> for Swap {}
+
+impl From<Swap> for pb::Swap {
+    fn from(swap: Swap) -> Self {
+        let proof: Vec<u8> = swap.proof.into();
+        pb::Swap {
+            body: Some(swap.body.into()),
+            zkproof: proof.into(),
+        }
+    }
+}
+
+impl TryFrom<pb::Swap> for Swap {
+    type Error = Error;
+
+    fn try_from(proto: pb::Swap) -> anyhow::Result<Self, Self::Error> {
+        Ok(Swap {
+            body: proto
+                .body
+                .ok_or_else(|| anyhow::anyhow!("missing swap body"))?
+                .try_into()?,
+            proof: proto.zkproof[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap proof malformed"))?,
+        })
+    }
+}
+
+impl Protobuf<pb::SwapBody> for Body {}
+
+impl From<Body> for pb::SwapBody {
+    fn from(body: Body) -> Self {
+        let cv_bytes: [u8; 32] = body.value_commitment.into();
+        pb::SwapBody {
+            trading_pair: Some(body.trading_pair.into()),
+            value_commitment: Bytes::copy_from_slice(&cv_bytes),
+            encryption_key: Bytes::copy_from_slice(&body.encryption_key.to_bytes()),
+            encrypted_delta_1: body.encrypted_delta_1.to_bytes().into(),
+            encrypted_delta_2: body.encrypted_delta_2.to_bytes().into(),
+            swap_nft: Some(body.swap_nft.into()),
+            encrypted_swap: body.encrypted_swap.into(),
+        }
+    }
+}
+
+impl TryFrom<pb::SwapBody> for Body {
+    type Error = Error;
+
+    fn try_from(proto: pb::SwapBody) -> anyhow::Result<Self, Self::Error> {
+        Ok(Body {
+            trading_pair: proto
+                .trading_pair
+                .ok_or_else(|| anyhow::anyhow!("missing swap trading pair"))?
+                .try_into()?,
+            value_commitment: (proto.value_commitment[..])
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap body malformed"))?,
+            encryption_key: proto.encryption_key[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap body malformed"))?,
+            encrypted_delta_1: proto.encrypted_delta_1[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap body malformed"))?,
+            encrypted_delta_2: proto.encrypted_delta_2[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap body malformed"))?,
+            swap_nft: proto
+                .swap_nft
+                .ok_or_else(|| anyhow::anyhow!("missing swap nft"))?
+                .try_into()?,
+            encrypted_swap: proto.encrypted_swap.to_vec(),
+        })
+    }
+}
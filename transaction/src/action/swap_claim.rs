@@ -0,0 +1,217 @@
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::Error;
+use bytes::Bytes;
+use penumbra_crypto::{
+    ka, keys, merkle,
+    proofs::ProofSystem,
+    rdsa::{Signature, SigningKey, SpendAuth, VerificationKey},
+    Fr, Note, Nullifier, Value,
+};
+use penumbra_dex::{
+    proofs::{SwapClaimProof, SwapClaimPublicInputs},
+    BatchSwapOutputData, SwapPlaintext,
+};
+use penumbra_proto::{transaction as pb, Protobuf};
+use rand_core::{CryptoRng, RngCore};
+
+use super::output;
+
+/// Claims the outputs of a swap, at the clearing price of its batch.
+///
+/// The claim spends the swap NFT created by the [`Swap`](super::Swap), and
+/// creates notes for the swap's share of the batch outputs, proving that they
+/// match the swap's contents without revealing them.  The outputs are paid
+/// out of the batch, so the claim doesn't change the transaction's balance.
+#[derive(Clone, Debug)]
+pub struct SwapClaim {
+    pub body: Body,
+    /// The spend authorization signature over the transaction's sighash.
+    pub auth_sig: Signature<SpendAuth>,
+}
+
+/// The body of a swap claim, stored separately from its signature.
+#[derive(Clone, Debug)]
+pub struct Body {
+    /// The result of the batch the swap was cleared in.
+    pub output_data: BatchSwapOutputData,
+    /// The nullifier of the swap NFT.
+    pub nullifier: Nullifier,
+    /// The randomized verification key for the spend authorization signature.
+    pub rk: VerificationKey<SpendAuth>,
+    /// The index of the block of the note commitment tree containing the swap
+    /// NFT, as its epoch and block indices.
+    pub nft_block: u32,
+    /// The note for the claimed amount of asset 1.
+    pub output_1: output::Body,
+    /// The note for the claimed amount of asset 2.
+    pub output_2: output::Body,
+    /// The proof that the claim spends a swap NFT from the batch, and that
+    /// the notes are for the swap's share of its outputs.
+    pub proof: SwapClaimProof,
+}
+
+impl Body {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<R: RngCore + CryptoRng>(
+        proof_system: ProofSystem,
+        rng: &mut R,
+        anchor: merkle::Root,
+        swap_plaintext: &SwapPlaintext,
+        output_data: BatchSwapOutputData,
+        ask: SigningKey<SpendAuth>,
+        spend_auth_randomizer: Fr,
+        note_commitment_proof: merkle::Proof,
+        swap_nft: Note,
+        nk: keys::NullifierKey,
+    ) -> Body {
+        let rsk = ask.randomize(&spend_auth_randomizer);
+        let rk = rsk.into();
+        let position = note_commitment_proof.position();
+
+        // Both outputs are always created, even if one of them is zero, so as
+        // not to reveal which side of the batch the swap was on.
+        let (amount_1, amount_2) =
+            output_data.claim_amounts(swap_plaintext.delta_1, swap_plaintext.delta_2);
+        let mut output = |amount, asset_id| {
+            let note = Note::generate(
+                rng,
+                &swap_plaintext.claim_address,
+                Value { amount, asset_id },
+            );
+            let esk = ka::Secret::new(rng);
+            let body = output::Body {
+                note_commitment: note.commit(),
+                ephemeral_key: esk.diversified_public(&note.diversified_generator()),
+                encrypted_note: note.encrypt(&esk),
+            };
+            (note, body)
+        };
+        let (note_1, output_1) = output(amount_1, output_data.trading_pair.asset_1());
+        let (note_2, output_2) = output(amount_2, output_data.trading_pair.asset_2());
+
+        let proof = SwapClaimProof::prove(
+            proof_system,
+            rng,
+            anchor,
+            note_commitment_proof,
+            swap_plaintext,
+            swap_nft.note_blinding(),
+            spend_auth_randomizer,
+            ask.into(),
+            nk,
+            &output_data,
+            note_1.note_blinding(),
+            note_2.note_blinding(),
+        )
+        .expect("can generate swap claim proof");
+
+        Body {
+            output_data,
+            nullifier: nk.derive_nullifier(position, &swap_nft.commit()),
+            rk,
+            nft_block: (u64::from(position) >> 16) as u32,
+            output_1,
+            output_2,
+            proof,
+        }
+    }
+
+    /// The public inputs the proof is checked against, for the note
+    /// commitment tree root `anchor`.
+    pub fn public_inputs(&self, anchor: merkle::Root) -> SwapClaimPublicInputs {
+        SwapClaimPublicInputs {
+            anchor,
+            nullifier: self.nullifier,
+            rk: self.rk,
+            output_data: self.output_data,
+            nft_block: self.nft_block,
+            output_1: self.output_1.note_commitment,
+            output_2: self.output_2.note_commitment,
+        }
+    }
+}
+
+impl Protobuf<pb::SwapClaim> for SwapClaim {}
+
+impl From<SwapClaim> for pb::SwapClaim {
+    fn from(claim: SwapClaim) -> Self {
+        let sig_bytes: [u8; 64] = claim.auth_sig.into();
+        pb::SwapClaim {
+            body: Some(claim.body.into()),
+            auth_sig: Bytes::copy_from_slice(&sig_bytes),
+        }
+    }
+}
+
+impl TryFrom<pb::SwapClaim> for SwapClaim {
+    type Error = Error;
+
+    fn try_from(proto: pb::SwapClaim) -> anyhow::Result<Self, Self::Error> {
+        let sig_bytes: [u8; 64] = proto.auth_sig[..]
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("swap claim malformed"))?;
+
+        Ok(SwapClaim {
+            body: proto
+                .body
+                .ok_or_else(|| anyhow::anyhow!("missing swap claim body"))?
+                .try_into()?,
+            auth_sig: sig_bytes.into(),
+        })
+    }
+}
+
+impl Protobuf<pb::SwapClaimBody> for Body {}
+
+impl From<Body> for pb::SwapClaimBody {
+    fn from(body: Body) -> Self {
+        let nullifier_bytes: [u8; 32] = body.nullifier.into();
+        let rk_bytes: [u8; 32] = body.rk.into();
+        let proof: Vec<u8> = body.proof.into();
+        pb::SwapClaimBody {
+            output_data: Some(body.output_data.into()),
+            nullifier: Bytes::copy_from_slice(&nullifier_bytes),
+            rk: Bytes::copy_from_slice(&rk_bytes),
+            nft_block: body.nft_block,
+            output_1: Some(body.output_1.into()),
+            output_2: Some(body.output_2.into()),
+            zkproof: proof.into(),
+        }
+    }
+}
+
+impl TryFrom<pb::SwapClaimBody> for Body {
+    type Error = Error;
+
+    fn try_from(proto: pb::SwapClaimBody) -> anyhow::Result<Self, Self::Error> {
+        let rk_bytes: [u8; 32] = proto.rk[..]
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("swap claim body malformed"))?;
+
+        Ok(Body {
+            output_data: proto
+                .output_data
+                .ok_or_else(|| anyhow::anyhow!("missing batch swap output data"))?
+                .try_into()?,
+            nullifier: proto.nullifier[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap claim body malformed"))?,
+            rk: rk_bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap claim body malformed"))?,
+            nft_block: proto.nft_block,
+            output_1: proto
+                .output_1
+                .ok_or_else(|| anyhow::anyhow!("missing swap claim output"))?
+                .try_into()?,
+            output_2: proto
+                .output_2
+                .ok_or_else(|| anyhow::anyhow!("missing swap claim output"))?
+                .try_into()?,
+            proof: proto.zkproof[..]
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("swap claim body malformed"))?,
+        })
+    }
+}
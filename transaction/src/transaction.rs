@@ -3,13 +3,16 @@ use std::convert::{TryFrom, TryInto};
 use anyhow::Error;
 use ark_ff::Zero;
 use bytes::Bytes;
-use decaf377::FieldExt;
 use penumbra_crypto::{
     merkle,
+    proofs::ProofSystem,
     rdsa::{Binding, Signature, VerificationKey, VerificationKeyBytes},
     Fr, Nullifier, Value,
 };
-use penumbra_ibc::IBCAction;
+use penumbra_dex::action::{PositionClose, PositionOpen, PositionWithdraw};
+use penumbra_flow_encryption::action::{DkgMessage, FlowDecryption};
+use penumbra_governance::action::{CommunityPoolDeposit, DelegatorVote, Proposal, ValidatorVote};
+use penumbra_ibc::{IBCAction, Ics20Withdrawal};
 use penumbra_proto::{
     transaction::{
         Fee as ProtoFee, Transaction as ProtoTransaction, TransactionBody as ProtoTransactionBody,
@@ -21,7 +24,10 @@ use penumbra_stake::{
     STAKING_TOKEN_ASSET_ID,
 };
 
-use crate::{action::output, Action};
+use crate::{
+    action::{output, Swap, SwapClaim},
+    Action,
+};
 
 mod builder;
 pub use builder::Builder;
@@ -74,6 +80,7 @@ impl Transaction {
             merkle_root,
             expiry_height: None,
             chain_id: None,
+            proof_system: ProofSystem::default(),
         }
     }
 
@@ -111,6 +118,16 @@ impl Transaction {
         })
     }
 
+    pub fn ics20_withdrawals(&self) -> impl Iterator<Item = &Ics20Withdrawal> {
+        self.actions().filter_map(|action| {
+            if let Action::Ics20Withdrawal(w) = action {
+                Some(w)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn validator_definitions(&self) -> impl Iterator<Item = &ValidatorDefinition> {
         self.actions().filter_map(|action| {
             if let Action::ValidatorDefinition(d) = action {
@@ -121,15 +138,131 @@ impl Transaction {
         })
     }
 
+    pub fn proposals(&self) -> impl Iterator<Item = &Proposal> {
+        self.actions().filter_map(|action| {
+            if let Action::Proposal(p) = action {
+                Some(p)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn validator_votes(&self) -> impl Iterator<Item = &ValidatorVote> {
+        self.actions().filter_map(|action| {
+            if let Action::ValidatorVote(v) = action {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn delegator_votes(&self) -> impl Iterator<Item = &DelegatorVote> {
+        self.actions().filter_map(|action| {
+            if let Action::DelegatorVote(v) = action {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn community_pool_deposits(&self) -> impl Iterator<Item = &CommunityPoolDeposit> {
+        self.actions().filter_map(|action| {
+            if let Action::CommunityPoolDeposit(d) = action {
+                Some(d)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn swaps(&self) -> impl Iterator<Item = &Swap> {
+        self.actions().filter_map(|action| {
+            if let Action::Swap(s) = action {
+                Some(s)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn swap_claims(&self) -> impl Iterator<Item = &SwapClaim> {
+        self.actions().filter_map(|action| {
+            if let Action::SwapClaim(c) = action {
+                Some(c)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn position_opens(&self) -> impl Iterator<Item = &PositionOpen> {
+        self.actions().filter_map(|action| {
+            if let Action::PositionOpen(p) = action {
+                Some(p)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn position_closes(&self) -> impl Iterator<Item = &PositionClose> {
+        self.actions().filter_map(|action| {
+            if let Action::PositionClose(p) = action {
+                Some(p)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn position_withdrawals(&self) -> impl Iterator<Item = &PositionWithdraw> {
+        self.actions().filter_map(|action| {
+            if let Action::PositionWithdraw(p) = action {
+                Some(p)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn dkg_messages(&self) -> impl Iterator<Item = &DkgMessage> {
+        self.actions().filter_map(|action| {
+            if let Action::DkgMessage(m) = action {
+                Some(m)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn flow_decryptions(&self) -> impl Iterator<Item = &FlowDecryption> {
+        self.actions().filter_map(|action| {
+            if let Action::FlowDecryption(d) = action {
+                Some(d)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn output_bodies(&self) -> Vec<output::Body> {
         self.transaction_body
             .actions
             .iter()
-            .filter_map(|action| {
-                if let Action::Output(output) = action {
-                    Some(output.body.clone())
-                } else {
-                    None
+            .flat_map(|action| {
+                match action {
+                    Action::Output(output) => vec![output.body.clone()],
+                    // Swaps create a swap NFT, which is added to the note
+                    // commitment tree like any other output.
+                    Action::Swap(swap) => vec![swap.body.swap_nft.clone()],
+                    // Swap claims create a note for each asset of the pair.
+                    Action::SwapClaim(claim) => {
+                        vec![claim.body.output_1.clone(), claim.body.output_2.clone()]
+                    }
+                    _ => vec![],
                 }
             })
             .collect()
@@ -142,10 +275,10 @@ impl Transaction {
             .filter_map(|action| {
                 // Note: adding future actions that include nullifiers
                 // will need to be matched here as well as Spends
-                if let Action::Spend(spend) = action {
-                    Some(spend.body.nullifier.clone())
-                } else {
-                    None
+                match action {
+                    Action::Spend(spend) => Some(spend.body.nullifier.clone()),
+                    Action::SwapClaim(claim) => Some(claim.body.nullifier.clone()),
+                    _ => None,
                 }
             })
             .collect()
@@ -207,7 +340,7 @@ impl From<TransactionBody> for ProtoTransactionBody {
     fn from(msg: TransactionBody) -> Self {
         ProtoTransactionBody {
             actions: msg.actions.into_iter().map(|x| x.into()).collect(),
-            anchor: Bytes::copy_from_slice(&msg.merkle_root.0.to_bytes()),
+            anchor: Bytes::copy_from_slice(&<[u8; 32]>::from(msg.merkle_root)),
             expiry_height: msg.expiry_height,
             chain_id: msg.chain_id,
             fee: Some(msg.fee.into()),
@@ -342,7 +475,7 @@ mod tests {
     use penumbra_crypto::{
         keys::{SeedPhrase, SpendKey, SpendSeed},
         memo::MemoPlaintext,
-        Fq, Value,
+        Value,
     };
     use rand_core::OsRng;
 
@@ -365,8 +498,9 @@ mod tests {
         let ivk_recipient = fvk_recipient.incoming();
         let (dest, _dtk_d) = ivk_recipient.payment_address(0u64.into());
 
-        let merkle_root = merkle::Root(Fq::zero());
+        let merkle_root = merkle::NoteCommitmentTree::new().root();
         let transaction = Transaction::build_with_root(merkle_root)
+            .set_proof_system(ProofSystem::Transparent)
             .set_fee(20)
             .set_chain_id("penumbra".to_string())
             .add_output(
@@ -1,11 +1,11 @@
 use std::ops::Deref;
 
 use ark_ff::{UniformRand, Zero};
-use incrementalmerkletree::Tree;
 use penumbra_crypto::{
     keys::{OutgoingViewingKey, SpendKey},
     memo::MemoPlaintext,
     merkle::{self, NoteCommitmentTree},
+    proofs::ProofSystem,
     rdsa::{Binding, Signature, SigningKey, SpendAuth},
     value, Address, Fr, Note, Value,
 };
@@ -50,6 +50,8 @@ pub struct Builder {
     pub expiry_height: Option<u32>,
     /// Chain ID. None if unset.
     pub chain_id: Option<String>,
+    /// The proof system used for spend and output proofs.
+    pub proof_system: ProofSystem,
 }
 
 impl Builder {
@@ -61,11 +63,10 @@ impl Builder {
         spend_key: &SpendKey,
         note: Note,
     ) -> Result<&mut Self, anyhow::Error> {
-        let merkle_path = note_commitment_tree
-            .authentication_path(&note.commit())
-            .ok_or_else(|| {
+        let note_commitment_proof =
+            note_commitment_tree.witness(note.commit()).ok_or_else(|| {
                 anyhow::anyhow!(
-                    "Note commitment tree cannot construct an auth path for note commitment {:?}",
+                    "Note commitment tree cannot witness note commitment {:?}",
                     note.commit()
                 )
             })?;
@@ -83,10 +84,13 @@ impl Builder {
         let rsk = spend_key.spend_auth_key().randomize(&spend_auth_randomizer);
 
         let body = spend::Body::new(
+            self.proof_system,
+            rng,
             value_commitment,
             *spend_key.spend_auth_key(),
             spend_auth_randomizer,
-            merkle_path,
+            self.merkle_root.clone(),
+            note_commitment_proof,
             note,
             v_blinding,
             *spend_key.nullifier_key(),
@@ -127,7 +131,15 @@ impl Builder {
         let note = Note::generate(rng, dest, value_to_send);
 
         let v_blinding = Fr::rand(rng);
-        let output = Output::new(rng, note.clone(), memo, dest, ovk, v_blinding);
+        let output = Output::new(
+            self.proof_system,
+            rng,
+            note.clone(),
+            memo,
+            dest,
+            ovk,
+            v_blinding,
+        );
 
         // Outputs subtract from the transaction's value balance.
         self.synthetic_blinding_factor -= v_blinding;
@@ -219,6 +231,13 @@ impl Builder {
         self
     }
 
+    /// Set the proof system used for spend and output proofs, which must be
+    /// the one the chain requires.
+    pub fn set_proof_system(&mut self, proof_system: ProofSystem) -> &mut Self {
+        self.proof_system = proof_system;
+        self
+    }
+
     /// Add the binding signature based on the current sum of synthetic blinding factors.
     #[allow(non_snake_case)]
     pub fn compute_binding_sig<R: CryptoRng + RngCore>(
@@ -9,11 +9,14 @@ use penumbra_chain::{params::ChainParams, sync::CompactBlock};
 use penumbra_crypto::{
     asset::{self, Denom},
     memo,
-    merkle::{Frontier, NoteCommitmentTree, Tree, TreeExt},
-    note, Address, FieldExt, Note, Nullifier, Value,
+    merkle::{self, NoteCommitmentTree},
+    note,
+    proofs::ProofSystem,
+    Address, FieldExt, Note, Nullifier, Value,
 };
 use penumbra_stake::{
-    action::ValidatorDefinition, rate::RateData, STAKING_TOKEN_ASSET_ID, STAKING_TOKEN_DENOM,
+    action::ValidatorDefinition, rate::RateData, Epoch, IdentityKey, STAKING_TOKEN_ASSET_ID,
+    STAKING_TOKEN_DENOM,
 };
 use penumbra_transaction::{action::output, Transaction};
 use rand::seq::SliceRandom;
@@ -23,11 +26,15 @@ use tracing::instrument;
 
 use crate::Wallet;
 
-const MAX_MERKLE_CHECKPOINTS_CLIENT: usize = 10;
-
 /// The time after which a locally cached submitted transaction is considered to have failed.
 const SUBMITTED_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// The number of blocks after the last synced height at which transactions expire by default.
+///
+/// This should be short enough that a transaction can't be included after its
+/// submission has timed out locally (see [`SUBMITTED_TRANSACTION_TIMEOUT`]).
+const DEFAULT_EXPIRY_BLOCKS: u64 = 6;
+
 /// State about the chain and our transactions.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(
@@ -47,6 +54,12 @@ pub struct ClientState {
     submitted_spend_set: BTreeMap<note::Commitment, (SystemTime, Note)>,
     /// Notes that we anticipate receiving on-chain as change but which have not yet been confirmed.
     submitted_change_set: BTreeMap<note::Commitment, (SystemTime, Note)>,
+    /// Notes that we have spent in an undelegation, whose nullifiers are held in quarantine by
+    /// the chain until the end of the unbonding period, along with the validator undelegated from.
+    quarantined_spend_set: BTreeMap<note::Commitment, (IdentityKey, Note)>,
+    /// Notes that we will receive from an undelegation, which are held in quarantine by the chain
+    /// until the end of the unbonding period, along with the validator undelegated from.
+    quarantined_change_set: BTreeMap<note::Commitment, (IdentityKey, Note)>,
     /// Notes that we have spent.
     spent_set: BTreeMap<note::Commitment, Note>,
     /// Map of note commitment to full transaction data for transactions we have visibility into.
@@ -72,10 +85,12 @@ pub enum UnspentNote<'a> {
     /// confirmation nor a submitted change output waiting for confirmation.
     Ready(&'a Note),
     /// A note which we have submitted in a spend transaction but which has not yet been
-    /// confirmed on the chain (so if the transaction is rejected, we may get it back again).
+    /// confirmed on the chain (so if the transaction is rejected, we may get it back again), or
+    /// which was spent in an undelegation still in quarantine.
     SubmittedSpend(&'a Note),
     /// A note which resulted as predicted change from a spend transaction, but which has not
-    /// yet been confirmed on the chain (so we cannot spend it yet).
+    /// yet been confirmed on the chain (so we cannot spend it yet), or which is the output of an
+    /// undelegation still in quarantine.
     SubmittedChange(&'a Note),
 }
 
@@ -101,13 +116,21 @@ impl AsRef<Note> for UnspentNote<'_> {
 
 impl ClientState {
     pub fn new(wallet: Wallet) -> Self {
+        // Open the genesis block, mirroring the chain's note commitment tree.
+        let mut note_commitment_tree = NoteCommitmentTree::new();
+        note_commitment_tree
+            .insert_block(merkle::Block::new())
+            .expect("empty note commitment tree is not full");
+
         Self {
             last_block_height: None,
-            note_commitment_tree: NoteCommitmentTree::new(MAX_MERKLE_CHECKPOINTS_CLIENT),
+            note_commitment_tree,
             nullifier_map: BTreeMap::new(),
             unspent_set: BTreeMap::new(),
             submitted_spend_set: BTreeMap::new(),
             submitted_change_set: BTreeMap::new(),
+            quarantined_spend_set: BTreeMap::new(),
+            quarantined_change_set: BTreeMap::new(),
             spent_set: BTreeMap::new(),
             transactions: BTreeMap::new(),
             asset_cache: Default::default(),
@@ -258,6 +281,28 @@ impl ClientState {
         self.chain_params().map(|p| p.chain_id.clone())
     }
 
+    /// Returns the proof system the chain expects, falling back to the
+    /// default if the chain parameters are not set.
+    pub fn proof_system(&self) -> ProofSystem {
+        self.chain_params()
+            .map(|p| p.proof_system)
+            .unwrap_or_default()
+    }
+
+    /// Returns the default expiry height for new transactions,
+    /// [`DEFAULT_EXPIRY_BLOCKS`] after the last synced block height.
+    ///
+    /// Errors if that height doesn't fit in a transaction's `u32` expiry height.
+    pub fn default_expiry_height(&self) -> Result<u32, anyhow::Error> {
+        let expiry_height = self.last_block_height.unwrap_or(0) + DEFAULT_EXPIRY_BLOCKS;
+        expiry_height.try_into().map_err(|_| {
+            anyhow!(
+                "expiry height {} does not fit in a transaction expiry height",
+                expiry_height
+            )
+        })
+    }
+
     /// Generate a new transaction delegating stake
     #[instrument(skip(self, rng, rate_data))]
     pub fn build_delegate<R: RngCore + CryptoRng>(
@@ -274,11 +319,13 @@ impl ClientState {
             .wallet()
             .address_by_index(source_address.unwrap_or(0) as usize)?;
 
-        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root2());
+        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root());
 
         tx_builder
             .set_fee(fee)
             .set_chain_id(self.chain_id().ok_or_else(|| anyhow!("missing chain_id"))?)
+            .set_proof_system(self.proof_system())
+            .set_expiry_height(self.default_expiry_height()?)
             .add_delegation(&rate_data, unbonded_amount);
 
         let spend_amount = unbonded_amount + fee;
@@ -343,11 +390,13 @@ impl ClientState {
             .wallet()
             .address_by_index(source_address.unwrap_or(0) as usize)?;
 
-        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root2());
+        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root());
 
         tx_builder
             .set_fee(fee)
             .set_chain_id(self.chain_id().ok_or_else(|| anyhow!("missing chain_id"))?)
+            .set_proof_system(self.proof_system())
+            .set_expiry_height(self.default_expiry_height()?)
             .add_undelegation(&rate_data, delegation_amount);
 
         // Because the outputs of an undelegation are quarantined, we want to
@@ -424,11 +473,13 @@ impl ClientState {
         fee: u64,
         source_address: Option<u64>,
     ) -> Result<Transaction, anyhow::Error> {
-        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root2());
+        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root());
 
         tx_builder
             .set_fee(fee)
-            .set_chain_id(self.chain_id().ok_or_else(|| anyhow!("missing chain_id"))?);
+            .set_chain_id(self.chain_id().ok_or_else(|| anyhow!("missing chain_id"))?)
+            .set_proof_system(self.proof_system())
+            .set_expiry_height(self.default_expiry_height()?);
 
         // Add the Validator to the tx_builder.
         tx_builder.add_validator_definition(new_validator);
@@ -503,11 +554,13 @@ impl ClientState {
         source_address: Option<u64>,
         tx_memo: Option<String>,
     ) -> Result<Transaction, anyhow::Error> {
-        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root2());
+        let mut tx_builder = Transaction::build_with_root(self.note_commitment_tree.root());
 
         tx_builder
             .set_fee(fee)
-            .set_chain_id(self.chain_id().ok_or_else(|| anyhow!("missing chain_id"))?);
+            .set_chain_id(self.chain_id().ok_or_else(|| anyhow!("missing chain_id"))?)
+            .set_proof_system(self.proof_system())
+            .set_expiry_height(self.default_expiry_height()?);
 
         let mut output_value = HashMap::<Denom, u64>::new();
         for Value { amount, asset_id } in values {
@@ -611,6 +664,16 @@ impl ClientState {
                     .values()
                     .map(|(_, note)| UnspentNote::SubmittedChange(note)),
             )
+            .chain(
+                self.quarantined_spend_set
+                    .values()
+                    .map(|(_, note)| UnspentNote::SubmittedSpend(note)),
+            )
+            .chain(
+                self.quarantined_change_set
+                    .values()
+                    .map(|(_, note)| UnspentNote::SubmittedChange(note)),
+            )
             .map(|note| {
                 // Any notes we have in the unspent set we will have the corresponding denominations
                 // for since the notes and asset registry are both part of the sync.
@@ -732,13 +795,15 @@ impl ClientState {
     /// Scan the provided block and update the client state.
     ///
     /// The provided block must be the one immediately following [`Self::last_block_height`].
-    #[instrument(skip(self, outputs, nullifiers))]
+    #[instrument(skip(self, outputs, nullifiers, quarantined, slashed))]
     pub fn scan_block(
         &mut self,
         CompactBlock {
             height,
             outputs,
             nullifiers,
+            quarantined,
+            slashed,
         }: CompactBlock,
     ) -> Result<(), anyhow::Error> {
         // We have to do a bit of a dance to use None as "-1" and handle genesis notes.
@@ -755,37 +820,85 @@ impl ClientState {
         }
         tracing::debug!(outputs_len = outputs.len(), "starting block scan");
 
+        // Undelegations are held in quarantine until the end of their unbonding period, so
+        // process them first, in case they are released at the end of this same block.
+        for group in quarantined.groups() {
+            for output::Body {
+                note_commitment,
+                ephemeral_key,
+                encrypted_note,
+            } in group.outputs.iter()
+            {
+                // Quarantined notes aren't in the note commitment tree yet, so we can only
+                // remember them until they're released.
+                if let Ok(note) = Note::decrypt(
+                    encrypted_note.as_ref(),
+                    self.wallet.incoming_viewing_key(),
+                    ephemeral_key,
+                ) {
+                    tracing::debug!(
+                        ?note_commitment,
+                        ?note,
+                        "found quarantined note while scanning"
+                    );
+                    self.submitted_change_set.remove(note_commitment);
+                    self.quarantined_change_set
+                        .insert(*note_commitment, (group.identity_key.clone(), note));
+                }
+            }
+
+            for nullifier in group.nullifiers.iter() {
+                if let Some(&note_commitment) = self.nullifier_map.get(nullifier) {
+                    let note = self.unspent_set.remove(&note_commitment).or_else(|| {
+                        self.submitted_spend_set
+                            .remove(&note_commitment)
+                            .map(|(_, note)| note)
+                    });
+                    if let Some(note) = note {
+                        tracing::debug!(
+                            value = ?note.value(),
+                            ?nullifier,
+                            "found quarantined nullifier, marking note as quarantined"
+                        );
+                        self.quarantined_spend_set
+                            .insert(note_commitment, (group.identity_key.clone(), note));
+                    }
+                }
+            }
+        }
+
         for output::Body {
             note_commitment,
             ephemeral_key,
             encrypted_note,
         } in outputs.into_iter()
         {
-            // Unconditionally insert the note commitment into the merkle tree
-            tracing::debug!(?note_commitment, "appending to note commitment tree");
-            self.note_commitment_tree.append(&note_commitment);
-
             // Try to decrypt the encrypted note using the ephemeral key and persistent incoming
             // viewing key -- if it doesn't decrypt, it wasn't meant for us.
-            if let Ok(note) = Note::decrypt(
+            let note = Note::decrypt(
                 encrypted_note.as_ref(),
                 self.wallet.incoming_viewing_key(),
                 &ephemeral_key,
-            ) {
+            );
+
+            // Unconditionally insert the note commitment into the note commitment tree, but
+            // only keep track of its authentication path if the note is ours
+            tracing::debug!(?note_commitment, "appending to note commitment tree");
+            let witness = if note.is_ok() {
+                merkle::Keep
+            } else {
+                merkle::Forget
+            };
+            let position = self.note_commitment_tree.insert(witness, note_commitment)?;
+
+            if let Ok(note) = note {
                 tracing::debug!(?note_commitment, ?note, "found note while scanning");
-                // Mark the most-recently-inserted note commitment (the one corresponding to this
-                // note) as worth keeping track of, because it's ours
-                self.note_commitment_tree.witness();
 
                 // Insert the note associated with its computed nullifier into the nullifier map
-                let (pos, _auth_path) = self
-                    .note_commitment_tree
-                    .authentication_path(&note_commitment)
-                    .expect("we just witnessed this commitment");
                 self.nullifier_map.insert(
                     self.wallet
                         .full_viewing_key()
-                        .derive_nullifier(pos, &note_commitment),
+                        .derive_nullifier(position, &note_commitment),
                     note_commitment,
                 );
 
@@ -794,6 +907,15 @@ impl ClientState {
                     tracing::debug!(value = ?note.value(), "found submitted change note while scanning, removing it from the submitted change set");
                 }
 
+                // If the note was released from quarantine, remove it from the quarantined change set
+                if self
+                    .quarantined_change_set
+                    .remove(&note_commitment)
+                    .is_some()
+                {
+                    tracing::debug!(value = ?note.value(), "found quarantined note released while scanning, removing it from the quarantined change set");
+                }
+
                 // Insert the note into the received set
                 self.unspent_set.insert(note_commitment, note.clone());
             }
@@ -813,7 +935,7 @@ impl ClientState {
                         "found nullifier for unspent note, marking it as spent"
                     );
                     self.spent_set.insert(note_commitment, note);
-                    self.note_commitment_tree.remove_witness(&note_commitment);
+                    self.note_commitment_tree.forget(note_commitment);
                 } else if let Some((_, note)) = self.submitted_spend_set.remove(&note_commitment) {
                     // Insert the note into the spent set
                     tracing::debug!(
@@ -822,7 +944,7 @@ impl ClientState {
                         "found nullifier for submitted spend note, marking it as spent"
                     );
                     self.spent_set.insert(note_commitment, note);
-                    self.note_commitment_tree.remove_witness(&note_commitment);
+                    self.note_commitment_tree.forget(note_commitment);
                 } else if let Some((_, note)) = self.submitted_change_set.remove(&note_commitment) {
                     // Insert the note into the spent set
                     tracing::debug!(
@@ -831,7 +953,17 @@ impl ClientState {
                         "found nullifier for submitted change note, marking it as spent"
                     );
                     self.spent_set.insert(note_commitment, note);
-                    self.note_commitment_tree.remove_witness(&note_commitment);
+                    self.note_commitment_tree.forget(note_commitment);
+                } else if let Some((_, note)) = self.quarantined_spend_set.remove(&note_commitment)
+                {
+                    // Insert the note into the spent set
+                    tracing::debug!(
+                        value = ?note.value(),
+                        ?nullifier,
+                        "found released nullifier for quarantined note, marking it as spent"
+                    );
+                    self.spent_set.insert(note_commitment, note);
+                    self.note_commitment_tree.forget(note_commitment);
                 } else if self.spent_set.contains_key(&note_commitment) {
                     // If the nullifier is already in the spent set, it means we've already
                     // processed this note and it's spent. This should never happen
@@ -847,6 +979,38 @@ impl ClientState {
             }
         }
 
+        // If any validators were slashed, the chain has rolled back their quarantined
+        // undelegations: the quarantined notes will never be released, and the notes we spent
+        // are ours to spend again.
+        for identity_key in slashed {
+            let (rolled_back, kept) =
+                mem::take(&mut self.quarantined_spend_set)
+                    .into_iter()
+                    .partition::<BTreeMap<_, _>, _>(|(_, (ik, _))| *ik == identity_key);
+            self.quarantined_spend_set = kept;
+            for (note_commitment, (_, note)) in rolled_back {
+                tracing::debug!(value = ?note.value(), ?identity_key, "validator slashed, returning quarantined note to the unspent set");
+                self.unspent_set.insert(note_commitment, note);
+            }
+
+            self.quarantined_change_set
+                .retain(|_, (ik, _)| *ik != identity_key);
+        }
+
+        // Close this block in the note commitment tree (and this epoch, if the block ends it),
+        // exactly as the chain does, so that our root matches the chain's anchor.
+        let epoch_duration = self
+            .chain_params
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("chain parameters must be fetched before scanning"))?
+            .epoch_duration;
+        if Epoch::from_height(height, epoch_duration).is_epoch_end(height) {
+            self.note_commitment_tree
+                .insert_epoch(merkle::Epoch::new())?;
+        }
+        self.note_commitment_tree
+            .insert_block(merkle::Block::new())?;
+
         // Remember that we've scanned this block & we're ready for the next one.
         self.last_block_height = Some(height);
         tracing::debug!(self.last_block_height, "finished scanning block");
@@ -873,6 +1037,10 @@ mod serde_helpers {
         submitted_spend_set: Vec<(String, SystemTime, String)>,
         #[serde(default, alias = "pending_change_set")]
         submitted_change_set: Vec<(String, SystemTime, String)>,
+        #[serde(default)]
+        quarantined_spend_set: Vec<(String, String, String)>,
+        #[serde(default)]
+        quarantined_change_set: Vec<(String, String, String)>,
         spent_set: Vec<(String, String)>,
         transactions: Vec<(String, String)>,
         asset_registry: Vec<(asset::Id, String)>,
@@ -968,6 +1136,28 @@ mod serde_helpers {
                         )
                     })
                     .collect(),
+                quarantined_spend_set: state
+                    .quarantined_spend_set
+                    .iter()
+                    .map(|(commitment, (identity_key, note))| {
+                        (
+                            hex::encode(commitment.0.to_bytes()),
+                            identity_key.to_string(),
+                            hex::encode(note.to_bytes()),
+                        )
+                    })
+                    .collect(),
+                quarantined_change_set: state
+                    .quarantined_change_set
+                    .iter()
+                    .map(|(commitment, (identity_key, note))| {
+                        (
+                            hex::encode(commitment.0.to_bytes()),
+                            identity_key.to_string(),
+                            hex::encode(note.to_bytes()),
+                        )
+                    })
+                    .collect(),
                 spent_set: state
                     .spent_set
                     .iter()
@@ -1027,6 +1217,28 @@ mod serde_helpers {
                 );
             }
 
+            let mut quarantined_spend_set = BTreeMap::new();
+            for (commitment, identity_key, note) in state.quarantined_spend_set.into_iter() {
+                quarantined_spend_set.insert(
+                    hex::decode(commitment)?.as_slice().try_into()?,
+                    (
+                        identity_key.parse()?,
+                        hex::decode(note)?.as_slice().try_into()?,
+                    ),
+                );
+            }
+
+            let mut quarantined_change_set = BTreeMap::new();
+            for (commitment, identity_key, note) in state.quarantined_change_set.into_iter() {
+                quarantined_change_set.insert(
+                    hex::decode(commitment)?.as_slice().try_into()?,
+                    (
+                        identity_key.parse()?,
+                        hex::decode(note)?.as_slice().try_into()?,
+                    ),
+                );
+            }
+
             let mut spent_set = BTreeMap::new();
             for (commitment, note) in state.spent_set.into_iter() {
                 spent_set.insert(
@@ -1048,6 +1260,8 @@ mod serde_helpers {
                 unspent_set,
                 submitted_spend_set,
                 submitted_change_set,
+                quarantined_spend_set,
+                quarantined_change_set,
                 spent_set,
                 asset_cache: asset_registry.try_into()?,
                 // TODO: serialize full transactions